@@ -1,14 +1,16 @@
+use crate::audit::{AuditScope, AuditSource};
 use crate::compact::{self, CompactOptions, CompactPhase, CompactReason, InitialContextInjection};
 use crate::log;
-use crate::permissions::{Decision, Permissions, RuntimeApprovals};
+use crate::permissions::{CallDecision, Decision, Permissions, RuntimeApprovals, ToolCall};
 use crate::provider::{self, ChatOptions, Provider, ProviderError, ToolDefinition};
 use crate::tools::{self, ToolContext, ToolRegistry, ToolResult};
 use crate::{ApiConfig, AuxiliaryTask, EngineConfig, ModelConfig};
 use protocol::{
-    Content, EngineEvent, Message, Mode, ReasoningEffort, Role, ToolOutcome, TurnMeta, UiCommand,
+    Content, EngineEvent, Message, Mode, ReasoningEffort, Role, StopReason, ToolOutcome, TurnMeta,
+    UiCommand,
 };
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
@@ -28,6 +30,7 @@ pub async fn engine_task(
     mut config: EngineConfig,
     mut registry: ToolRegistry,
     processes: tools::ProcessRegistry,
+    changes: tools::SessionChanges,
     mut cmd_rx: mpsc::UnboundedReceiver<UiCommand>,
     event_tx: mpsc::UnboundedSender<EngineEvent>,
 ) {
@@ -49,6 +52,9 @@ pub async fn engine_task(
     } else {
         None
     };
+    if let Some(ref allowed) = config.allowed_tools {
+        registry.retain(|name| allowed.iter().any(|a| a == name));
+    }
 
     let _ = event_tx.send(EngineEvent::Ready);
 
@@ -102,11 +108,17 @@ pub async fn engine_task(
                             } else {
                                 vec![]
                             };
+                            let profiles = if ma.depth < ma.max_depth {
+                                ma.profiles.prompt_listing()
+                            } else {
+                                String::new()
+                            };
                             Some(crate::AgentPromptConfig {
                                 agent_id,
                                 depth: ma.depth,
                                 parent_id,
                                 siblings,
+                                profiles,
                             })
                         } else {
                             None
@@ -147,14 +159,41 @@ pub async fn engine_task(
                             tps_samples: Vec::new(),
                             tool_elapsed: HashMap::new(),
                             file_locks: &file_locks,
+                            changes: &changes,
                             context_window,
                             compacted_this_turn: false,
+                            steered: HashSet::new(),
+                            llm_calls: 0,
+                            tool_calls: 0,
+                            tool_limit_hit: false,
                         };
+                        // The deadline fires the turn's cancel token; the
+                        // turn reports it as a timeout.
+                        let deadline = config.turn_limits.timeout.map(|limit| {
+                            let cancel = turn.cancel.clone();
+                            tokio::spawn(async move {
+                                tokio::time::sleep(limit).await;
+                                cancel.cancel();
+                            })
+                        });
                         turn.run(input_content, history).await;
+                        if let Some(deadline) = deadline {
+                            deadline.abort();
+                        }
                         // Cache the (possibly fetched) context window for future turns.
                         context_window = turn.context_window;
                     }
                     UiCommand::Compact { history, instructions } => {
+                        if let Err(hit) = config.budget.check() {
+                            let _ = event_tx.send(EngineEvent::TurnError {
+                                message: format!(
+                                    "{} budget exceeded, compaction skipped",
+                                    hit.scope.label()
+                                ),
+                                reason: StopReason::Budget,
+                            });
+                            continue;
+                        }
                         let request = config.aux_or_primary(AuxiliaryTask::Compaction);
                         let provider = build_provider_from_api(&request.api, &client);
                         let cancel = crate::cancel::CancellationToken::new();
@@ -168,12 +207,15 @@ pub async fn engine_task(
                                 injection: InitialContextInjection::DoNotInject,
                                 phase: CompactPhase::Manual,
                                 reason: CompactReason::UserRequested,
+                                prune_target: None,
                             },
                         )
                         .await
                         {
                             Ok((messages, usage)) => {
-                                emit_usage_background(&event_tx, &request.api, &request.model, usage);
+                                if let Some(usage) = usage {
+                                    emit_usage_background(&event_tx, &config, &request.api, &request.model, usage);
+                                }
                                 let _ = event_tx.send(EngineEvent::CompactionComplete { messages });
                             }
                             Err(e) => {
@@ -183,7 +225,10 @@ pub async fn engine_task(
                                     }
                                     _ => format!("compaction failed: {}", e.to_string().replace('\n', " ")),
                                 };
-                                let _ = event_tx.send(EngineEvent::TurnError { message: msg });
+                                let _ = event_tx.send(EngineEvent::TurnError {
+                                    message: msg,
+                                    reason: StopReason::Error,
+                                });
                             }
                         }
                     }
@@ -213,17 +258,29 @@ pub async fn engine_task(
                             &event_tx,
                         );
                     }
+                    // Predictions are speculative; don't spend past a limit.
                     UiCommand::PredictInput {
                         history,
                         generation,
-                    } => {
+                    } if config.budget.check().is_ok() => {
                         spawn_predict_request(&config, &client, history, &event_tx, generation);
                     }
-                    UiCommand::SetModel { model, api_base, api_key, provider_type } => {
+                    UiCommand::SetModel {
+                        model,
+                        api_base,
+                        api_key,
+                        provider_type,
+                        auth,
+                        capabilities,
+                    } => {
                         config.api.base = api_base;
                         config.api.key = api_key;
                         config.api.provider_type = provider_type;
+                        config.api.model_config.auth = auth;
+                        config.api.model_config.capabilities = capabilities;
                         config.model = model;
+                        // Re-resolve the window for the new model.
+                        context_window = config.context_window;
                     }
                     _ => {} // Steer, Cancel, etc. only relevant during a turn
                 }
@@ -247,9 +304,13 @@ fn spawn_title_generation(
     assistant_tail: String,
     event_tx: &mpsc::UnboundedSender<EngineEvent>,
 ) {
+    // A title is cosmetic; don't spend past a limit for one.
+    if config.budget.check().is_err() {
+        return;
+    }
     let request = config.aux_or_primary(AuxiliaryTask::Title);
     let provider = build_provider_from_api(&request.api, client);
-    let pricing = PricingContext::from_api(&request.api);
+    let pricing = PricingContext::new(config, &request.api);
     let model = request.model;
     let tx = event_tx.clone();
     tokio::spawn(async move {
@@ -290,6 +351,7 @@ fn spawn_title_generation(
                     let _ = tx.send(EngineEvent::TurnError {
                         message: "API quota exceeded — check your plan and billing details"
                             .to_string(),
+                        reason: StopReason::Error,
                     });
                     return;
                 }
@@ -318,9 +380,15 @@ fn spawn_btw_request(
     history: Vec<protocol::Message>,
     event_tx: &mpsc::UnboundedSender<EngineEvent>,
 ) {
+    if let Err(hit) = config.budget.check() {
+        let _ = event_tx.send(EngineEvent::BtwResponse {
+            content: format!("error: {} budget exceeded", hit.scope.label()),
+        });
+        return;
+    }
     let request = config.aux_or_primary(AuxiliaryTask::Btw);
     let provider = build_provider_from_api(&request.api, client);
-    let pricing = PricingContext::from_api(&request.api);
+    let pricing = PricingContext::new(config, &request.api);
     let model = request.model;
     let tx = event_tx.clone();
     let redact = config.redact_secrets;
@@ -374,7 +442,7 @@ fn spawn_predict_request(
 ) {
     let request = config.aux_or_primary(AuxiliaryTask::Prediction);
     let provider = build_provider_from_api(&request.api, client);
-    let pricing = PricingContext::from_api(&request.api);
+    let pricing = PricingContext::new(config, &request.api);
     let model = request.model;
     let tx = event_tx.clone();
     tokio::spawn(async move {
@@ -488,6 +556,18 @@ struct ToolSlot<'a> {
     start: Instant,
 }
 
+impl ToolSlot<'_> {
+    /// The arguments the tool runs with: redacted secrets are restored for
+    /// tools that act locally, while history keeps the placeholders.
+    fn execution_args(&self) -> HashMap<String, Value> {
+        let mut args = self.args.clone();
+        if self.tool.restores_secrets() {
+            crate::redact::restore_args(&mut args);
+        }
+        args
+    }
+}
+
 /// Tool calls from one LLM response, sorted by how they execute.
 ///
 /// Tools whose base decision is `Allow` go into `ready` (run concurrently)
@@ -503,6 +583,17 @@ struct ToolExecutionPlan<'a> {
     sequential: Vec<usize>,
 }
 
+/// Fields of a `SetModel` received mid-request: model, API base, API key,
+/// provider type, auth and capability overrides.
+type ModelChange = (
+    String,
+    String,
+    String,
+    String,
+    protocol::ProviderAuth,
+    protocol::CapabilityOverrides,
+);
+
 /// Encapsulates the state of a single agent turn.
 struct Turn<'a> {
     provider: Provider,
@@ -517,6 +608,7 @@ struct Turn<'a> {
     http_client: &'a reqwest::Client,
     cancel: crate::cancel::CancellationToken,
     file_locks: &'a tools::FileLocks,
+    changes: &'a tools::SessionChanges,
     messages: Vec<Message>,
     mode: Mode,
     reasoning_effort: ReasoningEffort,
@@ -533,24 +625,75 @@ struct Turn<'a> {
     /// on the first turn if not set via config.
     context_window: Option<u32>,
     compacted_this_turn: bool,
+    /// Ids of `Steer` commands already injected this turn. A re-sent id
+    /// (the UI resyncs its queue after edits) is ignored.
+    steered: HashSet<u64>,
+    /// LLM round-trips and executed tool calls so far, for `TurnLimits`.
+    llm_calls: u32,
+    tool_calls: u32,
+    /// Set once a tool call was refused for exceeding `max_tool_calls`.
+    tool_limit_hit: bool,
 }
 
 impl<'a> Turn<'a> {
+    fn audit_scope(&self) -> AuditScope {
+        AuditScope {
+            session_id: self.session_id.clone(),
+            agent_id: self
+                .agent_config
+                .as_ref()
+                .map(|a| a.agent_id.clone())
+                .filter(|id| !id.is_empty()),
+            cwd: self.config.cwd.display().to_string(),
+            mode: self.mode,
+        }
+    }
+
     fn emit(&self, event: EngineEvent) {
         let _ = self.event_tx.send(event);
     }
 
     /// Push a message into history. When `redact_secrets` is enabled, content
     /// from user/tool/agent roles — the only roles that carry data entering
-    /// from outside the model — is scrubbed at this boundary. Model-generated
-    /// messages (assistant, system) are passed through untouched.
+    /// from outside the model — is scrubbed at this boundary and what was
+    /// scrubbed is reported to the UI. Model-generated messages (assistant,
+    /// system) are passed through untouched.
     fn push_message(&mut self, mut msg: Message) {
         if self.config.redact_secrets && matches!(msg.role, Role::User | Role::Tool | Role::Agent) {
-            crate::redact::redact_message(&mut msg);
+            let redactions = crate::redact::redact_message(&mut msg);
+            if !redactions.is_empty() {
+                self.emit(EngineEvent::Redacted {
+                    source: self.message_source(&msg),
+                    redactions,
+                });
+            }
         }
         self.messages.push(msg);
     }
 
+    /// Where an incoming message came from, for the redaction report.
+    fn message_source(&self, msg: &Message) -> String {
+        match msg.role {
+            Role::Tool => {
+                let tool = msg.tool_call_id.as_ref().and_then(|id| {
+                    self.messages
+                        .iter()
+                        .rev()
+                        .filter_map(|m| m.tool_calls.as_ref())
+                        .flatten()
+                        .find(|tc| &tc.id == id)
+                        .map(|tc| tc.function.name.clone())
+                });
+                format!("{} result", tool.as_deref().unwrap_or("tool"))
+            }
+            Role::Agent => format!(
+                "message from {}",
+                msg.agent_from_id.as_deref().unwrap_or("agent")
+            ),
+            _ => "user message".into(),
+        }
+    }
+
     /// Rebuild the system prompt after a mid-turn mode change so the LLM sees
     /// the correct mode instructions on the next API call.
     fn regenerate_system_prompt(&mut self) {
@@ -614,6 +757,10 @@ impl<'a> Turn<'a> {
         if (prompt_tokens as u64) * 100 < (ctx as u64) * threshold {
             return false;
         }
+        // Over budget, the turn stops before its next call anyway.
+        if self.config.budget.check().is_err() {
+            return false;
+        }
         self.compacted_this_turn = true;
         debug_assert!(
             matches!(self.messages[0].role, Role::System),
@@ -631,12 +778,25 @@ impl<'a> Turn<'a> {
                 injection: InitialContextInjection::BeforeLastUserMessage,
                 phase: CompactPhase::MidTurn,
                 reason: CompactReason::ContextLimit,
+                prune_target: Some(compact::prune_target(
+                    ctx,
+                    prompt_tokens,
+                    &self.messages[1..],
+                )),
             },
         )
         .await;
         match result {
             Ok((compacted, usage)) => {
-                emit_usage_background(self.event_tx, &request.api, &request.model, usage);
+                if let Some(usage) = usage {
+                    emit_usage_background(
+                        self.event_tx,
+                        self.config,
+                        &request.api,
+                        &request.model,
+                        usage,
+                    );
+                }
                 self.messages.truncate(1);
                 self.messages.extend(compacted);
                 self.emit_messages_snapshot();
@@ -680,7 +840,8 @@ impl<'a> Turn<'a> {
         }
     }
 
-    fn emit_turn_complete(&mut self, interrupted: bool) {
+    fn emit_turn_complete(&mut self, reason: StopReason) {
+        let interrupted = matches!(reason, StopReason::Cancelled | StopReason::Timeout);
         let meta = self.build_meta(interrupted);
         self.messages.remove(0);
         let msgs = std::mem::take(&mut self.messages);
@@ -688,9 +849,31 @@ impl<'a> Turn<'a> {
             turn_id: self.turn_id,
             messages: msgs,
             meta: Some(meta),
+            reason,
         });
     }
 
+    /// Reason to report once the cancel token fired: the turn deadline
+    /// cancels the same token as the user does.
+    fn cancel_reason(&self) -> StopReason {
+        match self.config.turn_limits.timeout {
+            Some(t) if self.started_at.elapsed() >= t => StopReason::Timeout,
+            _ => StopReason::Cancelled,
+        }
+    }
+
+    /// The first per-turn limit that stops the next LLM call, if any.
+    fn limit_reached(&self) -> Option<StopReason> {
+        let limits = &self.config.turn_limits;
+        if self.tool_limit_hit {
+            return Some(StopReason::MaxToolCalls);
+        }
+        if limits.max_turns.is_some_and(|max| self.llm_calls >= max) {
+            return Some(StopReason::MaxTurns);
+        }
+        None
+    }
+
     fn build_meta(&self, interrupted: bool) -> TurnMeta {
         let avg_tps = if self.tps_samples.is_empty() {
             None
@@ -713,10 +896,18 @@ impl<'a> Turn<'a> {
         api_base: String,
         api_key: String,
         provider_type: String,
+        auth: protocol::ProviderAuth,
+        capabilities: protocol::CapabilityOverrides,
     ) {
         self.model = model;
+        self.context_window = self.config.context_window;
+        let model_config = crate::ModelConfig {
+            auth,
+            capabilities,
+            ..self.config.api.model_config.clone()
+        };
         self.provider = Provider::new(api_base, api_key, &provider_type, self.http_client.clone())
-            .with_model_config(self.config.api.model_config.clone());
+            .with_model_config(model_config);
     }
 
     /// Handle a command that arrived during a turn but isn't turn-specific.
@@ -759,15 +950,21 @@ impl<'a> Turn<'a> {
     /// Returns true if the command was consumed here.
     fn handle_turn_cmd(&mut self, cmd: UiCommand) -> bool {
         match cmd {
-            UiCommand::Steer { text } => {
+            UiCommand::Steer { text, id } => {
+                if id != 0 && !self.steered.insert(id) {
+                    return true;
+                }
                 self.emit(EngineEvent::Steered {
                     text: text.clone(),
                     count: 1,
+                    id,
                 });
                 self.push_message(Message::user(Content::text(text)));
                 self.emit_messages_snapshot();
                 true
             }
+            // Only meaningful within a batch; see `steer::coalesce`.
+            UiCommand::CancelSteer { .. } => true,
             UiCommand::Unsteer { count } => {
                 for _ in 0..count {
                     if let Some(pos) = self.messages.iter().rposition(|m| m.role == Role::User) {
@@ -791,8 +988,17 @@ impl<'a> Turn<'a> {
                 api_base,
                 api_key,
                 provider_type,
+                auth,
+                capabilities,
             } => {
-                self.apply_model_change(model, api_base, api_key, provider_type);
+                self.apply_model_change(
+                    model,
+                    api_base,
+                    api_key,
+                    provider_type,
+                    auth,
+                    capabilities,
+                );
                 true
             }
             UiCommand::Cancel => {
@@ -845,25 +1051,63 @@ impl<'a> Turn<'a> {
 
             // Recompute tool definitions each iteration — mode may have
             // changed (e.g. Plan → Apply after plan approval).
-            let tool_defs: Vec<ToolDefinition> = if self.provider.tool_calling() {
-                self.registry
-                    .definitions(self.permissions, self.mode, self.config.interactive)
-            } else {
-                Vec::new()
-            };
+            let tool_defs: Vec<ToolDefinition> =
+                if self.provider.capabilities(&self.model).tool_calling {
+                    self.registry
+                        .definitions(self.permissions, self.mode, self.config.interactive)
+                } else {
+                    Vec::new()
+                };
 
             if self.cancel.is_cancelled() {
-                self.emit_turn_complete(true);
+                self.emit_turn_complete(self.cancel_reason());
+                return;
+            }
+
+            // Stop before spending more once a budget is exhausted. Usage
+            // recorded by other tasks (subagents, auxiliary requests) counts.
+            if let Some(hit) = self.config.budget.exceeded() {
+                log::entry(
+                    log::Level::Warn,
+                    "agent_stop",
+                    &serde_json::json!({
+                        "reason": "budget_exceeded",
+                        "scope": hit.scope.label(),
+                        "spent_usd": hit.spent_usd,
+                        "limit_usd": hit.limit_usd,
+                    }),
+                );
+                self.emit(EngineEvent::BudgetExceeded {
+                    scope: hit.scope,
+                    spent_usd: hit.spent_usd,
+                    limit_usd: hit.limit_usd,
+                });
+                self.emit_turn_complete(StopReason::Budget);
+                return;
+            }
+
+            if let Some(reason) = self.limit_reached() {
+                log::entry(
+                    log::Level::Warn,
+                    "agent_stop",
+                    &serde_json::json!({
+                        "reason": reason,
+                        "llm_calls": self.llm_calls,
+                        "tool_calls": self.tool_calls,
+                    }),
+                );
+                self.emit_turn_complete(reason);
                 return;
             }
 
             // Call LLM with cancel monitoring
+            self.llm_calls += 1;
             let (result, partial_text, partial_reasoning) = self.call_llm(&tool_defs).await;
             let (resp, had_injected) = match result {
                 Ok(r) => r,
                 Err(ProviderError::Cancelled) => {
                     self.commit_partial_assistant(partial_text, partial_reasoning);
-                    self.emit_turn_complete(true);
+                    self.emit_turn_complete(self.cancel_reason());
                     return;
                 }
                 Err(ProviderError::QuotaExceeded(ref body)) => {
@@ -872,10 +1116,11 @@ impl<'a> Turn<'a> {
                         "agent_stop",
                         &serde_json::json!({"reason": "quota_exceeded", "error": body}),
                     );
-                    self.emit_turn_complete(false);
+                    self.emit_turn_complete(StopReason::Error);
                     self.emit(EngineEvent::TurnError {
                         message: "API quota exceeded — check your plan and billing details"
                             .to_string(),
+                        reason: StopReason::Error,
                     });
                     return;
                 }
@@ -888,13 +1133,16 @@ impl<'a> Turn<'a> {
                     );
                     // Send final history so the TUI can persist tool results
                     // accumulated before the error.
-                    self.emit_turn_complete(false);
-                    self.emit(EngineEvent::TurnError { message: error_msg });
+                    self.emit_turn_complete(StopReason::Error);
+                    self.emit(EngineEvent::TurnError {
+                        message: error_msg,
+                        reason: StopReason::Error,
+                    });
                     return;
                 }
             };
 
-            let prompt_tokens = resp.usage.prompt_tokens;
+            let prompt_tokens = resp.usage.context_tokens();
             if prompt_tokens.is_some() {
                 let tokens_per_sec = resp.tokens_per_sec;
                 if let Some(tps) = tokens_per_sec {
@@ -902,6 +1150,7 @@ impl<'a> Turn<'a> {
                 }
                 send_usage(
                     self.event_tx,
+                    &self.config.budget,
                     &self.config.api.provider_type,
                     &self.config.api.model_config,
                     &self.model,
@@ -977,7 +1226,7 @@ impl<'a> Turn<'a> {
                 self.messages
                     .push(Message::assistant(content, reasoning, None));
                 self.emit_messages_snapshot();
-                self.emit_turn_complete(false);
+                self.emit_turn_complete(StopReason::Completed);
                 return;
             }
 
@@ -1003,7 +1252,7 @@ impl<'a> Turn<'a> {
                 self.mark_unfinished_cancelled(&plan, &completed);
             }
             self.collect_results(&plan, completed);
-            for cmd in deferred {
+            for cmd in crate::steer::coalesce(deferred) {
                 self.handle_turn_cmd(cmd);
             }
         }
@@ -1024,6 +1273,7 @@ impl<'a> Turn<'a> {
             sequential: Vec::new(),
         };
 
+        let audit = self.audit_scope();
         for tc in tool_calls {
             self.drain_commands();
             if self.cancel.is_cancelled() {
@@ -1042,6 +1292,23 @@ impl<'a> Turn<'a> {
                 summary,
             });
 
+            if self
+                .config
+                .turn_limits
+                .max_tool_calls
+                .is_some_and(|max| self.tool_calls >= max)
+            {
+                self.tool_limit_hit = true;
+                self.push_tool_result(
+                    &tc.id,
+                    "Tool call limit reached for this turn; the call was not run.",
+                    true,
+                    Some(tool_start),
+                );
+                continue;
+            }
+            self.tool_calls += 1;
+
             let tool = match self.registry.get(&tc.function.name) {
                 Some(t) => t,
                 None => {
@@ -1055,24 +1322,23 @@ impl<'a> Turn<'a> {
                 }
             };
 
-            let mut decision = tool
-                .decide_override(&args, self.mode, &self.session_dir)
-                .unwrap_or_else(|| {
-                    self.permissions
-                        .decide(self.mode, &tc.function.name, &args, tool.is_mcp())
-                });
-
-            // Runtime approvals (session + workspace) can turn Ask → Allow.
-            if decision == Decision::Ask {
-                let rt = self.runtime_approvals.read().unwrap();
-                let desc = tool
-                    .needs_confirm(&args)
-                    .unwrap_or_else(|| tc.function.name.clone());
-                if rt.is_auto_approved(self.permissions, self.mode, &tc.function.name, &args, &desc)
-                {
-                    decision = Decision::Allow;
-                }
-            }
+            let call = ToolCall {
+                name: &tc.function.name,
+                args: &args,
+                is_mcp: tool.is_mcp(),
+                tool: Some(tool),
+                session_dir: Some(&self.session_dir),
+            };
+            let rt = self.runtime_approvals.read().unwrap();
+            let CallDecision {
+                decision,
+                source,
+                rule,
+                uses_secrets,
+                ..
+            } = self.permissions.decide_call(self.mode, &call, &rt);
+            drop(rt);
+            audit.record(&tc.function.name, &args, &decision, source, rule);
 
             // Pre-flight validation: catch errors before prompting (e.g. stale file hash).
             if let Some(err) = tool.preflight(&args) {
@@ -1106,9 +1372,12 @@ impl<'a> Turn<'a> {
                     );
                 }
                 Decision::Ask => {
-                    let desc = tool
+                    let mut desc = tool
                         .needs_confirm(&args)
                         .unwrap_or_else(|| tc.function.name.clone());
+                    if uses_secrets {
+                        desc.push_str(" (uses a redacted secret)");
+                    }
                     let approval_patterns = tool.approval_patterns(&args);
                     let cmd_summary = if tc.function.name == "bash" {
                         let d = tools::str_arg(&args, "description");
@@ -1172,7 +1441,10 @@ impl<'a> Turn<'a> {
                 session_id: &self.session_id,
                 session_dir: &self.session_dir,
                 file_locks: self.file_locks,
+                changes: self.changes,
                 engine_config: self.config,
+                permissions: self.permissions,
+                mode: self.mode,
             })
             .collect();
 
@@ -1182,10 +1454,11 @@ impl<'a> Turn<'a> {
         for &i in &plan.ready {
             let fut = plan.slots[i]
                 .tool
-                .execute(plan.slots[i].args.clone(), &contexts[i]);
+                .execute(plan.slots[i].execution_args(), &contexts[i]);
             futs.push(Box::pin(async move { (i, fut.await) }));
         }
 
+        let audit = self.audit_scope();
         let mut outstanding = plan.ready.len() + plan.pending_perms.len();
         let cancel = &self.cancel;
         let cmd_rx = &mut self.cmd_rx;
@@ -1210,11 +1483,19 @@ impl<'a> Turn<'a> {
                             .position(|(_, rid)| *rid == request_id)
                         {
                             let (idx, _) = plan.pending_perms.swap_remove(pos);
+                            let decision = if approved { Decision::Allow } else { Decision::Deny };
+                            audit.record(
+                                &plan.slots[idx].tc.function.name,
+                                &plan.slots[idx].args,
+                                &decision,
+                                AuditSource::User,
+                                None,
+                            );
                             if approved {
                                 plan.slots[idx].confirm_msg = message;
                                 let fut = plan.slots[idx]
                                     .tool
-                                    .execute(plan.slots[idx].args.clone(), &contexts[idx]);
+                                    .execute(plan.slots[idx].execution_args(), &contexts[idx]);
                                 futs.push(Box::pin(async move { (idx, fut.await) }));
                             } else {
                                 let denial = match message {
@@ -1236,6 +1517,7 @@ impl<'a> Turn<'a> {
                     }
                     UiCommand::AgentMessage { .. }
                     | UiCommand::Steer { .. }
+                    | UiCommand::CancelSteer { .. }
                     | UiCommand::Unsteer { .. }
                     | UiCommand::SetMode { .. }
                     | UiCommand::SetReasoningEffort { .. }
@@ -1343,7 +1625,11 @@ impl<'a> Turn<'a> {
 
     /// Drain pending commands (steering, mode changes, cancel).
     fn drain_commands(&mut self) {
+        let mut cmds = Vec::new();
         while let Ok(cmd) = self.cmd_rx.try_recv() {
+            cmds.push(cmd);
+        }
+        for cmd in crate::steer::coalesce(cmds) {
             self.handle_turn_cmd(cmd);
         }
     }
@@ -1363,7 +1649,7 @@ impl<'a> Turn<'a> {
     ) {
         // The chat future borrows self.provider and self.model, so model
         // changes received mid-request are deferred until the future resolves.
-        let mut pending_model: Option<(String, String, String, String)> = None;
+        let mut pending_model: Option<ModelChange> = None;
         let mut deferred_turn_cmds: Vec<UiCommand> = Vec::new();
 
         let partial_text = std::sync::Mutex::new(String::new());
@@ -1421,10 +1707,19 @@ impl<'a> Turn<'a> {
                         }
                         UiCommand::SetMode { mode } => self.mode = mode,
                         UiCommand::SetReasoningEffort { effort } => self.reasoning_effort = effort,
-                        UiCommand::SetModel { model, api_base, api_key, provider_type } => {
-                            pending_model = Some((model, api_base, api_key, provider_type));
+                        UiCommand::SetModel {
+                            model,
+                            api_base,
+                            api_key,
+                            provider_type,
+                            auth,
+                            capabilities,
+                        } => {
+                            pending_model =
+                                Some((model, api_base, api_key, provider_type, auth, capabilities));
                         }
                         UiCommand::Steer { .. }
+                        | UiCommand::CancelSteer { .. }
                         | UiCommand::Unsteer { .. }
                         | UiCommand::AgentMessage { .. } => deferred_turn_cmds.push(cmd),
                         other => {
@@ -1438,12 +1733,15 @@ impl<'a> Turn<'a> {
         let pt = partial_text.into_inner().unwrap_or_default();
         let pr = partial_reasoning.into_inner().unwrap_or_default();
 
-        if let Some((model, api_base, api_key, provider_type)) = pending_model {
-            self.apply_model_change(model, api_base, api_key, provider_type);
+        if let Some((model, api_base, api_key, provider_type, auth, capabilities)) = pending_model {
+            self.apply_model_change(model, api_base, api_key, provider_type, auth, capabilities);
         }
-        let had_injected = deferred_turn_cmds
-            .iter()
-            .any(|c| matches!(c, UiCommand::Steer { .. } | UiCommand::AgentMessage { .. }));
+        let deferred_turn_cmds = crate::steer::coalesce(deferred_turn_cmds);
+        let had_injected = deferred_turn_cmds.iter().any(|c| match c {
+            UiCommand::Steer { id, .. } => *id == 0 || !self.steered.contains(id),
+            UiCommand::AgentMessage { .. } => true,
+            _ => false,
+        });
         for cmd in deferred_turn_cmds {
             self.handle_turn_cmd(cmd);
         }
@@ -1479,7 +1777,16 @@ impl<'a> Turn<'a> {
                     api_base,
                     api_key,
                     provider_type,
-                }) => self.apply_model_change(model, api_base, api_key, provider_type),
+                    auth,
+                    capabilities,
+                }) => self.apply_model_change(
+                    model,
+                    api_base,
+                    api_key,
+                    provider_type,
+                    auth,
+                    capabilities,
+                ),
                 Some(UiCommand::Cancel) => {
                     self.cancel.cancel();
                     return None;
@@ -1523,8 +1830,10 @@ impl<'a> Turn<'a> {
 
 // ── Helpers ─────────────────────────────────────────────────────────────────
 
+#[allow(clippy::too_many_arguments)]
 fn send_usage(
     tx: &mpsc::UnboundedSender<EngineEvent>,
+    budget: &crate::budget::Budget,
     provider_type: &str,
     model_config: &crate::ModelConfig,
     model: &str,
@@ -1540,6 +1849,13 @@ fn send_usage(
         cost_usd: if cost > 0.0 { Some(cost) } else { None },
         background,
     });
+    for crossed in budget.record(cost) {
+        let _ = tx.send(EngineEvent::BudgetWarning {
+            scope: crossed.scope,
+            spent_usd: crossed.spent_usd,
+            limit_usd: crossed.limit_usd,
+        });
+    }
 }
 
 /// Calculate cost from token usage and emit a `TokenUsage` event.
@@ -1551,6 +1867,7 @@ pub fn emit_usage(
 ) {
     send_usage(
         tx,
+        &config.budget,
         &config.api.provider_type,
         &config.api.model_config,
         model,
@@ -1564,12 +1881,14 @@ pub fn emit_usage(
 /// Cost is tracked but prompt_tokens won't update displayed context usage.
 fn emit_usage_background(
     tx: &mpsc::UnboundedSender<EngineEvent>,
+    config: &EngineConfig,
     api: &crate::ApiConfig,
     model: &str,
     usage: protocol::TokenUsage,
 ) {
     send_usage(
         tx,
+        &config.budget,
         &api.provider_type,
         &api.model_config,
         model,
@@ -1584,13 +1903,15 @@ fn emit_usage_background(
 struct PricingContext {
     provider_type: String,
     model_config: crate::ModelConfig,
+    budget: Arc<crate::budget::Budget>,
 }
 
 impl PricingContext {
-    fn from_api(api: &crate::ApiConfig) -> Self {
+    fn new(config: &EngineConfig, api: &crate::ApiConfig) -> Self {
         Self {
             provider_type: api.provider_type.clone(),
             model_config: api.model_config.clone(),
+            budget: Arc::clone(&config.budget),
         }
     }
 
@@ -1602,6 +1923,7 @@ impl PricingContext {
     ) {
         send_usage(
             tx,
+            &self.budget,
             &self.provider_type,
             &self.model_config,
             model,
@@ -1611,3 +1933,194 @@ impl PricingContext {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::budget::{Budget, BudgetLimits};
+    use crate::TurnLimits;
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// An OpenAI-compatible server that answers every request with one
+    /// call to a tool that does not exist, or never answers at all.
+    async fn mock_server(respond: bool) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut n = 0;
+            while let Ok((mut stream, _)) = listener.accept().await {
+                n += 1;
+                tokio::spawn(async move {
+                    let mut buf = Vec::new();
+                    let mut chunk = [0u8; 4096];
+                    loop {
+                        let Ok(read) = stream.read(&mut chunk).await else {
+                            return;
+                        };
+                        if read == 0 {
+                            return;
+                        }
+                        buf.extend_from_slice(&chunk[..read]);
+                        let text = String::from_utf8_lossy(&buf);
+                        if let Some(end) = text.find("\r\n\r\n") {
+                            let len = text[..end]
+                                .lines()
+                                .find_map(|l| {
+                                    let (k, v) = l.split_once(':')?;
+                                    k.eq_ignore_ascii_case("content-length")
+                                        .then(|| v.trim().parse::<usize>().ok())?
+                                })
+                                .unwrap_or(0);
+                            if buf.len() >= end + 4 + len {
+                                break;
+                            }
+                        }
+                    }
+                    if !respond {
+                        std::future::pending::<()>().await;
+                    }
+                    let delta = serde_json::json!({"choices": [{"delta": {"tool_calls": [{
+                        "index": 0,
+                        "id": format!("call_{n}"),
+                        "function": {"name": "no_such_tool", "arguments": "{}"},
+                    }]}}]});
+                    let body = format!("data: {delta}\n\ndata: [DONE]\n\n");
+                    let resp = format!(
+                        "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\n\
+                         content-length: {}\r\nconnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                    let _ = stream.write_all(resp.as_bytes()).await;
+                    let _ = stream.shutdown().await;
+                });
+            }
+        });
+        format!("http://{addr}/v1")
+    }
+
+    fn config(base: String, limits: TurnLimits, budget: Budget) -> EngineConfig {
+        let cwd = std::env::temp_dir();
+        EngineConfig {
+            api: ApiConfig {
+                base,
+                key: "test".into(),
+                key_env: String::new(),
+                provider_type: "openai-compatible".into(),
+                model_config: ModelConfig {
+                    tool_calling: Some(true),
+                    ..ModelConfig::default()
+                },
+            },
+            model: "test-model".into(),
+            auxiliary: Default::default(),
+            instructions: None,
+            system_prompt_override: Some("test".into()),
+            cwd: cwd.clone(),
+            permissions: Arc::new(Permissions::load()),
+            runtime_approvals: Arc::new(RwLock::new(RuntimeApprovals::new())),
+            multi_agent: None,
+            interactive: false,
+            mcp_servers: HashMap::new(),
+            skills: None,
+            auto_compact: false,
+            context_window: Some(100_000),
+            redact_secrets: false,
+            budget: Arc::new(budget),
+            turn_limits: limits,
+            allowed_tools: None,
+        }
+    }
+
+    /// Run one turn and return the reason it stopped with.
+    async fn run_turn(config: EngineConfig, cancel_after: Option<Duration>) -> StopReason {
+        let mut handle = crate::start(config);
+        handle.send(UiCommand::StartTurn {
+            turn_id: 1,
+            content: Content::text("go"),
+            mode: Mode::Normal,
+            model: "test-model".into(),
+            reasoning_effort: ReasoningEffort::Off,
+            history: Vec::new(),
+            api_base: None,
+            api_key: None,
+            session_id: "test".into(),
+            session_dir: std::env::temp_dir(),
+            model_config_overrides: None,
+            permission_overrides: None,
+        });
+        if let Some(delay) = cancel_after {
+            tokio::time::sleep(delay).await;
+            handle.send(UiCommand::Cancel);
+        }
+        let wait = async {
+            loop {
+                match handle.recv().await {
+                    Some(EngineEvent::TurnComplete { reason, .. }) => return reason,
+                    Some(_) => {}
+                    None => panic!("engine stopped without completing the turn"),
+                }
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(20), wait)
+            .await
+            .expect("turn did not complete")
+    }
+
+    #[tokio::test]
+    async fn max_turns_stops_after_that_many_llm_calls() {
+        let limits = TurnLimits {
+            max_turns: Some(2),
+            ..TurnLimits::default()
+        };
+        let config = config(mock_server(true).await, limits, Budget::unlimited());
+        assert_eq!(run_turn(config, None).await, StopReason::MaxTurns);
+    }
+
+    #[tokio::test]
+    async fn max_tool_calls_refuses_the_call_past_the_cap() {
+        let limits = TurnLimits {
+            max_tool_calls: Some(1),
+            max_turns: Some(10),
+            ..TurnLimits::default()
+        };
+        let config = config(mock_server(true).await, limits, Budget::unlimited());
+        assert_eq!(run_turn(config, None).await, StopReason::MaxToolCalls);
+    }
+
+    #[tokio::test]
+    async fn timeout_cancels_a_hung_request() {
+        let limits = TurnLimits {
+            timeout: Some(Duration::from_millis(200)),
+            ..TurnLimits::default()
+        };
+        let config = config(mock_server(false).await, limits, Budget::unlimited());
+        assert_eq!(run_turn(config, None).await, StopReason::Timeout);
+    }
+
+    #[tokio::test]
+    async fn user_cancel_before_the_deadline_is_not_a_timeout() {
+        let limits = TurnLimits {
+            timeout: Some(Duration::from_secs(60)),
+            ..TurnLimits::default()
+        };
+        let config = config(mock_server(false).await, limits, Budget::unlimited());
+        let reason = run_turn(config, Some(Duration::from_millis(200))).await;
+        assert_eq!(reason, StopReason::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn exhausted_budget_stops_before_the_first_call() {
+        let budget = Budget::new(
+            BudgetLimits {
+                session_usd: Some(1.0),
+                ..BudgetLimits::default()
+            },
+            0.0,
+        );
+        budget.record(2.0);
+        let config = config(mock_server(true).await, TurnLimits::default(), budget);
+        assert_eq!(run_turn(config, None).await, StopReason::Budget);
+    }
+}
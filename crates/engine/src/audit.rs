@@ -0,0 +1,262 @@
+//! Append-only audit log of permission decisions (`audit.jsonl` in the state
+//! dir).
+//!
+//! Every tool call the engine decides on leaves one line: what ran, in which
+//! session and mode, the outcome, and what produced it — a config rule, a
+//! runtime approval, or the user answering a prompt. Prompted calls leave two
+//! lines, the `ask` and the user's answer. Arguments are stored only as a
+//! digest plus a secret-redacted summary.
+
+use crate::permissions::Decision;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// What produced a decision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditSource {
+    /// A permission rule from config or the built-in defaults.
+    Rule,
+    /// No rule matched; the mode's default applied.
+    Default,
+    /// The tool's own override (e.g. editing the plan file in Plan mode).
+    Tool,
+    /// A runtime approval saved for this session.
+    Session,
+    /// A runtime approval saved for this workspace.
+    Workspace,
+    /// The user answered a permission prompt.
+    User,
+    /// A deny rule from the organization-managed policy.
+    Managed,
+}
+
+impl AuditSource {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Rule => "rule",
+            Self::Default => "default",
+            Self::Tool => "tool",
+            Self::Session => "session",
+            Self::Workspace => "workspace",
+            Self::User => "user",
+            Self::Managed => "managed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp_ms: u64,
+    pub session_id: String,
+    /// Id of the agent that made the call; absent for a lone main agent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
+    pub cwd: String,
+    pub mode: String,
+    pub tool: String,
+    /// SHA-256 of the call's arguments, to match entries for the same call
+    /// without storing the arguments themselves.
+    pub args_digest: String,
+    /// Short description of the call (command, path or URL), with secrets
+    /// redacted.
+    pub summary: String,
+    /// `allow`, `ask` or `deny`.
+    pub decision: String,
+    pub source: AuditSource,
+    /// The deciding rule, e.g. `bash.allow "git log *"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+}
+
+pub fn audit_path() -> PathBuf {
+    crate::paths::state_dir().join("audit.jsonl")
+}
+
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Hex SHA-256 of `args`, independent of key order.
+pub fn args_digest(args: &HashMap<String, Value>) -> String {
+    let sorted: BTreeMap<_, _> = args.iter().collect();
+    let json = serde_json::to_string(&sorted).unwrap_or_default();
+    Sha256::digest(json.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// Append one entry. Failures are ignored: the log must never fail a turn.
+/// The write is synchronous so lines land in decision order and none are
+/// lost when the process exits right after.
+pub fn append(entry: &AuditEntry) {
+    let Ok(mut line) = serde_json::to_string(entry) else {
+        return;
+    };
+    line.push('\n');
+    write_line(&line);
+}
+
+/// One `write_all` on an `O_APPEND` handle, so lines from concurrent
+/// processes never interleave.
+fn write_line(line: &str) {
+    let path = audit_path();
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Ok(mut f) = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+    {
+        let _ = f.write_all(line.as_bytes());
+    }
+}
+
+/// Who is deciding: the fields shared by every entry a turn writes.
+pub(crate) struct AuditScope {
+    pub session_id: String,
+    pub agent_id: Option<String>,
+    pub cwd: String,
+    pub mode: protocol::Mode,
+}
+
+impl AuditScope {
+    pub fn record(
+        &self,
+        tool: &str,
+        args: &HashMap<String, Value>,
+        decision: &Decision,
+        source: AuditSource,
+        rule: Option<String>,
+    ) {
+        append(&AuditEntry {
+            timestamp_ms: now_ms(),
+            session_id: self.session_id.clone(),
+            agent_id: self.agent_id.clone(),
+            cwd: self.cwd.clone(),
+            mode: self.mode.as_str().to_string(),
+            tool: tool.to_string(),
+            args_digest: args_digest(args),
+            summary: crate::redact::redact(&crate::tools::tool_arg_summary(tool, args)),
+            decision: decision.label().to_string(),
+            source,
+            rule,
+        });
+    }
+}
+
+/// Load all entries, skipping lines that fail to parse.
+pub fn load() -> Vec<AuditEntry> {
+    let Ok(f) = std::fs::File::open(audit_path()) else {
+        return Vec::new();
+    };
+    std::io::BufReader::new(f)
+        .lines()
+        .filter_map(|line| serde_json::from_str(&line.ok()?).ok())
+        .collect()
+}
+
+/// One JSON object per line, in the same shape as `audit.jsonl`.
+pub fn entries_jsonl(entries: &[AuditEntry]) -> String {
+    let mut out = String::new();
+    for e in entries {
+        if let Ok(line) = serde_json::to_string(e) {
+            out.push_str(&line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Filters for `smelt audit`. Unset fields match everything.
+#[derive(Debug, Default)]
+pub struct AuditQuery {
+    /// Only entries at or after this timestamp.
+    pub since_ms: Option<u64>,
+    /// Session id prefix.
+    pub session: Option<String>,
+    pub agent: Option<String>,
+    pub tool: Option<String>,
+    pub decision: Option<String>,
+    pub source: Option<AuditSource>,
+}
+
+impl AuditQuery {
+    pub fn matches(&self, e: &AuditEntry) -> bool {
+        self.since_ms.is_none_or(|t| e.timestamp_ms >= t)
+            && self
+                .session
+                .as_ref()
+                .is_none_or(|s| e.session_id.starts_with(s.as_str()))
+            && self
+                .agent
+                .as_ref()
+                .is_none_or(|a| e.agent_id.as_ref() == Some(a))
+            && self.tool.as_ref().is_none_or(|t| &e.tool == t)
+            && self.decision.as_ref().is_none_or(|d| &e.decision == d)
+            && self.source.is_none_or(|s| e.source == s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digest_ignores_key_order() {
+        let a: HashMap<String, Value> =
+            serde_json::from_str(r#"{"path":"a.rs","content":"x"}"#).unwrap();
+        let b: HashMap<String, Value> =
+            serde_json::from_str(r#"{"content":"x","path":"a.rs"}"#).unwrap();
+        assert_eq!(args_digest(&a), args_digest(&b));
+        assert_eq!(args_digest(&a).len(), 64);
+    }
+
+    #[test]
+    fn entry_round_trips() {
+        let entry = AuditEntry {
+            timestamp_ms: 1,
+            session_id: "s".into(),
+            agent_id: None,
+            cwd: "/w".into(),
+            mode: "normal".into(),
+            tool: "bash".into(),
+            args_digest: "d".into(),
+            summary: "ls".into(),
+            decision: "allow".into(),
+            source: AuditSource::Rule,
+            rule: Some("bash.allow \"ls *\"".into()),
+        };
+        let line = serde_json::to_string(&entry).unwrap();
+        assert!(line.contains(r#""source":"rule""#));
+        assert!(!line.contains("agent_id"));
+        let back: AuditEntry = serde_json::from_str(&line).unwrap();
+        assert_eq!(back.rule, entry.rule);
+
+        let query = |q: AuditQuery| q.matches(&entry);
+        assert!(query(AuditQuery::default()));
+        assert!(query(AuditQuery {
+            session: Some("s".into()),
+            decision: Some("allow".into()),
+            ..AuditQuery::default()
+        }));
+        assert!(!query(AuditQuery {
+            source: Some(AuditSource::User),
+            ..AuditQuery::default()
+        }));
+        assert!(!query(AuditQuery {
+            since_ms: Some(2),
+            ..AuditQuery::default()
+        }));
+    }
+}
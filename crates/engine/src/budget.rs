@@ -0,0 +1,226 @@
+//! Spending limits.
+//!
+//! A [`Budget`] is shared between the engine and the UI. The engine records
+//! the cost of every LLM call it makes (turns and auxiliary requests alike)
+//! and checks [`Budget::check`] before starting any of them, so once a limit
+//! is exhausted no turn, title, compaction, `/btw` or prediction request goes
+//! out. The UI records usage the engine never sees — subagents run in their
+//! own processes — stops running subagents once a limit is exhausted, and
+//! resets the session total when the session changes.
+
+use protocol::BudgetScope;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Default warning threshold, as a percentage of a limit.
+pub const DEFAULT_WARN_PERCENT: u8 = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BudgetLimits {
+    /// Cap on spend for the current session (or headless run), in USD.
+    pub session_usd: Option<f64>,
+    /// Cap on spend per UTC day across all sessions, in USD.
+    pub daily_usd: Option<f64>,
+    /// Warn once spend reaches this percentage of a limit.
+    pub warn_percent: u8,
+}
+
+impl Default for BudgetLimits {
+    fn default() -> Self {
+        Self {
+            session_usd: None,
+            daily_usd: None,
+            warn_percent: DEFAULT_WARN_PERCENT,
+        }
+    }
+}
+
+/// Spend against one limit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BudgetStatus {
+    pub scope: BudgetScope,
+    pub spent_usd: f64,
+    pub limit_usd: f64,
+}
+
+#[derive(Debug, Default)]
+struct Spend {
+    session: f64,
+    daily: f64,
+    day: u64,
+    session_warned: bool,
+    daily_warned: bool,
+}
+
+#[derive(Debug)]
+pub struct Budget {
+    limits: BudgetLimits,
+    spend: Mutex<Spend>,
+}
+
+fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+        / (24 * 3600)
+}
+
+impl Budget {
+    /// `spent_today` is what earlier sessions already spent since midnight
+    /// UTC, so a daily limit holds across restarts.
+    pub fn new(limits: BudgetLimits, spent_today: f64) -> Self {
+        Self {
+            limits,
+            spend: Mutex::new(Spend {
+                daily: spent_today,
+                day: today(),
+                ..Spend::default()
+            }),
+        }
+    }
+
+    /// A budget with no limits; recording is still tracked.
+    pub fn unlimited() -> Self {
+        Self::new(BudgetLimits::default(), 0.0)
+    }
+
+    pub fn limits(&self) -> BudgetLimits {
+        self.limits
+    }
+
+    /// Add `cost_usd` to the session and daily totals. Returns the limits
+    /// whose warning threshold this spend crossed for the first time.
+    pub fn record(&self, cost_usd: f64) -> Vec<BudgetStatus> {
+        self.record_on(today(), cost_usd)
+    }
+
+    fn record_on(&self, day: u64, cost_usd: f64) -> Vec<BudgetStatus> {
+        let mut s = self.spend.lock().unwrap();
+        if s.day != day {
+            s.day = day;
+            s.daily = 0.0;
+            s.daily_warned = false;
+        }
+        s.session += cost_usd;
+        s.daily += cost_usd;
+
+        let warn = self.limits.warn_percent as f64 / 100.0;
+        let mut crossed = Vec::new();
+        if let Some(limit) = self.limits.session_usd {
+            if !s.session_warned && s.session >= limit * warn {
+                s.session_warned = true;
+                crossed.push(BudgetStatus {
+                    scope: BudgetScope::Session,
+                    spent_usd: s.session,
+                    limit_usd: limit,
+                });
+            }
+        }
+        if let Some(limit) = self.limits.daily_usd {
+            if !s.daily_warned && s.daily >= limit * warn {
+                s.daily_warned = true;
+                crossed.push(BudgetStatus {
+                    scope: BudgetScope::Daily,
+                    spent_usd: s.daily,
+                    limit_usd: limit,
+                });
+            }
+        }
+        crossed
+    }
+
+    /// `Ok` while every limit has room; the first exhausted limit
+    /// otherwise. Call before starting an LLM request.
+    pub fn check(&self) -> Result<(), BudgetStatus> {
+        match self.exceeded() {
+            Some(hit) => Err(hit),
+            None => Ok(()),
+        }
+    }
+
+    /// The first exhausted limit, if any.
+    pub fn exceeded(&self) -> Option<BudgetStatus> {
+        self.exceeded_on(today())
+    }
+
+    fn exceeded_on(&self, day: u64) -> Option<BudgetStatus> {
+        let s = self.spend.lock().unwrap();
+        let daily = if s.day == day { s.daily } else { 0.0 };
+        [
+            (BudgetScope::Session, s.session, self.limits.session_usd),
+            (BudgetScope::Daily, daily, self.limits.daily_usd),
+        ]
+        .into_iter()
+        .find_map(|(scope, spent, limit)| {
+            let limit = limit?;
+            (spent >= limit).then_some(BudgetStatus {
+                scope,
+                spent_usd: spent,
+                limit_usd: limit,
+            })
+        })
+    }
+
+    /// Start counting a new session from `spent_usd` (non-zero when
+    /// resuming). Re-arms the session warning.
+    pub fn reset_session(&self, spent_usd: f64) {
+        let mut s = self.spend.lock().unwrap();
+        s.session = spent_usd;
+        s.session_warned = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget(session: Option<f64>, daily: Option<f64>, spent_today: f64) -> Budget {
+        Budget::new(
+            BudgetLimits {
+                session_usd: session,
+                daily_usd: daily,
+                warn_percent: 80,
+            },
+            spent_today,
+        )
+    }
+
+    #[test]
+    fn warns_once_then_exceeds() {
+        let b = budget(Some(1.0), None, 0.0);
+        assert!(b.record(0.5).is_empty());
+        let warned = b.record(0.35);
+        assert_eq!(warned.len(), 1);
+        assert_eq!(warned[0].scope, BudgetScope::Session);
+        assert!(b.exceeded().is_none());
+        assert!(b.check().is_ok());
+        assert!(b.record(0.2).is_empty());
+        assert_eq!(b.check().unwrap_err().scope, BudgetScope::Session);
+        let hit = b.exceeded().unwrap();
+        assert_eq!(hit.scope, BudgetScope::Session);
+        assert_eq!(hit.limit_usd, 1.0);
+    }
+
+    #[test]
+    fn daily_limit_counts_earlier_spend_and_rolls_over() {
+        let b = budget(None, Some(5.0), 4.5);
+        let day = today();
+        assert_eq!(b.record_on(day, 0.1)[0].scope, BudgetScope::Daily);
+        b.record_on(day, 0.5);
+        assert_eq!(b.exceeded_on(day).unwrap().scope, BudgetScope::Daily);
+        assert!(b.exceeded_on(day + 1).is_none());
+        b.record_on(day + 1, 1.0);
+        assert!(b.exceeded_on(day + 1).is_none());
+    }
+
+    #[test]
+    fn reset_session_rearms_warning() {
+        let b = budget(Some(1.0), None, 0.0);
+        b.record(2.0);
+        assert!(b.exceeded().is_some());
+        b.reset_session(0.0);
+        assert!(b.exceeded().is_none());
+        assert_eq!(b.record(0.9).len(), 1);
+    }
+}
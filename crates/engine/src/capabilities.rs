@@ -0,0 +1,145 @@
+//! Per-model capability registry.
+//!
+//! Each capability is resolved from, in order: the model's `capabilities`
+//! overrides in config, the models.dev catalog (remote, then the bundled
+//! snapshot), and finally name-based defaults. Providers, the compactor and
+//! the attachment code consult the result so that unsupported features are
+//! turned off before a request is sent rather than rejected by the API.
+
+use crate::config::ModelConfig;
+use crate::pricing;
+
+/// How a model takes its reasoning effort.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReasoningStyle {
+    /// No reasoning controls; the effort setting is not sent.
+    None,
+    /// `reasoning.effort` (Responses API) or `reasoning_effort`.
+    Effort,
+    /// Anthropic adaptive thinking with `output_config.effort`.
+    Adaptive,
+}
+
+impl ReasoningStyle {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Effort => "effort",
+            Self::Adaptive => "adaptive",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelCapabilities {
+    /// Input context window in tokens.
+    pub context_window: Option<u32>,
+    /// Largest completion the model can produce, in tokens.
+    pub max_output: Option<u32>,
+    pub vision: bool,
+    pub reasoning: ReasoningStyle,
+    pub tool_calling: bool,
+    pub parallel_tools: bool,
+    pub prompt_caching: bool,
+}
+
+/// Resolve the capabilities of `model` served by `provider_type`.
+pub fn resolve(model: &str, provider_type: &str, config: &ModelConfig) -> ModelCapabilities {
+    let o = &config.capabilities;
+    let catalog = pricing::catalog_model(provider_type, model).unwrap_or_default();
+    let anthropic = matches!(provider_type, "anthropic" | "bedrock");
+
+    let reasoning = if anthropic {
+        // The catalog says whether a model thinks, not how: only models
+        // with adaptive thinking accept an effort without a token budget.
+        match o.reasoning {
+            Some(true) => ReasoningStyle::Adaptive,
+            Some(false) => ReasoningStyle::None,
+            None if supports_adaptive_thinking(model) => ReasoningStyle::Adaptive,
+            None => ReasoningStyle::None,
+        }
+    } else {
+        match o.reasoning.or(catalog.reasoning) {
+            Some(false) => ReasoningStyle::None,
+            _ => ReasoningStyle::Effort,
+        }
+    };
+
+    let cached_prices = catalog.pricing.map(|p| p.cache_read > 0.0);
+
+    ModelCapabilities {
+        context_window: o.context_window.or(catalog.context_window),
+        max_output: o.max_output.or(catalog.max_output),
+        vision: o.vision.or(catalog.vision).unwrap_or(true),
+        reasoning,
+        tool_calling: config.tool_calling.or(catalog.tool_calling).unwrap_or(true),
+        parallel_tools: o.parallel_tools.unwrap_or(true),
+        prompt_caching: o.prompt_caching.or(cached_prices).unwrap_or(true),
+    }
+}
+
+fn supports_adaptive_thinking(model: &str) -> bool {
+    model.contains("opus-4-6") || model.contains("sonnet-4-6")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol::CapabilityOverrides;
+
+    #[test]
+    fn catalog_fills_unset_capabilities() {
+        let caps = resolve("o3-mini", "openai", &ModelConfig::default());
+        assert_eq!(caps.context_window, Some(200_000));
+        assert!(!caps.vision);
+        assert_eq!(caps.reasoning, ReasoningStyle::Effort);
+
+        let caps = resolve("gpt-4o", "openai", &ModelConfig::default());
+        assert_eq!(caps.reasoning, ReasoningStyle::None);
+        assert!(caps.vision);
+    }
+
+    #[test]
+    fn unknown_models_keep_permissive_defaults() {
+        let caps = resolve(
+            "my-local-model",
+            "openai-compatible",
+            &ModelConfig::default(),
+        );
+        assert_eq!(caps.context_window, None);
+        assert!(caps.vision && caps.tool_calling && caps.parallel_tools);
+        assert_eq!(caps.reasoning, ReasoningStyle::Effort);
+    }
+
+    #[test]
+    fn overrides_beat_catalog() {
+        let config = ModelConfig {
+            tool_calling: Some(false),
+            capabilities: CapabilityOverrides {
+                context_window: Some(32_000),
+                vision: Some(true),
+                reasoning: Some(true),
+                parallel_tools: Some(false),
+                ..CapabilityOverrides::default()
+            },
+            ..ModelConfig::default()
+        };
+        let caps = resolve("o3-mini", "openai", &config);
+        assert_eq!(caps.context_window, Some(32_000));
+        assert!(caps.vision && !caps.tool_calling && !caps.parallel_tools);
+
+        // An Anthropic model not yet known to need adaptive thinking.
+        assert_eq!(
+            resolve("claude-opus-5", "anthropic", &ModelConfig::default()).reasoning,
+            ReasoningStyle::None
+        );
+        assert_eq!(
+            resolve("claude-opus-5", "anthropic", &config).reasoning,
+            ReasoningStyle::Adaptive
+        );
+        assert_eq!(
+            resolve("claude-sonnet-4-6", "anthropic", &ModelConfig::default()).reasoning,
+            ReasoningStyle::Adaptive
+        );
+    }
+}
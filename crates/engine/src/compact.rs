@@ -1,7 +1,18 @@
 //! Conversation history compaction.
 //!
-//! Replaces older history with a model-generated handoff summary so the
-//! conversation can keep growing without overflowing the context window.
+//! Compaction runs in stages so the conversation can keep growing without
+//! overflowing the context window:
+//!
+//! 1. Tool outputs older than the preserved window are pruned to short
+//!    stubs. When that alone frees enough room (automatic compaction only),
+//!    no summarization call is made.
+//! 2. Otherwise only the history before the last [`PRESERVED_TURNS`] turns
+//!    is summarized; those turns are kept verbatim after the summary.
+//!
+//! The summary is structured (progress, facts, open tasks, files touched).
+//! A prior summary is handed back to the summarizer to merge, and the
+//! "files touched" list is maintained from tool calls rather than by the
+//! model, so it survives any number of compactions.
 //!
 //! Resilience notes:
 //! - The summarization call itself can overflow the model's context window
@@ -24,6 +35,34 @@ pub const SUMMARIZATION_PROMPT: &str = include_str!("prompts/compact.md");
 /// reference material rather than a user instruction.
 pub const SUMMARY_PREFIX: &str = include_str!("prompts/compact_summary_prefix.md");
 
+/// Turns (a user message and everything after it) kept verbatim after the
+/// summary.
+pub const PRESERVED_TURNS: usize = 2;
+
+/// Most recent tool outputs left unpruned, even inside the preserved turns:
+/// a single long autonomous turn can hold most of the context.
+const PRESERVED_TOOL_OUTPUTS: usize = 4;
+
+/// Tool outputs shorter than this are not worth replacing with a stub.
+const PRUNE_MIN_BYTES: usize = 512;
+
+/// Lead-in of the stub that replaces a pruned tool output.
+const PRUNED_PREFIX: &str = "[tool output pruned during compaction:";
+
+/// Automatic compaction skips summarizing when pruning alone brings the
+/// prompt under this percentage of the context window.
+pub const PRUNE_TARGET_PERCENT: usize = 50;
+
+/// Heading of the summary section maintained from tool calls.
+const FILES_SECTION: &str = "## Files touched";
+
+/// File-editing tools and the argument that names the file.
+const FILE_TOOLS: &[(&str, &str)] = &[
+    ("edit_file", "file_path"),
+    ("write_file", "file_path"),
+    ("edit_notebook", "notebook_path"),
+];
+
 /// Soft cap on user-message text preserved verbatim after compaction, so the
 /// replacement history leaves room for the next turn in the context window.
 pub const COMPACT_USER_MESSAGE_MAX_TOKENS: usize = 20_000;
@@ -40,7 +79,8 @@ const MAX_CONTEXT_TRIMS: usize = 20;
 /// summary.
 const MAX_EMPTY_RETRIES: u8 = 2;
 
-/// Controls how much context the replacement history preserves.
+/// Controls which user messages are carried forward when the whole history
+/// had to be summarized (too few turns to preserve any verbatim).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum InitialContextInjection {
     /// Drop everything except the summary. Fits a user-initiated `/compact`
@@ -93,11 +133,15 @@ pub struct CompactOptions {
     pub injection: InitialContextInjection,
     pub phase: CompactPhase,
     pub reason: CompactReason,
+    /// When set, stop after pruning if the history's estimated size is at
+    /// most this many tokens. See [`prune_target`].
+    pub prune_target: Option<usize>,
 }
 
 /// Run a compaction pass against `history` (which must NOT contain the
 /// system prompt — the caller owns that) and return the replacement
-/// history plus token usage.
+/// history plus the summarizer's token usage (`None` when pruning was
+/// enough and no summarization call was made).
 ///
 /// Resilience:
 /// - Retries with exponential backoff are already handled inside
@@ -114,7 +158,7 @@ pub async fn run_compact(
     instructions: Option<&str>,
     cancel: &CancellationToken,
     options: CompactOptions,
-) -> Result<(Vec<Message>, TokenUsage), ProviderError> {
+) -> Result<(Vec<Message>, Option<TokenUsage>), ProviderError> {
     if history.is_empty() {
         return Err(ProviderError::InvalidResponse(
             "not enough history to compact".into(),
@@ -125,6 +169,7 @@ pub async fn run_compact(
         injection,
         phase,
         reason,
+        prune_target,
     } = options;
 
     log::entry(
@@ -141,6 +186,50 @@ pub async fn run_compact(
         }),
     );
 
+    // Stage 1: prune tool outputs before the preserved window, and inside
+    // it all but the most recent few.
+    let mut messages = history.to_vec();
+    let split = preserved_start(&messages, PRESERVED_TURNS);
+    let prune_end = split.max(recent_tool_outputs_start(&messages, PRESERVED_TOOL_OUTPUTS));
+    let pruned = prune_tool_outputs(&mut messages[..prune_end]);
+
+    if let Some(target) = prune_target {
+        let tokens = approx_history_tokens(&messages);
+        if pruned > 0 && tokens <= target {
+            log::entry(
+                log::Level::Info,
+                "compaction_complete",
+                &serde_json::json!({
+                    "phase": phase.as_str(),
+                    "reason": reason.as_str(),
+                    "stage": "prune",
+                    "pruned_outputs": pruned,
+                    "approx_tokens": tokens,
+                }),
+            );
+            return Ok((messages, None));
+        }
+    }
+
+    // Stage 2: summarize everything before the preserved turns. With too
+    // few turns to preserve, the whole history is summarized.
+    let (older, recent) = if split > 0 {
+        messages.split_at(split)
+    } else {
+        (&messages[..], &[][..])
+    };
+    let previous = previous_summary(older);
+    let to_summarize: Vec<Message> = older
+        .iter()
+        .filter(|m| !is_summary_message(m))
+        .cloned()
+        .collect();
+    if to_summarize.is_empty() {
+        return Err(ProviderError::InvalidResponse(
+            "not enough history to compact".into(),
+        ));
+    }
+
     let mut window_start = 0usize;
     let mut empty_retries = 0u8;
     let mut context_trims = 0usize;
@@ -150,8 +239,12 @@ pub async fn run_compact(
             return Err(ProviderError::Cancelled);
         }
 
-        let request_messages =
-            build_summarize_request(&history[window_start..], instructions, SUMMARIZATION_PROMPT);
+        let request_messages = build_summarize_request(
+            &to_summarize[window_start..],
+            previous.as_deref(),
+            instructions,
+            SUMMARIZATION_PROMPT,
+        );
 
         let opts = ChatOptions::new(cancel);
         match provider
@@ -177,7 +270,7 @@ pub async fn run_compact(
                 break (text, resp.usage);
             }
             Err(e) if is_context_window_error(&e) => {
-                if window_start + 1 < history.len() && context_trims < MAX_CONTEXT_TRIMS {
+                if window_start + 1 < to_summarize.len() && context_trims < MAX_CONTEXT_TRIMS {
                     window_start += 1;
                     context_trims += 1;
                     log::entry(
@@ -185,7 +278,7 @@ pub async fn run_compact(
                         "compaction_trim_oldest",
                         &serde_json::json!({
                             "trimmed": context_trims,
-                            "remaining_items": history.len() - window_start,
+                            "remaining_items": to_summarize.len() - window_start,
                             "error": e.to_string(),
                         }),
                     );
@@ -215,8 +308,24 @@ pub async fn run_compact(
         }
     };
 
-    let user_messages = collect_user_messages(history);
-    let replacement = build_compacted_history(user_messages, &summary_text, injection);
+    let mut files = previous
+        .as_deref()
+        .map(|p| section_items(p, FILES_SECTION))
+        .unwrap_or_default();
+    for file in files_touched(older) {
+        if !files.contains(&file) {
+            files.push(file);
+        }
+    }
+    let summary = structured_summary(&summary_text, &files);
+
+    let replacement = if recent.is_empty() {
+        build_compacted_history(collect_user_messages(history), &summary, injection)
+    } else {
+        let mut out = vec![summary_message(&summary)];
+        out.extend_from_slice(recent);
+        out
+    };
 
     log::entry(
         log::Level::Info,
@@ -224,20 +333,187 @@ pub async fn run_compact(
         &serde_json::json!({
             "phase": phase.as_str(),
             "reason": reason.as_str(),
+            "stage": "summarize",
+            "pruned_outputs": pruned,
             "context_trims": context_trims,
             "empty_retries": empty_retries,
-            "user_messages_kept": replacement.len().saturating_sub(1),
+            "messages_preserved": recent.len(),
+            "files_touched": files.len(),
             "prompt_tokens": usage.prompt_tokens,
             "completion_tokens": usage.completion_tokens,
         }),
     );
 
-    Ok((replacement, usage))
+    Ok((replacement, Some(usage)))
+}
+
+/// Token target for [`CompactOptions::prune_target`]: the history budget
+/// that keeps the prompt under [`PRUNE_TARGET_PERCENT`] of the context
+/// window, given that `prompt_tokens` were last reported for `history`
+/// (the difference is system prompt and tool definitions).
+pub fn prune_target(context_window: u32, prompt_tokens: u32, history: &[Message]) -> usize {
+    let overhead = (prompt_tokens as usize).saturating_sub(approx_history_tokens(history));
+    (context_window as usize * PRUNE_TARGET_PERCENT / 100).saturating_sub(overhead)
+}
+
+/// Index of the first message of the last `turns` turns. A turn starts at
+/// a user message; prior summaries don't count. Returns 0 when the history
+/// holds `turns` turns or fewer, i.e. nothing precedes the window.
+fn preserved_start(history: &[Message], turns: usize) -> usize {
+    if turns == 0 {
+        return history.len();
+    }
+    let starts: Vec<usize> = history
+        .iter()
+        .enumerate()
+        .filter(|(_, m)| matches!(m.role, Role::User) && !is_summary_message(m))
+        .map(|(i, _)| i)
+        .collect();
+    if starts.len() <= turns {
+        return 0;
+    }
+    starts[starts.len() - turns]
+}
+
+/// Index of the `keep`-th last tool output, so everything before it can be
+/// pruned. Returns 0 when there are `keep` outputs or fewer.
+fn recent_tool_outputs_start(history: &[Message], keep: usize) -> usize {
+    history
+        .iter()
+        .enumerate()
+        .rev()
+        .filter(|(_, m)| matches!(m.role, Role::Tool))
+        .nth(keep.saturating_sub(1))
+        .map(|(i, _)| i)
+        .unwrap_or(0)
+}
+
+/// Replace large tool outputs with a stub naming their size. Returns how
+/// many were pruned; already-pruned outputs are left alone.
+pub fn prune_tool_outputs(messages: &mut [Message]) -> usize {
+    let mut pruned = 0;
+    for m in messages.iter_mut() {
+        if !matches!(m.role, Role::Tool) {
+            continue;
+        }
+        let Some(text) = m.content.as_ref().map(|c| c.text_content()) else {
+            continue;
+        };
+        if text.len() < PRUNE_MIN_BYTES || text.starts_with(PRUNED_PREFIX) {
+            continue;
+        }
+        m.content = Some(Content::text(format!(
+            "{PRUNED_PREFIX} {} bytes]",
+            text.len()
+        )));
+        pruned += 1;
+    }
+    pruned
+}
+
+/// Rough token size of `messages`, counting text, reasoning and tool-call
+/// arguments.
+pub fn approx_history_tokens(messages: &[Message]) -> usize {
+    messages
+        .iter()
+        .map(|m| {
+            let mut bytes = m.content.as_ref().map_or(0, |c| c.text_content().len());
+            bytes += m.reasoning_content.as_ref().map_or(0, String::len);
+            if let Some(calls) = &m.tool_calls {
+                bytes += calls
+                    .iter()
+                    .map(|c| c.function.name.len() + c.function.arguments.len())
+                    .sum::<usize>();
+            }
+            bytes.div_ceil(4)
+        })
+        .sum()
+}
+
+/// Body of the most recent handoff summary in `messages`, without the
+/// lead-in.
+fn previous_summary(messages: &[Message]) -> Option<String> {
+    let m = messages.iter().rev().find(|m| is_summary_message(m))?;
+    let text = m.content.as_ref()?.as_text();
+    Some(
+        text.trim_start()
+            .strip_prefix(SUMMARY_PREFIX.trim_end())
+            .unwrap_or(text)
+            .trim()
+            .to_string(),
+    )
+}
+
+/// Files written or edited by tool calls in `messages`, in first-touch
+/// order.
+fn files_touched(messages: &[Message]) -> Vec<String> {
+    let mut files: Vec<String> = Vec::new();
+    let calls = messages
+        .iter()
+        .filter_map(|m| m.tool_calls.as_ref())
+        .flatten();
+    for call in calls {
+        let Some((_, arg)) = FILE_TOOLS
+            .iter()
+            .find(|(name, _)| *name == call.function.name)
+        else {
+            continue;
+        };
+        let Ok(args) = serde_json::from_str::<serde_json::Value>(&call.function.arguments) else {
+            continue;
+        };
+        if let Some(path) = args.get(*arg).and_then(|v| v.as_str()) {
+            if !path.is_empty() && !files.iter().any(|f| f == path) {
+                files.push(path.to_string());
+            }
+        }
+    }
+    files
+}
+
+/// Bullet items (`- ...`) under the `heading` section of a summary.
+fn section_items(summary: &str, heading: &str) -> Vec<String> {
+    summary
+        .lines()
+        .skip_while(|l| l.trim() != heading)
+        .skip(1)
+        .take_while(|l| !l.trim_start().starts_with("## "))
+        .filter_map(|l| l.trim().strip_prefix("- "))
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty() && item != "none")
+        .collect()
+}
+
+/// The model's summary with the files-touched section replaced by `files`.
+fn structured_summary(model_text: &str, files: &[String]) -> String {
+    let mut in_files = false;
+    let mut out: Vec<&str> = Vec::new();
+    for line in model_text.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("## ") {
+            in_files = trimmed == FILES_SECTION;
+        }
+        if !in_files {
+            out.push(line);
+        }
+    }
+    let mut text = out.join("\n").trim().to_string();
+    text.push_str("\n\n");
+    text.push_str(FILES_SECTION);
+    if files.is_empty() {
+        text.push_str("\n- none");
+    }
+    for file in files {
+        text.push_str("\n- ");
+        text.push_str(file);
+    }
+    text
 }
 
 /// Build the messages that get sent to the summarizer.
 fn build_summarize_request(
     history: &[Message],
+    previous_summary: Option<&str>,
     instructions: Option<&str>,
     prompt: &str,
 ) -> Vec<Message> {
@@ -255,11 +531,15 @@ fn build_summarize_request(
         }
     }
 
+    let request = match previous_summary {
+        Some(previous) => format!(
+            "Previous summary:\n\n{previous}\n\nConversation to summarize:\n\n{conversation}"
+        ),
+        None => format!("Conversation to summarize:\n\n{conversation}"),
+    };
     vec![
         Message::system(system_text),
-        Message::user(Content::text(format!(
-            "Conversation to summarize:\n\n{conversation}"
-        ))),
+        Message::user(Content::text(request)),
     ]
 }
 
@@ -395,15 +675,19 @@ pub fn build_compacted_history(
         }
     }
 
+    out.push(summary_message(summary_text));
+    out
+}
+
+/// The handoff summary as a user message, behind [`SUMMARY_PREFIX`].
+fn summary_message(summary_text: &str) -> Message {
     let summary_body = if summary_text.trim().is_empty() {
         "(no summary available)".to_string()
     } else {
         summary_text.trim().to_string()
     };
     let prefixed = format!("{}\n{}", SUMMARY_PREFIX.trim_end(), summary_body);
-    out.push(Message::user(Content::text(prefixed)));
-
-    out
+    Message::user(Content::text(prefixed))
 }
 
 /// Select the most recent user messages (in chronological order) that fit
@@ -537,6 +821,112 @@ mod tests {
         assert_eq!(collected, vec!["hello".to_string(), "hi again".to_string()]);
     }
 
+    fn edit_call(id: &str, path: &str) -> Message {
+        let call = protocol::ToolCall::new(
+            id.into(),
+            protocol::FunctionCall {
+                name: "edit_file".into(),
+                arguments: serde_json::json!({ "file_path": path }).to_string(),
+            },
+        );
+        Message::assistant(None, None, Some(vec![call]))
+    }
+
+    #[test]
+    fn preserved_window_starts_at_nth_last_turn() {
+        let history = vec![
+            Message::user(Content::text("one")),
+            Message::tool("c1".into(), "x", false),
+            Message::user(Content::text("two")),
+            Message::user(Content::text("three")),
+            Message::tool("c2".into(), "y", false),
+        ];
+        assert_eq!(preserved_start(&history, 2), 2);
+        assert_eq!(preserved_start(&history, 3), 0);
+        assert_eq!(preserved_start(&history, 0), history.len());
+    }
+
+    #[test]
+    fn prune_stubs_large_outputs_once() {
+        let mut history = vec![
+            Message::tool("a".into(), "x".repeat(PRUNE_MIN_BYTES), false),
+            Message::tool("b".into(), "short", false),
+        ];
+        assert_eq!(prune_tool_outputs(&mut history), 1);
+        let stub = history[0].content.as_ref().unwrap().as_text().to_string();
+        assert!(stub.starts_with(PRUNED_PREFIX));
+        assert_eq!(history[1].content.as_ref().unwrap().as_text(), "short");
+        assert_eq!(prune_tool_outputs(&mut history), 0);
+    }
+
+    #[test]
+    fn recent_tool_outputs_are_kept() {
+        let history: Vec<Message> = (0..6)
+            .map(|i| Message::tool(format!("c{i}"), "out", false))
+            .collect();
+        assert_eq!(recent_tool_outputs_start(&history, 4), 2);
+        assert_eq!(recent_tool_outputs_start(&history[..3], 4), 0);
+    }
+
+    #[tokio::test]
+    async fn prune_reaches_into_a_huge_preserved_turn() {
+        let big = "x".repeat(PRUNE_MIN_BYTES * 4);
+        let mut history = vec![
+            Message::user(Content::text("one")),
+            Message::user(Content::text("two")),
+            Message::user(Content::text("three")),
+        ];
+        history.extend((0..6).map(|i| Message::tool(format!("c{i}"), big.clone(), false)));
+        let provider = Provider::new(
+            "http://127.0.0.1:9".into(),
+            String::new(),
+            "openai-compatible",
+            reqwest::Client::new(),
+        );
+        let options = CompactOptions {
+            injection: InitialContextInjection::DoNotInject,
+            phase: CompactPhase::MidTurn,
+            reason: CompactReason::ContextLimit,
+            prune_target: Some(approx_history_tokens(&history) * 3 / 4),
+        };
+        let cancel = CancellationToken::new();
+        let (pruned, usage) = run_compact(&provider, &history, "m", None, &cancel, options)
+            .await
+            .expect("pruning alone is enough");
+        assert!(usage.is_none(), "no summarization call");
+        let texts: Vec<&str> = pruned[3..]
+            .iter()
+            .map(|m| m.content.as_ref().unwrap().as_text())
+            .collect();
+        assert!(texts[..2].iter().all(|t| t.starts_with(PRUNED_PREFIX)));
+        assert!(texts[2..].iter().all(|t| *t == big));
+    }
+
+    #[test]
+    fn files_section_survives_recompaction() {
+        let first = structured_summary(
+            "## Progress\ndid things\n\n## Files touched\n- made/up.rs",
+            &files_touched(&[edit_call("1", "src/a.rs"), edit_call("2", "src/a.rs")]),
+        );
+        assert!(!first.contains("made/up.rs"));
+        assert_eq!(section_items(&first, FILES_SECTION), vec!["src/a.rs"]);
+
+        let history = vec![
+            summary_message(&first),
+            Message::user(Content::text("next")),
+            edit_call("3", "src/b.rs"),
+        ];
+        let previous = previous_summary(&history).unwrap();
+        assert!(previous.starts_with("## Progress"));
+        let mut files = section_items(&previous, FILES_SECTION);
+        files.extend(files_touched(&history));
+        let second = structured_summary("## Progress\nmore", &files);
+        assert_eq!(
+            section_items(&second, FILES_SECTION),
+            vec!["src/a.rs", "src/b.rs"]
+        );
+    }
+
     #[test]
     fn context_error_detection() {
         assert!(is_context_window_error(&ProviderError::InvalidResponse(
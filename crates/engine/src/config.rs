@@ -18,10 +18,79 @@ pub struct ModelConfig {
     pub cache_read_cost: Option<f64>,
     /// Cost per 1M cache-write tokens in USD.
     pub cache_write_cost: Option<f64>,
+    /// Multiplier and per-request charge, merged from provider and model.
+    pub pricing: crate::pricing::PriceRule,
+    /// Lifetime of Anthropic prompt-cache entries. Defaults to 5 minutes.
+    pub cache_ttl: Option<CacheTtl>,
+    /// Cheaper, slower processing tier. Set from `--service-tier`.
+    pub service_tier: Option<ServiceTier>,
+    /// `api-version` query parameter for Azure OpenAI.
+    pub api_version: Option<String>,
+    /// Token command and extra headers from the provider config.
+    pub auth: protocol::ProviderAuth,
+    /// Overrides for the capability registry.
+    pub capabilities: protocol::CapabilityOverrides,
 }
 
 impl ModelConfig {
-    pub fn tool_calling(&self) -> bool {
-        self.tool_calling.unwrap_or(true)
+    pub fn cache_ttl(&self) -> CacheTtl {
+        self.cache_ttl.unwrap_or_default()
+    }
+}
+
+/// How long cached prompt prefixes live on the provider side.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum CacheTtl {
+    /// Send no cache breakpoints.
+    #[serde(rename = "off")]
+    Off,
+    #[default]
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    /// Longer-lived entries; writes are billed at twice the input rate.
+    #[serde(rename = "1h")]
+    OneHour,
+}
+
+impl CacheTtl {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::FiveMinutes => "5m",
+            Self::OneHour => "1h",
+        }
+    }
+}
+
+/// Discounted processing tiers for non-interactive runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ServiceTier {
+    /// OpenAI flex processing: same API, slower and may be queued.
+    Flex,
+    /// Provider batch API: the request is submitted as a one-item batch and
+    /// polled until it completes.
+    Batch,
+}
+
+impl ServiceTier {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Flex => "flex",
+            Self::Batch => "batch",
+        }
+    }
+
+    /// Fraction of the standard price charged on this tier.
+    pub fn price_factor(self) -> f64 {
+        0.5
+    }
+
+    /// Whether a provider type offers this tier.
+    pub fn supported_by(self, provider_type: &str) -> bool {
+        match self {
+            Self::Flex => provider_type == "openai",
+            Self::Batch => matches!(provider_type, "openai" | "anthropic"),
+        }
     }
 }
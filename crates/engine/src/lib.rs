@@ -1,6 +1,9 @@
 mod agent;
+pub mod audit;
 pub mod auth;
+pub mod budget;
 pub mod cancel;
+pub mod capabilities;
 pub mod compact;
 pub mod config;
 pub mod config_file;
@@ -10,12 +13,16 @@ pub mod mcp;
 pub mod paths;
 pub mod permissions;
 pub mod plan;
+pub mod policy;
 pub mod pricing;
+pub mod profiles;
 pub mod provider;
 pub mod redact;
 pub mod registry;
+pub mod schema;
 pub mod skills;
 pub mod socket;
+mod steer;
 pub mod tools;
 
 use protocol::{EngineEvent, UiCommand};
@@ -49,6 +56,7 @@ pub use config::ModelConfig;
 pub use mcp::McpServerConfig;
 pub use paths::{cache_dir, config_dir, home_dir, state_dir};
 pub use permissions::Permissions;
+pub use profiles::AgentProfiles;
 pub use provider::{Provider, ProviderKind};
 pub use skills::SkillLoader;
 
@@ -123,6 +131,7 @@ pub fn render_system_prompt(ctx: &PromptContext<'_>) -> String {
             agent_id => agent_id,
             parent_id => parent_id,
             siblings => siblings,
+            profiles => ctx.multi_agent.map(|m| m.profiles.as_str()).unwrap_or(""),
             skills_section => ctx.skills_section.unwrap_or(""),
             extra_instructions => ctx.extra_instructions.unwrap_or(""),
         })
@@ -154,6 +163,9 @@ pub struct AgentPromptConfig {
     pub parent_id: Option<String>,
     /// Sibling agent names (other children of the same parent).
     pub siblings: Vec<String>,
+    /// Profiles `spawn_agent` accepts, one per line. Empty when there are
+    /// none or this agent cannot spawn.
+    pub profiles: String,
 }
 
 /// Multi-agent configuration. Present when multi-agent mode is enabled.
@@ -165,6 +177,19 @@ pub struct MultiAgentConfig {
     /// Optional preselected agent ID for interactive root agents.
     /// When provided, engine tools use this exact identity.
     pub agent_id: Option<String>,
+    /// Profiles subagents can be spawned from.
+    pub profiles: Arc<AgentProfiles>,
+}
+
+/// Per-turn caps for autonomous runs. `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TurnLimits {
+    /// LLM round-trips per turn.
+    pub max_turns: Option<u32>,
+    /// Tool calls executed per turn. Calls past the cap are refused.
+    pub max_tool_calls: Option<u32>,
+    /// Wall-clock time per turn.
+    pub timeout: Option<std::time::Duration>,
 }
 
 /// API connection and model configuration, grouped for clarity.
@@ -224,6 +249,13 @@ pub struct EngineConfig {
     /// When true, redact detected secrets from messages sent to the LLM,
     /// debug logs, and inter-agent socket communication.
     pub redact_secrets: bool,
+    /// Spending limits shared between engine and TUI.
+    pub budget: Arc<budget::Budget>,
+    /// Turn, tool-call and wall-clock caps applied to every turn.
+    pub turn_limits: TurnLimits,
+    /// When set, only these tools (including MCP tools) are available.
+    /// Comes from a subagent profile.
+    pub allowed_tools: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -262,7 +294,11 @@ pub struct EngineHandle {
     event_tx: mpsc::UnboundedSender<EngineEvent>,
     event_rx: mpsc::UnboundedReceiver<EngineEvent>,
     pub processes: tools::ProcessRegistry,
+    /// Pre-edit baselines of files mutated this session.
+    pub changes: tools::SessionChanges,
     pub permissions: Arc<Permissions>,
+    /// Spend tracking and limits, shared with the engine.
+    pub budget: Arc<budget::Budget>,
     runtime_approvals: Arc<std::sync::RwLock<permissions::RuntimeApprovals>>,
     agent_msg_tx: Option<tokio::sync::broadcast::Sender<tools::AgentMessageNotification>>,
     spawned_rx: Option<mpsc::UnboundedReceiver<tools::SpawnedChild>>,
@@ -371,10 +407,11 @@ pub fn start(config: EngineConfig) -> EngineHandle {
     let (event_tx, event_rx) = mpsc::unbounded_channel();
 
     let processes = tools::ProcessRegistry::new();
+    let changes = tools::SessionChanges::new();
 
-    // Broadcast channel for agent message notifications (blocking spawn_agent).
-    // Only created for interactive agents (depth == 0) that can spawn children.
-    let agent_msg_tx = if config.multi_agent.as_ref().is_some_and(|ma| ma.depth == 0) {
+    // Broadcast channel for agent message notifications: blocking
+    // spawn_agent and message_agent calls waiting for a structured reply.
+    let agent_msg_tx = if config.multi_agent.is_some() {
         let (tx, _) = tokio::sync::broadcast::channel(16);
         Some(tx)
     } else {
@@ -410,6 +447,7 @@ pub fn start(config: EngineConfig) -> EngineHandle {
             api_key_env: config.api.key_env.clone(),
             model: config.api.model_config.name.clone().unwrap_or_default(),
             provider_type: config.api.provider_type.clone(),
+            profiles: Arc::clone(&ma.profiles),
             agent_msg_tx: agent_msg_tx.clone(),
             spawned_tx: Some(spawned_tx),
         })
@@ -421,6 +459,7 @@ pub fn start(config: EngineConfig) -> EngineHandle {
 
     let permissions = Arc::clone(&config.permissions);
     let runtime_approvals = Arc::clone(&config.runtime_approvals);
+    let budget = Arc::clone(&config.budget);
     let has_multi_agent = config.multi_agent.is_some();
     let processes_clone = processes.clone();
     let event_tx_clone = event_tx.clone();
@@ -428,6 +467,7 @@ pub fn start(config: EngineConfig) -> EngineHandle {
         config,
         registry,
         processes_clone,
+        changes.clone(),
         cmd_rx,
         event_tx,
     ));
@@ -437,7 +477,9 @@ pub fn start(config: EngineConfig) -> EngineHandle {
         event_tx: event_tx_clone,
         event_rx,
         processes,
+        changes,
         permissions,
+        budget,
         runtime_approvals,
         agent_msg_tx,
         spawned_rx: if has_multi_agent {
@@ -4,10 +4,11 @@
 //! `"cargo test *"` once, and future matching calls skip the confirm dialog.
 
 use crate::permissions::bash::split_shell_commands;
-use crate::permissions::rules::{check_ruleset, Decision, RuleSet};
+use crate::permissions::rules::{check_ruleset, matches_rule, Decision, RuleOrigin, RuleSet};
 use crate::permissions::Permissions;
 use protocol::Mode;
 use serde_json::Value;
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
@@ -30,23 +31,12 @@ impl RuntimeApprovals {
 
     /// Add session-scoped tool approval patterns. Empty `patterns` = blanket approval.
     pub fn add_session_tool(&mut self, tool: &str, patterns: Vec<glob::Pattern>) {
-        let entry = self.session_tools.entry(tool.to_string()).or_default();
-        if patterns.is_empty() || entry.is_empty() {
-            // Blanket approval: clear existing patterns.
-            entry.clear();
-        } else {
-            entry.extend(patterns);
-        }
+        add_tool_patterns(&mut self.session_tools, tool, patterns);
     }
 
     /// Add workspace-scoped tool approval patterns. Empty `patterns` = blanket approval.
     pub fn add_workspace_tool(&mut self, tool: &str, patterns: Vec<glob::Pattern>) {
-        let entry = self.workspace_tools.entry(tool.to_string()).or_default();
-        if patterns.is_empty() || entry.is_empty() {
-            entry.clear();
-        } else {
-            entry.extend(patterns);
-        }
+        add_tool_patterns(&mut self.workspace_tools, tool, patterns);
     }
 
     pub fn add_session_dir(&mut self, dir: PathBuf) {
@@ -118,7 +108,7 @@ impl RuntimeApprovals {
 
         subcmds.iter().all(|sc| {
             // Check runtime approval patterns.
-            all_pats.iter().any(|p| p.matches(sc))
+            all_pats.iter().any(|p| matches_rule(p, sc))
             // For bash: also check if the config already allows this subcommand
             // (e.g. DEFAULT_BASH_ALLOW patterns like "head *", "cat *").
             // The full compound command was Ask because of OTHER subcommands,
@@ -140,6 +130,10 @@ impl RuntimeApprovals {
         args: &HashMap<String, Value>,
         desc: &str,
     ) -> bool {
+        // An `ask` path rule means every time, not until approved once.
+        if permissions.check_paths(mode, tool_name, args) == Some(Decision::Ask) {
+            return false;
+        }
         let config_bash = if tool_name == "bash" {
             Some(permissions.bash_ruleset(mode))
         } else {
@@ -162,6 +156,61 @@ impl RuntimeApprovals {
         false
     }
 
+    /// Whether workspace approvals alone cover the call, i.e. the approval
+    /// [`Self::is_auto_approved`] found is workspace-scoped.
+    pub fn is_workspace_approved(
+        &self,
+        permissions: &Permissions,
+        mode: Mode,
+        tool_name: &str,
+        args: &HashMap<String, Value>,
+        desc: &str,
+    ) -> bool {
+        let workspace_only = Self {
+            workspace_tools: self.workspace_tools.clone(),
+            workspace_dirs: self.workspace_dirs.clone(),
+            ..Self::default()
+        };
+        workspace_only.is_auto_approved(permissions, mode, tool_name, args, desc)
+    }
+
+    /// The saved approval for `tool_name` that covers `subject` (a
+    /// sub-command or call description), as `(scope, pattern)`. A blanket
+    /// approval reports `*`. Session approvals are reported first.
+    pub fn approval_for(&self, tool_name: &str, subject: &str) -> Option<(RuleOrigin, String)> {
+        let scopes = [
+            (RuleOrigin::Session, &self.session_tools),
+            (RuleOrigin::Workspace, &self.workspace_tools),
+        ];
+        for (origin, tools) in scopes {
+            let Some(pats) = tools.get(tool_name) else {
+                continue;
+            };
+            if pats.is_empty() {
+                return Some((origin, "*".into()));
+            }
+            if let Some(p) = pats.iter().find(|p| matches_rule(p, subject)) {
+                return Some((origin, p.as_str().to_string()));
+            }
+        }
+        None
+    }
+
+    /// The saved directory approval covering `path`, as `(scope, dir)`.
+    pub fn dir_approval_for(&self, path: &str) -> Option<(RuleOrigin, PathBuf)> {
+        let path = crate::paths::expand_tilde(Path::new(path));
+        let dir = path.parent().unwrap_or(&path);
+        let session = self.session_dirs.iter().map(|d| (RuleOrigin::Session, d));
+        let workspace = self
+            .workspace_dirs
+            .iter()
+            .map(|d| (RuleOrigin::Workspace, d));
+        session
+            .chain(workspace)
+            .find(|(_, ad)| dir.starts_with(ad) || path.starts_with(ad))
+            .map(|(origin, ad)| (origin, ad.clone()))
+    }
+
     /// Check whether a specific pattern string is already present in the
     /// runtime approvals for a tool (used to filter already-approved patterns
     /// from the confirm dialog options).
@@ -226,3 +275,20 @@ impl RuntimeApprovals {
         })
     }
 }
+
+fn add_tool_patterns(
+    tools: &mut HashMap<String, Vec<glob::Pattern>>,
+    tool: &str,
+    patterns: Vec<glob::Pattern>,
+) {
+    match tools.entry(tool.to_string()) {
+        // An empty list is already a blanket approval; keep it.
+        Entry::Occupied(e) if e.get().is_empty() => {}
+        // Blanket approval: clear existing patterns.
+        Entry::Occupied(mut e) if patterns.is_empty() => e.get_mut().clear(),
+        Entry::Occupied(mut e) => e.get_mut().extend(patterns),
+        Entry::Vacant(e) => {
+            e.insert(patterns);
+        }
+    }
+}
@@ -0,0 +1,249 @@
+//! Dry-run explanation of a permission decision.
+//!
+//! [`Permissions::explain`] walks the same rules as [`Permissions::decide`]
+//! and records every step: how a bash command was split, which rule each
+//! piece matched and where that rule came from, the workspace restriction,
+//! saved approvals, and any managed-policy deny. The final decision comes
+//! from [`Permissions::decide_call`], the path the engine takes for a real
+//! call, including the tool's own override and the forced ask for calls that
+//! restore a redacted secret.
+
+use super::bash::{has_output_redirection, is_cd_command, split_shell_commands};
+use super::rules::{check_ruleset_rule, winning_rule, Decision, RuleOrigin, RuleSet};
+use super::workspace::{extract_tool_paths, matches_path_rule, modifies_files, FILE_TOOLS};
+use super::{Permissions, RuntimeApprovals, ToolCall};
+use crate::audit::AuditSource;
+use crate::tools::str_arg;
+use protocol::Mode;
+use std::fmt;
+
+/// A rule that matched, e.g. `bash.allow "git *"` from config.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleMatch {
+    /// `tools`, `bash`, `web_fetch`, `mcp`, `paths` or `approval`.
+    pub category: &'static str,
+    pub decision: Decision,
+    pub pattern: String,
+    pub origin: RuleOrigin,
+}
+
+impl fmt::Display for RuleMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{} \"{}\" ({})",
+            self.category,
+            self.decision.label(),
+            self.pattern,
+            self.origin.label()
+        )
+    }
+}
+
+/// One thing the rules were matched against: a bash sub-command, a URL, a
+/// file path or an MCP tool name.
+#[derive(Debug, Clone)]
+pub struct Step {
+    pub subject: String,
+    /// The decision for this piece alone; `None` when no rule applies and
+    /// the tool rule decides.
+    pub decision: Option<Decision>,
+    pub rule: Option<RuleMatch>,
+    /// Why the decision differs from the rule, or why no rule was needed.
+    pub note: Option<&'static str>,
+    /// Saved approval covering this bash sub-command.
+    pub approval: Option<RuleMatch>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Explanation {
+    pub mode: Mode,
+    /// The mode whose rules applied, when the managed policy disables `mode`.
+    pub fallback_mode: Option<Mode>,
+    /// Managed-policy deny rule that blocks the call regardless of the rest.
+    pub managed: Option<RuleMatch>,
+    pub tool: String,
+    /// The per-tool rule (`tools.allow`, ...), if the tool has one.
+    pub tool_rule: Option<RuleMatch>,
+    pub steps: Vec<Step>,
+    /// Paths outside the workspace, each with the directory approval that
+    /// covers it, if any.
+    pub outside_workspace: Vec<(String, Option<RuleMatch>)>,
+    /// Allow was downgraded to Ask by `restrict_to_workspace`.
+    pub downgraded: bool,
+    /// Saved approval covering the whole call (tools other than bash).
+    pub approval: Option<RuleMatch>,
+    /// What produced the final decision; `Tool` when the tool's own
+    /// override decided.
+    pub source: AuditSource,
+    /// The call restores a redacted secret, so it asks unless denied.
+    pub uses_secrets: bool,
+    /// The decision before saved approvals.
+    pub rule_decision: Decision,
+    /// The decision after saved approvals.
+    pub decision: Decision,
+}
+
+fn approval_match((origin, pattern): (RuleOrigin, String)) -> RuleMatch {
+    RuleMatch {
+        category: "approval",
+        decision: Decision::Allow,
+        pattern,
+        origin,
+    }
+}
+
+impl Permissions {
+    /// Explain how [`Self::decide_call`] reaches its decision for `call`,
+    /// with `approvals` standing in for the session and workspace approvals.
+    pub fn explain(
+        &self,
+        mode: Mode,
+        call: &ToolCall,
+        approvals: &RuntimeApprovals,
+    ) -> Explanation {
+        let (tool_name, args, is_mcp) = (call.name, call.args, call.is_mcp);
+        let effective = self.effective_mode(mode);
+        let perms = self.mode_perms(mode);
+        let rule_match = |category: &'static str, ruleset: &RuleSet, rule| {
+            let (decision, pat): (Decision, &glob::Pattern) = rule;
+            RuleMatch {
+                category,
+                decision,
+                pattern: pat.as_str().to_string(),
+                origin: ruleset.origin(pat),
+            }
+        };
+        let step = |subject: String, rule: Option<RuleMatch>| Step {
+            decision: Some(rule.as_ref().map_or(Decision::Ask, |r| r.decision.clone())),
+            note: rule.is_none().then_some("no rule matched"),
+            subject,
+            rule,
+            approval: None,
+        };
+
+        let tool_rule = perms
+            .tools
+            .get(tool_name)
+            .filter(|_| !is_mcp)
+            .map(|d| RuleMatch {
+                category: "tools",
+                decision: d.clone(),
+                pattern: tool_name.to_string(),
+                origin: if perms.builtin_tools.contains(tool_name) {
+                    RuleOrigin::Default
+                } else {
+                    RuleOrigin::Config
+                },
+            });
+
+        let mut steps = Vec::new();
+        if is_mcp {
+            let rule = check_ruleset_rule(&perms.mcp, tool_name);
+            let rule = rule.map(|r| rule_match("mcp", &perms.mcp, r));
+            steps.push(step(tool_name.to_string(), rule));
+        } else if tool_name == "bash" {
+            let escalate_redirect = matches!(effective, Mode::Normal | Mode::Plan);
+            for subcmd in split_shell_commands(str_arg(args, "command").trim()) {
+                if is_cd_command(&subcmd) {
+                    steps.push(Step {
+                        subject: subcmd,
+                        decision: Some(Decision::Allow),
+                        rule: None,
+                        note: Some(
+                            "cd is always allowed; its path is checked against the workspace",
+                        ),
+                        approval: None,
+                    });
+                    continue;
+                }
+                let rule = check_ruleset_rule(&perms.bash, &subcmd);
+                let rule = rule.map(|r| rule_match("bash", &perms.bash, r));
+                let mut s = step(subcmd, rule);
+                if escalate_redirect
+                    && s.decision == Some(Decision::Allow)
+                    && has_output_redirection(&s.subject)
+                {
+                    s.decision = Some(Decision::Ask);
+                    s.note = Some("output redirection raises allow to ask in this mode");
+                }
+                if s.decision == Some(Decision::Ask) {
+                    s.approval = approvals
+                        .approval_for("bash", &s.subject)
+                        .map(approval_match);
+                }
+                steps.push(s);
+            }
+        } else if tool_name == "web_fetch" {
+            let url = str_arg(args, "url");
+            let rule = check_ruleset_rule(&perms.web_fetch, &url);
+            let rule = rule.map(|r| rule_match("web_fetch", &perms.web_fetch, r));
+            steps.push(step(url, rule));
+        } else if FILE_TOOLS.contains(&tool_name) {
+            for path in extract_tool_paths(tool_name, args) {
+                let rule = winning_rule(&perms.paths, |pat| {
+                    matches_path_rule(pat, &path, &self.workspace)
+                });
+                let mut rule = rule.map(|r| rule_match("paths", &perms.paths, r));
+                let mut note = rule
+                    .is_none()
+                    .then_some("no path rule; the tool rule decides");
+                if !modifies_files(tool_name)
+                    && rule.as_ref().is_some_and(|r| r.decision != Decision::Deny)
+                {
+                    rule = None;
+                    note = Some("only deny path rules apply to reads");
+                }
+                steps.push(Step {
+                    subject: path,
+                    decision: rule.as_ref().map(|r| r.decision.clone()),
+                    rule,
+                    note,
+                    approval: None,
+                });
+            }
+        }
+
+        let outside_workspace = self
+            .outside_workspace_paths(tool_name, args)
+            .into_iter()
+            .map(|p| {
+                let approval = approvals
+                    .dir_approval_for(&p)
+                    .map(|(origin, dir)| approval_match((origin, dir.display().to_string())));
+                (p, approval)
+            })
+            .collect();
+
+        let decided = self.decide_call(mode, call, approvals);
+        let approval = (tool_name != "bash" && decided.decision != decided.rule_decision)
+            .then(|| approvals.approval_for(tool_name, &call.approval_desc()))
+            .flatten()
+            .map(approval_match);
+
+        let managed = self
+            .managed_match(tool_name, args, is_mcp)
+            .map(|(category, pattern)| RuleMatch {
+                category,
+                decision: Decision::Deny,
+                pattern,
+                origin: RuleOrigin::Managed,
+            });
+
+        Explanation {
+            mode,
+            fallback_mode: (effective != mode).then_some(effective),
+            managed,
+            tool: tool_name.to_string(),
+            tool_rule,
+            steps,
+            outside_workspace,
+            downgraded: !is_mcp && self.was_downgraded(mode, tool_name, args),
+            approval,
+            source: decided.source,
+            uses_secrets: decided.uses_secrets,
+            rule_decision: decided.rule_decision,
+            decision: decided.decision,
+        }
+    }
+}
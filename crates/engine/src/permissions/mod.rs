@@ -5,13 +5,19 @@
 //! - [`bash`]: shell command splitting / heredoc parsing / redirection detection
 //! - [`workspace`]: path extraction + workspace boundary enforcement
 //! - [`approvals`]: runtime auto-approval tracking
+//! - [`explain`]: step-by-step account of a decision, for dry runs
+//!
+//! Deny rules from the managed policy ([`crate::policy`]) are checked ahead
+//! of everything else; nothing the user configures or approves lifts them.
 //!
 //! The public surface is this module: `Permissions`, `Decision`,
-//! `RuntimeApprovals`, and two helpers consumed by tool implementations
+//! `RuntimeApprovals`, `ToolCall` (what [`Permissions::decide_call`] takes),
+//! and two helpers consumed by tool implementations
 //! (`split_shell_commands`, `DEFAULT_BASH_ALLOW`).
 
 pub mod approvals;
 pub mod bash;
+pub mod explain;
 pub mod rules;
 pub mod workspace;
 
@@ -20,16 +26,65 @@ mod tests;
 
 pub use approvals::RuntimeApprovals;
 pub use bash::{split_shell_commands, split_shell_commands_with_ops};
-pub use rules::{Decision, RuleSet, DEFAULT_BASH_ALLOW};
+pub use explain::{Explanation, RuleMatch, Step};
+pub use rules::{Decision, RuleOrigin, RuleSet, DEFAULT_BASH_ALLOW};
 
-use crate::tools::str_arg;
+use crate::audit::AuditSource;
+use crate::tools::{str_arg, Tool};
 use bash::{has_output_redirection, is_cd_command};
 use protocol::Mode;
-use rules::{build_mode, check_ruleset, compile_patterns, merge_mode, ModePerms, RawConfig};
+use rules::{
+    build_mode, check_ruleset, check_ruleset_rule, compile_patterns, matches_rule, merge_mode,
+    winning_rule, ManagedRules, ModePerms, RawConfig,
+};
 use serde_json::Value;
 use std::collections::HashMap;
-use std::path::PathBuf;
-use workspace::{extract_tool_paths, has_paths_outside_workspace, is_in_workspace};
+use std::path::{Path, PathBuf};
+use workspace::{
+    extract_tool_paths, has_paths_outside_workspace, is_in_workspace, matches_path_rule,
+    modifies_files, FILE_TOOLS,
+};
+
+/// A tool call as the engine sees it when deciding on it.
+pub struct ToolCall<'a> {
+    pub name: &'a str,
+    pub args: &'a HashMap<String, Value>,
+    pub is_mcp: bool,
+    /// The tool itself, for its decision override, secret restoring and
+    /// confirm text. `None` behaves like a tool with none of these.
+    pub tool: Option<&'a dyn Tool>,
+    /// Session directory passed to the tool's override; without one the
+    /// override is not consulted.
+    pub session_dir: Option<&'a Path>,
+}
+
+impl ToolCall<'_> {
+    /// The confirm text, which saved approvals are matched against.
+    pub fn approval_desc(&self) -> String {
+        self.tool
+            .and_then(|t| t.needs_confirm(self.args))
+            .unwrap_or_else(|| self.name.to_string())
+    }
+
+    /// Whether running the call would restore a redacted secret.
+    pub fn uses_secrets(&self) -> bool {
+        self.tool.is_some_and(|t| t.restores_secrets())
+            && crate::redact::args_use_secrets(self.args)
+    }
+}
+
+/// The engine's decision on one tool call and what produced it.
+#[derive(Debug, Clone)]
+pub struct CallDecision {
+    /// The decision before saved approvals.
+    pub rule_decision: Decision,
+    pub decision: Decision,
+    pub source: AuditSource,
+    /// The deciding rule, as recorded in the audit log.
+    pub rule: Option<String>,
+    /// The call restores a redacted secret, so it asks unless denied.
+    pub uses_secrets: bool,
+}
 
 #[derive(Debug, Clone)]
 pub struct Permissions {
@@ -39,6 +94,7 @@ pub struct Permissions {
     yolo: ModePerms,
     restrict_to_workspace: bool,
     workspace: PathBuf,
+    managed: ManagedRules,
 }
 
 impl Permissions {
@@ -54,6 +110,7 @@ impl Permissions {
             yolo: build_mode(&merge_mode(def, &raw.permissions.yolo), Mode::Yolo),
             restrict_to_workspace: true,
             workspace: PathBuf::new(),
+            managed: ManagedRules::default(),
         }
     }
 
@@ -64,6 +121,9 @@ impl Permissions {
         let mut cloned = self.clone();
         fn apply_to_mode(mode: &mut ModePerms, overrides: &protocol::PermissionOverrides) {
             if let Some(ref tools) = overrides.tools {
+                for name in tools.allow.iter().chain(&tools.ask).chain(&tools.deny) {
+                    mode.builtin_tools.remove(name);
+                }
                 for name in &tools.allow {
                     mode.tools.insert(name.clone(), Decision::Allow);
                 }
@@ -75,6 +135,9 @@ impl Permissions {
                 }
             }
             if let Some(ref bash) = overrides.bash {
+                for pat in &bash.allow {
+                    mode.bash.builtin.remove(pat);
+                }
                 let mut allow = compile_patterns(&bash.allow);
                 allow.append(&mut mode.bash.allow);
                 mode.bash.allow = allow;
@@ -86,6 +149,9 @@ impl Permissions {
                 mode.bash.deny = deny;
             }
             if let Some(ref wf) = overrides.web_fetch {
+                for pat in &wf.allow {
+                    mode.web_fetch.builtin.remove(pat);
+                }
                 let mut allow = compile_patterns(&wf.allow);
                 allow.append(&mut mode.web_fetch.allow);
                 mode.web_fetch.allow = allow;
@@ -104,6 +170,16 @@ impl Permissions {
         cloned
     }
 
+    /// Apply the organization-managed policy: its deny rules are checked
+    /// before all others, and its disabled modes fall back to an enabled one.
+    pub fn set_policy(&mut self, policy: &crate::policy::ManagedPolicy) {
+        self.managed = ManagedRules::new(policy);
+    }
+
+    pub fn policy(&self) -> &crate::policy::ManagedPolicy {
+        &self.managed.policy
+    }
+
     pub fn set_workspace(&mut self, path: PathBuf) {
         self.workspace = path;
     }
@@ -116,8 +192,14 @@ impl Permissions {
         self.restrict_to_workspace = val;
     }
 
+    /// The mode whose rules apply: a mode disabled by the managed policy
+    /// gets the rules of the policy's fallback mode.
+    fn effective_mode(&self, mode: Mode) -> Mode {
+        self.managed.policy.effective_mode(mode)
+    }
+
     fn mode_perms(&self, mode: Mode) -> &ModePerms {
-        match mode {
+        match self.effective_mode(mode) {
             Mode::Normal => &self.normal,
             Mode::Plan => &self.plan,
             Mode::Apply => &self.apply,
@@ -126,6 +208,7 @@ impl Permissions {
     }
 
     pub fn check_tool(&self, mode: Mode, tool_name: &str) -> Decision {
+        let mode = self.effective_mode(mode);
         let perms = self.mode_perms(mode);
         let default = if mode == Mode::Yolo {
             Decision::Allow
@@ -148,6 +231,7 @@ impl Permissions {
     /// (e.g. `filesystem_read_file`) against glob patterns in the `mcp` ruleset.
     /// Defaults to Allow in yolo mode, Ask otherwise, if no pattern matches.
     pub fn check_mcp(&self, mode: Mode, qualified_name: &str) -> Decision {
+        let mode = self.effective_mode(mode);
         let perms = self.mode_perms(mode);
         let decision = check_ruleset(&perms.mcp, qualified_name);
         if decision == Decision::Ask && mode == Mode::Yolo {
@@ -157,7 +241,77 @@ impl Permissions {
         }
     }
 
+    /// Check the paths a file tool touches against the `paths` rules. The
+    /// strictest decision over all paths wins; `None` when no rule matches.
+    /// Only `deny` rules apply to `read_file`.
+    pub fn check_paths(
+        &self,
+        mode: Mode,
+        tool_name: &str,
+        args: &HashMap<String, Value>,
+    ) -> Option<Decision> {
+        self.path_rule(mode, tool_name, args).map(|(d, _)| d)
+    }
+
+    /// [`Self::check_paths`] along with the deciding pattern.
+    fn path_rule(
+        &self,
+        mode: Mode,
+        tool_name: &str,
+        args: &HashMap<String, Value>,
+    ) -> Option<(Decision, &glob::Pattern)> {
+        if !FILE_TOOLS.contains(&tool_name) {
+            return None;
+        }
+        let ruleset = &self.mode_perms(mode).paths;
+        let mut result = None;
+        for path in extract_tool_paths(tool_name, args) {
+            let rule = winning_rule(ruleset, |pat| {
+                matches_path_rule(pat, &path, &self.workspace)
+            });
+            match rule {
+                Some((Decision::Deny, pat)) => return Some((Decision::Deny, pat)),
+                _ if !modifies_files(tool_name) => {}
+                Some((Decision::Ask, pat)) if !matches!(result, Some((Decision::Ask, _))) => {
+                    result = Some((Decision::Ask, pat));
+                }
+                Some(allow) if result.is_none() => result = Some(allow),
+                _ => {}
+            }
+        }
+        result
+    }
+
+    /// Whether a `paths` deny rule, from config or the managed policy,
+    /// matches `path`. `grep` and `glob` drop such files from their results,
+    /// since only their search root is checked before they run.
+    pub fn denies_path(&self, mode: Mode, path: &str) -> bool {
+        let matches = |pat: &glob::Pattern| matches_path_rule(pat, path, &self.workspace);
+        self.managed.paths.iter().any(matches)
+            || matches!(
+                winning_rule(&self.mode_perms(mode).paths, matches),
+                Some((Decision::Deny, _))
+            )
+    }
+
+    /// Configured `paths` rules for a mode, for display.
+    pub fn path_rules(&self, mode: Mode) -> Vec<(Decision, String)> {
+        let rules = &self.mode_perms(mode).paths;
+        [
+            (Decision::Deny, &rules.deny),
+            (Decision::Ask, &rules.ask),
+            (Decision::Allow, &rules.allow),
+        ]
+        .into_iter()
+        .flat_map(|(d, pats)| {
+            pats.iter()
+                .map(move |p| (d.clone(), p.as_str().to_string()))
+        })
+        .collect()
+    }
+
     pub fn check_bash(&self, mode: Mode, command: &str) -> Decision {
+        let mode = self.effective_mode(mode);
         let perms = self.mode_perms(mode);
         let command = command.trim();
         // Escalate output redirection only in Normal/Plan modes.
@@ -206,6 +360,9 @@ impl Permissions {
         args: &HashMap<String, Value>,
         is_mcp: bool,
     ) -> Decision {
+        if self.managed_match(tool_name, args, is_mcp).is_some() {
+            return Decision::Deny;
+        }
         let base = if is_mcp {
             self.check_mcp(mode, tool_name)
         } else {
@@ -221,6 +378,196 @@ impl Permissions {
         base
     }
 
+    /// Decide a tool call the way the engine does: a managed deny first,
+    /// then the tool's own override, then the config rules. A call that
+    /// restores a redacted secret always asks unless denied; otherwise saved
+    /// approvals can raise Ask to Allow.
+    pub fn decide_call(
+        &self,
+        mode: Mode,
+        call: &ToolCall,
+        approvals: &RuntimeApprovals,
+    ) -> CallDecision {
+        let managed = self.managed_rule(call.name, call.args, call.is_mcp);
+        let uses_secrets = call.uses_secrets();
+        let overridden = match (call.tool, call.session_dir) {
+            (Some(tool), Some(dir)) if managed.is_none() && !uses_secrets => {
+                tool.decide_override(call.args, mode, dir)
+            }
+            _ => None,
+        };
+        let (mut decision, mut source, mut rule) = match overridden {
+            Some(decision) => (decision, AuditSource::Tool, None),
+            None => {
+                let rule = self.matched_rule(mode, call.name, call.args, call.is_mcp);
+                let source = if managed.is_some() {
+                    AuditSource::Managed
+                } else if rule.is_some() {
+                    AuditSource::Rule
+                } else {
+                    AuditSource::Default
+                };
+                let decision = self.decide(mode, call.name, call.args, call.is_mcp);
+                (decision, source, rule)
+            }
+        };
+
+        if uses_secrets && decision != Decision::Deny {
+            decision = Decision::Ask;
+            source = AuditSource::Rule;
+            rule = Some("restores a redacted secret".into());
+        }
+        let rule_decision = decision.clone();
+
+        if decision == Decision::Ask && !uses_secrets {
+            let desc = call.approval_desc();
+            if approvals.is_auto_approved(self, mode, call.name, call.args, &desc) {
+                decision = Decision::Allow;
+                source = if approvals.is_workspace_approved(self, mode, call.name, call.args, &desc)
+                {
+                    AuditSource::Workspace
+                } else {
+                    AuditSource::Session
+                };
+            }
+        }
+
+        CallDecision {
+            rule_decision,
+            decision,
+            source,
+            rule,
+            uses_secrets,
+        }
+    }
+
+    /// Describe the rule behind [`Self::decide`]'s result, e.g.
+    /// `bash.allow "git log *"` or `tools.ask edit_file`. `None` when no
+    /// rule matched and the mode default applied.
+    pub fn matched_rule(
+        &self,
+        mode: Mode,
+        tool_name: &str,
+        args: &HashMap<String, Value>,
+        is_mcp: bool,
+    ) -> Option<String> {
+        if let Some(rule) = self.managed_rule(tool_name, args, is_mcp) {
+            return Some(rule);
+        }
+        let perms = self.mode_perms(mode);
+        let pattern_rule = |category: &str, (d, pat): (Decision, &glob::Pattern)| {
+            format!("{category}.{} \"{}\"", d.label(), pat.as_str())
+        };
+        if is_mcp {
+            return check_ruleset_rule(&perms.mcp, tool_name).map(|r| pattern_rule("mcp", r));
+        }
+        if self.was_downgraded(mode, tool_name, args) {
+            return Some("restrict_to_workspace".into());
+        }
+        let tool_decision = perms.tools.get(tool_name);
+        let tool_rule = tool_decision.map(|d| format!("tools.{} {tool_name}", d.label()));
+        if tool_decision == Some(&Decision::Deny) {
+            return tool_rule;
+        }
+        match tool_name {
+            "bash" => self.bash_rule(mode, &str_arg(args, "command")),
+            "web_fetch" => check_ruleset_rule(&perms.web_fetch, &str_arg(args, "url"))
+                .map(|r| pattern_rule("web_fetch", r))
+                .or(tool_rule),
+            _ => self
+                .path_rule(mode, tool_name, args)
+                .map(|r| pattern_rule("paths", r))
+                .or(tool_rule),
+        }
+    }
+
+    /// The managed-policy deny rule that blocks this call, e.g.
+    /// `managed bash.deny "curl *"`. Runtime approvals and tool overrides
+    /// never lift it.
+    pub fn managed_rule(
+        &self,
+        tool_name: &str,
+        args: &HashMap<String, Value>,
+        is_mcp: bool,
+    ) -> Option<String> {
+        self.managed_match(tool_name, args, is_mcp)
+            .map(|(category, pat)| match category {
+                "tools" => format!("managed tools.deny {pat}"),
+                _ => format!("managed {category}.deny \"{pat}\""),
+            })
+    }
+
+    /// Category and pattern of the managed deny rule matching this call.
+    /// Bash is checked per sub-command; path rules apply to reads as well.
+    fn managed_match(
+        &self,
+        tool_name: &str,
+        args: &HashMap<String, Value>,
+        is_mcp: bool,
+    ) -> Option<(&'static str, String)> {
+        let managed = &self.managed;
+        let find = |pats: &[glob::Pattern], value: &str| {
+            pats.iter()
+                .find(|p| matches_rule(p, value))
+                .map(|p| p.as_str().to_string())
+        };
+        if is_mcp {
+            return find(&managed.mcp, tool_name).map(|p| ("mcp", p));
+        }
+        if managed.policy.deny.tools.iter().any(|t| t == tool_name) {
+            return Some(("tools", tool_name.to_string()));
+        }
+        match tool_name {
+            "bash" => split_shell_commands(str_arg(args, "command").trim())
+                .iter()
+                .find_map(|subcmd| find(&managed.bash, subcmd))
+                .map(|p| ("bash", p)),
+            "web_fetch" => {
+                find(&managed.web_fetch, &str_arg(args, "url")).map(|p| ("web_fetch", p))
+            }
+            _ if FILE_TOOLS.contains(&tool_name) => extract_tool_paths(tool_name, args)
+                .iter()
+                .find_map(|path| {
+                    managed
+                        .paths
+                        .iter()
+                        .find(|pat| matches_path_rule(pat, path, &self.workspace))
+                })
+                .map(|p| ("paths", p.as_str().to_string())),
+            _ => None,
+        }
+    }
+
+    /// The rule that decided [`Self::check_bash`]: the first denied
+    /// sub-command, else the first prompting one, else the last allowed.
+    fn bash_rule(&self, mode: Mode, command: &str) -> Option<String> {
+        let mode = self.effective_mode(mode);
+        let perms = self.mode_perms(mode);
+        let escalate_redirect = matches!(mode, Mode::Normal | Mode::Plan);
+        let mut ask: Option<Option<String>> = None;
+        let mut allow = None;
+        for subcmd in split_shell_commands(command.trim()) {
+            if is_cd_command(&subcmd) {
+                continue;
+            }
+            let rule = check_ruleset_rule(&perms.bash, &subcmd);
+            let label = rule
+                .as_ref()
+                .map(|(d, pat)| format!("bash.{} \"{}\"", d.label(), pat.as_str()));
+            match rule.map(|(d, _)| d) {
+                Some(Decision::Deny) => return label,
+                Some(Decision::Allow) if escalate_redirect && has_output_redirection(&subcmd) => {
+                    ask.get_or_insert(Some("output redirection".into()));
+                }
+                Some(Decision::Allow) => allow = label,
+                _ => {
+                    ask.get_or_insert(label);
+                }
+            }
+        }
+        ask.unwrap_or(allow)
+    }
+
     /// Whether this tool call's base permission is Allow but was downgraded
     /// to Ask solely because of paths outside the workspace.
     pub fn was_downgraded(
@@ -292,6 +639,15 @@ fn decide_base(
             (Decision::Allow, Decision::Ask) => Decision::Ask,
             _ => pattern_decision,
         }
+    } else if FILE_TOOLS.contains(&tool_name) {
+        let tool_decision = permissions.check_tool(mode, tool_name);
+        if tool_decision == Decision::Deny {
+            return Decision::Deny;
+        }
+        // A matching path rule replaces the tool-level decision.
+        permissions
+            .check_paths(mode, tool_name, args)
+            .unwrap_or(tool_decision)
     } else {
         permissions.check_tool(mode, tool_name)
     }
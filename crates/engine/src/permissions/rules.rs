@@ -5,11 +5,13 @@
 //! - Raw deserialization types and merge helpers
 //! - `DEFAULT_BASH_ALLOW` safe-read-only-command list
 //! - `build_mode` (materializes a `ModePerms` for one Mode)
-//! - `check_ruleset` (the core pattern-matching decision)
+//! - `check_ruleset` / `match_ruleset` / `winning_rule` (the core
+//!   pattern-matching decision)
 
+use crate::policy::ManagedPolicy;
 use protocol::Mode;
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Decision {
@@ -18,6 +20,43 @@ pub enum Decision {
     Deny,
 }
 
+impl Decision {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Decision::Allow => "allow",
+            Decision::Ask => "ask",
+            Decision::Deny => "deny",
+        }
+    }
+}
+
+/// Where a rule came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleOrigin {
+    /// Built into smelt.
+    Default,
+    /// The user's `config.yaml` (or per-run overrides).
+    Config,
+    /// An approval saved for this workspace.
+    Workspace,
+    /// An approval granted earlier in this session.
+    Session,
+    /// The organization-managed policy.
+    Managed,
+}
+
+impl RuleOrigin {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Config => "config",
+            Self::Workspace => "workspace",
+            Self::Session => "session",
+            Self::Managed => "managed",
+        }
+    }
+}
+
 #[derive(Debug, Default, Deserialize)]
 #[serde(default)]
 pub(super) struct RawRuleSet {
@@ -33,6 +72,7 @@ pub(super) struct RawModePerms {
     pub(super) bash: RawRuleSet,
     pub(super) web_fetch: RawRuleSet,
     pub(super) mcp: RawRuleSet,
+    pub(super) paths: RawRuleSet,
 }
 
 #[derive(Debug, Default, Deserialize)]
@@ -59,6 +99,7 @@ pub(super) fn merge_mode(default: &RawModePerms, mode: &RawModePerms) -> RawMode
         bash: merge_ruleset(&default.bash, &mode.bash),
         web_fetch: merge_ruleset(&default.web_fetch, &mode.web_fetch),
         mcp: merge_ruleset(&default.mcp, &mode.mcp),
+        paths: merge_ruleset(&default.paths, &mode.paths),
     }
 }
 
@@ -73,14 +114,53 @@ pub struct RuleSet {
     pub(super) allow: Vec<glob::Pattern>,
     pub(super) ask: Vec<glob::Pattern>,
     pub(super) deny: Vec<glob::Pattern>,
+    /// Patterns that come from the built-in defaults rather than config.
+    pub(super) builtin: HashSet<String>,
+}
+
+impl RuleSet {
+    pub(super) fn origin(&self, pat: &glob::Pattern) -> RuleOrigin {
+        if self.builtin.contains(pat.as_str()) {
+            RuleOrigin::Default
+        } else {
+            RuleOrigin::Config
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub(super) struct ModePerms {
     pub(super) tools: HashMap<String, Decision>,
+    /// Entries of `tools` that come from the built-in defaults.
+    pub(super) builtin_tools: HashSet<String>,
     pub(super) bash: RuleSet,
     pub(super) web_fetch: RuleSet,
     pub(super) mcp: RuleSet,
+    /// Glob rules on the paths touched by file tools. No built-in defaults.
+    pub(super) paths: RuleSet,
+}
+
+/// Deny rules from the managed policy, compiled. Checked before every other
+/// rule and left untouched by per-run overrides.
+#[derive(Debug, Clone, Default)]
+pub(super) struct ManagedRules {
+    pub(super) policy: ManagedPolicy,
+    pub(super) bash: Vec<glob::Pattern>,
+    pub(super) web_fetch: Vec<glob::Pattern>,
+    pub(super) mcp: Vec<glob::Pattern>,
+    pub(super) paths: Vec<glob::Pattern>,
+}
+
+impl ManagedRules {
+    pub(super) fn new(policy: &ManagedPolicy) -> Self {
+        Self {
+            bash: compile_patterns(&policy.deny.bash),
+            web_fetch: compile_patterns(&policy.deny.web_fetch),
+            mcp: compile_patterns(&policy.deny.mcp),
+            paths: compile_path_patterns(&policy.deny.paths),
+            policy: policy.clone(),
+        }
+    }
 }
 
 pub(super) fn compile_patterns(raw: &[String]) -> Vec<glob::Pattern> {
@@ -150,6 +230,7 @@ pub const DEFAULT_BASH_ALLOW: &[&str] = &[
 
 pub(super) fn build_mode(raw: &RawModePerms, mode: Mode) -> ModePerms {
     let mut tools = build_tool_map(&raw.tools);
+    let configured: HashSet<String> = tools.keys().cloned().collect();
 
     if mode == Mode::Yolo {
         // Yolo defaults: everything allowed unless explicitly overridden.
@@ -217,8 +298,15 @@ pub(super) fn build_mode(raw: &RawModePerms, mode: Mode) -> ModePerms {
         tools.entry(name.to_string()).or_insert(Decision::Allow);
     }
 
+    let builtin_tools = tools
+        .keys()
+        .filter(|name| !configured.contains(*name))
+        .cloned()
+        .collect();
+
     let mut bash_allow = compile_patterns(&raw.bash.allow);
-    if bash_allow.is_empty() {
+    let bash_builtin = bash_allow.is_empty();
+    if bash_builtin {
         if mode == Mode::Yolo {
             bash_allow = vec![glob::Pattern::new("*").unwrap()];
         } else {
@@ -232,82 +320,130 @@ pub(super) fn build_mode(raw: &RawModePerms, mode: Mode) -> ModePerms {
     }
 
     let mut web_allow = compile_patterns(&raw.web_fetch.allow);
-    if mode == Mode::Yolo && web_allow.is_empty() {
+    let web_builtin = mode == Mode::Yolo && web_allow.is_empty();
+    if web_builtin {
         web_allow = vec![glob::Pattern::new("*").unwrap()];
     }
 
     let mut mcp_allow = compile_patterns(&raw.mcp.allow);
-    if mode == Mode::Yolo && mcp_allow.is_empty() {
+    let mcp_builtin = mode == Mode::Yolo && mcp_allow.is_empty();
+    if mcp_builtin {
         mcp_allow = vec![glob::Pattern::new("*").unwrap()];
     }
 
+    let builtin = |is_builtin: bool, allow: &[glob::Pattern]| -> HashSet<String> {
+        if is_builtin {
+            allow.iter().map(|p| p.as_str().to_string()).collect()
+        } else {
+            HashSet::new()
+        }
+    };
+
     ModePerms {
         tools,
+        builtin_tools,
         bash: RuleSet {
+            builtin: builtin(bash_builtin, &bash_allow),
             allow: bash_allow,
             ask: compile_patterns(&raw.bash.ask),
             deny: compile_patterns(&raw.bash.deny),
         },
         web_fetch: RuleSet {
+            builtin: builtin(web_builtin, &web_allow),
             allow: web_allow,
             ask: compile_patterns(&raw.web_fetch.ask),
             deny: compile_patterns(&raw.web_fetch.deny),
         },
         mcp: RuleSet {
+            builtin: builtin(mcp_builtin, &mcp_allow),
             allow: mcp_allow,
             ask: compile_patterns(&raw.mcp.ask),
             deny: compile_patterns(&raw.mcp.deny),
         },
+        paths: RuleSet {
+            allow: compile_path_patterns(&raw.paths.allow),
+            ask: compile_path_patterns(&raw.paths.ask),
+            deny: compile_path_patterns(&raw.paths.deny),
+            builtin: HashSet::new(),
+        },
     }
 }
 
-fn matches_rule(pat: &glob::Pattern, value: &str) -> bool {
+/// Like [`compile_patterns`], with a leading `~/` expanded to the home
+/// directory so that path rules can name files outside the workspace.
+pub(super) fn compile_path_patterns(raw: &[String]) -> Vec<glob::Pattern> {
+    raw.iter()
+        .map(|s| match s.strip_prefix("~/") {
+            Some(rest) => crate::paths::home_dir().join(rest).display().to_string(),
+            None => s.clone(),
+        })
+        .filter_map(|s| glob::Pattern::new(&s).ok())
+        .collect()
+}
+
+pub(super) fn matches_rule(pat: &glob::Pattern, value: &str) -> bool {
     // Match both the value as-is and with a trailing space to handle
     // patterns like "ls *" matching bare "ls" (no arguments).
     pat.matches(value) || pat.matches(&format!("{value} "))
 }
 
 pub(super) fn check_ruleset(ruleset: &RuleSet, value: &str) -> Decision {
+    match_ruleset(ruleset, |pat| matches_rule(pat, value)).unwrap_or(Decision::Ask)
+}
+
+/// Decision of the winning rule in `ruleset`, or `None` when no rule
+/// matches. `matches` decides whether a single pattern applies.
+pub(super) fn match_ruleset(
+    ruleset: &RuleSet,
+    matches: impl Fn(&glob::Pattern) -> bool,
+) -> Option<Decision> {
+    winning_rule(ruleset, matches).map(|(d, _)| d)
+}
+
+/// Like [`match_ruleset`], also returning the pattern that decided.
+pub(super) fn winning_rule(
+    ruleset: &RuleSet,
+    matches: impl Fn(&glob::Pattern) -> bool,
+) -> Option<(Decision, &glob::Pattern)> {
     // Deny always wins — checked first regardless of specificity.
-    for pat in &ruleset.deny {
-        if matches_rule(pat, value) {
-            return Decision::Deny;
-        }
+    if let Some(pat) = ruleset.deny.iter().find(|p| matches(p)) {
+        return Some((Decision::Deny, pat));
     }
 
     // Among allow and ask, the most specific (longest pattern) wins.
     // On tie, ask wins (safer default). Pattern length is a heuristic for
     // specificity — works well for typical patterns like "git *" vs "git push *".
-    let mut best_allow: Option<usize> = None;
-    let mut best_ask: Option<usize> = None;
-
-    for pat in &ruleset.allow {
-        if matches_rule(pat, value) {
-            let len = pat.as_str().len();
-            if best_allow.is_none_or(|prev| len > prev) {
-                best_allow = Some(len);
-            }
-        }
-    }
-    for pat in &ruleset.ask {
-        if matches_rule(pat, value) {
-            let len = pat.as_str().len();
-            if best_ask.is_none_or(|prev| len > prev) {
-                best_ask = Some(len);
+    let longest = |pats: &'_ [glob::Pattern]| -> Option<usize> {
+        let mut best: Option<usize> = None;
+        for (i, pat) in pats.iter().enumerate() {
+            if matches(pat) && best.is_none_or(|b| pat.as_str().len() > pats[b].as_str().len()) {
+                best = Some(i);
             }
         }
-    }
+        best
+    };
+    let best_allow = longest(&ruleset.allow).map(|i| &ruleset.allow[i]);
+    let best_ask = longest(&ruleset.ask).map(|i| &ruleset.ask[i]);
 
     match (best_allow, best_ask) {
         (Some(a), Some(k)) => {
-            if k >= a {
-                Decision::Ask
+            if k.as_str().len() >= a.as_str().len() {
+                Some((Decision::Ask, k))
             } else {
-                Decision::Allow
+                Some((Decision::Allow, a))
             }
         }
-        (Some(_), None) => Decision::Allow,
-        (None, Some(_)) => Decision::Ask,
-        (None, None) => Decision::Ask,
+        (Some(a), None) => Some((Decision::Allow, a)),
+        (None, Some(k)) => Some((Decision::Ask, k)),
+        (None, None) => None,
     }
 }
+
+/// Match `value` against `ruleset` like [`check_ruleset`], returning the
+/// deciding pattern as well.
+pub(super) fn check_ruleset_rule<'a>(
+    ruleset: &'a RuleSet,
+    value: &str,
+) -> Option<(Decision, &'a glob::Pattern)> {
+    winning_rule(ruleset, |pat| matches_rule(pat, value))
+}
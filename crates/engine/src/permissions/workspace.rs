@@ -1,7 +1,8 @@
-//! Path extraction + workspace boundary enforcement.
+//! Path extraction, workspace boundary enforcement and path-rule matching.
 //!
-//! Given a tool call (name + args), pull out filesystem paths it touches
-//! and decide whether any of them escape the configured workspace root.
+//! Given a tool call (name + args), pull out filesystem paths it touches,
+//! decide whether any of them escape the configured workspace root, and
+//! match them against the `paths` glob rules.
 
 use crate::permissions::bash::strip_heredoc_bodies;
 use crate::tools::str_arg;
@@ -21,10 +22,19 @@ pub(super) fn extract_tool_paths(tool_name: &str, args: &HashMap<String, Value>)
                 vec![p]
             }
         }
+        "edit_notebook" => {
+            let p = str_arg(args, "notebook_path");
+            if p.is_empty() {
+                vec![]
+            } else {
+                vec![p]
+            }
+        }
         "glob" | "grep" => {
+            // An empty path searches the working directory.
             let p = str_arg(args, "path");
             if p.is_empty() {
-                vec![]
+                vec![".".into()]
             } else {
                 vec![p]
             }
@@ -63,23 +73,22 @@ fn normalize_path(path: &Path) -> PathBuf {
     components.iter().collect()
 }
 
-fn resolve_path(path_str: &str, workspace: &Path) -> PathBuf {
+/// Absolute form of a tool path, without following symlinks.
+fn absolute_path(path_str: &str, workspace: &Path) -> PathBuf {
     if let Some(rest) = path_str.strip_prefix("~/") {
-        let resolved = crate::paths::home_dir().join(rest);
-        resolved
-            .canonicalize()
-            .unwrap_or_else(|_| normalize_path(&resolved))
+        normalize_path(&crate::paths::home_dir().join(rest))
     } else if path_str.starts_with('/') {
-        let p = PathBuf::from(path_str);
-        p.canonicalize().unwrap_or_else(|_| normalize_path(&p))
+        normalize_path(Path::new(path_str))
     } else {
-        let resolved = workspace.join(path_str);
-        resolved
-            .canonicalize()
-            .unwrap_or_else(|_| normalize_path(&resolved))
+        normalize_path(&workspace.join(path_str))
     }
 }
 
+fn resolve_path(path_str: &str, workspace: &Path) -> PathBuf {
+    let path = absolute_path(path_str, workspace);
+    path.canonicalize().unwrap_or(path)
+}
+
 pub(super) fn is_in_workspace(path_str: &str, workspace: &Path) -> bool {
     let resolved = resolve_path(path_str, workspace);
     let ws = workspace
@@ -96,3 +105,66 @@ pub(super) fn has_paths_outside_workspace(
     let paths = extract_tool_paths(tool_name, args);
     paths.iter().any(|p| !is_in_workspace(p, workspace))
 }
+
+// ── Path rules ───────────────────────────────────────────────────────────────
+
+/// Tools whose paths are checked against the `paths` rules. For `glob` and
+/// `grep` that is the search root; files beneath it are filtered out of the
+/// results with [`super::Permissions::denies_path`]. `bash` is not covered:
+/// a shell command can read any file.
+pub(super) const FILE_TOOLS: &[&str] = &[
+    "read_file",
+    "write_file",
+    "edit_file",
+    "edit_notebook",
+    "glob",
+    "grep",
+];
+
+/// Whether a file tool changes the file. Only `deny` path rules apply to
+/// tools that just read.
+pub(super) fn modifies_files(tool_name: &str) -> bool {
+    !matches!(tool_name, "read_file" | "glob" | "grep")
+}
+
+/// Match one `paths` rule against a tool path, gitignore-style:
+/// - no `/` in the pattern: matches the file name at any depth (`.env*`)
+/// - leading `/`: matches the absolute path
+/// - otherwise: matches the path relative to the workspace (`src/**`)
+///
+/// `*` stays within one path component; `**` crosses directories.
+///
+/// Both the path as given and its symlink-resolved form are matched, so a
+/// rule can't be sidestepped through a link in either direction.
+pub(super) fn matches_path_rule(pat: &glob::Pattern, path_str: &str, workspace: &Path) -> bool {
+    let given = absolute_path(path_str, workspace);
+    let canonical = given.canonicalize().ok();
+    let ws = normalize_path(workspace);
+    let canonical_ws = workspace.canonicalize().ok();
+    std::iter::once(&given)
+        .chain(canonical.as_ref())
+        .any(|path| {
+            std::iter::once(&ws)
+                .chain(canonical_ws.as_ref())
+                .any(|ws| matches_resolved(pat, path, ws))
+        })
+}
+
+fn matches_resolved(pat: &glob::Pattern, path: &Path, workspace: &Path) -> bool {
+    let opts = glob::MatchOptions {
+        require_literal_separator: true,
+        ..glob::MatchOptions::new()
+    };
+    let pattern = pat.as_str();
+    if !pattern.contains('/') {
+        return path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| pat.matches_with(n, opts));
+    }
+    if pattern.starts_with('/') {
+        return pat.matches_path_with(path, opts);
+    }
+    path.strip_prefix(workspace)
+        .is_ok_and(|rel| pat.matches_path_with(rel, opts))
+}
@@ -0,0 +1,243 @@
+//! Organization-managed policy (`/etc/smelt/policy.yaml`).
+//!
+//! The policy is installed by an administrator and always wins over the
+//! user's `config.yaml`, per-run overrides and saved approvals:
+//!
+//! ```yaml
+//! deny:
+//!   tools: [web_search]
+//!   bash: ["curl *", "rm -rf *"]
+//!   web_fetch: ["*.internal.example.com*"]
+//!   mcp: ["shell_*"]
+//!   paths: ["~/.ssh/**", "**/.env*"]
+//! disabled_modes: [yolo]
+//! allowed_providers: [anthropic, openai]
+//! allowed_models: ["claude-*", "gpt-5*"]
+//! allowed_mcp_servers: [github]
+//! redact_secrets: true
+//! ```
+//!
+//! Its path is fixed and has no environment override, so a user cannot
+//! point smelt at a policy of their own. Every section is optional; an
+//! `allowed_*` list that is absent leaves that choice unrestricted.
+
+use protocol::Mode;
+use serde::Deserialize;
+use std::path::Path;
+
+pub const POLICY_PATH: &str = "/etc/smelt/policy.yaml";
+
+/// Patterns denied in every mode, in the same syntax as the `deny` lists of
+/// the `permissions` config.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ManagedDeny {
+    pub tools: Vec<String>,
+    pub bash: Vec<String>,
+    pub web_fetch: Vec<String>,
+    pub mcp: Vec<String>,
+    pub paths: Vec<String>,
+}
+
+impl ManagedDeny {
+    fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+            && self.bash.is_empty()
+            && self.web_fetch.is_empty()
+            && self.mcp.is_empty()
+            && self.paths.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ManagedPolicy {
+    pub deny: ManagedDeny,
+    pub disabled_modes: Vec<Mode>,
+    /// Provider types (`anthropic`, `openai`, `openai-compatible`, ...).
+    pub allowed_providers: Option<Vec<String>>,
+    /// Glob patterns matched against the model name.
+    pub allowed_models: Option<Vec<String>>,
+    /// Names of the MCP servers from config that may be started.
+    pub allowed_mcp_servers: Option<Vec<String>>,
+    /// Force secret redaction on, whatever the user's setting.
+    pub redact_secrets: bool,
+}
+
+impl ManagedPolicy {
+    /// Load the policy from [`POLICY_PATH`]. A missing file is an empty
+    /// policy; a file that cannot be read or parsed, or that disables every
+    /// mode, is an error, so that a broken policy never silently stops
+    /// applying.
+    pub fn load() -> Result<Self, String> {
+        Self::load_from(Path::new(POLICY_PATH))
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, String> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(format!("managed policy {}: {e}", path.display())),
+        };
+        if contents.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        let policy: Self = serde_yml::from_str(&contents)
+            .map_err(|e| format!("managed policy {}: {e}", path.display()))?;
+        if Mode::ALL.iter().all(|m| policy.disables(*m)) {
+            return Err(format!(
+                "managed policy {}: policy disables every mode",
+                path.display()
+            ));
+        }
+        Ok(policy)
+    }
+
+    /// Whether the policy restricts anything at all.
+    pub fn is_empty(&self) -> bool {
+        self.deny.is_empty()
+            && self.disabled_modes.is_empty()
+            && self.allowed_providers.is_none()
+            && self.allowed_models.is_none()
+            && self.allowed_mcp_servers.is_none()
+            && !self.redact_secrets
+    }
+
+    pub fn disables(&self, mode: Mode) -> bool {
+        self.disabled_modes.contains(&mode)
+    }
+
+    /// The mode to use instead of a disabled one: the first enabled mode in
+    /// `Mode::ALL` order.
+    pub fn fallback_mode(&self) -> Mode {
+        Mode::ALL
+            .iter()
+            .copied()
+            .find(|m| !self.disables(*m))
+            .unwrap_or(Mode::Normal)
+    }
+
+    /// `mode` if the policy allows it, else [`Self::fallback_mode`].
+    pub fn effective_mode(&self, mode: Mode) -> Mode {
+        if self.disables(mode) {
+            self.fallback_mode()
+        } else {
+            mode
+        }
+    }
+
+    pub fn allows_model(&self, provider_type: &str, model: &str) -> bool {
+        let provider_ok = self
+            .allowed_providers
+            .as_ref()
+            .is_none_or(|p| p.iter().any(|p| p == provider_type));
+        let model_ok = self.allowed_models.as_ref().is_none_or(|pats| {
+            pats.iter()
+                .any(|p| glob::Pattern::new(p).is_ok_and(|p| p.matches(model)))
+        });
+        provider_ok && model_ok
+    }
+
+    pub fn allows_mcp_server(&self, name: &str) -> bool {
+        self.allowed_mcp_servers
+            .as_ref()
+            .is_none_or(|s| s.iter().any(|s| s == name))
+    }
+
+    /// One line per restriction, for display.
+    pub fn summary(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for (category, pats) in [
+            ("tools", &self.deny.tools),
+            ("bash", &self.deny.bash),
+            ("web_fetch", &self.deny.web_fetch),
+            ("mcp", &self.deny.mcp),
+            ("paths", &self.deny.paths),
+        ] {
+            lines.extend(pats.iter().map(|p| format!("{category}.deny: {p}")));
+        }
+        if !self.disabled_modes.is_empty() {
+            let modes: Vec<&str> = self.disabled_modes.iter().map(|m| m.as_str()).collect();
+            lines.push(format!("disabled modes: {}", modes.join(", ")));
+        }
+        for (label, list) in [
+            ("providers", &self.allowed_providers),
+            ("models", &self.allowed_models),
+            ("mcp servers", &self.allowed_mcp_servers),
+        ] {
+            if let Some(list) = list {
+                let allowed = if list.is_empty() {
+                    "none".to_string()
+                } else {
+                    list.join(", ")
+                };
+                lines.push(format!("allowed {label}: {allowed}"));
+            }
+        }
+        if self.redact_secrets {
+            lines.push("redact_secrets: always on".into());
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(yaml: &str) -> ManagedPolicy {
+        serde_yml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn parses_policy() {
+        let p = parse(
+            r#"
+deny:
+  bash: ["curl *"]
+  paths: ["~/.ssh/**"]
+disabled_modes: [yolo, apply]
+allowed_providers: [anthropic]
+allowed_models: ["claude-*"]
+allowed_mcp_servers: []
+redact_secrets: true
+"#,
+        );
+        assert_eq!(p.deny.bash, vec!["curl *"]);
+        assert!(p.disables(Mode::Yolo));
+        assert_eq!(p.effective_mode(Mode::Apply), Mode::Normal);
+        assert_eq!(p.effective_mode(Mode::Plan), Mode::Plan);
+        assert!(p.allows_model("anthropic", "claude-sonnet-4"));
+        assert!(!p.allows_model("anthropic", "other"));
+        assert!(!p.allows_model("openai", "claude-sonnet-4"));
+        assert!(!p.allows_mcp_server("github"));
+        assert!(p.redact_secrets);
+        assert!(p
+            .summary()
+            .contains(&"allowed mcp servers: none".to_string()));
+    }
+
+    #[test]
+    fn empty_policy_restricts_nothing() {
+        let p = ManagedPolicy::default();
+        assert!(p.is_empty());
+        assert!(p.allows_model("openai", "gpt-5"));
+        assert!(p.allows_mcp_server("anything"));
+        assert_eq!(p.effective_mode(Mode::Yolo), Mode::Yolo);
+        assert!(p.summary().is_empty());
+    }
+
+    #[test]
+    fn missing_file_is_empty_and_bad_file_is_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("policy.yaml");
+        assert!(ManagedPolicy::load_from(&path).unwrap().is_empty());
+        std::fs::write(&path, "deny: {bash: [1, 2]\n").unwrap();
+        assert!(ManagedPolicy::load_from(&path).is_err());
+        std::fs::write(&path, "disabled_modes: [turbo]\n").unwrap();
+        assert!(ManagedPolicy::load_from(&path).is_err());
+        std::fs::write(&path, "disabled_modes: [normal, plan, apply, yolo]\n").unwrap();
+        let err = ManagedPolicy::load_from(&path).unwrap_err();
+        assert!(err.contains("policy disables every mode"), "{err}");
+    }
+}
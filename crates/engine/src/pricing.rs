@@ -1,4 +1,5 @@
 use protocol::TokenUsage;
+use serde::Deserialize;
 use std::collections::HashMap;
 use std::sync::OnceLock;
 use std::time::Duration;
@@ -10,10 +11,12 @@ pub struct ModelPricing {
     pub output: f64,
     pub cache_read: f64,
     pub cache_write: f64,
+    /// Flat USD charge per LLM request, on top of the token prices.
+    pub per_request: f64,
 }
 
 impl ModelPricing {
-    /// Calculate the cost in USD for the given token usage.
+    /// Calculate the cost in USD for one request with the given token usage.
     pub fn cost(&self, usage: &TokenUsage) -> f64 {
         let input = usage.prompt_tokens.unwrap_or(0) as f64;
         let output = usage.completion_tokens.unwrap_or(0) as f64;
@@ -28,10 +31,21 @@ impl ModelPricing {
             + self.cache_read * cache_read
             + self.cache_write * cache_write)
             / 1_000_000.0
+            + self.per_request
     }
 
     pub fn is_zero(&self) -> bool {
-        self.input == 0.0 && self.output == 0.0
+        self.input == 0.0 && self.output == 0.0 && self.per_request == 0.0
+    }
+
+    fn scaled(self, factor: f64) -> Self {
+        Self {
+            input: self.input * factor,
+            output: self.output * factor,
+            cache_read: self.cache_read * factor,
+            cache_write: self.cache_write * factor,
+            per_request: self.per_request,
+        }
     }
 }
 
@@ -40,8 +54,57 @@ const ZERO: ModelPricing = ModelPricing {
     output: 0.0,
     cache_read: 0.0,
     cache_write: 0.0,
+    per_request: 0.0,
 };
 
+/// Adjustment applied on top of per-token prices, set per provider or per
+/// model in config.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PriceRule {
+    /// Scales token prices; `0` makes the model free.
+    pub multiplier: Option<f64>,
+    /// Flat USD charge per LLM request (e.g. Copilot premium requests).
+    pub per_request: Option<f64>,
+}
+
+impl PriceRule {
+    /// Fill unset fields from `fallback`.
+    pub fn or(self, fallback: PriceRule) -> PriceRule {
+        PriceRule {
+            multiplier: self.multiplier.or(fallback.multiplier),
+            per_request: self.per_request.or(fallback.per_request),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.multiplier.is_none() && self.per_request.is_none()
+    }
+
+    pub fn label(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(m) = self.multiplier {
+            parts.push(format!("×{m}"));
+        }
+        if let Some(r) = self.per_request {
+            parts.push(format!("${r}/request"));
+        }
+        parts.join(", ")
+    }
+}
+
+/// Built-in rule for providers billed by subscription rather than by token.
+/// Only applies when the model has no cost overrides in config.
+fn default_rule(provider_type: &str) -> PriceRule {
+    match provider_type {
+        "codex" | "copilot" | "github-copilot" => PriceRule {
+            multiplier: Some(0.0),
+            per_request: None,
+        },
+        _ => PriceRule::default(),
+    }
+}
+
 /// Where the resolved pricing came from.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PricingSource {
@@ -49,6 +112,8 @@ pub enum PricingSource {
     Config,
     /// Matched from the models.dev remote catalog.
     Catalog,
+    /// Matched from the snapshot compiled into the binary.
+    Bundled,
     /// No pricing data available (local/unknown model).
     None,
 }
@@ -58,6 +123,7 @@ impl PricingSource {
         match self {
             Self::Config => "config override",
             Self::Catalog => "models.dev",
+            Self::Bundled => "bundled snapshot",
             Self::None => "none",
         }
     }
@@ -66,8 +132,11 @@ impl PricingSource {
 /// Resolved pricing plus its source.
 #[derive(Debug, Clone, Copy)]
 pub struct ResolvedPricing {
+    /// Effective prices, with `rule` already applied.
     pub pricing: ModelPricing,
     pub source: PricingSource,
+    /// The price rule that was applied (empty when none).
+    pub rule: PriceRule,
 }
 
 // ── Remote catalog (models.dev) ──────────────────────────────────────────
@@ -76,8 +145,32 @@ const MODELS_API_URL: &str = "https://models.dev/api.json";
 const CACHE_KEY: &str = "models_dev_pricing";
 const CACHE_TTL: Duration = Duration::from_secs(60 * 60); // 1 hour
 
+/// One models.dev entry: prices plus the fields the capability registry
+/// reads. Missing fields are `None`.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct CatalogModel {
+    pub pricing: Option<ModelPricing>,
+    pub context_window: Option<u32>,
+    pub max_output: Option<u32>,
+    pub vision: Option<bool>,
+    pub reasoning: Option<bool>,
+    pub tool_calling: Option<bool>,
+}
+
+type Catalog = HashMap<(String, String), CatalogModel>;
+
 /// Global catalog keyed by (provider, model_id).
-static CATALOG: OnceLock<HashMap<(String, String), ModelPricing>> = OnceLock::new();
+static CATALOG: OnceLock<Catalog> = OnceLock::new();
+
+/// Snapshot of the models.dev catalog for common providers, used when the
+/// remote catalog is unavailable (offline, or not fetched yet).
+const SNAPSHOT_JSON: &str = include_str!("pricing_snapshot.json");
+
+static SNAPSHOT: OnceLock<Catalog> = OnceLock::new();
+
+fn snapshot() -> &'static Catalog {
+    SNAPSHOT.get_or_init(|| parse_catalog(SNAPSHOT_JSON).unwrap_or_default())
+}
 
 /// Fetch pricing from models.dev in the background. Call once at startup.
 /// Safe to call multiple times — only the first call populates the catalog.
@@ -86,12 +179,21 @@ pub fn spawn_catalog_fetch(client: reqwest::Client) {
         return;
     }
     tokio::spawn(async move {
-        let map = load_or_fetch(&client).await;
-        let _ = CATALOG.set(map);
+        load_catalog(&client).await;
     });
 }
 
-async fn load_or_fetch(client: &reqwest::Client) -> HashMap<(String, String), ModelPricing> {
+/// Populate the remote catalog and wait for it. For one-shot commands that
+/// need the catalog before resolving prices.
+pub async fn load_catalog(client: &reqwest::Client) {
+    if CATALOG.get().is_some() {
+        return;
+    }
+    let map = load_or_fetch(client).await;
+    let _ = CATALOG.set(map);
+}
+
+async fn load_or_fetch(client: &reqwest::Client) -> Catalog {
     // Try disk cache first.
     if let Some(json) = crate::tools::web_cache::get(CACHE_KEY) {
         if let Some(map) = parse_catalog(&json) {
@@ -113,8 +215,8 @@ async fn load_or_fetch(client: &reqwest::Client) -> HashMap<(String, String), Mo
     map
 }
 
-/// Parse the models.dev JSON into a (provider, model_id) → pricing map.
-fn parse_catalog(json: &str) -> Option<HashMap<(String, String), ModelPricing>> {
+/// Parse the models.dev JSON into a (provider, model_id) → entry map.
+fn parse_catalog(json: &str) -> Option<Catalog> {
     let root: serde_json::Value = serde_json::from_str(json).ok()?;
     let obj = root.as_object()?;
     let mut map = HashMap::new();
@@ -125,22 +227,35 @@ fn parse_catalog(json: &str) -> Option<HashMap<(String, String), ModelPricing>>
             None => continue,
         };
         for (model_id, model_val) in models {
-            let cost = match model_val.get("cost") {
-                Some(c) => c,
-                None => continue,
-            };
+            let cost = &model_val["cost"];
             let input = cost["input"].as_f64().unwrap_or(0.0);
             let output = cost["output"].as_f64().unwrap_or(0.0);
-            if input == 0.0 && output == 0.0 {
-                continue;
-            }
+            let pricing = (input != 0.0 || output != 0.0).then(|| ModelPricing {
+                input,
+                output,
+                cache_read: cost["cache_read"].as_f64().unwrap_or(0.0),
+                cache_write: cost["cache_write"].as_f64().unwrap_or(0.0),
+                per_request: 0.0,
+            });
+            let limit = |key: &str| {
+                model_val["limit"][key]
+                    .as_u64()
+                    .filter(|&n| n > 0)
+                    .map(|n| n as u32)
+            };
+            let vision = match model_val["modalities"]["input"].as_array() {
+                Some(inputs) => Some(inputs.iter().any(|m| m == "image")),
+                None => model_val["attachment"].as_bool(),
+            };
             map.insert(
                 (provider.clone(), model_id.clone()),
-                ModelPricing {
-                    input,
-                    output,
-                    cache_read: cost["cache_read"].as_f64().unwrap_or(0.0),
-                    cache_write: cost["cache_write"].as_f64().unwrap_or(0.0),
+                CatalogModel {
+                    pricing,
+                    context_window: limit("context"),
+                    max_output: limit("output"),
+                    vision,
+                    reasoning: model_val["reasoning"].as_bool(),
+                    tool_calling: model_val["tool_call"].as_bool(),
                 },
             );
         }
@@ -148,12 +263,29 @@ fn parse_catalog(json: &str) -> Option<HashMap<(String, String), ModelPricing>>
     Some(map)
 }
 
-/// Look up pricing for a (provider, model) pair from the remote catalog.
-/// Returns `None` when the provider/model combination isn't found.
-fn lookup(provider_type: &str, model: &str) -> Option<ModelPricing> {
-    let catalog = CATALOG.get()?;
-    let key = catalog_key(provider_type)?;
-    catalog.get(&(key.to_string(), model.to_string())).copied()
+/// Look up pricing for a (provider, model) pair, preferring the remote
+/// catalog over the bundled snapshot. Returns `None` when the
+/// provider/model combination isn't found in either.
+fn lookup(provider_type: &str, model: &str) -> Option<(ModelPricing, PricingSource)> {
+    let key = (catalog_key(provider_type)?.to_string(), model.to_string());
+    if let Some(p) = CATALOG.get().and_then(|c| c.get(&key)?.pricing) {
+        return Some((p, PricingSource::Catalog));
+    }
+    snapshot()
+        .get(&key)?
+        .pricing
+        .map(|p| (p, PricingSource::Bundled))
+}
+
+/// Catalog entry for a (provider, model) pair, preferring the remote
+/// catalog over the bundled snapshot.
+pub(crate) fn catalog_model(provider_type: &str, model: &str) -> Option<CatalogModel> {
+    let key = (catalog_key(provider_type)?.to_string(), model.to_string());
+    CATALOG
+        .get()
+        .and_then(|c| c.get(&key))
+        .or_else(|| snapshot().get(&key))
+        .copied()
 }
 
 /// Map a provider_type string to the corresponding models.dev provider key.
@@ -164,6 +296,9 @@ fn catalog_key(provider_type: &str) -> Option<&str> {
         "openai" | "codex" => Some("openai"),
         "anthropic" => Some("anthropic"),
         "copilot" | "github-copilot" => Some("github-copilot"),
+        "bedrock" => Some("amazon-bedrock"),
+        // Deployment names usually match the underlying model id.
+        "azure-openai" => Some("azure"),
         "openai-compatible" => None,
         other => Some(other),
     }
@@ -177,28 +312,172 @@ pub fn resolve(
 ) -> ResolvedPricing {
     let has_config_override = config.input_cost.is_some() || config.output_cost.is_some();
 
-    if has_config_override {
-        let catalog = lookup(provider_type, model).unwrap_or(ZERO);
-        return ResolvedPricing {
-            pricing: ModelPricing {
+    let (mut base, source) = if has_config_override {
+        let (catalog, _) = lookup(provider_type, model).unwrap_or((ZERO, PricingSource::None));
+        (
+            ModelPricing {
                 input: config.input_cost.unwrap_or(catalog.input),
                 output: config.output_cost.unwrap_or(catalog.output),
                 cache_read: config.cache_read_cost.unwrap_or(catalog.cache_read),
                 cache_write: config.cache_write_cost.unwrap_or(catalog.cache_write),
+                per_request: 0.0,
             },
-            source: PricingSource::Config,
+            PricingSource::Config,
+        )
+    } else {
+        lookup(provider_type, model).unwrap_or((ZERO, PricingSource::None))
+    };
+
+    // Catalog cache-write prices assume the 5-minute TTL; hour-long
+    // entries cost twice the input rate to write.
+    if config.cache_ttl() == crate::config::CacheTtl::OneHour && config.cache_write_cost.is_none() {
+        base.cache_write = base.input * 2.0;
+    }
+
+    if let Some(tier) = config.service_tier {
+        base = base.scaled(tier.price_factor());
+    }
+
+    // Explicit per-token prices outrank the built-in subscription rule.
+    let rule = if has_config_override {
+        config.pricing
+    } else {
+        config.pricing.or(default_rule(provider_type))
+    };
+    ResolvedPricing {
+        pricing: apply_rule(base, rule),
+        source,
+        rule,
+    }
+}
+
+fn apply_rule(pricing: ModelPricing, rule: PriceRule) -> ModelPricing {
+    let mut out = match rule.multiplier {
+        Some(m) => pricing.scaled(m),
+        None => pricing,
+    };
+    if let Some(r) = rule.per_request {
+        out.per_request = r;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ModelConfig;
+
+    fn usage(prompt: u32, completion: u32) -> TokenUsage {
+        TokenUsage {
+            prompt_tokens: Some(prompt),
+            completion_tokens: Some(completion),
+            ..TokenUsage::default()
+        }
+    }
+
+    #[test]
+    fn snapshot_parses_and_prices_known_models() {
+        let r = resolve("gpt-4o", "openai", &ModelConfig::default());
+        assert_eq!(r.source, PricingSource::Bundled);
+        assert!(r.pricing.cost(&usage(1_000_000, 0)) > 0.0);
+        assert_eq!(
+            resolve("no-such-model", "openai", &ModelConfig::default()).source,
+            PricingSource::None
+        );
+    }
+
+    #[test]
+    fn multiplier_and_per_request_apply() {
+        let config = ModelConfig {
+            input_cost: Some(2.0),
+            output_cost: Some(4.0),
+            pricing: PriceRule {
+                multiplier: Some(0.5),
+                per_request: Some(0.04),
+            },
+            ..ModelConfig::default()
+        };
+        let r = resolve("custom", "openai-compatible", &config);
+        assert_eq!(r.source, PricingSource::Config);
+        let cost = r.pricing.cost(&usage(1_000_000, 1_000_000));
+        assert!((cost - 3.04).abs() < 1e-9);
+    }
+
+    #[test]
+    fn hour_cache_ttl_doubles_write_price() {
+        let config = ModelConfig {
+            input_cost: Some(3.0),
+            output_cost: Some(15.0),
+            cache_ttl: Some(crate::config::CacheTtl::OneHour),
+            ..ModelConfig::default()
+        };
+        let r = resolve("custom", "anthropic", &config);
+        assert_eq!(r.pricing.cache_write, 6.0);
+
+        let explicit = ModelConfig {
+            cache_write_cost: Some(4.0),
+            ..config
         };
+        assert_eq!(
+            resolve("custom", "anthropic", &explicit)
+                .pricing
+                .cache_write,
+            4.0
+        );
     }
 
-    if let Some(catalog) = lookup(provider_type, model) {
-        return ResolvedPricing {
-            pricing: catalog,
-            source: PricingSource::Catalog,
+    #[test]
+    fn service_tier_discounts_token_prices() {
+        let config = ModelConfig {
+            input_cost: Some(2.0),
+            output_cost: Some(8.0),
+            service_tier: Some(crate::config::ServiceTier::Batch),
+            ..ModelConfig::default()
         };
+        let r = resolve("custom", "openai", &config);
+        assert_eq!(r.pricing.input, 1.0);
+        assert_eq!(r.pricing.output, 4.0);
     }
 
-    ResolvedPricing {
-        pricing: ZERO,
-        source: PricingSource::None,
+    #[test]
+    fn subscription_providers_are_free_unless_overridden() {
+        let free = resolve("gpt-5", "codex", &ModelConfig::default());
+        assert!(free.pricing.is_zero());
+        assert_eq!(free.rule.multiplier, Some(0.0));
+
+        let premium = ModelConfig {
+            pricing: PriceRule {
+                per_request: Some(0.04),
+                ..PriceRule::default()
+            },
+            ..ModelConfig::default()
+        };
+        let r = resolve("gpt-4.1", "copilot", &premium);
+        assert!((r.pricing.cost(&usage(10, 10)) - 0.04).abs() < 1e-9);
+
+        let priced = ModelConfig {
+            input_cost: Some(1.0),
+            ..ModelConfig::default()
+        };
+        assert!(!resolve("gpt-5", "codex", &priced).pricing.is_zero());
+    }
+
+    #[test]
+    fn price_rule_falls_back_per_field() {
+        let model = PriceRule {
+            per_request: Some(0.1),
+            ..PriceRule::default()
+        };
+        let provider = PriceRule {
+            multiplier: Some(0.0),
+            per_request: Some(0.04),
+        };
+        assert_eq!(
+            model.or(provider),
+            PriceRule {
+                multiplier: Some(0.0),
+                per_request: Some(0.1),
+            }
+        );
     }
 }
@@ -0,0 +1,324 @@
+//! Subagent profiles: named presets for `spawn_agent`.
+//!
+//! A profile is a markdown file in `~/.config/smelt/agents/` or
+//! `.smelt/agents/` (project-local, overriding global ones of the same name).
+//! The frontmatter sets up the subagent; the body is appended to its system
+//! prompt:
+//!
+//! ```markdown
+//! ---
+//! description: Reviews diffs for bugs and style problems
+//! model: anthropic/claude-sonnet-4
+//! reasoning_effort: high
+//! mode: plan
+//! tools: [read_file, grep, glob, bash]
+//! permissions:
+//!   bash:
+//!     allow: ["git diff *", "git log *"]
+//!     deny: ["git push *"]
+//! ---
+//!
+//! You review changes. Report problems; never edit files.
+//! ```
+//!
+//! Every field is optional. The name is the file stem unless `name` is set.
+//! A profile leaves unset fields as they would be for a plain subagent.
+//!
+//! Project-local profiles come with the repository, so they can only
+//! tighten: their `mode` is capped at the spawning agent's mode, and their
+//! `allow` rules and tool-level `ask` overrides are dropped with a warning.
+
+use protocol::{Mode, PermissionOverrides, ReasoningEffort};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ProfileFrontmatter {
+    name: Option<String>,
+    description: String,
+    model: Option<String>,
+    reasoning_effort: Option<ReasoningEffort>,
+    mode: Option<Mode>,
+    tools: Option<Vec<String>>,
+    permissions: PermissionOverrides,
+}
+
+#[derive(Debug, Clone)]
+pub struct AgentProfile {
+    pub name: String,
+    pub description: String,
+    /// Model reference, as for `--model`.
+    pub model: Option<String>,
+    pub reasoning_effort: Option<ReasoningEffort>,
+    pub mode: Option<Mode>,
+    /// Only these tools are available when set.
+    pub tools: Option<Vec<String>>,
+    /// Layered over the user's permission rules.
+    pub permissions: PermissionOverrides,
+    /// Extra system prompt instructions (the markdown body).
+    pub instructions: String,
+    /// Loaded from the project's `.smelt/agents/`.
+    pub project: bool,
+}
+
+/// How much a mode lets run without asking, from Plan up to Yolo.
+fn looseness(mode: Mode) -> u8 {
+    match mode {
+        Mode::Plan => 0,
+        Mode::Normal => 1,
+        Mode::Apply => 2,
+        Mode::Yolo => 3,
+    }
+}
+
+impl AgentProfile {
+    /// The mode to start in when spawned by an agent in `parent` mode. A
+    /// project profile never runs looser than its parent, or than Normal
+    /// when the parent's mode is unknown.
+    pub fn mode_under(&self, parent: Option<Mode>) -> Option<Mode> {
+        let mode = self.mode?;
+        if !self.project {
+            return Some(mode);
+        }
+        let ceiling = parent.unwrap_or(Mode::Normal);
+        Some(if looseness(mode) > looseness(ceiling) {
+            ceiling
+        } else {
+            mode
+        })
+    }
+
+    /// Drop the permission overrides that could loosen the user's rules:
+    /// every `allow`, and tool-level `ask` (which would replace a tool
+    /// `deny`). Returns a description of each dropped entry.
+    fn keep_tightening_rules(&mut self) -> Vec<String> {
+        let mut dropped = Vec::new();
+        let p = &mut self.permissions;
+        for (category, rules) in [
+            ("tools", &mut p.tools),
+            ("bash", &mut p.bash),
+            ("web_fetch", &mut p.web_fetch),
+        ] {
+            let Some(rules) = rules else {
+                continue;
+            };
+            for pat in rules.allow.drain(..) {
+                dropped.push(format!("{category}.allow \"{pat}\""));
+            }
+            if category == "tools" {
+                for name in rules.ask.drain(..) {
+                    dropped.push(format!("tools.ask \"{name}\""));
+                }
+            }
+        }
+        dropped
+    }
+}
+
+/// The profiles found in the agent directories.
+#[derive(Debug, Clone, Default)]
+pub struct AgentProfiles {
+    profiles: BTreeMap<String, AgentProfile>,
+    warnings: Vec<String>,
+}
+
+impl AgentProfiles {
+    /// Scan the profile directories (later entries override earlier ones):
+    ///   1. ~/.config/smelt/agents/*.md
+    ///   2. .smelt/agents/*.md (project-local)
+    pub fn load() -> Self {
+        let mut profiles = Self::default();
+        profiles.scan_dir(&crate::config_dir().join("agents"), false);
+        if let Ok(cwd) = std::env::current_dir() {
+            profiles.scan_dir(&cwd.join(".smelt/agents"), true);
+        }
+        profiles
+    }
+
+    pub fn get(&self, name: &str) -> Option<&AgentProfile> {
+        self.profiles.get(name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.profiles.is_empty()
+    }
+
+    /// Names of all profiles, sorted.
+    pub fn names(&self) -> Vec<&str> {
+        self.profiles.keys().map(|s| s.as_str()).collect()
+    }
+
+    /// Profile files that could not be loaded, one message each.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// One `  - name: description` line per profile, for the system prompt.
+    pub fn prompt_listing(&self) -> String {
+        self.profiles
+            .values()
+            .map(|p| {
+                if p.description.is_empty() {
+                    format!("  - {}", p.name)
+                } else {
+                    format!("  - {}: {}", p.name, p.description)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn scan_dir(&mut self, dir: &Path, project: bool) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        let mut paths: Vec<_> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.is_file() && p.extension().is_some_and(|e| e == "md"))
+            .collect();
+        paths.sort();
+        for path in paths {
+            match parse_profile(&path) {
+                Ok(mut profile) => {
+                    if project {
+                        profile.project = true;
+                        for rule in profile.keep_tightening_rules() {
+                            self.warnings.push(format!(
+                                "agent profile {}: ignoring {rule}; project profiles can only tighten permissions",
+                                path.display()
+                            ));
+                        }
+                    }
+                    self.profiles.insert(profile.name.clone(), profile);
+                }
+                Err(e) => self
+                    .warnings
+                    .push(format!("agent profile {}: {e}", path.display())),
+            }
+        }
+    }
+}
+
+fn parse_profile(path: &Path) -> Result<AgentProfile, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    parse(&stem, &text)
+}
+
+fn parse(stem: &str, text: &str) -> Result<AgentProfile, String> {
+    let (meta, body) = match crate::skills::split_frontmatter(text) {
+        Some((yaml, body)) if !yaml.is_empty() => (
+            serde_yml::from_str::<ProfileFrontmatter>(yaml).map_err(|e| e.to_string())?,
+            body,
+        ),
+        Some((_, body)) => (ProfileFrontmatter::default(), body),
+        None => (ProfileFrontmatter::default(), text),
+    };
+    let name = meta.name.unwrap_or_else(|| stem.to_string());
+    if name.is_empty() || name.chars().any(char::is_whitespace) {
+        return Err(format!("invalid name {name:?}"));
+    }
+    Ok(AgentProfile {
+        name,
+        description: meta.description,
+        model: meta.model,
+        reasoning_effort: meta.reasoning_effort,
+        mode: meta.mode,
+        tools: meta.tools,
+        permissions: meta.permissions,
+        instructions: body.trim().to_string(),
+        project: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_profile() {
+        let text = "---\ndescription: Reviews diffs\nmodel: openai/gpt-5\nreasoning_effort: high\nmode: plan\ntools: [read_file, grep]\npermissions:\n  bash:\n    deny: [\"git push *\"]\n---\n\nNever edit files.\n";
+        let p = parse("reviewer", text).unwrap();
+        assert_eq!(p.name, "reviewer");
+        assert_eq!(p.model.as_deref(), Some("openai/gpt-5"));
+        assert_eq!(p.reasoning_effort, Some(ReasoningEffort::High));
+        assert_eq!(p.mode, Some(Mode::Plan));
+        assert_eq!(p.tools.unwrap(), ["read_file", "grep"]);
+        assert_eq!(p.permissions.bash.unwrap().deny, ["git push *"]);
+        assert_eq!(p.instructions, "Never edit files.");
+    }
+
+    #[test]
+    fn body_only_profile() {
+        let p = parse("scout", "Explore and summarize.").unwrap();
+        assert_eq!(p.name, "scout");
+        assert!(p.model.is_none());
+        assert_eq!(p.instructions, "Explore and summarize.");
+    }
+
+    #[test]
+    fn rejects_bad_frontmatter() {
+        assert!(parse("x", "---\nmode: turbo\n---\nbody").is_err());
+        assert!(parse("x", "---\nmodle: gpt-5\n---\nbody").is_err());
+        assert!(parse("x", "---\nname: two words\n---\nbody").is_err());
+    }
+
+    #[test]
+    fn project_profiles_override_global() {
+        let global = tempfile::tempdir().unwrap();
+        let project = tempfile::tempdir().unwrap();
+        std::fs::write(
+            global.path().join("a.md"),
+            "---\ndescription: global\n---\n",
+        )
+        .unwrap();
+        std::fs::write(global.path().join("b.md"), "---\nmode: yolo\n---\n").unwrap();
+        std::fs::write(
+            project.path().join("a.md"),
+            "---\ndescription: local\n---\n",
+        )
+        .unwrap();
+        std::fs::write(project.path().join("notes.txt"), "ignored").unwrap();
+        let mut profiles = AgentProfiles::default();
+        profiles.scan_dir(global.path(), false);
+        profiles.scan_dir(project.path(), true);
+        assert_eq!(profiles.names(), ["a", "b"]);
+        assert_eq!(profiles.get("a").unwrap().description, "local");
+        assert_eq!(profiles.prompt_listing(), "  - a: local\n  - b");
+    }
+
+    #[test]
+    fn project_profiles_cannot_loosen() {
+        let global = tempfile::tempdir().unwrap();
+        let project = tempfile::tempdir().unwrap();
+        let text = "---\nmode: yolo\npermissions:\n  tools:\n    allow: [bash]\n    ask: [web_fetch]\n    deny: [write_file]\n  bash:\n    allow: [\"*\"]\n    ask: [\"git push *\"]\n    deny: [\"rm *\"]\n---\n";
+        std::fs::write(global.path().join("mine.md"), text).unwrap();
+        std::fs::write(project.path().join("repo.md"), text).unwrap();
+        let mut profiles = AgentProfiles::default();
+        profiles.scan_dir(global.path(), false);
+        profiles.scan_dir(project.path(), true);
+
+        let mine = profiles.get("mine").unwrap();
+        assert_eq!(mine.mode_under(Some(Mode::Normal)), Some(Mode::Yolo));
+        assert_eq!(mine.permissions.bash.as_ref().unwrap().allow, ["*"]);
+
+        let repo = profiles.get("repo").unwrap();
+        assert_eq!(repo.mode_under(Some(Mode::Plan)), Some(Mode::Plan));
+        assert_eq!(repo.mode_under(Some(Mode::Apply)), Some(Mode::Apply));
+        assert_eq!(repo.mode_under(None), Some(Mode::Normal));
+        let tools = repo.permissions.tools.as_ref().unwrap();
+        assert!(tools.allow.is_empty() && tools.ask.is_empty());
+        assert_eq!(tools.deny, ["write_file"]);
+        let bash = repo.permissions.bash.as_ref().unwrap();
+        assert!(bash.allow.is_empty());
+        assert_eq!(bash.ask, ["git push *"]);
+        assert_eq!(bash.deny, ["rm *"]);
+        assert_eq!(profiles.warnings().len(), 3);
+    }
+}
@@ -1,14 +1,20 @@
 use super::{collect_indexed_tool_calls, non_empty, sse};
 use super::{ParsedResponse, ProviderError, StreamDelta, ToolDefinition};
 use crate::cancel::CancellationToken;
-use crate::config::ModelConfig;
+use crate::capabilities::{ModelCapabilities, ReasoningStyle};
+use crate::config::{CacheTtl, ModelConfig};
 use crate::tools::{trim_tool_output, MAX_TOOL_OUTPUT_LINES};
 use protocol::{FunctionCall, Message, ReasoningEffort, Role, TokenUsage, ToolCall};
 use std::collections::HashMap;
 
-fn supports_adaptive_thinking(model: &str) -> bool {
-    model.contains("opus-4-6") || model.contains("sonnet-4-6")
-}
+/// Beta flag that enables the `1h` prompt-cache TTL.
+pub(super) const EXTENDED_CACHE_TTL_BETA: &str = "extended-cache-ttl-2025-04-11";
+
+/// `max_tokens` when the model's output limit is unknown.
+const DEFAULT_MAX_TOKENS: u32 = 4096;
+/// Upper bound on `max_tokens`, however large the model's limit: larger
+/// values make non-streaming requests liable to time out.
+const MAX_TOKENS_CAP: u32 = 32_000;
 
 fn parse_cache_write_tokens(u: &serde_json::Value) -> Option<u32> {
     u["cache_creation_input_tokens"]
@@ -32,6 +38,7 @@ pub(super) fn build_body(
     model: &str,
     effort: ReasoningEffort,
     config: &ModelConfig,
+    caps: &ModelCapabilities,
 ) -> serde_json::Value {
     let mut system_content: Option<String> = None;
     let mut content: Vec<serde_json::Value> = Vec::new();
@@ -110,7 +117,7 @@ pub(super) fn build_body(
     let mut body = serde_json::json!({
         "model": model,
         "messages": content,
-        "max_tokens": 4096,
+        "max_tokens": caps.max_output.map_or(DEFAULT_MAX_TOKENS, |n| n.min(MAX_TOKENS_CAP)),
     });
 
     if let Some(sys) = system_content {
@@ -118,6 +125,12 @@ pub(super) fn build_body(
     }
     if !api_tools.is_empty() {
         body["tools"] = serde_json::json!(api_tools);
+        if !caps.parallel_tools {
+            body["tool_choice"] = serde_json::json!({
+                "type": "auto",
+                "disable_parallel_tool_use": true,
+            });
+        }
     }
     if let Some(v) = config.temperature {
         body["temperature"] = serde_json::json!(v);
@@ -126,7 +139,11 @@ pub(super) fn build_body(
         body["top_p"] = serde_json::json!(v);
     }
 
-    if effort != ReasoningEffort::Off && supports_adaptive_thinking(model) {
+    if caps.prompt_caching {
+        add_cache_breakpoints(&mut body, config.cache_ttl());
+    }
+
+    if effort != ReasoningEffort::Off && caps.reasoning == ReasoningStyle::Adaptive {
         body["thinking"] = serde_json::json!({
             "type": "adaptive",
             "display": "summarized",
@@ -139,6 +156,61 @@ pub(super) fn build_body(
     body
 }
 
+fn cache_control(ttl: CacheTtl) -> Option<serde_json::Value> {
+    match ttl {
+        CacheTtl::Off => None,
+        CacheTtl::FiveMinutes => Some(serde_json::json!({"type": "ephemeral"})),
+        CacheTtl::OneHour => Some(serde_json::json!({"type": "ephemeral", "ttl": "1h"})),
+    }
+}
+
+/// Mark the cacheable prefix of a request. Uses all four breakpoints the
+/// API allows: the tool list, the system prompt, the end of the previous
+/// request's history (so it is read back even when many blocks were added
+/// since) and the end of this one (so the next request can read it).
+fn add_cache_breakpoints(body: &mut serde_json::Value, ttl: CacheTtl) {
+    let Some(cc) = cache_control(ttl) else {
+        return;
+    };
+    if let Some(last) = body["tools"].as_array_mut().and_then(|t| t.last_mut()) {
+        last["cache_control"] = cc.clone();
+    }
+    if let Some(sys) = body["system"].as_array_mut().and_then(|s| s.last_mut()) {
+        sys["cache_control"] = cc.clone();
+    }
+    let Some(messages) = body["messages"].as_array_mut() else {
+        return;
+    };
+    let Some(last) = messages.len().checked_sub(1) else {
+        return;
+    };
+    mark_last_block(&mut messages[last], &cc);
+    // The previous request ended with the user message just before the
+    // latest assistant reply.
+    let previous = messages[..last]
+        .iter()
+        .rposition(|m| m["role"] == "assistant")
+        .and_then(|a| messages[..a].iter().rposition(|m| m["role"] == "user"));
+    if let Some(i) = previous {
+        mark_last_block(&mut messages[i], &cc);
+    }
+}
+
+fn mark_last_block(message: &mut serde_json::Value, cc: &serde_json::Value) {
+    if let Some(text) = message["content"].as_str() {
+        if text.is_empty() {
+            return;
+        }
+        message["content"] = serde_json::json!([{"type": "text", "text": text}]);
+    }
+    if let Some(block) = message["content"]
+        .as_array_mut()
+        .and_then(|blocks| blocks.last_mut())
+    {
+        block["cache_control"] = cc.clone();
+    }
+}
+
 pub(super) fn parse_response(data: &serde_json::Value) -> Result<ParsedResponse, ProviderError> {
     let mut content: Option<String> = None;
     let mut reasoning: Option<String> = None;
@@ -209,21 +281,36 @@ pub(super) async fn read_stream(
     cancel: &CancellationToken,
     on_delta: &(dyn Fn(StreamDelta) + Send + Sync),
 ) -> Result<ParsedResponse, ProviderError> {
-    let mut content = String::new();
-    let mut reasoning = String::new();
-    let mut tool_calls: HashMap<usize, (String, String, String)> = HashMap::new();
-    let mut usage = TokenUsage::default();
+    let mut state = StreamState::default();
+    sse::read_events(resp, cancel, |ev| state.handle(ev, on_delta)).await?;
+    Ok(state.finish())
+}
 
-    sse::read_events(resp, cancel, |ev| {
+/// Accumulates a Messages API event stream. Shared with Bedrock, which
+/// wraps the same events in its own framing.
+#[derive(Default)]
+pub(super) struct StreamState {
+    content: String,
+    reasoning: String,
+    tool_calls: HashMap<usize, (String, String, String)>,
+    usage: TokenUsage,
+}
+
+impl StreamState {
+    pub(super) fn handle(
+        &mut self,
+        ev: &serde_json::Value,
+        on_delta: &(dyn Fn(StreamDelta) + Send + Sync),
+    ) {
         let event_type = ev["type"].as_str().unwrap_or("");
 
         match event_type {
             "message_start" => {
                 if let Some(u) = ev.get("message").and_then(|m| m.get("usage")) {
-                    usage.prompt_tokens = u["input_tokens"].as_u64().map(|n| n as u32);
-                    usage.cache_read_tokens =
+                    self.usage.prompt_tokens = u["input_tokens"].as_u64().map(|n| n as u32);
+                    self.usage.cache_read_tokens =
                         u["cache_read_input_tokens"].as_u64().map(|n| n as u32);
-                    usage.cache_write_tokens = parse_cache_write_tokens(u);
+                    self.usage.cache_write_tokens = parse_cache_write_tokens(u);
                 }
             }
             "content_block_start" => {
@@ -232,7 +319,8 @@ pub(super) async fn read_stream(
                         if cb["type"].as_str() == Some("tool_use") {
                             let id = cb["id"].as_str().unwrap_or_default().to_string();
                             let name = cb["name"].as_str().unwrap_or_default().to_string();
-                            tool_calls.insert(idx as usize, (id, name, String::new()));
+                            self.tool_calls
+                                .insert(idx as usize, (id, name, String::new()));
                         }
                     }
                 }
@@ -243,7 +331,7 @@ pub(super) async fn read_stream(
                         Some("text_delta") => {
                             if let Some(text) = delta["text"].as_str() {
                                 if !text.is_empty() {
-                                    content.push_str(text);
+                                    self.content.push_str(text);
                                     on_delta(StreamDelta::Text(text));
                                 }
                             }
@@ -251,7 +339,7 @@ pub(super) async fn read_stream(
                         Some("thinking_delta") => {
                             if let Some(text) = delta["thinking"].as_str() {
                                 if !text.is_empty() {
-                                    reasoning.push_str(text);
+                                    self.reasoning.push_str(text);
                                     on_delta(StreamDelta::Thinking(text));
                                 }
                             }
@@ -259,7 +347,7 @@ pub(super) async fn read_stream(
                         Some("input_json_delta") => {
                             if let Some(partial_json) = delta["partial_json"].as_str() {
                                 if let Some(idx) = ev["index"].as_u64() {
-                                    if let Some(entry) = tool_calls.get_mut(&(idx as usize)) {
+                                    if let Some(entry) = self.tool_calls.get_mut(&(idx as usize)) {
                                         entry.2.push_str(partial_json);
                                     }
                                 }
@@ -271,21 +359,111 @@ pub(super) async fn read_stream(
             }
             "message_delta" => {
                 if let Some(u) = ev.get("usage") {
-                    usage.completion_tokens = u["output_tokens"].as_u64().map(|n| n as u32);
-                    if usage.prompt_tokens.is_none() {
-                        usage.prompt_tokens = u["input_tokens"].as_u64().map(|n| n as u32);
+                    self.usage.completion_tokens = u["output_tokens"].as_u64().map(|n| n as u32);
+                    if self.usage.prompt_tokens.is_none() {
+                        self.usage.prompt_tokens = u["input_tokens"].as_u64().map(|n| n as u32);
                     }
                 }
             }
             _ => {}
         }
-    })
-    .await?;
+    }
 
-    Ok(ParsedResponse {
-        content: non_empty(content),
-        reasoning: non_empty(reasoning),
-        tool_calls: collect_indexed_tool_calls(tool_calls),
-        usage,
-    })
+    pub(super) fn finish(self) -> ParsedResponse {
+        ParsedResponse {
+            content: non_empty(self.content),
+            reasoning: non_empty(self.reasoning),
+            tool_calls: collect_indexed_tool_calls(self.tool_calls),
+            usage: self.usage,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol::Content;
+
+    fn history() -> Vec<Message> {
+        let call = ToolCall::new(
+            "t1".into(),
+            FunctionCall {
+                name: "read_file".into(),
+                arguments: "{}".into(),
+            },
+        );
+        vec![
+            Message::system("be brief"),
+            Message::user(Content::text("first")),
+            Message::assistant(None, None, Some(vec![call])),
+            Message::tool("t1".into(), "contents", false),
+        ]
+    }
+
+    fn request(model: &str, effort: ReasoningEffort, config: &ModelConfig) -> serde_json::Value {
+        let caps = crate::capabilities::resolve(model, "anthropic", config);
+        build_body(&history(), &[], model, effort, config, &caps)
+    }
+
+    #[test]
+    fn breakpoints_cover_system_and_rolling_tail() {
+        let body = request(
+            "claude-sonnet-4-5",
+            ReasoningEffort::Off,
+            &ModelConfig::default(),
+        );
+        let ephemeral = serde_json::json!({"type": "ephemeral"});
+        assert_eq!(body["system"][0]["cache_control"], ephemeral);
+        let messages = body["messages"].as_array().unwrap();
+        // Previous request's tail: the user text, converted to a block.
+        assert_eq!(messages[0]["content"][0]["text"], "first");
+        assert_eq!(messages[0]["content"][0]["cache_control"], ephemeral);
+        assert!(messages[1]["content"][0].get("cache_control").is_none());
+        assert_eq!(messages[2]["content"][0]["cache_control"], ephemeral);
+    }
+
+    #[test]
+    fn cache_ttl_controls_breakpoints() {
+        let hour = ModelConfig {
+            cache_ttl: Some(CacheTtl::OneHour),
+            ..ModelConfig::default()
+        };
+        let body = request("m", ReasoningEffort::Off, &hour);
+        assert_eq!(body["system"][0]["cache_control"]["ttl"], "1h");
+
+        let off = ModelConfig {
+            cache_ttl: Some(CacheTtl::Off),
+            ..ModelConfig::default()
+        };
+        let body = request("m", ReasoningEffort::Off, &off);
+        assert!(body["system"][0].get("cache_control").is_none());
+        assert_eq!(body["messages"][0]["content"], "first");
+    }
+
+    #[test]
+    fn capabilities_shape_the_request() {
+        let sonnet = request(
+            "claude-sonnet-4-5",
+            ReasoningEffort::High,
+            &ModelConfig::default(),
+        );
+        assert_eq!(sonnet["max_tokens"], MAX_TOKENS_CAP);
+        assert!(sonnet.get("thinking").is_none());
+
+        let unknown = request("m", ReasoningEffort::High, &ModelConfig::default());
+        assert_eq!(unknown["max_tokens"], DEFAULT_MAX_TOKENS);
+
+        let config = ModelConfig {
+            capabilities: protocol::CapabilityOverrides {
+                max_output: Some(8192),
+                reasoning: Some(true),
+                ..Default::default()
+            },
+            ..ModelConfig::default()
+        };
+        let custom = request("m", ReasoningEffort::High, &config);
+        assert_eq!(custom["max_tokens"], 8192);
+        assert_eq!(custom["thinking"]["type"], "adaptive");
+        assert_eq!(custom["output_config"]["effort"], "high");
+    }
 }
@@ -0,0 +1,42 @@
+//! Azure OpenAI: the Responses API on an Azure resource endpoint.
+//!
+//! Models are deployment names. Requests carry an `api-version` query
+//! parameter and authenticate with either a resource key (`api-key` header)
+//! or a Microsoft Entra access token (`Authorization: Bearer`).
+
+/// Used when the provider config sets no `api_version`.
+pub(super) const DEFAULT_API_VERSION: &str = "2025-04-01-preview";
+
+pub(super) fn url(api_base: &str, api_version: Option<&str>) -> String {
+    let base = api_base.trim_end_matches("/openai");
+    format!(
+        "{base}/openai/responses?api-version={}",
+        api_version.unwrap_or(DEFAULT_API_VERSION)
+    )
+}
+
+/// Attach credentials. Entra tokens are JWTs; anything else is a resource key.
+pub(super) fn authorize(req: reqwest::RequestBuilder, key: &str) -> reqwest::RequestBuilder {
+    if key.starts_with("eyJ") {
+        req.bearer_auth(key)
+    } else {
+        req.header("api-key", key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url_appends_api_version() {
+        assert_eq!(
+            url(
+                "https://res.openai.azure.com/openai",
+                Some("2025-03-01-preview")
+            ),
+            "https://res.openai.azure.com/openai/responses?api-version=2025-03-01-preview"
+        );
+        assert!(url("https://res.openai.azure.com", None).ends_with(DEFAULT_API_VERSION));
+    }
+}
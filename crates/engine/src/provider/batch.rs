@@ -0,0 +1,358 @@
+//! Batch-tier requests: one chat request submitted as a single-item batch
+//! and polled until the provider finishes it.
+//!
+//! - **Anthropic**: `POST /messages/batches`, poll, then fetch `results_url`.
+//! - **OpenAI**: upload a JSONL file, `POST /batches` against
+//!   `/v1/responses`, poll, then download the output file.
+
+use super::{anthropic, openai, unix_now, ParsedResponse, Provider, ProviderError, ProviderKind};
+use crate::cancel::CancellationToken;
+use crate::config::CacheTtl;
+use crate::log;
+use std::time::Duration;
+
+/// How often to check on a submitted batch.
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+const CUSTOM_ID: &str = "smelt-0";
+
+impl Provider {
+    pub(super) async fn chat_batch(
+        &self,
+        mut body: serde_json::Value,
+        cancel: &CancellationToken,
+    ) -> Result<ParsedResponse, ProviderError> {
+        if let Some(obj) = body.as_object_mut() {
+            obj.remove("stream");
+            obj.remove("stream_options");
+        }
+        match self.kind {
+            ProviderKind::Anthropic => self.anthropic_batch(body, cancel).await,
+            ProviderKind::OpenAi => self.openai_batch(body, cancel).await,
+            _ => Err(ProviderError::InvalidResponse(
+                "batch service tier is not supported by this provider".into(),
+            )),
+        }
+    }
+
+    /// Build an authenticated request. Fetches the key each time so that
+    /// command-issued tokens stay fresh over a long poll.
+    async fn batch_request(
+        &self,
+        method: reqwest::Method,
+        url: &str,
+    ) -> Result<reqwest::RequestBuilder, ProviderError> {
+        let key = self.api_key(false).await?;
+        let req = self.with_extra_headers(self.client.request(method, url));
+        Ok(if self.kind == ProviderKind::Anthropic {
+            let req = req
+                .header("x-api-key", key)
+                .header("anthropic-version", "2023-06-01");
+            if self.model_config.cache_ttl() == CacheTtl::OneHour {
+                req.header("anthropic-beta", anthropic::EXTENDED_CACHE_TTL_BETA)
+            } else {
+                req
+            }
+        } else {
+            req.bearer_auth(key)
+        })
+    }
+
+    /// Send a batch-management request and decode the JSON reply.
+    async fn batch_send(
+        &self,
+        req: reqwest::RequestBuilder,
+    ) -> Result<serde_json::Value, ProviderError> {
+        let text = self.batch_send_text(req).await?;
+        serde_json::from_str(&text).map_err(|e| ProviderError::InvalidResponse(e.to_string()))
+    }
+
+    async fn batch_send_text(&self, req: reqwest::RequestBuilder) -> Result<String, ProviderError> {
+        let resp = req
+            .send()
+            .await
+            .map_err(|e| ProviderError::Network(e.to_string()))?;
+        let code = resp.status().as_u16();
+        let ok = resp.status().is_success();
+        let text = resp
+            .text()
+            .await
+            .map_err(|e| ProviderError::Network(e.to_string()))?;
+        if ok {
+            Ok(text)
+        } else {
+            Err(ProviderError::from_http(code, text, None))
+        }
+    }
+
+    /// Poll `url` until `done` accepts the batch object. Transient failures
+    /// are logged and retried on the next poll, and a rejected
+    /// `api_key_command` token is refreshed once and retried at once;
+    /// cancellation asks the provider to cancel the batch.
+    async fn poll_batch(
+        &self,
+        url: &str,
+        cancel_url: &str,
+        cancel: &CancellationToken,
+        done: impl Fn(&serde_json::Value) -> bool,
+    ) -> Result<serde_json::Value, ProviderError> {
+        let mut key_refreshed = false;
+        loop {
+            let polled = match self.batch_request(reqwest::Method::GET, url).await {
+                Ok(req) => self.batch_send(req).await,
+                Err(e) => Err(e),
+            };
+            match polled {
+                Ok(batch) if done(&batch) => return Ok(batch),
+                Ok(batch) => {
+                    key_refreshed = false;
+                    log::entry(
+                        log::Level::Info,
+                        "batch_status",
+                        &serde_json::json!({
+                            "id": batch["id"],
+                            "status": batch["status"].as_str().or(batch["processing_status"].as_str()),
+                        }),
+                    );
+                }
+                Err(ProviderError::Auth(_))
+                    if !key_refreshed && self.model_config.auth.api_key_command.is_some() =>
+                {
+                    key_refreshed = true;
+                    self.api_key(true).await?;
+                    log::entry(
+                        log::Level::Info,
+                        "api_key_command_refresh",
+                        &serde_json::json!({ "batch": url }),
+                    );
+                    continue;
+                }
+                Err(e) if e.is_retryable() || matches!(e, ProviderError::RateLimited { .. }) => {
+                    log::entry(
+                        log::Level::Warn,
+                        "batch_poll_error",
+                        &serde_json::json!({ "error": e.to_string() }),
+                    );
+                }
+                Err(e) => return Err(e),
+            }
+            tokio::select! {
+                _ = cancel.cancelled() => {
+                    if let Ok(req) = self.batch_request(reqwest::Method::POST, cancel_url).await {
+                        let _ = self.batch_send_text(req).await;
+                    }
+                    return Err(ProviderError::Cancelled);
+                }
+                _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            }
+        }
+    }
+
+    async fn anthropic_batch(
+        &self,
+        body: serde_json::Value,
+        cancel: &CancellationToken,
+    ) -> Result<ParsedResponse, ProviderError> {
+        let base = format!("{}/messages/batches", self.api_base);
+        let req = self.batch_request(reqwest::Method::POST, &base).await?;
+        let created = self
+            .batch_send(req.json(&serde_json::json!({
+                "requests": [{ "custom_id": CUSTOM_ID, "params": body }],
+            })))
+            .await?;
+        let id = created["id"]
+            .as_str()
+            .ok_or_else(|| ProviderError::InvalidResponse("batch has no id".into()))?;
+        log::entry(
+            log::Level::Info,
+            "batch_submitted",
+            &serde_json::json!({ "id": id }),
+        );
+
+        let batch = self
+            .poll_batch(
+                &format!("{base}/{id}"),
+                &format!("{base}/{id}/cancel"),
+                cancel,
+                |b| b["processing_status"] == "ended",
+            )
+            .await?;
+        let results_url = batch["results_url"]
+            .as_str()
+            .ok_or_else(|| ProviderError::InvalidResponse("batch has no results".into()))?;
+        let req = self
+            .batch_request(reqwest::Method::GET, results_url)
+            .await?;
+        let results = self.batch_send_text(req).await?;
+        let line = find_result(&results)?;
+        let result = &line["result"];
+        match result["type"].as_str() {
+            Some("succeeded") => anthropic::parse_response(&result["message"]),
+            Some("errored") => Err(ProviderError::InvalidResponse(
+                result["error"]["error"]["message"]
+                    .as_str()
+                    .unwrap_or("batch request failed")
+                    .to_string(),
+            )),
+            other => Err(ProviderError::InvalidResponse(format!(
+                "batch request {}",
+                other.unwrap_or("failed")
+            ))),
+        }
+    }
+
+    async fn openai_batch(
+        &self,
+        body: serde_json::Value,
+        cancel: &CancellationToken,
+    ) -> Result<ParsedResponse, ProviderError> {
+        let line = serde_json::json!({
+            "custom_id": CUSTOM_ID,
+            "method": "POST",
+            "url": "/v1/responses",
+            "body": body,
+        });
+        let boundary = format!("smelt-{}", unix_now());
+        let upload = format!("{}/files", self.api_base);
+        let file = self
+            .batch_send(
+                self.batch_request(reqwest::Method::POST, &upload)
+                    .await?
+                    .header(
+                        "content-type",
+                        format!("multipart/form-data; boundary={boundary}"),
+                    )
+                    .body(multipart_upload(&boundary, &format!("{line}\n"))),
+            )
+            .await?;
+        let file_id = file["id"]
+            .as_str()
+            .ok_or_else(|| ProviderError::InvalidResponse("upload has no file id".into()))?;
+
+        let base = format!("{}/batches", self.api_base);
+        let req = self.batch_request(reqwest::Method::POST, &base).await?;
+        let created = self
+            .batch_send(req.json(&serde_json::json!({
+                "input_file_id": file_id,
+                "endpoint": "/v1/responses",
+                "completion_window": "24h",
+            })))
+            .await?;
+        let id = created["id"]
+            .as_str()
+            .ok_or_else(|| ProviderError::InvalidResponse("batch has no id".into()))?;
+        log::entry(
+            log::Level::Info,
+            "batch_submitted",
+            &serde_json::json!({ "id": id }),
+        );
+
+        let batch = self
+            .poll_batch(
+                &format!("{base}/{id}"),
+                &format!("{base}/{id}/cancel"),
+                cancel,
+                |b| {
+                    matches!(
+                        b["status"].as_str(),
+                        Some("completed" | "failed" | "expired" | "cancelled")
+                    )
+                },
+            )
+            .await?;
+        let Some(output) = batch["output_file_id"]
+            .as_str()
+            .or(batch["error_file_id"].as_str())
+        else {
+            return Err(ProviderError::InvalidResponse(format!(
+                "batch {}",
+                batch["status"].as_str().unwrap_or("failed")
+            )));
+        };
+        let content = format!("{}/files/{output}/content", self.api_base);
+        let req = self.batch_request(reqwest::Method::GET, &content).await?;
+        let results = self.batch_send_text(req).await?;
+        let line = find_result(&results)?;
+        let response = &line["response"];
+        let code = response["status_code"].as_u64().unwrap_or(0) as u16;
+        if code != 200 {
+            return Err(ProviderError::from_http(
+                code,
+                response["body"].to_string(),
+                None,
+            ));
+        }
+        openai::parse_response(&response["body"])
+    }
+}
+
+/// Find our request's line in a batch results JSONL file.
+fn find_result(jsonl: &str) -> Result<serde_json::Value, ProviderError> {
+    jsonl
+        .lines()
+        .filter_map(|l| serde_json::from_str::<serde_json::Value>(l).ok())
+        .find(|v| v["custom_id"] == CUSTOM_ID)
+        .ok_or_else(|| ProviderError::InvalidResponse("batch returned no result".into()))
+}
+
+/// Build a `multipart/form-data` body for the OpenAI file upload.
+fn multipart_upload(boundary: &str, jsonl: &str) -> String {
+    format!(
+        "--{boundary}\r\n\
+         Content-Disposition: form-data; name=\"purpose\"\r\n\r\n\
+         batch\r\n\
+         --{boundary}\r\n\
+         Content-Disposition: form-data; name=\"file\"; filename=\"batch.jsonl\"\r\n\
+         Content-Type: application/jsonl\r\n\r\n\
+         {jsonl}\r\n\
+         --{boundary}--\r\n"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_result_matches_custom_id() {
+        let jsonl = "{\"custom_id\":\"other\"}\n{\"custom_id\":\"smelt-0\",\"result\":{\"type\":\"succeeded\"}}\n";
+        let line = find_result(jsonl).unwrap();
+        assert_eq!(line["result"]["type"], "succeeded");
+        assert!(find_result("").is_err());
+    }
+
+    #[tokio::test]
+    async fn anthropic_one_hour_ttl_sends_beta_header() {
+        let provider = Provider::new(
+            "https://api.anthropic.com/v1".into(),
+            "key".into(),
+            "anthropic",
+            reqwest::Client::new(),
+        )
+        .with_model_config(crate::config::ModelConfig {
+            cache_ttl: Some(CacheTtl::OneHour),
+            ..Default::default()
+        });
+        let req = provider
+            .batch_request(
+                reqwest::Method::POST,
+                "https://api.anthropic.com/v1/messages/batches",
+            )
+            .await
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(
+            req.headers()["anthropic-beta"],
+            anthropic::EXTENDED_CACHE_TTL_BETA
+        );
+    }
+
+    #[test]
+    fn multipart_has_both_fields() {
+        let body = multipart_upload("b", "{}\n");
+        assert!(body.starts_with("--b\r\n"));
+        assert!(body.contains("name=\"purpose\"\r\n\r\nbatch\r\n"));
+        assert!(body.contains("filename=\"batch.jsonl\""));
+        assert!(body.ends_with("--b--\r\n"));
+    }
+}
@@ -0,0 +1,526 @@
+//! AWS Bedrock runtime for Anthropic models.
+//!
+//! Requests reuse the Anthropic Messages body (minus `model` and `stream`)
+//! against `InvokeModel` / `InvokeModelWithResponseStream`. Streaming
+//! responses arrive in AWS event-stream framing, each `chunk` carrying a
+//! base64-encoded Anthropic stream event.
+//!
+//! Authentication is a Bedrock API key (sent as a bearer token) when one is
+//! configured, otherwise SigV4 with credentials from the `AWS_*`
+//! environment variables or `~/.aws/credentials`.
+
+use super::anthropic::{StreamState, EXTENDED_CACHE_TTL_BETA};
+use super::{ParsedResponse, ProviderError, StreamDelta};
+use crate::cancel::CancellationToken;
+use crate::config::{CacheTtl, ModelConfig};
+use base64::Engine as _;
+use futures_util::StreamExt;
+use sha2::{Digest, Sha256};
+
+const ANTHROPIC_VERSION: &str = "bedrock-2023-05-31";
+const SERVICE: &str = "bedrock";
+const DEFAULT_REGION: &str = "us-east-1";
+
+/// Runtime endpoint for the region in `AWS_REGION` / `AWS_DEFAULT_REGION`.
+pub(super) fn default_api_base() -> String {
+    let region = std::env::var("AWS_REGION")
+        .or_else(|_| std::env::var("AWS_DEFAULT_REGION"))
+        .unwrap_or_else(|_| DEFAULT_REGION.to_string());
+    format!("https://bedrock-runtime.{region}.amazonaws.com")
+}
+
+/// Region from a `bedrock-runtime.<region>.amazonaws.com` host.
+fn region_from_base(api_base: &str) -> Option<&str> {
+    let host = api_base.split("://").nth(1)?.split(['/', ':']).next()?;
+    host.strip_prefix("bedrock-runtime.")?
+        .strip_suffix(".amazonaws.com")
+        .filter(|r| !r.contains('.'))
+}
+
+pub(super) fn url(api_base: &str, model: &str, stream: bool) -> String {
+    let action = if stream {
+        "invoke-with-response-stream"
+    } else {
+        "invoke"
+    };
+    format!("{api_base}/model/{}/{action}", uri_encode(model))
+}
+
+/// Turn an Anthropic Messages body into an InvokeModel body.
+pub(super) fn adapt_body(body: &mut serde_json::Value, config: &ModelConfig) {
+    if let Some(obj) = body.as_object_mut() {
+        obj.remove("model");
+        obj.remove("stream");
+        obj.insert("anthropic_version".into(), ANTHROPIC_VERSION.into());
+        if config.cache_ttl() == CacheTtl::OneHour {
+            obj.insert(
+                "anthropic_beta".into(),
+                serde_json::json!([EXTENDED_CACHE_TTL_BETA]),
+            );
+        }
+    }
+}
+
+// ── Credentials and signing ─────────────────────────────────────────────────
+
+pub(super) struct AwsCredentials {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+}
+
+impl AwsCredentials {
+    pub(super) fn load() -> Result<Self, ProviderError> {
+        if let (Ok(id), Ok(secret)) = (
+            std::env::var("AWS_ACCESS_KEY_ID"),
+            std::env::var("AWS_SECRET_ACCESS_KEY"),
+        ) {
+            return Ok(Self {
+                access_key_id: id,
+                secret_access_key: secret,
+                session_token: std::env::var("AWS_SESSION_TOKEN").ok(),
+            });
+        }
+        Self::from_credentials_file().ok_or_else(|| {
+            ProviderError::Auth(
+                "no AWS credentials: set AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY, \
+                 add them to ~/.aws/credentials, or set api_key_env to a Bedrock API key"
+                    .into(),
+            )
+        })
+    }
+
+    fn from_credentials_file() -> Option<Self> {
+        let path = std::env::var("AWS_SHARED_CREDENTIALS_FILE")
+            .map(std::path::PathBuf::from)
+            .unwrap_or_else(|_| crate::home_dir().join(".aws/credentials"));
+        let text = std::fs::read_to_string(path).ok()?;
+        let profile = std::env::var("AWS_PROFILE").unwrap_or_else(|_| "default".into());
+        parse_credentials(&text, &profile)
+    }
+}
+
+/// Read one profile from an INI-style AWS credentials file.
+fn parse_credentials(text: &str, profile: &str) -> Option<AwsCredentials> {
+    let mut in_profile = false;
+    let (mut id, mut secret, mut token) = (None, None, None);
+    for line in text.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            in_profile = name.trim() == profile;
+            continue;
+        }
+        if !in_profile {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = Some(value.trim().to_string());
+        match key.trim() {
+            "aws_access_key_id" => id = value,
+            "aws_secret_access_key" => secret = value,
+            "aws_session_token" => token = value,
+            _ => {}
+        }
+    }
+    Some(AwsCredentials {
+        access_key_id: id?,
+        secret_access_key: secret?,
+        session_token: token,
+    })
+}
+
+/// SigV4-sign a POST of `body` to `url`, returning the headers to add.
+pub(super) fn sign(
+    url: &str,
+    body: &[u8],
+    creds: &AwsCredentials,
+    now: u64,
+) -> Result<Vec<(&'static str, String)>, ProviderError> {
+    let parsed = url::Url::parse(url).map_err(|e| ProviderError::Network(e.to_string()))?;
+    let host = match (parsed.host_str(), parsed.port()) {
+        (Some(h), Some(p)) => format!("{h}:{p}"),
+        (Some(h), None) => h.to_string(),
+        (None, _) => return Err(ProviderError::Network(format!("bad URL: {url}"))),
+    };
+    let region = region_from_base(url)
+        .map(str::to_string)
+        .or_else(|| std::env::var("AWS_REGION").ok())
+        .unwrap_or_else(|| DEFAULT_REGION.to_string());
+    let amz_date = amz_date(now);
+
+    let mut headers = vec![
+        ("content-type", "application/json".to_string()),
+        ("host", host),
+        ("x-amz-date", amz_date.clone()),
+    ];
+    if let Some(token) = &creds.session_token {
+        headers.push(("x-amz-security-token", token.clone()));
+    }
+    let signature = signature(
+        "POST",
+        parsed.path(),
+        parsed.query().unwrap_or(""),
+        &headers,
+        &hex(&Sha256::digest(body)),
+        &amz_date,
+        &region,
+        SERVICE,
+        &creds.secret_access_key,
+    );
+    let signed: Vec<&str> = headers.iter().map(|(k, _)| *k).collect();
+    let scope = format!("{}/{region}/{SERVICE}/aws4_request", &amz_date[..8]);
+
+    let mut out: Vec<(&'static str, String)> = headers
+        .into_iter()
+        .filter(|(k, _)| *k != "host" && *k != "content-type")
+        .collect();
+    out.push((
+        "authorization",
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={}, Signature={signature}",
+            creds.access_key_id,
+            signed.join(";")
+        ),
+    ));
+    Ok(out)
+}
+
+/// Compute a SigV4 signature. `headers` must be lowercase and sorted.
+#[allow(clippy::too_many_arguments)]
+fn signature(
+    method: &str,
+    path: &str,
+    query: &str,
+    headers: &[(&str, String)],
+    payload_hash: &str,
+    amz_date: &str,
+    region: &str,
+    service: &str,
+    secret: &str,
+) -> String {
+    // Non-S3 services sign the path with each segment encoded again.
+    let canonical_uri = path
+        .split('/')
+        .map(uri_encode)
+        .collect::<Vec<_>>()
+        .join("/");
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(k, v)| format!("{k}:{}\n", v.trim()))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(k, _)| *k)
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_request = format!(
+        "{method}\n{canonical_uri}\n{query}\n{canonical_headers}\n{signed_headers}\n{payload_hash}"
+    );
+
+    let date = &amz_date[..8];
+    let scope = format!("{date}/{region}/{service}/aws4_request");
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+        hex(&Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let k_date = hmac_sha256(format!("AWS4{secret}").as_bytes(), date.as_bytes());
+    let k_region = hmac_sha256(&k_date, region.as_bytes());
+    let k_service = hmac_sha256(&k_region, service.as_bytes());
+    let k_signing = hmac_sha256(&k_service, b"aws4_request");
+    hex(&hmac_sha256(&k_signing, string_to_sign.as_bytes()))
+}
+
+pub(crate) fn hmac_sha256(key: &[u8], msg: &[u8]) -> [u8; 32] {
+    const BLOCK: usize = 64;
+    let mut block = [0u8; BLOCK];
+    if key.len() > BLOCK {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let mut inner = Sha256::new();
+    inner.update(block.map(|b| b ^ 0x36));
+    inner.update(msg);
+    let mut outer = Sha256::new();
+    outer.update(block.map(|b| b ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Percent-encode everything except RFC 3986 unreserved characters.
+fn uri_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b'~') {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
+
+/// `YYYYMMDDTHHMMSSZ` for a Unix timestamp.
+fn amz_date(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    // Civil-from-days (Howard Hinnant).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}{month:02}{day:02}T{:02}{:02}{:02}Z",
+        rem / 3600,
+        rem / 60 % 60,
+        rem % 60
+    )
+}
+
+// ── Event stream ────────────────────────────────────────────────────────────
+
+/// One decoded event-stream message.
+struct Frame {
+    message_type: String,
+    event_type: String,
+    payload: Vec<u8>,
+}
+
+/// Pop every complete frame off the front of `buf`.
+fn decode_frames(buf: &mut Vec<u8>) -> Result<Vec<Frame>, ProviderError> {
+    let mut frames = Vec::new();
+    loop {
+        if buf.len() < 12 {
+            break;
+        }
+        let total = u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]) as usize;
+        let headers_len = u32::from_be_bytes([buf[4], buf[5], buf[6], buf[7]]) as usize;
+        if total < 16 + headers_len {
+            return Err(ProviderError::InvalidResponse(
+                "malformed event-stream frame".into(),
+            ));
+        }
+        if buf.len() < total {
+            break;
+        }
+        let frame: Vec<u8> = buf.drain(..total).collect();
+        let headers = parse_headers(&frame[12..12 + headers_len])?;
+        let header = |name: &str| {
+            headers
+                .iter()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| v.clone())
+                .unwrap_or_default()
+        };
+        let mut event_type = header(":event-type");
+        if event_type.is_empty() {
+            event_type = header(":exception-type");
+        }
+        frames.push(Frame {
+            message_type: header(":message-type"),
+            event_type,
+            payload: frame[12 + headers_len..total - 4].to_vec(),
+        });
+    }
+    Ok(frames)
+}
+
+/// Parse event-stream headers, keeping only string-valued ones.
+fn parse_headers(mut raw: &[u8]) -> Result<Vec<(String, String)>, ProviderError> {
+    let bad = || ProviderError::InvalidResponse("malformed event-stream header".into());
+    let mut out = Vec::new();
+    while !raw.is_empty() {
+        let name_len = *raw.first().ok_or_else(bad)? as usize;
+        let name = raw.get(1..1 + name_len).ok_or_else(bad)?;
+        let kind = *raw.get(1 + name_len).ok_or_else(bad)?;
+        raw = &raw[2 + name_len..];
+        let value_len = match kind {
+            0 | 1 => 0,
+            2 => 1,
+            3 => 2,
+            4 => 4,
+            5 | 8 => 8,
+            9 => 16,
+            6 | 7 => {
+                let len = raw.get(..2).ok_or_else(bad)?;
+                raw = &raw[2..];
+                u16::from_be_bytes([len[0], len[1]]) as usize
+            }
+            _ => return Err(bad()),
+        };
+        let value = raw.get(..value_len).ok_or_else(bad)?;
+        if kind == 7 {
+            out.push((
+                String::from_utf8_lossy(name).into_owned(),
+                String::from_utf8_lossy(value).into_owned(),
+            ));
+        }
+        raw = &raw[value_len..];
+    }
+    Ok(out)
+}
+
+fn exception_error(kind: &str, payload: &[u8]) -> ProviderError {
+    let message = serde_json::from_slice::<serde_json::Value>(payload)
+        .ok()
+        .and_then(|v| v["message"].as_str().map(str::to_string))
+        .unwrap_or_default();
+    match kind {
+        "throttlingException" => ProviderError::RateLimited { resets_at: None },
+        "serviceUnavailableException" | "internalServerException" => ProviderError::Server {
+            status: 503,
+            body: message,
+        },
+        _ => ProviderError::InvalidResponse(format!("{kind}: {message}")),
+    }
+}
+
+pub(super) async fn read_stream(
+    resp: reqwest::Response,
+    cancel: &CancellationToken,
+    on_delta: &(dyn Fn(StreamDelta) + Send + Sync),
+) -> Result<ParsedResponse, ProviderError> {
+    let mut state = StreamState::default();
+    let mut buf = Vec::new();
+    let mut stream = resp.bytes_stream();
+
+    loop {
+        let chunk = tokio::select! {
+            _ = cancel.cancelled() => return Err(ProviderError::Cancelled),
+            chunk = stream.next() => chunk,
+        };
+        match chunk {
+            Some(Ok(bytes)) => buf.extend_from_slice(&bytes),
+            Some(Err(e)) => return Err(ProviderError::Network(e.to_string())),
+            None => break,
+        }
+        for frame in decode_frames(&mut buf)? {
+            if frame.message_type != "event" {
+                return Err(exception_error(&frame.event_type, &frame.payload));
+            }
+            if frame.event_type != "chunk" {
+                continue;
+            }
+            let Ok(wrapper) = serde_json::from_slice::<serde_json::Value>(&frame.payload) else {
+                continue;
+            };
+            let Some(bytes) = wrapper["bytes"]
+                .as_str()
+                .and_then(|b| base64::engine::general_purpose::STANDARD.decode(b).ok())
+            else {
+                continue;
+            };
+            if let Ok(ev) = serde_json::from_slice::<serde_json::Value>(&bytes) {
+                state.handle(&ev, on_delta);
+            }
+        }
+    }
+
+    Ok(state.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hmac_matches_rfc4231() {
+        let mac = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+        assert_eq!(
+            hex(&mac),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn signature_matches_aws_get_vanilla() {
+        let headers = [
+            ("host", "example.amazonaws.com".to_string()),
+            ("x-amz-date", "20150830T123600Z".to_string()),
+        ];
+        let sig = signature(
+            "GET",
+            "/",
+            "",
+            &headers,
+            &hex(&Sha256::digest(b"")),
+            "20150830T123600Z",
+            "us-east-1",
+            "service",
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+        );
+        assert_eq!(
+            sig,
+            "5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+    }
+
+    #[test]
+    fn model_ids_are_encoded_and_regions_parsed() {
+        let base = "https://bedrock-runtime.eu-west-1.amazonaws.com";
+        assert_eq!(region_from_base(base), Some("eu-west-1"));
+        assert_eq!(
+            url(base, "anthropic.claude-v1:0", true),
+            format!("{base}/model/anthropic.claude-v1%3A0/invoke-with-response-stream")
+        );
+        assert_eq!(amz_date(1_440_938_160), "20150830T123600Z");
+    }
+
+    #[test]
+    fn credentials_file_profiles() {
+        let text = "[default]\naws_access_key_id = A\naws_secret_access_key = B\n\
+                    [work]\naws_access_key_id=C\naws_secret_access_key=D\naws_session_token=E\n";
+        let work = parse_credentials(text, "work").unwrap();
+        assert_eq!(work.access_key_id, "C");
+        assert_eq!(work.session_token.as_deref(), Some("E"));
+        assert_eq!(
+            parse_credentials(text, "default")
+                .unwrap()
+                .secret_access_key,
+            "B"
+        );
+        assert!(parse_credentials(text, "missing").is_none());
+    }
+
+    #[test]
+    fn decodes_chunk_frames() {
+        fn header(name: &str, value: &str) -> Vec<u8> {
+            let mut h = vec![name.len() as u8];
+            h.extend_from_slice(name.as_bytes());
+            h.push(7);
+            h.extend_from_slice(&(value.len() as u16).to_be_bytes());
+            h.extend_from_slice(value.as_bytes());
+            h
+        }
+        let mut headers = header(":message-type", "event");
+        headers.extend(header(":event-type", "chunk"));
+        let payload = br#"{"bytes":"e30="}"#;
+        let total = 16 + headers.len() + payload.len();
+        let mut frame = Vec::new();
+        frame.extend_from_slice(&(total as u32).to_be_bytes());
+        frame.extend_from_slice(&(headers.len() as u32).to_be_bytes());
+        frame.extend_from_slice(&[0; 4]);
+        frame.extend_from_slice(&headers);
+        frame.extend_from_slice(payload);
+        frame.extend_from_slice(&[0; 4]);
+
+        let mut buf = frame.clone();
+        buf.extend_from_slice(&frame[..5]);
+        let frames = decode_frames(&mut buf).unwrap();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].event_type, "chunk");
+        assert_eq!(frames[0].payload, payload);
+        assert_eq!(buf.len(), 5);
+    }
+}
@@ -2,6 +2,7 @@ use super::extract::extract_tool_calls_from_text;
 use super::{collect_indexed_tool_calls, non_empty, sse};
 use super::{ParsedResponse, ProviderError, StreamDelta, ToolDefinition};
 use crate::cancel::CancellationToken;
+use crate::capabilities::ModelCapabilities;
 use crate::config::ModelConfig;
 use crate::tools::{trim_tool_output, MAX_TOOL_OUTPUT_LINES};
 use protocol::{Message, ReasoningEffort, Role, TokenUsage, ToolCall};
@@ -13,6 +14,7 @@ pub(super) fn build_body(
     model: &str,
     effort: ReasoningEffort,
     config: &ModelConfig,
+    caps: &ModelCapabilities,
 ) -> serde_json::Value {
     let api_messages: Vec<serde_json::Value> = messages
         .iter()
@@ -39,6 +41,9 @@ pub(super) fn build_body(
 
     if !tools.is_empty() {
         body["tools"] = serde_json::to_value(tools).unwrap();
+        if !caps.parallel_tools {
+            body["parallel_tool_calls"] = serde_json::json!(false);
+        }
     }
     if let Some(v) = config.temperature {
         body["temperature"] = serde_json::json!(v);
@@ -101,27 +106,33 @@ pub(super) fn parse_response(data: &serde_json::Value) -> Result<ParsedResponse,
         }
     }
 
-    let u = &data["usage"];
-    let usage = TokenUsage {
-        prompt_tokens: u["prompt_tokens"].as_u64().map(|n| n as u32),
-        completion_tokens: u["completion_tokens"].as_u64().map(|n| n as u32),
-        cache_read_tokens: u["prompt_tokens_details"]["cached_tokens"]
-            .as_u64()
-            .map(|n| n as u32),
-        cache_write_tokens: None,
-        reasoning_tokens: u["completion_tokens_details"]["reasoning_tokens"]
-            .as_u64()
-            .map(|n| n as u32),
-    };
-
     Ok(ParsedResponse {
         content,
         reasoning,
         tool_calls,
-        usage,
+        usage: parse_usage(&data["usage"]),
     })
 }
 
+fn parse_usage(u: &serde_json::Value) -> TokenUsage {
+    // `prompt_tokens` includes cached input here; report it uncached like
+    // the other providers so cost and context math stay consistent.
+    let cached = u["prompt_tokens_details"]["cached_tokens"]
+        .as_u64()
+        .map(|n| n as u32);
+    TokenUsage {
+        prompt_tokens: u["prompt_tokens"]
+            .as_u64()
+            .map(|n| (n as u32).saturating_sub(cached.unwrap_or(0))),
+        completion_tokens: u["completion_tokens"].as_u64().map(|n| n as u32),
+        cache_read_tokens: cached,
+        cache_write_tokens: None,
+        reasoning_tokens: u["completion_tokens_details"]["reasoning_tokens"]
+            .as_u64()
+            .map(|n| n as u32),
+    }
+}
+
 pub(super) async fn read_stream(
     resp: reqwest::Response,
     cancel: &CancellationToken,
@@ -135,16 +146,11 @@ pub(super) async fn read_stream(
     sse::read_events(resp, cancel, |ev| {
         // Usage from the final chunk
         if let Some(u) = ev.get("usage") {
-            usage.prompt_tokens = u["prompt_tokens"].as_u64().map(|n| n as u32);
-            usage.completion_tokens = usage
-                .completion_tokens
-                .or(u["completion_tokens"].as_u64().map(|n| n as u32));
-            usage.cache_read_tokens = u["prompt_tokens_details"]["cached_tokens"]
-                .as_u64()
-                .map(|n| n as u32);
-            usage.reasoning_tokens = u["completion_tokens_details"]["reasoning_tokens"]
-                .as_u64()
-                .map(|n| n as u32);
+            let chunk = parse_usage(u);
+            usage = TokenUsage {
+                completion_tokens: usage.completion_tokens.or(chunk.completion_tokens),
+                ..chunk
+            };
         }
 
         let Some(delta) = ev["choices"].get(0).and_then(|c| c.get("delta")) else {
@@ -213,3 +219,33 @@ pub(super) async fn read_stream(
         usage,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn streamed_usage_excludes_cached_prompt_tokens() {
+        let body = concat!(
+            "data: {\"choices\":[{\"delta\":{\"content\":\"hi\"}}]}\n\n",
+            "data: {\"choices\":[],\"usage\":{\"prompt_tokens\":1000,",
+            "\"completion_tokens\":5,\"prompt_tokens_details\":{\"cached_tokens\":800}}}\n\n",
+            "data: [DONE]\n\n",
+        );
+        let resp = reqwest::Response::from(http::Response::new(body));
+        let parsed = read_stream(resp, &CancellationToken::new(), &|_| {})
+            .await
+            .unwrap();
+        assert_eq!(parsed.content.as_deref(), Some("hi"));
+        assert_eq!(parsed.usage.prompt_tokens, Some(200));
+        assert_eq!(parsed.usage.cache_read_tokens, Some(800));
+        assert_eq!(parsed.usage.completion_tokens, Some(5));
+
+        let data = serde_json::json!({
+            "choices": [{"message": {"content": "hi"}}],
+            "usage": {"prompt_tokens": 1000, "prompt_tokens_details": {"cached_tokens": 800}},
+        });
+        let usage = parse_response(&data).unwrap().usage;
+        assert_eq!(usage.prompt_tokens, parsed.usage.prompt_tokens);
+    }
+}
@@ -0,0 +1,129 @@
+//! `api_key_command`: obtain short-lived API tokens from an external command.
+//!
+//! The command runs through `sh -c` and prints either a bare token or a JSON
+//! object with `token` (or `access_token` / `api_key`) and an optional
+//! `expires_in` (seconds) or `expires_at` (Unix seconds). Tokens are cached
+//! per command, process-wide, so the main and auxiliary models share them.
+
+use super::{unix_now, ProviderError};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// Lifetime assumed for tokens that don't report an expiry.
+const DEFAULT_TTL_SECS: u64 = 300;
+/// Refresh this long before the reported expiry.
+const EXPIRY_MARGIN_SECS: u64 = 30;
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+
+struct CachedToken {
+    token: String,
+    expires_at: u64,
+}
+
+fn cache() -> &'static Mutex<HashMap<String, CachedToken>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, CachedToken>>> = OnceLock::new();
+    CACHE.get_or_init(Default::default)
+}
+
+/// Return a cached token for `command`, running it when the cache is empty,
+/// expired, or `refresh` is set (e.g. after a 401).
+pub(super) async fn token(command: &str, refresh: bool) -> Result<String, ProviderError> {
+    if !refresh {
+        if let Some(cached) = cache().lock().unwrap().get(command) {
+            if cached.expires_at > unix_now() + EXPIRY_MARGIN_SECS {
+                return Ok(cached.token.clone());
+            }
+        }
+    }
+    let output = run(command).await?;
+    let (token, expires_at) = parse_output(&output, unix_now())
+        .ok_or_else(|| ProviderError::Auth("api_key_command printed no token".into()))?;
+    cache().lock().unwrap().insert(
+        command.to_string(),
+        CachedToken {
+            token: token.clone(),
+            expires_at,
+        },
+    );
+    Ok(token)
+}
+
+async fn run(command: &str) -> Result<String, ProviderError> {
+    let fail = |msg: String| ProviderError::Auth(format!("api_key_command failed: {msg}"));
+    let child = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .output();
+    let output = tokio::time::timeout(COMMAND_TIMEOUT, child)
+        .await
+        .map_err(|_| fail(format!("timed out after {}s", COMMAND_TIMEOUT.as_secs())))?
+        .map_err(|e| fail(e.to_string()))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(fail(format!("{}: {}", output.status, stderr.trim())));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Extract `(token, expires_at)` from command output.
+fn parse_output(output: &str, now: u64) -> Option<(String, u64)> {
+    let trimmed = output.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(trimmed) else {
+        return Some((trimmed.to_string(), now + DEFAULT_TTL_SECS));
+    };
+    if !json.is_object() {
+        return Some((trimmed.to_string(), now + DEFAULT_TTL_SECS));
+    }
+    let token = ["token", "access_token", "api_key"]
+        .iter()
+        .find_map(|k| json[k].as_str())?
+        .to_string();
+    let expires_at = json["expires_at"]
+        .as_u64()
+        .or_else(|| json["expires_in"].as_u64().map(|s| now + s))
+        .unwrap_or(now + DEFAULT_TTL_SECS);
+    Some((token, expires_at))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_plain_and_json_output() {
+        assert_eq!(
+            parse_output("tok-123\n", 100),
+            Some(("tok-123".into(), 100 + DEFAULT_TTL_SECS))
+        );
+        assert_eq!(
+            parse_output(r#"{"access_token":"abc","expires_in":60}"#, 100),
+            Some(("abc".into(), 160))
+        );
+        assert_eq!(
+            parse_output(r#"{"token":"abc","expires_at":5000}"#, 100),
+            Some(("abc".into(), 5000))
+        );
+        assert_eq!(parse_output(r#"{"nope":1}"#, 100), None);
+        assert_eq!(parse_output("  ", 100), None);
+    }
+
+    #[tokio::test]
+    async fn caches_until_refresh() {
+        let dir = tempfile::tempdir().unwrap();
+        let counter = dir.path().join("n");
+        let command = format!(
+            "echo x >> {0}; printf 'tok-%s' $(wc -l < {0} | tr -d ' ')",
+            counter.display()
+        );
+        assert_eq!(token(&command, false).await.unwrap(), "tok-1");
+        assert_eq!(token(&command, false).await.unwrap(), "tok-1");
+        assert_eq!(token(&command, true).await.unwrap(), "tok-2");
+        assert!(token("exit 3", false).await.is_err());
+    }
+}
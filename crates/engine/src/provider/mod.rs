@@ -1,13 +1,21 @@
 mod anthropic;
 mod auth_storage;
+mod azure;
+mod batch;
+mod bedrock;
 mod chat_completions;
 pub mod codex;
 pub mod copilot;
 mod extract;
+mod key_command;
 mod openai;
 mod sse;
 
+pub(crate) use bedrock::hmac_sha256;
+
 use crate::cancel::CancellationToken;
+use crate::capabilities::{self, ModelCapabilities, ReasoningStyle};
+use crate::config::{CacheTtl, ServiceTier};
 use crate::log;
 pub use protocol::TokenUsage;
 use protocol::{Content, Message, ReasoningEffort, ToolCall};
@@ -256,6 +264,12 @@ fn backoff_delay(attempt: usize) -> Duration {
     Duration::from_millis(500 * 2u64.pow(attempt as u32))
 }
 
+/// Flex requests may sit in a queue; OpenAI recommends a long client timeout.
+const FLEX_REQUEST_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+/// Flex capacity errors come back as 429s; keep retrying them for a while.
+const FLEX_MAX_RETRIES: usize = 20;
+const FLEX_MAX_BACKOFF: Duration = Duration::from_secs(120);
+
 /// Parse the `retry-after` header from an HTTP response (seconds).
 fn parse_retry_after(resp: &reqwest::Response) -> Option<Duration> {
     let val = resp.headers().get("retry-after")?.to_str().ok()?;
@@ -273,13 +287,20 @@ pub enum ProviderKind {
     Codex,
     Anthropic,
     Copilot,
+    Bedrock,
+    AzureOpenAi,
     Local,
 }
 
 impl ProviderKind {
     pub fn default_reasoning_cycle(self) -> &'static [ReasoningEffort] {
         match self {
-            Self::OpenAi | Self::Codex | Self::Anthropic | Self::Copilot => &[
+            Self::OpenAi
+            | Self::Codex
+            | Self::Anthropic
+            | Self::Copilot
+            | Self::Bedrock
+            | Self::AzureOpenAi => &[
                 ReasoningEffort::Off,
                 ReasoningEffort::Low,
                 ReasoningEffort::Medium,
@@ -301,6 +322,8 @@ impl ProviderKind {
             "codex" => Self::Codex,
             "anthropic" => Self::Anthropic,
             "copilot" | "github-copilot" => Self::Copilot,
+            "bedrock" => Self::Bedrock,
+            "azure-openai" => Self::AzureOpenAi,
             _ => Self::Local,
         }
     }
@@ -314,6 +337,10 @@ impl ProviderKind {
             Self::Anthropic
         } else if api_base.contains("githubcopilot.com") {
             Self::Copilot
+        } else if api_base.contains("bedrock-runtime.") {
+            Self::Bedrock
+        } else if api_base.contains(".openai.azure.com") {
+            Self::AzureOpenAi
         } else {
             Self::Local
         }
@@ -325,6 +352,8 @@ impl ProviderKind {
             Self::Codex => "codex",
             Self::Anthropic => "anthropic",
             Self::Copilot => "copilot",
+            Self::Bedrock => "bedrock",
+            Self::AzureOpenAi => "azure-openai",
             Self::Local => "openai-compatible",
         }
     }
@@ -410,8 +439,12 @@ pub(crate) fn fixup_agent_message(m: &Message, v: &mut serde_json::Value) {
 
 impl Provider {
     pub fn new(api_base: String, api_key: String, provider_type: &str, client: Client) -> Self {
-        let api_base = api_base.trim_end_matches('/').to_string();
         let kind = ProviderKind::from_config(provider_type);
+        let api_base = if kind == ProviderKind::Bedrock && api_base.is_empty() {
+            bedrock::default_api_base()
+        } else {
+            api_base.trim_end_matches('/').to_string()
+        };
         Self {
             api_base,
             api_key,
@@ -432,8 +465,25 @@ impl Provider {
         self
     }
 
-    pub fn tool_calling(&self) -> bool {
-        self.model_config.tool_calling()
+    /// Capabilities of `model` on this provider.
+    pub fn capabilities(&self, model: &str) -> ModelCapabilities {
+        capabilities::resolve(model, self.kind.as_config_str(), &self.model_config)
+    }
+
+    /// The API key to send: the static key, or a token from
+    /// `api_key_command` (re-run when `refresh` is set or it has expired).
+    async fn api_key(&self, refresh: bool) -> Result<String, ProviderError> {
+        match &self.model_config.auth.api_key_command {
+            Some(command) => key_command::token(command, refresh).await,
+            None => Ok(self.api_key.clone()),
+        }
+    }
+
+    fn with_extra_headers(&self, mut req: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        for (k, v) in &self.model_config.auth.headers {
+            req = req.header(k, v);
+        }
+        req
     }
 
     pub fn apply_model_overrides(&mut self, overrides: &protocol::ModelConfigOverrides) {
@@ -467,6 +517,23 @@ impl Provider {
         let is_anthropic = self.kind == ProviderKind::Anthropic;
         let is_codex = self.kind == ProviderKind::Codex;
         let is_copilot = self.kind == ProviderKind::Copilot;
+        let is_bedrock = self.kind == ProviderKind::Bedrock;
+        let is_azure = self.kind == ProviderKind::AzureOpenAi;
+        let use_stream = opts.on_delta.is_some() || is_codex;
+
+        let caps = self.capabilities(model);
+        let effort = if caps.reasoning == ReasoningStyle::None {
+            ReasoningEffort::Off
+        } else {
+            effort
+        };
+        let stripped;
+        let messages = if !caps.vision && messages.iter().any(carries_image) {
+            stripped = strip_images(messages, model);
+            &stripped[..]
+        } else {
+            messages
+        };
 
         // Codex: resolve OAuth access token (refreshing if needed).
         let mut codex_auth = if is_codex {
@@ -492,16 +559,20 @@ impl Provider {
         };
         let mut copilot_401_retried = false;
 
+        let mut api_key = self.api_key(false).await?;
+        let mut key_refreshed = false;
+
         let (mut url, mut body) = match self.kind {
             ProviderKind::OpenAi => {
                 let url = format!("{}/responses", self.api_base);
-                let body = openai::build_body(messages, tools, model, effort, &self.model_config);
+                let body =
+                    openai::build_body(messages, tools, model, effort, &self.model_config, &caps);
                 (url, body)
             }
             ProviderKind::Codex => {
                 let url = codex::CODEX_API_ENDPOINT.to_string();
                 let mut body =
-                    openai::build_body(messages, tools, model, effort, &self.model_config);
+                    openai::build_body(messages, tools, model, effort, &self.model_config, &caps);
                 body["store"] = serde_json::json!(false);
                 // Codex API doesn't support temperature/top_p; remove them.
                 if let Some(obj) = body.as_object_mut() {
@@ -512,8 +583,32 @@ impl Provider {
             }
             ProviderKind::Anthropic => {
                 let url = format!("{}/messages", self.api_base);
+                let body = anthropic::build_body(
+                    messages,
+                    tools,
+                    model,
+                    effort,
+                    &self.model_config,
+                    &caps,
+                );
+                (url, body)
+            }
+            ProviderKind::Bedrock => {
+                let url = bedrock::url(&self.api_base, model, use_stream);
+                let body = anthropic::build_body(
+                    messages,
+                    tools,
+                    model,
+                    effort,
+                    &self.model_config,
+                    &caps,
+                );
+                (url, body)
+            }
+            ProviderKind::AzureOpenAi => {
+                let url = azure::url(&self.api_base, self.model_config.api_version.as_deref());
                 let body =
-                    anthropic::build_body(messages, tools, model, effort, &self.model_config);
+                    openai::build_body(messages, tools, model, effort, &self.model_config, &caps);
                 (url, body)
             }
             ProviderKind::Copilot => {
@@ -529,6 +624,7 @@ impl Provider {
                     model,
                     effort,
                     &self.model_config,
+                    &caps,
                 );
                 (url, body)
             }
@@ -540,6 +636,7 @@ impl Provider {
                     model,
                     effort,
                     &self.model_config,
+                    &caps,
                 );
                 (url, body)
             }
@@ -562,7 +659,18 @@ impl Provider {
             apply_response_format(&mut body, self.kind, fmt);
         }
 
-        let use_stream = opts.on_delta.is_some() || is_codex;
+        let tier = self.model_config.service_tier;
+        if tier == Some(ServiceTier::Batch) {
+            return Ok(self
+                .chat_batch(body, opts.cancel)
+                .await?
+                .into_response(None));
+        }
+        let flex = tier == Some(ServiceTier::Flex);
+        if flex {
+            body["service_tier"] = serde_json::json!("flex");
+        }
+
         if use_stream {
             body["stream"] = serde_json::json!(true);
             // Request usage data in the final streaming chunk.
@@ -572,6 +680,20 @@ impl Provider {
             }
         }
 
+        // Bedrock picks streaming by URL and needs the exact signed bytes.
+        let mut bedrock_body = None;
+        let mut aws_creds = None;
+        if is_bedrock {
+            bedrock::adapt_body(&mut body, &self.model_config);
+            bedrock_body = Some(
+                serde_json::to_vec(&body)
+                    .map_err(|e| ProviderError::InvalidResponse(e.to_string()))?,
+            );
+            if api_key.is_empty() {
+                aws_creds = Some(bedrock::AwsCredentials::load()?);
+            }
+        }
+
         if log::Level::Debug.enabled() {
             // `body` is derived from redacted history; safe to log as-is.
             log::entry(
@@ -585,12 +707,33 @@ impl Provider {
             );
         }
 
-        let max_retries = 9;
+        let max_retries = if flex { FLEX_MAX_RETRIES } else { 9 };
+        let retry_delay = |attempt: usize| {
+            let delay = backoff_delay(attempt);
+            if flex {
+                delay.min(FLEX_MAX_BACKOFF)
+            } else {
+                delay
+            }
+        };
 
-        for attempt in 0..=max_retries {
+        // Retrying with a refreshed credential doesn't count as an attempt;
+        // each refresh happens at most once.
+        let mut attempt = 0;
+        while attempt <= max_retries {
             let request_start = Instant::now();
 
-            let mut req = self.client.post(&url).json(&body);
+            let mut req = match &bedrock_body {
+                Some(bytes) => self
+                    .client
+                    .post(&url)
+                    .header("content-type", "application/json")
+                    .body(bytes.clone()),
+                None => self.client.post(&url).json(&body),
+            };
+            if flex {
+                req = req.timeout(FLEX_REQUEST_TIMEOUT);
+            }
             if is_codex {
                 if let Some(ref tokens) = codex_auth {
                     req = req.bearer_auth(&tokens.access_token);
@@ -615,16 +758,26 @@ impl Provider {
                 if copilot_has_images {
                     req = req.header("Copilot-Vision-Request", "true");
                 }
-            } else if !self.api_key.is_empty() {
+            } else if let (Some(creds), Some(bytes)) = (&aws_creds, &bedrock_body) {
+                for (k, v) in bedrock::sign(&url, bytes, creds, unix_now())? {
+                    req = req.header(k, v);
+                }
+            } else if !api_key.is_empty() {
                 if is_anthropic {
-                    req = req.header("x-api-key", &self.api_key);
+                    req = req.header("x-api-key", &api_key);
+                } else if is_azure {
+                    req = azure::authorize(req, &api_key);
                 } else {
-                    req = req.bearer_auth(&self.api_key);
+                    req = req.bearer_auth(&api_key);
                 }
             }
             if is_anthropic {
                 req = req.header("anthropic-version", "2023-06-01");
+                if self.model_config.cache_ttl() == CacheTtl::OneHour {
+                    req = req.header("anthropic-beta", anthropic::EXTENDED_CACHE_TTL_BETA);
+                }
             }
+            req = self.with_extra_headers(req);
 
             let resp = tokio::select! {
                 _ = opts.cancel.cancelled() => {
@@ -639,11 +792,12 @@ impl Provider {
                             "error": format!("{e:?}"),
                         }));
                         if attempt < max_retries {
-                            let delay = backoff_delay(attempt);
+                            let delay = retry_delay(attempt);
                             if attempt > 0 {
                                 if let Some(f) = opts.on_retry { f(delay, attempt as u32); }
                             }
                             tokio::time::sleep(delay).await;
+                            attempt += 1;
                             continue;
                         }
                         return Err(err);
@@ -712,8 +866,24 @@ impl Provider {
                     }
                 }
 
-                if err.is_retryable() && attempt < max_retries {
-                    let backoff = backoff_delay(attempt);
+                // Command-issued tokens are short-lived; fetch a fresh one once.
+                if matches!(err, ProviderError::Auth(_)) && !key_refreshed {
+                    if let Some(command) = &self.model_config.auth.api_key_command {
+                        key_refreshed = true;
+                        api_key = key_command::token(command, true).await?;
+                        log::entry(
+                            log::Level::Info,
+                            "api_key_command_refresh",
+                            &serde_json::json!({ "status": code }),
+                        );
+                        continue;
+                    }
+                }
+
+                let retryable = err.is_retryable()
+                    || (flex && matches!(err, ProviderError::RateLimited { .. }));
+                if retryable && attempt < max_retries {
+                    let backoff = retry_delay(attempt);
                     let delay = retry_after.map_or(backoff, |ra| ra.max(backoff));
                     if attempt > 0 {
                         if let Some(f) = opts.on_retry {
@@ -721,6 +891,7 @@ impl Provider {
                         }
                     }
                     tokio::time::sleep(delay).await;
+                    attempt += 1;
                     continue;
                 }
                 return Err(err);
@@ -739,12 +910,15 @@ impl Provider {
 
             let parsed = if use_stream {
                 match self.kind {
-                    ProviderKind::OpenAi | ProviderKind::Codex => {
+                    ProviderKind::OpenAi | ProviderKind::Codex | ProviderKind::AzureOpenAi => {
                         openai::read_stream(resp, opts.cancel, on_delta).await
                     }
                     ProviderKind::Anthropic => {
                         anthropic::read_stream(resp, opts.cancel, on_delta).await
                     }
+                    ProviderKind::Bedrock => {
+                        bedrock::read_stream(resp, opts.cancel, on_delta).await
+                    }
                     ProviderKind::Copilot | ProviderKind::Local => {
                         chat_completions::read_stream(resp, opts.cancel, on_delta).await
                     }
@@ -769,8 +943,12 @@ impl Provider {
                 }
 
                 match self.kind {
-                    ProviderKind::OpenAi | ProviderKind::Codex => openai::parse_response(&data)?,
-                    ProviderKind::Anthropic => anthropic::parse_response(&data)?,
+                    ProviderKind::OpenAi | ProviderKind::Codex | ProviderKind::AzureOpenAi => {
+                        openai::parse_response(&data)?
+                    }
+                    ProviderKind::Anthropic | ProviderKind::Bedrock => {
+                        anthropic::parse_response(&data)?
+                    }
                     ProviderKind::Copilot | ProviderKind::Local => {
                         chat_completions::parse_response(&data)?
                     }
@@ -807,19 +985,25 @@ impl Provider {
 
     // ── Utility methods ─────────────────────────────────────────────────
 
-    /// Fetch the context window size (in tokens) from the provider's API.
+    /// Resolve the context window size (in tokens). A config override wins,
+    /// then the provider's own answer, then the capability registry.
     ///
     /// - **Anthropic**: `GET /v1/models/{model}` → `max_input_tokens`
     /// - **Local** (llama.cpp): `GET /models` → parse `--ctx-size` from args
-    /// - **OpenAI / Codex**: the standard API does not expose this, returns `None`.
+    /// - **Codex / Copilot**: the cached model list.
+    /// - **OpenAI / Bedrock / Azure**: not exposed; catalog only.
     pub async fn fetch_context_window(&self, model: &str) -> Option<u32> {
-        let result = match self.kind {
+        if let Some(window) = self.model_config.capabilities.context_window {
+            return Some(window);
+        }
+        let reported = match self.kind {
             ProviderKind::Anthropic => self.fetch_context_window_anthropic(model).await,
             ProviderKind::Local => self.fetch_context_window_local(model).await,
-            ProviderKind::OpenAi => None,
+            ProviderKind::OpenAi | ProviderKind::Bedrock | ProviderKind::AzureOpenAi => None,
             ProviderKind::Codex => codex::cached_context_window(model),
             ProviderKind::Copilot => copilot::cached_context_window(model),
         };
+        let result = reported.or_else(|| self.capabilities(model).context_window);
         crate::log::entry(
             crate::log::Level::Info,
             "fetch_context_window",
@@ -834,10 +1018,10 @@ impl Provider {
 
     async fn fetch_context_window_anthropic(&self, model: &str) -> Option<u32> {
         let url = format!("{}/models/{}", self.api_base, model);
+        let key = self.api_key(false).await.ok()?;
         let resp = self
-            .client
-            .get(&url)
-            .header("x-api-key", &self.api_key)
+            .with_extra_headers(self.client.get(&url))
+            .header("x-api-key", key)
             .header("anthropic-version", "2023-06-01")
             .send()
             .await
@@ -853,9 +1037,10 @@ impl Provider {
     /// Supports vLLM/SGLang (`max_model_len`) and llama.cpp (`--ctx-size`).
     async fn fetch_context_window_local(&self, model: &str) -> Option<u32> {
         let url = format!("{}/models", self.api_base);
-        let mut req = self.client.get(&url);
-        if !self.api_key.is_empty() {
-            req = req.bearer_auth(&self.api_key);
+        let mut req = self.with_extra_headers(self.client.get(&url));
+        let key = self.api_key(false).await.ok()?;
+        if !key.is_empty() {
+            req = req.bearer_auth(key);
         }
         let resp = req.send().await.ok()?;
         if !resp.status().is_success() {
@@ -1037,10 +1222,11 @@ fn parse_title_and_slug(raw: &str) -> (String, String) {
 /// - OpenAI Responses API (and Codex): `text.format` with a `json_schema` entry.
 /// - chat/completions-style (Local/vLLM/llama.cpp/OpenAI-compatible):
 ///   `response_format` with a `json_schema` entry.
-/// - Anthropic: no patch yet; falls back to prompt-based JSON.
+/// - Anthropic: `output_config.format` on models that support it.
+/// - Bedrock: no patch; falls back to prompt-based JSON.
 fn apply_response_format(body: &mut serde_json::Value, kind: ProviderKind, fmt: &ResponseFormat) {
     match kind {
-        ProviderKind::OpenAi | ProviderKind::Codex => {
+        ProviderKind::OpenAi | ProviderKind::Codex | ProviderKind::AzureOpenAi => {
             body["text"] = serde_json::json!({
                 "format": {
                     "type": "json_schema",
@@ -1082,6 +1268,7 @@ fn apply_response_format(body: &mut serde_json::Value, kind: ProviderKind, fmt:
                 }
             }
         }
+        ProviderKind::Bedrock => {}
     }
 }
 
@@ -1102,6 +1289,46 @@ fn extract_json_title_slug(raw: &str) -> Option<(String, String)> {
     ))
 }
 
+/// Replace image input with a note for models that cannot see it: image
+/// parts in user messages, and images returned by tools.
+fn strip_images(messages: &[Message], model: &str) -> Vec<Message> {
+    let note = format!("[image omitted: {model} does not accept images]");
+    messages
+        .iter()
+        .map(|m| {
+            let mut m = m.clone();
+            if !carries_image(&m) {
+                return m;
+            }
+            m.content = match m.content {
+                Some(Content::Parts(parts)) => Some(Content::Parts(
+                    parts
+                        .into_iter()
+                        .map(|p| match p {
+                            protocol::ContentPart::ImageUrl { .. } => {
+                                protocol::ContentPart::Text { text: note.clone() }
+                            }
+                            p => p,
+                        })
+                        .collect(),
+                )),
+                Some(Content::Text(_)) => Some(Content::Text(note.clone())),
+                other => other,
+            };
+            m
+        })
+        .collect()
+}
+
+/// Whether a message has image parts or is a tool result holding an image.
+fn carries_image(m: &Message) -> bool {
+    match &m.content {
+        Some(c @ Content::Parts(_)) => c.image_count() > 0,
+        Some(Content::Text(t)) => m.role == protocol::Role::Tool && t.starts_with("![image](data:"),
+        None => false,
+    }
+}
+
 /// Returns true if any user or tool-result message contains an image part.
 fn messages_have_images(messages: &[Message]) -> bool {
     messages.iter().any(|m| match m.role {
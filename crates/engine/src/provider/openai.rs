@@ -2,6 +2,7 @@ use super::non_empty;
 use super::sse;
 use super::{ParsedResponse, ProviderError, StreamDelta, ToolDefinition};
 use crate::cancel::CancellationToken;
+use crate::capabilities::ModelCapabilities;
 use crate::config::ModelConfig;
 use crate::log;
 use crate::tools::{trim_tool_output, MAX_TOOL_OUTPUT_LINES};
@@ -43,6 +44,7 @@ pub(super) fn build_body(
     model: &str,
     effort: ReasoningEffort,
     config: &ModelConfig,
+    caps: &ModelCapabilities,
 ) -> serde_json::Value {
     let mut instructions = String::new();
     let mut input = Vec::new();
@@ -156,6 +158,9 @@ pub(super) fn build_body(
 
     if !api_tools.is_empty() {
         body["tools"] = serde_json::json!(api_tools);
+        if !caps.parallel_tools {
+            body["parallel_tool_calls"] = serde_json::json!(false);
+        }
     }
     if let Some(v) = config.temperature {
         body["temperature"] = serde_json::json!(v);
@@ -1,5 +1,18 @@
+//! The agent registry: one entry per running agent, used to discover and
+//! address other agents.
+//!
+//! Entries live in a [`RegistryBackend`], by default one JSON file per agent
+//! under `state_dir()/registry`. Pointing several hosts at a shared
+//! directory (`agents.registry`) lets agents in containers or on other
+//! machines find each other; they are then reached over TCP (see
+//! [`crate::socket`]). Each host only checks and prunes its own entries,
+//! since PIDs mean nothing across hosts; entries from other hosts count as
+//! live while their agent keeps refreshing them (see [`spawn_heartbeat`]).
+
+use crate::socket::Endpoint;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::sync::OnceLock;
 
 /// A single agent's entry in the on-disk registry.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +30,34 @@ pub struct RegistryEntry {
     pub socket_path: String,
     pub depth: u8,
     pub started_at: String,
+    /// Host the agent runs on. Filled in by [`register`].
+    #[serde(default)]
+    pub host: String,
+    /// `tcp://` or `tls://` address, when the agent accepts network
+    /// connections.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    /// Unix time of the last write. Filled in by [`register`].
+    #[serde(default)]
+    pub updated_at: u64,
+}
+
+impl RegistryEntry {
+    /// Whether the agent runs on this host.
+    pub fn is_local(&self) -> bool {
+        self.host.is_empty() || self.host == local_host()
+    }
+
+    /// Where to reach the agent: its Unix socket when it runs on this host,
+    /// otherwise its network address.
+    pub fn endpoint(&self) -> Endpoint {
+        match &self.address {
+            Some(address) if !self.is_local() || self.socket_path.is_empty() => {
+                Endpoint::parse(address)
+            }
+            _ => Endpoint::Unix(PathBuf::from(&self.socket_path)),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -26,36 +67,145 @@ pub enum AgentStatus {
     Idle,
 }
 
-fn registry_dir() -> PathBuf {
-    crate::paths::state_dir().join("registry")
+/// This machine's hostname, used to tell local entries from remote ones.
+pub fn local_host() -> &'static str {
+    static HOST: OnceLock<String> = OnceLock::new();
+    HOST.get_or_init(|| {
+        let mut buf = [0u8; 256];
+        let ok = unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) } == 0;
+        let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+        match std::str::from_utf8(&buf[..len]) {
+            Ok(host) if ok && !host.is_empty() => host.to_string(),
+            _ => "localhost".to_string(),
+        }
+    })
 }
 
-fn entry_path(pid: u32) -> PathBuf {
-    registry_dir().join(format!("{pid}.json"))
+// ── Backends ────────────────────────────────────────────────────────────────
+
+/// Storage for registry entries. Entries are keyed by host and PID.
+pub trait RegistryBackend: Send + Sync {
+    /// All readable entries.
+    fn list(&self) -> Vec<RegistryEntry>;
+    fn get(&self, host: &str, pid: u32) -> Option<RegistryEntry>;
+    fn put(&self, entry: &RegistryEntry) -> std::io::Result<()>;
+    fn remove(&self, host: &str, pid: u32);
 }
 
-/// Read and parse all valid registry entries from disk.
-fn iter_entries() -> Vec<(PathBuf, RegistryEntry)> {
-    let dir = registry_dir();
-    let entries = match std::fs::read_dir(&dir) {
-        Ok(e) => e,
-        Err(_) => return vec![],
-    };
-    let mut result = vec![];
-    for entry in entries.flatten() {
-        let path = entry.path();
-        if path.extension().and_then(|e| e.to_str()) != Some("json") {
-            continue;
+/// One JSON file per agent, named `{host}-{pid}.json`. Entries written
+/// before hosts were recorded are named `{pid}.json` and have an empty host.
+pub struct FileRegistry {
+    dir: PathBuf,
+}
+
+impl FileRegistry {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn path(&self, host: &str, pid: u32) -> PathBuf {
+        if host.is_empty() {
+            return self.dir.join(format!("{pid}.json"));
         }
-        let data = match std::fs::read_to_string(&path) {
-            Ok(d) => d,
-            Err(_) => continue,
+        let host: String = host
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.dir.join(format!("{host}-{pid}.json"))
+    }
+}
+
+impl RegistryBackend for FileRegistry {
+    fn list(&self) -> Vec<RegistryEntry> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(e) => e,
+            Err(_) => return vec![],
         };
-        if let Ok(reg) = serde_json::from_str::<RegistryEntry>(&data) {
-            result.push((path, reg));
+        let mut result = vec![];
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let data = match std::fs::read_to_string(&path) {
+                Ok(d) => d,
+                Err(_) => continue,
+            };
+            if let Ok(reg) = serde_json::from_str::<RegistryEntry>(&data) {
+                result.push(reg);
+            }
         }
+        result
+    }
+
+    fn get(&self, host: &str, pid: u32) -> Option<RegistryEntry> {
+        let data = std::fs::read_to_string(self.path(host, pid)).ok()?;
+        serde_json::from_str(&data).ok()
+    }
+
+    fn put(&self, entry: &RegistryEntry) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let json = serde_json::to_string_pretty(entry).map_err(std::io::Error::other)?;
+        std::fs::write(self.path(&entry.host, entry.pid), json)
+    }
+
+    fn remove(&self, host: &str, pid: u32) {
+        let _ = std::fs::remove_file(self.path(host, pid));
     }
-    result
+}
+
+static BACKEND: OnceLock<Box<dyn RegistryBackend>> = OnceLock::new();
+
+/// Install the registry backend. Called once at startup, before any agent
+/// registers; later calls are ignored.
+pub fn set_backend(backend: Box<dyn RegistryBackend>) {
+    let _ = BACKEND.set(backend);
+}
+
+fn backend() -> &'static dyn RegistryBackend {
+    BACKEND
+        .get_or_init(|| {
+            Box::new(FileRegistry::new(
+                crate::paths::state_dir().join("registry"),
+            ))
+        })
+        .as_ref()
+}
+
+/// How often a running agent refreshes its entry.
+const HEARTBEAT_SECS: u64 = 30;
+
+/// Entries from other hosts not refreshed for this long are treated as dead.
+const REMOTE_TTL_SECS: u64 = 4 * HEARTBEAT_SECS;
+
+/// Whether an entry's agent is still running. PIDs on other hosts can't be
+/// checked, so their entries are alive while they keep being refreshed.
+fn is_alive(entry: &RegistryEntry) -> bool {
+    if entry.is_local() {
+        is_pid_alive(entry.pid)
+    } else {
+        crate::provider::unix_now().saturating_sub(entry.updated_at) <= REMOTE_TTL_SECS
+    }
+}
+
+/// Remove a local entry whose process has exited. Returns whether it was
+/// skipped as dead. Stale entries from other hosts are skipped but left for
+/// their own host to prune.
+fn prune(backend: &dyn RegistryBackend, entry: &RegistryEntry) -> bool {
+    if is_alive(entry) {
+        return false;
+    }
+    if !entry.is_local() {
+        return true;
+    }
+    backend.remove(&entry.host, entry.pid);
+    true
 }
 
 /// Deregister this agent and clean up its socket. Does NOT send SIGTERM
@@ -77,10 +227,8 @@ const AGENT_NAMES: &[&str] = &[
 /// Generate a unique agent name. Picks from a pool of short words, falling
 /// back to `word-{hash}` if all are taken.
 pub fn next_agent_id() -> String {
-    let used: std::collections::HashSet<String> = iter_entries()
-        .into_iter()
-        .map(|(_, e)| e.agent_id)
-        .collect();
+    let used: std::collections::HashSet<String> =
+        backend().list().into_iter().map(|e| e.agent_id).collect();
     // Pick randomly from available names.
     let available: Vec<&&str> = AGENT_NAMES.iter().filter(|n| !used.contains(**n)).collect();
     if !available.is_empty() {
@@ -102,24 +250,40 @@ pub fn next_agent_id() -> String {
 
 /// Find an agent by its human-readable ID (e.g. "agent-1").
 pub fn find_by_id(agent_id: &str) -> Option<RegistryEntry> {
-    iter_entries()
+    backend()
+        .list()
         .into_iter()
-        .map(|(_, e)| e)
-        .find(|e| e.agent_id == agent_id && is_pid_alive(e.pid))
+        .find(|e| e.agent_id == agent_id && is_alive(e))
 }
 
-/// Write this agent's entry to the registry.
+/// Write an entry for an agent on this host to the registry.
 pub fn register(entry: &RegistryEntry) -> std::io::Result<()> {
-    let dir = registry_dir();
-    std::fs::create_dir_all(&dir)?;
-    let path = entry_path(entry.pid);
-    let json = serde_json::to_string_pretty(entry).map_err(std::io::Error::other)?;
-    std::fs::write(path, json)
+    let mut entry = entry.clone();
+    if entry.host.is_empty() {
+        entry.host = local_host().to_string();
+    }
+    entry.updated_at = crate::provider::unix_now();
+    backend().put(&entry)
 }
 
-/// Remove this agent's entry from the registry.
+/// Refresh this agent's entry every [`HEARTBEAT_SECS`] so agents on other
+/// hosts sharing the registry keep seeing it. Stops once the entry is gone.
+pub fn spawn_heartbeat(pid: u32) {
+    tokio::spawn(async move {
+        let period = std::time::Duration::from_secs(HEARTBEAT_SECS);
+        loop {
+            tokio::time::sleep(period).await;
+            let Ok(entry) = read_entry(pid) else {
+                break;
+            };
+            let _ = register(&entry);
+        }
+    });
+}
+
+/// Remove a local agent's entry from the registry.
 pub fn deregister(pid: u32) {
-    let _ = std::fs::remove_file(entry_path(pid));
+    backend().remove(local_host(), pid);
 }
 
 /// Update the status field for this agent.
@@ -138,11 +302,11 @@ pub fn update_slug(pid: u32, slug: &str) {
     }
 }
 
-/// Read a single registry entry by PID.
+/// Read a local agent's registry entry by PID.
 pub fn read_entry(pid: u32) -> Result<RegistryEntry, String> {
-    let path = entry_path(pid);
-    let data = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
-    serde_json::from_str(&data).map_err(|e| e.to_string())
+    backend()
+        .get(local_host(), pid)
+        .ok_or_else(|| format!("no registry entry for pid {pid}"))
 }
 
 /// Check if a PID is alive.
@@ -156,20 +320,23 @@ fn is_pid_alive(_pid: u32) -> bool {
     true
 }
 
-/// Discover all live agents in the same scope (git root or cwd).
+/// Discover all live agents in the same scope (git root or cwd). Agents on
+/// other hosts see the project under other paths, so they are always
+/// included: sharing a registry is itself the opt-in.
 /// Prunes dead entries as a side effect.
 pub fn discover(scope: &str) -> Vec<RegistryEntry> {
+    let backend = backend();
     let mut result = vec![];
-    for (path, reg) in iter_entries() {
-        if !is_pid_alive(reg.pid) {
-            let _ = std::fs::remove_file(&path);
+    for reg in backend.list() {
+        if prune(backend, &reg) {
             continue;
         }
-        let matches = reg
-            .git_root
-            .as_deref()
-            .map(|gr| gr == scope)
-            .unwrap_or(false)
+        let matches = !reg.is_local()
+            || reg
+                .git_root
+                .as_deref()
+                .map(|gr| gr == scope)
+                .unwrap_or(false)
             || reg.cwd == scope;
         if matches {
             result.push(reg);
@@ -178,21 +345,23 @@ pub fn discover(scope: &str) -> Vec<RegistryEntry> {
     result
 }
 
-/// Find all direct children of a given PID.
+/// Find all direct children of a given local PID.
 pub fn children_of(parent_pid: u32) -> Vec<RegistryEntry> {
-    iter_entries()
+    backend()
+        .list()
         .into_iter()
-        .map(|(_, e)| e)
-        .filter(|e| e.parent_pid == Some(parent_pid) && is_pid_alive(e.pid))
+        .filter(|e| e.is_local() && e.parent_pid == Some(parent_pid) && is_alive(e))
         .collect()
 }
 
-/// Prune all dead entries from the registry directory.
+/// Prune this host's dead entries from the registry.
 pub fn prune_dead() {
-    for (path, reg) in iter_entries() {
-        if !is_pid_alive(reg.pid) {
-            let _ = std::fs::remove_file(&path);
-        }
+    prune_dead_in(backend());
+}
+
+fn prune_dead_in(backend: &dyn RegistryBackend) {
+    for reg in backend.list() {
+        prune(backend, &reg);
     }
 }
 
@@ -260,3 +429,103 @@ pub fn kill_descendants(pid: u32) {
 
 #[cfg(not(unix))]
 pub fn kill_descendants(_pid: u32) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(agent_id: &str, host: &str, pid: u32) -> RegistryEntry {
+        RegistryEntry {
+            agent_id: agent_id.into(),
+            pid,
+            parent_pid: None,
+            git_root: None,
+            git_branch: None,
+            cwd: "/work".into(),
+            status: AgentStatus::Idle,
+            task_slug: None,
+            session_id: String::new(),
+            socket_path: format!("/state/sockets/{pid}.sock"),
+            depth: 0,
+            started_at: String::new(),
+            host: host.into(),
+            address: Some("tls://devbox:7420".into()),
+            updated_at: crate::provider::unix_now(),
+        }
+    }
+
+    #[test]
+    fn stale_remote_entries_are_skipped_but_not_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = FileRegistry::new(dir.path().join("registry"));
+        let stale = RegistryEntry {
+            updated_at: 0,
+            ..entry("pine", "some-other-host.invalid", 42)
+        };
+        backend.put(&stale).unwrap();
+        assert!(prune(&backend, &stale));
+        assert!(backend.get("some-other-host.invalid", 42).is_some());
+    }
+
+    #[test]
+    fn file_registry_keys_by_host_and_pid() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = FileRegistry::new(dir.path().join("registry"));
+        backend.put(&entry("oak", "host-a", 42)).unwrap();
+        backend.put(&entry("pine", "container/1", 42)).unwrap();
+
+        let mut ids: Vec<_> = backend.list().into_iter().map(|e| e.agent_id).collect();
+        ids.sort();
+        assert_eq!(ids, ["oak", "pine"]);
+        assert_eq!(backend.get("container/1", 42).unwrap().agent_id, "pine");
+
+        backend.remove("host-a", 42);
+        assert!(backend.get("host-a", 42).is_none());
+        assert_eq!(backend.list().len(), 1);
+    }
+
+    #[test]
+    fn dead_legacy_entries_are_pruned() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = FileRegistry::new(dir.path().join("registry"));
+        // Above any pid_max, so never a running process.
+        let pid = 0x3fff_ffff;
+        let legacy = entry("oak", "", pid);
+        std::fs::create_dir_all(dir.path().join("registry")).unwrap();
+        std::fs::write(
+            dir.path().join(format!("registry/{pid}.json")),
+            serde_json::to_string(&legacy)
+                .unwrap()
+                .replace(r#""host":"","#, ""),
+        )
+        .unwrap();
+        assert_eq!(backend.list().len(), 1);
+        assert!(backend.get("", pid).is_some());
+
+        prune_dead_in(&backend);
+        assert!(backend.list().is_empty());
+    }
+
+    #[test]
+    fn remote_entries_use_their_network_address() {
+        let remote = entry("pine", "some-other-host.invalid", u32::MAX);
+        assert!(!remote.is_local());
+        assert_eq!(remote.endpoint().to_string(), "tls://devbox:7420");
+        // PIDs on other hosts can't be checked; a recent refresh keeps the
+        // entry alive, and it expires once the agent stops refreshing it.
+        assert!(is_alive(&remote));
+        let stale = RegistryEntry {
+            updated_at: remote.updated_at - REMOTE_TTL_SECS - 1,
+            ..remote.clone()
+        };
+        assert!(!is_alive(&stale));
+
+        let local = entry("oak", local_host(), std::process::id());
+        assert!(local.is_local());
+        assert_eq!(
+            local.endpoint(),
+            Endpoint::Unix(PathBuf::from(&local.socket_path))
+        );
+        assert!(is_alive(&local));
+    }
+}
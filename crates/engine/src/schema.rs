@@ -0,0 +1,345 @@
+//! Structured subagent results.
+//!
+//! `spawn_agent` and `message_agent` accept a JSON schema that the
+//! subagent's final answer must satisfy. The subagent is told to answer with
+//! a single JSON value, its answer is checked before it is sent back (with
+//! one retry on mismatch), and the caller — a blocking `spawn_agent`, or a
+//! `message_agent` waiting for its reply — checks it again and returns the
+//! parsed value as tool metadata.
+//!
+//! Validation covers the commonly used subset of JSON Schema: `type`,
+//! `enum`, `const`, `properties`, `required`, `additionalProperties`,
+//! `items`, `minItems`/`maxItems`, `minLength`/`maxLength`,
+//! `minimum`/`maximum` and `anyOf`/`oneOf`/`allOf`. Other keywords are
+//! ignored.
+
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Read a schema from tool arguments. Models sometimes pass the schema as a
+/// JSON string rather than an object; both are accepted.
+pub fn from_args(args: &HashMap<String, Value>) -> Result<Option<Value>, String> {
+    let schema = match args.get("schema") {
+        None | Some(Value::Null) => return Ok(None),
+        Some(Value::String(s)) if s.trim().is_empty() => return Ok(None),
+        Some(Value::String(s)) => {
+            serde_json::from_str(s).map_err(|e| format!("schema is not valid JSON: {e}"))?
+        }
+        Some(v) => v.clone(),
+    };
+    if !schema.is_object() {
+        return Err("schema must be a JSON object".into());
+    }
+    Ok(Some(schema))
+}
+
+/// The instruction appended to a task that must end in a structured result.
+pub fn instruction(schema: &Value) -> String {
+    let pretty = serde_json::to_string_pretty(schema).unwrap_or_default();
+    format!(
+        "When you are done, your final answer must be a single JSON value matching this JSON schema, with no other text around it:\n```json\n{pretty}\n```"
+    )
+}
+
+/// The follow-up sent when a final answer did not match the schema.
+pub fn retry_message(error: &str) -> String {
+    format!(
+        "Your final answer does not match the required result schema: {error}. Reply again with only the corrected JSON value."
+    )
+}
+
+/// Parse an answer as JSON and check it against `schema`.
+pub fn parse_result(text: &str, schema: &Value) -> Result<Value, String> {
+    let value = extract_json(text).ok_or("answer is not valid JSON")?;
+    validate(&value, schema)?;
+    Ok(value)
+}
+
+/// Find the JSON value in an answer: the whole text, the body of a fenced
+/// code block, or the span from the first `{`/`[` to the last `}`/`]`.
+fn extract_json(text: &str) -> Option<Value> {
+    let text = text.trim();
+    if let Ok(v) = serde_json::from_str(text) {
+        return Some(v);
+    }
+    if let Some(start) = text.find("```") {
+        let body = &text[start + 3..];
+        let body = body.split_once('\n').map_or(body, |(_, rest)| rest);
+        if let Some(end) = body.find("```") {
+            if let Ok(v) = serde_json::from_str(body[..end].trim()) {
+                return Some(v);
+            }
+        }
+    }
+    let start = text.find(['{', '['])?;
+    let end = text.rfind(['}', ']'])?;
+    if end <= start {
+        return None;
+    }
+    serde_json::from_str(&text[start..=end]).ok()
+}
+
+/// Check `value` against `schema`. The error names the first offending
+/// location, e.g. `$.items[2].name: expected string, got number`.
+pub fn validate(value: &Value, schema: &Value) -> Result<(), String> {
+    check(value, schema, "$")
+}
+
+fn check(value: &Value, schema: &Value, path: &str) -> Result<(), String> {
+    let Some(schema) = schema.as_object() else {
+        // `true` accepts anything, `false` nothing.
+        return match schema {
+            Value::Bool(false) => Err(format!("{path}: not allowed")),
+            _ => Ok(()),
+        };
+    };
+
+    if let Some(ty) = schema.get("type") {
+        let types: Vec<&str> = match ty {
+            Value::String(s) => vec![s.as_str()],
+            Value::Array(a) => a.iter().filter_map(|t| t.as_str()).collect(),
+            _ => vec![],
+        };
+        if !types.is_empty() && !types.iter().any(|t| has_type(value, t)) {
+            return Err(format!(
+                "{path}: expected {}, got {}",
+                types.join(" or "),
+                type_name(value)
+            ));
+        }
+    }
+    if let Some(Value::Array(options)) = schema.get("enum") {
+        if !options.contains(value) {
+            let options: Vec<String> = options.iter().map(|o| o.to_string()).collect();
+            return Err(format!("{path}: must be one of {}", options.join(", ")));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if value != expected {
+            return Err(format!("{path}: must be {expected}"));
+        }
+    }
+
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::Array(required)) = schema.get("required") {
+                for key in required.iter().filter_map(|k| k.as_str()) {
+                    if !map.contains_key(key) {
+                        return Err(format!("{path}: missing required property \"{key}\""));
+                    }
+                }
+            }
+            let properties = schema.get("properties").and_then(|p| p.as_object());
+            for (key, v) in map {
+                let child = format!("{path}.{key}");
+                match properties.and_then(|p| p.get(key)) {
+                    Some(sub) => check(v, sub, &child)?,
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => {
+                            return Err(format!("{path}: unexpected property \"{key}\""));
+                        }
+                        Some(sub @ Value::Object(_)) => check(v, sub, &child)?,
+                        _ => {}
+                    },
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(min) = schema.get("minItems").and_then(|n| n.as_u64()) {
+                if (items.len() as u64) < min {
+                    return Err(format!("{path}: expected at least {min} items"));
+                }
+            }
+            if let Some(max) = schema.get("maxItems").and_then(|n| n.as_u64()) {
+                if items.len() as u64 > max {
+                    return Err(format!("{path}: expected at most {max} items"));
+                }
+            }
+            if let Some(sub) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    check(item, sub, &format!("{path}[{i}]"))?;
+                }
+            }
+        }
+        Value::String(s) => {
+            let len = s.chars().count() as u64;
+            if let Some(min) = schema.get("minLength").and_then(|n| n.as_u64()) {
+                if len < min {
+                    return Err(format!("{path}: shorter than {min} characters"));
+                }
+            }
+            if let Some(max) = schema.get("maxLength").and_then(|n| n.as_u64()) {
+                if len > max {
+                    return Err(format!("{path}: longer than {max} characters"));
+                }
+            }
+        }
+        Value::Number(n) => {
+            let n = n.as_f64().unwrap_or_default();
+            if let Some(min) = schema.get("minimum").and_then(|m| m.as_f64()) {
+                if n < min {
+                    return Err(format!("{path}: must be at least {min}"));
+                }
+            }
+            if let Some(max) = schema.get("maximum").and_then(|m| m.as_f64()) {
+                if n > max {
+                    return Err(format!("{path}: must be at most {max}"));
+                }
+            }
+        }
+        _ => {}
+    }
+
+    if let Some(Value::Array(all)) = schema.get("allOf") {
+        for sub in all {
+            check(value, sub, path)?;
+        }
+    }
+    if let Some(Value::Array(any)) = schema.get("anyOf") {
+        if !any.iter().any(|sub| check(value, sub, path).is_ok()) {
+            return Err(format!("{path}: does not match any allowed schema"));
+        }
+    }
+    if let Some(Value::Array(one)) = schema.get("oneOf") {
+        let matches = one
+            .iter()
+            .filter(|sub| check(value, sub, path).is_ok())
+            .count();
+        if matches != 1 {
+            return Err(format!(
+                "{path}: must match exactly one allowed schema, matches {matches}"
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn has_type(value: &Value, ty: &str) -> bool {
+    match ty {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|f| f.fract() == 0.0)
+        }
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn findings() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "verdict": { "enum": ["pass", "fail"] },
+                "issues": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "file": { "type": "string", "minLength": 1 },
+                            "line": { "type": "integer", "minimum": 1 }
+                        },
+                        "required": ["file"],
+                        "additionalProperties": false
+                    }
+                }
+            },
+            "required": ["verdict", "issues"]
+        })
+    }
+
+    #[test]
+    fn accepts_matching_values() {
+        let value = json!({
+            "verdict": "fail",
+            "issues": [{ "file": "src/lib.rs", "line": 12 }, { "file": "a.rs" }],
+            "extra": true
+        });
+        assert!(validate(&value, &findings()).is_ok());
+        assert!(validate(&json!(3), &json!({ "type": ["integer", "null"] })).is_ok());
+        assert!(validate(
+            &json!("x"),
+            &json!({ "anyOf": [{ "type": "number" }, { "const": "x" }] })
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn reports_first_mismatch_with_path() {
+        let schema = findings();
+        let err = |v: Value| validate(&v, &schema).unwrap_err();
+        assert_eq!(
+            err(json!({ "verdict": "pass" })),
+            "$: missing required property \"issues\""
+        );
+        assert_eq!(
+            err(json!({ "verdict": "maybe", "issues": [] })),
+            "$.verdict: must be one of \"pass\", \"fail\""
+        );
+        assert_eq!(
+            err(json!({ "verdict": "pass", "issues": [{ "file": "a", "line": "3" }] })),
+            "$.issues[0].line: expected integer, got string"
+        );
+        assert_eq!(
+            err(json!({ "verdict": "pass", "issues": [{ "file": "a", "col": 1 }] })),
+            "$.issues[0]: unexpected property \"col\""
+        );
+        assert_eq!(
+            err(json!({ "verdict": "pass", "issues": [{ "file": "" }] })),
+            "$.issues[0].file: shorter than 1 characters"
+        );
+    }
+
+    #[test]
+    fn extracts_json_from_answers() {
+        let schema = json!({ "type": "object", "required": ["ok"] });
+        assert_eq!(
+            parse_result("{\"ok\": true}", &schema).unwrap(),
+            json!({ "ok": true })
+        );
+        assert_eq!(
+            parse_result("Done.\n```json\n{\"ok\": 1}\n```\n", &schema).unwrap(),
+            json!({ "ok": 1 })
+        );
+        assert_eq!(
+            parse_result("Result: {\"ok\": null} as requested", &schema).unwrap(),
+            json!({ "ok": null })
+        );
+        assert_eq!(
+            parse_result("all good", &schema).unwrap_err(),
+            "answer is not valid JSON"
+        );
+        assert!(parse_result("{\"fine\": 1}", &schema).is_err());
+    }
+
+    #[test]
+    fn schema_from_args() {
+        let mut args = HashMap::new();
+        assert_eq!(from_args(&args), Ok(None));
+        args.insert("schema".into(), json!("{\"type\": \"object\"}"));
+        assert_eq!(from_args(&args), Ok(Some(json!({ "type": "object" }))));
+        args.insert("schema".into(), json!([1]));
+        assert!(from_args(&args).is_err());
+        args.insert("schema".into(), json!("{not json"));
+        assert!(from_args(&args).is_err());
+    }
+}
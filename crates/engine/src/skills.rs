@@ -141,7 +141,7 @@ fn parse_skill(path: &Path) -> Option<SkillEntry> {
 }
 
 /// Split a markdown file into (frontmatter_yaml, body) at `---` delimiters.
-fn split_frontmatter(text: &str) -> Option<(&str, &str)> {
+pub(crate) fn split_frontmatter(text: &str) -> Option<(&str, &str)> {
     let text = text.trim_start();
     if !text.starts_with("---") {
         return None;
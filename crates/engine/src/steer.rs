@@ -0,0 +1,76 @@
+//! Batch filtering for steer commands.
+//!
+//! The UI can withdraw or re-send a queued steer message until the turn
+//! consumes it. Steers only take effect when the engine drains its command
+//! channel, so a withdrawal that arrives in the same drained batch (or the
+//! same deferred list during an LLM call or tool run) as its `Steer` simply
+//! cancels it. A `CancelSteer` for an already-consumed id has nothing left
+//! to act on and is dropped.
+
+use protocol::UiCommand;
+
+/// Apply every `CancelSteer` in `cmds` to the `Steer`s queued before it and
+/// drop the cancel commands themselves. Other commands keep their order.
+pub(crate) fn coalesce(cmds: Vec<UiCommand>) -> Vec<UiCommand> {
+    let mut out: Vec<UiCommand> = Vec::with_capacity(cmds.len());
+    for cmd in cmds {
+        match cmd {
+            UiCommand::CancelSteer { id } => {
+                out.retain(|c| !matches!(c, UiCommand::Steer { id: sid, .. } if *sid == id));
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn steer(id: u64, text: &str) -> UiCommand {
+        UiCommand::Steer {
+            text: text.into(),
+            id,
+        }
+    }
+
+    fn steer_texts(cmds: &[UiCommand]) -> Vec<&str> {
+        cmds.iter()
+            .filter_map(|c| match c {
+                UiCommand::Steer { text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn cancel_drops_earlier_steer_with_same_id() {
+        let out = coalesce(vec![
+            steer(1, "a"),
+            steer(2, "b"),
+            UiCommand::CancelSteer { id: 1 },
+        ]);
+        assert_eq!(steer_texts(&out), vec!["b"]);
+        assert_eq!(out.len(), 1);
+    }
+
+    #[test]
+    fn resend_after_cancel_moves_steer_to_the_end() {
+        let out = coalesce(vec![
+            steer(1, "a"),
+            steer(2, "b"),
+            UiCommand::CancelSteer { id: 1 },
+            UiCommand::CancelSteer { id: 2 },
+            steer(2, "b"),
+            steer(1, "a edited"),
+        ]);
+        assert_eq!(steer_texts(&out), vec!["b", "a edited"]);
+    }
+
+    #[test]
+    fn cancel_without_matching_steer_is_dropped() {
+        let out = coalesce(vec![UiCommand::CancelSteer { id: 7 }, UiCommand::Cancel]);
+        assert!(matches!(out.as_slice(), [UiCommand::Cancel]));
+    }
+}
@@ -38,6 +38,10 @@ impl Tool for BashTool {
         Some(str_arg(args, "command"))
     }
 
+    fn restores_secrets(&self) -> bool {
+        true
+    }
+
     fn approval_patterns(&self, args: &HashMap<String, Value>) -> Vec<String> {
         let cmd = str_arg(args, "command");
         // split_shell_commands already extracts embedded commands from $(...),
@@ -0,0 +1,264 @@
+//! Session-wide record of files mutated by the file tools.
+//!
+//! The first time `edit_file`, `write_file` or `edit_notebook` touches a
+//! path, its pre-edit content is captured. The UI compares that baseline
+//! against the file on disk to show a consolidated diff and to revert.
+
+use super::file_state::normalize_path;
+use similar::{ChangeTag, TextDiff};
+use std::sync::{Arc, Mutex};
+
+/// A file whose current content differs from its pre-session baseline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    pub path: String,
+    /// Content before the first edit. `None` if the file did not exist.
+    pub original: Option<String>,
+    /// Content on disk now. `None` if the file no longer exists.
+    pub current: Option<String>,
+}
+
+impl FileChange {
+    /// Count `(added, removed)` lines between the baseline and now.
+    pub fn line_stats(&self) -> (usize, usize) {
+        let old = self.original.as_deref().unwrap_or("");
+        let new = self.current.as_deref().unwrap_or("");
+        let diff = TextDiff::from_lines(old, new);
+        diff.iter_all_changes()
+            .fold((0, 0), |(added, removed), change| match change.tag() {
+                ChangeTag::Insert => (added + 1, removed),
+                ChangeTag::Delete => (added, removed + 1),
+                ChangeTag::Equal => (added, removed),
+            })
+    }
+}
+
+/// On-disk state of a file at one point in time, kept as raw bytes so
+/// non-UTF-8 files round-trip through `revert` unchanged.
+#[derive(PartialEq, Eq)]
+enum Snapshot {
+    /// The file did not exist.
+    Missing,
+    Content(Vec<u8>),
+    /// The file existed but could not be read; it can't be diffed or restored.
+    Unreadable,
+}
+
+impl Snapshot {
+    fn take(path: &str) -> Self {
+        match std::fs::read(path) {
+            Ok(bytes) => Self::Content(bytes),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::Missing,
+            Err(_) => Self::Unreadable,
+        }
+    }
+
+    /// Lossy text for display. `None` for missing files.
+    fn text(&self) -> Option<String> {
+        match self {
+            Self::Content(bytes) => Some(String::from_utf8_lossy(bytes).into_owned()),
+            Self::Missing | Self::Unreadable => None,
+        }
+    }
+}
+
+struct Baseline {
+    path: String,
+    original: Snapshot,
+}
+
+/// Shared, cheap-to-clone tracker of pre-edit baselines, in first-touch order.
+#[derive(Clone, Default)]
+pub struct SessionChanges(Arc<Mutex<Vec<Baseline>>>);
+
+impl SessionChanges {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Capture `path`'s current content as its baseline unless one is
+    /// already recorded. Call before the tool writes to the file.
+    pub fn record(&self, path: &str) {
+        let key = normalize_path(path);
+        let Ok(mut entries) = self.0.lock() else {
+            return;
+        };
+        if entries.iter().any(|b| b.path == key) {
+            return;
+        }
+        let original = Snapshot::take(&key);
+        entries.push(Baseline {
+            path: key,
+            original,
+        });
+    }
+
+    /// Files whose on-disk content differs from their baseline.
+    pub fn list(&self) -> Vec<FileChange> {
+        let Ok(entries) = self.0.lock() else {
+            return Vec::new();
+        };
+        entries
+            .iter()
+            .filter_map(|b| {
+                if b.original == Snapshot::Unreadable {
+                    return None;
+                }
+                let current = Snapshot::take(&b.path);
+                if current == Snapshot::Unreadable || current == b.original {
+                    return None;
+                }
+                Some(FileChange {
+                    path: b.path.clone(),
+                    original: b.original.text(),
+                    current: current.text(),
+                })
+            })
+            .collect()
+    }
+
+    /// Restore `path` to its baseline, deleting it only if it did not exist
+    /// before the session touched it. Fails if the baseline could not be
+    /// read. The baseline is kept so later edits still diff against the
+    /// original.
+    pub fn revert(&self, path: &str) -> std::io::Result<()> {
+        let key = normalize_path(path);
+        let content = {
+            let Ok(entries) = self.0.lock() else {
+                return Err(std::io::Error::other("change tracker lock poisoned"));
+            };
+            let Some(b) = entries.iter().find(|b| b.path == key) else {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("no recorded changes for {key}"),
+                ));
+            };
+            match &b.original {
+                Snapshot::Content(bytes) => Some(bytes.clone()),
+                Snapshot::Missing => None,
+                Snapshot::Unreadable => {
+                    return Err(std::io::Error::other(format!(
+                        "original content of {key} was not readable"
+                    )));
+                }
+            }
+        };
+        match content {
+            Some(bytes) => std::fs::write(&key, bytes),
+            None => match std::fs::remove_file(&key) {
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                other => other,
+            },
+        }
+    }
+
+    /// Forget all baselines (e.g. when a new session starts).
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.0.lock() {
+            entries.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path_in(dir: &tempfile::TempDir, name: &str) -> String {
+        dir.path().join(name).to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn first_record_wins() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = path_in(&dir, "a.txt");
+        std::fs::write(&path, "one\n").unwrap();
+        let changes = SessionChanges::new();
+        changes.record(&path);
+        std::fs::write(&path, "two\n").unwrap();
+        changes.record(&path);
+        std::fs::write(&path, "three\n").unwrap();
+
+        let list = changes.list();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].original.as_deref(), Some("one\n"));
+        assert_eq!(list[0].current.as_deref(), Some("three\n"));
+    }
+
+    #[test]
+    fn unchanged_files_are_hidden() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = path_in(&dir, "a.txt");
+        std::fs::write(&path, "same\n").unwrap();
+        let changes = SessionChanges::new();
+        changes.record(&path);
+        assert!(changes.list().is_empty());
+    }
+
+    #[test]
+    fn revert_restores_original() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = path_in(&dir, "a.txt");
+        std::fs::write(&path, "before\n").unwrap();
+        let changes = SessionChanges::new();
+        changes.record(&path);
+        std::fs::write(&path, "after\n").unwrap();
+        changes.revert(&path).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "before\n");
+        assert!(changes.list().is_empty());
+    }
+
+    #[test]
+    fn revert_deletes_created_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = path_in(&dir, "new.txt");
+        let changes = SessionChanges::new();
+        changes.record(&path);
+        std::fs::write(&path, "created\n").unwrap();
+        let list = changes.list();
+        assert_eq!(list[0].original, None);
+        changes.revert(&path).unwrap();
+        assert!(!std::path::Path::new(&path).exists());
+    }
+
+    #[test]
+    fn revert_restores_non_utf8_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = path_in(&dir, "blob.bin");
+        let bytes = vec![0xff, 0xfe, 0x00, b'a', b'\n'];
+        std::fs::write(&path, &bytes).unwrap();
+        let changes = SessionChanges::new();
+        changes.record(&path);
+        std::fs::write(&path, "replaced\n").unwrap();
+        assert!(changes.list()[0].original.is_some());
+        changes.revert(&path).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), bytes);
+    }
+
+    #[test]
+    fn line_stats_counts_changed_region() {
+        let change = FileChange {
+            path: "x".into(),
+            original: Some("a\nb\nc\nd\n".into()),
+            current: Some("a\nX\nd\n".into()),
+        };
+        assert_eq!(change.line_stats(), (1, 2));
+    }
+
+    #[test]
+    fn line_stats_counts_separate_hunks() {
+        // A prefix/suffix trim would report the whole middle as changed.
+        let change = FileChange {
+            path: "x".into(),
+            original: Some("a\nb\nc\nd\ne\nf\n".into()),
+            current: Some("A\nb\nc\nd\ne\nF\nG\n".into()),
+        };
+        assert_eq!(change.line_stats(), (3, 2));
+        let created = FileChange {
+            path: "x".into(),
+            original: None,
+            current: Some("one\ntwo\n".into()),
+        };
+        assert_eq!(created.line_stats(), (2, 0));
+    }
+}
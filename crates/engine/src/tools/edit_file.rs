@@ -50,6 +50,10 @@ impl Tool for EditFileTool {
         Some(display_path(&str_arg(args, "file_path")))
     }
 
+    fn restores_secrets(&self) -> bool {
+        true
+    }
+
     fn preflight(&self, args: &HashMap<String, Value>) -> Option<String> {
         let path = str_arg(args, "file_path");
         staleness_error(&self.files, &path, "file")
@@ -79,6 +83,7 @@ impl Tool for EditFileTool {
         Box::pin(async move {
             let path = str_arg(&args, "file_path");
             let _guard = ctx.file_locks.lock(&path).await;
+            ctx.changes.record(&path);
             tokio::task::block_in_place(|| self.run(&args))
         })
     }
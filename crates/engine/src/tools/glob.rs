@@ -39,7 +39,7 @@ impl Tool for GlobTool {
     fn execute<'a>(
         &'a self,
         args: HashMap<String, Value>,
-        _ctx: &'a ToolContext<'a>,
+        ctx: &'a ToolContext<'a>,
     ) -> ToolFuture<'a> {
         Box::pin(async move {
             tokio::task::block_in_place(|| {
@@ -75,10 +75,14 @@ impl Tool for GlobTool {
                     if !matcher.is_match(relative) {
                         continue;
                     }
+                    let display = path.display().to_string();
+                    if ctx.permissions.denies_path(ctx.mode, &display) {
+                        continue;
+                    }
 
                     if let Ok(meta) = entry.metadata() {
                         if let Ok(mtime) = meta.modified() {
-                            entries.push((mtime, path.display().to_string()));
+                            entries.push((mtime, display));
                         }
                     }
 
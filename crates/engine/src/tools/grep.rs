@@ -94,13 +94,15 @@ impl Tool for GrepTool {
     fn execute<'a>(
         &'a self,
         args: HashMap<String, Value>,
-        _ctx: &'a ToolContext<'a>,
+        ctx: &'a ToolContext<'a>,
     ) -> ToolFuture<'a> {
-        Box::pin(async move { tokio::task::block_in_place(|| run_grep(&args)) })
+        let denied = |path: &str| ctx.permissions.denies_path(ctx.mode, path);
+        Box::pin(async move { tokio::task::block_in_place(|| run_grep(&args, &denied)) })
     }
 }
 
-fn run_grep(args: &HashMap<String, Value>) -> ToolResult {
+/// Run the search, leaving out files for which `denied` returns true.
+fn run_grep(args: &HashMap<String, Value>, denied: &dyn Fn(&str) -> bool) -> ToolResult {
     let pattern = str_arg(args, "pattern");
     let path = str_arg(args, "path");
     let glob_filter = str_arg(args, "glob");
@@ -125,7 +127,8 @@ fn run_grep(args: &HashMap<String, Value>) -> ToolResult {
 
     let search_path = if path.is_empty() { ".".into() } else { path };
 
-    let mut cmd_args: Vec<String> = Vec::new();
+    // NUL after each file name, so `drop_denied` can split it off reliably.
+    let mut cmd_args: Vec<String> = vec!["--null".into()];
 
     match output_mode.as_str() {
         "files_with_matches" => cmd_args.push("--files-with-matches".into()),
@@ -173,7 +176,9 @@ fn run_grep(args: &HashMap<String, Value>) -> ToolResult {
 
     match child {
         Ok(child) => {
-            let result = run_command_with_timeout(child, timeout);
+            let mut result = run_command_with_timeout(child, timeout);
+            // rg still prints matches when some files failed to read.
+            result.content = drop_denied(&result.content, denied);
             if result.is_error {
                 if result.content.is_empty() {
                     return ToolResult::ok("no matches found");
@@ -197,10 +202,41 @@ fn run_grep(args: &HashMap<String, Value>) -> ToolResult {
             timeout,
             offset,
             head_limit,
+            denied,
         ),
     }
 }
 
+/// Remove output lines from files `denied` matches and turn the NUL after
+/// each file name back into the usual separator. Lines without a file name
+/// (a single-file search, `--` group breaks) are kept; the search root was
+/// already checked against the path rules.
+fn drop_denied(content: &str, denied: &dyn Fn(&str) -> bool) -> String {
+    let mut out: Vec<String> = Vec::new();
+    for line in content.lines() {
+        match line.split_once('\0') {
+            Some((path, _)) if denied(path) => {}
+            Some((path, "")) => out.push(path.to_string()),
+            Some((path, rest)) => {
+                // `12:text` for a match, `12-text` for context.
+                let after_number = rest.trim_start_matches(|c: char| c.is_ascii_digit());
+                let sep = if after_number.len() < rest.len() && after_number.starts_with('-') {
+                    '-'
+                } else {
+                    ':'
+                };
+                out.push(format!("{path}{sep}{rest}"));
+            }
+            None if line == "--" && out.last().is_none_or(|l| l == "--") => {}
+            None => out.push(line.to_string()),
+        }
+    }
+    if out.last().is_some_and(|l| l == "--") {
+        out.pop();
+    }
+    out.join("\n")
+}
+
 fn apply_offset_and_limit(content: &str, offset: usize, head_limit: usize) -> String {
     if offset == 0 && head_limit == 0 {
         return content.to_string();
@@ -217,6 +253,7 @@ fn apply_offset_and_limit(content: &str, offset: usize, head_limit: usize) -> St
     lines[start..end].join("\n")
 }
 
+#[allow(clippy::too_many_arguments)]
 fn grep_fallback(
     pattern: &str,
     search_path: &str,
@@ -225,8 +262,9 @@ fn grep_fallback(
     timeout: Duration,
     offset: usize,
     head_limit: usize,
+    denied: &dyn Fn(&str) -> bool,
 ) -> ToolResult {
-    let mut cmd_args = vec!["-rn".to_string(), "--max-count=200".to_string()];
+    let mut cmd_args = vec!["-rnZ".to_string(), "--max-count=200".to_string()];
     if case_insensitive {
         cmd_args.push("-i".into());
     }
@@ -246,10 +284,11 @@ fn grep_fallback(
     match child {
         Ok(child) => {
             let result = run_command_with_timeout(child, timeout);
-            if !result.is_error && result.content.is_empty() {
+            let found = drop_denied(&result.content, denied);
+            if !result.is_error && found.is_empty() {
                 ToolResult::ok("no matches found")
             } else {
-                let content = apply_offset_and_limit(&result.content, offset, head_limit);
+                let content = apply_offset_and_limit(&found, offset, head_limit);
                 ToolResult {
                     content,
                     is_error: result.is_error,
@@ -260,3 +299,37 @@ fn grep_fallback(
         Err(e) => ToolResult::err(e.to_string()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn denied_files_are_left_out() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("certs")).unwrap();
+        std::fs::write(dir.path().join("certs/server.pem"), "SECRET key\n").unwrap();
+        std::fs::write(dir.path().join("certs/README"), "no key here\n").unwrap();
+        let args = HashMap::from([
+            ("pattern".to_string(), Value::from("key")),
+            (
+                "path".to_string(),
+                Value::from(dir.path().to_string_lossy()),
+            ),
+        ]);
+        let denied = |path: &str| path.ends_with(".pem");
+
+        let result = run_grep(&args, &denied);
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.contains("README:1:no key here"));
+        assert!(!result.content.contains("SECRET"));
+        assert!(!result.content.contains('\0'));
+    }
+
+    #[test]
+    fn drop_denied_keeps_separators_and_group_breaks() {
+        let out = "a.rs\x003:hit\na.rs\x004-ctx\n--\nb.pem\x001:secret\n--\nc.rs\x002:x\n";
+        let kept = drop_denied(out, &|p: &str| p.ends_with(".pem"));
+        assert_eq!(kept, "a.rs:3:hit\na.rs-4-ctx\n--\nc.rs:2:x");
+    }
+}
@@ -31,7 +31,10 @@ impl Tool for ListAgentsTool {
         Box::pin(async move {
             let entries = crate::registry::discover(&self.scope);
 
-            let others: Vec<_> = entries.iter().filter(|e| e.pid != self.my_pid).collect();
+            let others: Vec<_> = entries
+                .iter()
+                .filter(|e| !(e.is_local() && e.pid == self.my_pid))
+                .collect();
 
             if others.is_empty() {
                 return ToolResult::ok("No other agents found.");
@@ -40,7 +43,8 @@ impl Tool for ListAgentsTool {
             // Build parent_pid -> children map for in-memory tree walk.
             let parent_map: HashMap<u32, Vec<u32>> = {
                 let mut m: HashMap<u32, Vec<u32>> = HashMap::new();
-                for e in &entries {
+                // PIDs only identify processes on this host.
+                for e in entries.iter().filter(|e| e.is_local()) {
                     if let Some(ppid) = e.parent_pid {
                         m.entry(ppid).or_default().push(e.pid);
                     }
@@ -68,7 +72,7 @@ impl Tool for ListAgentsTool {
 
             let mut lines = Vec::new();
             for e in &others {
-                let agent_type = if is_descendant(e.pid, self.my_pid) {
+                let agent_type = if e.is_local() && is_descendant(e.pid, self.my_pid) {
                     "owned"
                 } else {
                     "peer "
@@ -78,10 +82,14 @@ impl Tool for ListAgentsTool {
                     crate::registry::AgentStatus::Idle => "idle",
                 };
                 let slug = e.task_slug.as_deref().unwrap_or("");
-                lines.push(format!(
+                let mut line = format!(
                     "{:<name_w$}  {:<5}  {:<status_w$}  {slug}",
                     e.agent_id, agent_type, status
-                ));
+                );
+                if !e.is_local() {
+                    line = format!("{} (on {})", line.trim_end(), e.host);
+                }
+                lines.push(line);
             }
 
             ToolResult::ok(lines.join("\n"))
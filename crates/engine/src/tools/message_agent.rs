@@ -1,10 +1,13 @@
-use super::{str_arg, Tool, ToolContext, ToolFuture, ToolResult};
+use super::spawn_agent::{structured, wait_for_message};
+use super::{str_arg, AgentMessageNotification, Tool, ToolContext, ToolFuture, ToolResult};
 use serde_json::Value;
 use std::collections::HashMap;
 
 pub struct MessageAgentTool {
     pub my_id: String,
     pub my_slug: std::sync::Arc<std::sync::Mutex<Option<String>>>,
+    /// Incoming agent messages, for waiting on a structured reply.
+    pub agent_msg_tx: Option<tokio::sync::broadcast::Sender<AgentMessageNotification>>,
 }
 
 impl Tool for MessageAgentTool {
@@ -28,6 +31,10 @@ impl Tool for MessageAgentTool {
                 "message": {
                     "type": "string",
                     "description": "The message to send"
+                },
+                "schema": {
+                    "type": "object",
+                    "description": "Optional JSON schema the recipient's reply must satisfy. Needs exactly one target, which must be a subagent. The call then waits for the reply and returns the parsed value as the structured result."
                 }
             },
             "required": ["targets", "message"]
@@ -37,10 +44,14 @@ impl Tool for MessageAgentTool {
     fn execute<'a>(
         &'a self,
         args: HashMap<String, Value>,
-        _ctx: &'a ToolContext<'a>,
+        ctx: &'a ToolContext<'a>,
     ) -> ToolFuture<'a> {
         Box::pin(async move {
             let message = str_arg(&args, "message");
+            let schema = match crate::schema::from_args(&args) {
+                Ok(schema) => schema,
+                Err(e) => return ToolResult::err(e),
+            };
             let targets: Vec<String> = args
                 .get("targets")
                 .and_then(|v| v.as_array())
@@ -54,28 +65,30 @@ impl Tool for MessageAgentTool {
             if targets.is_empty() {
                 return ToolResult::err("no targets specified");
             }
+            if let Some(ref schema) = schema {
+                let [target] = targets.as_slice() else {
+                    return ToolResult::err("a schema needs exactly one target");
+                };
+                return self.request_structured(target, &message, schema, ctx).await;
+            }
 
-            let slug = self
-                .my_slug
-                .lock()
-                .ok()
-                .and_then(|guard| guard.clone())
-                .unwrap_or_default();
+            let slug = self.slug();
 
             let mut delivered = vec![];
             let mut errors = vec![];
 
             for id in &targets {
                 let entry = crate::registry::find_by_id(id);
-                let socket_path = match entry {
-                    Some(e) => std::path::PathBuf::from(&e.socket_path),
+                let endpoint = match entry {
+                    Some(e) => e.endpoint(),
                     None => {
                         errors.push(format!("{id}: not found"));
                         continue;
                     }
                 };
 
-                match crate::socket::send_message(&socket_path, &self.my_id, &slug, &message).await
+                match crate::socket::send_message(&endpoint, &self.my_id, &slug, &message, None)
+                    .await
                 {
                     Ok(()) => delivered.push(id.clone()),
                     Err(e) => errors.push(format!("{id}: {e}")),
@@ -96,3 +109,54 @@ impl Tool for MessageAgentTool {
         })
     }
 }
+
+impl MessageAgentTool {
+    fn slug(&self) -> String {
+        self.my_slug
+            .lock()
+            .ok()
+            .and_then(|guard| guard.clone())
+            .unwrap_or_default()
+    }
+
+    /// Send a message whose reply must match `schema`, wait for the reply
+    /// and check it here rather than trusting the recipient's own check.
+    async fn request_structured(
+        &self,
+        id: &str,
+        message: &str,
+        schema: &Value,
+        ctx: &ToolContext<'_>,
+    ) -> ToolResult {
+        let Some(entry) = crate::registry::find_by_id(id) else {
+            return ToolResult::err(format!("{id}: not found"));
+        };
+        if entry.parent_pid.is_none() {
+            return ToolResult::err(format!(
+                "{id} is an interactive session and cannot reply with a schema"
+            ));
+        }
+        let Some(ref tx) = self.agent_msg_tx else {
+            return ToolResult::err("cannot wait for a reply (no message channel)");
+        };
+        // Subscribe first so a quick reply is not missed.
+        let rx = tx.subscribe();
+        let endpoint = entry.endpoint();
+        if let Err(e) =
+            crate::socket::send_message(&endpoint, &self.my_id, &self.slug(), message, Some(schema))
+                .await
+        {
+            return ToolResult::err(format!("{id}: {e}"));
+        }
+        let alive = || crate::registry::find_by_id(id).is_some();
+        match wait_for_message(rx, id, ctx, alive).await {
+            Ok(reply) => structured(
+                format!("agent {id} replied:\n{reply}"),
+                serde_json::json!({ "agent_id": id }),
+                &reply,
+                Some(schema),
+            ),
+            Err(e) => ToolResult::err(e),
+        }
+    }
+}
@@ -2,6 +2,7 @@ mod ask_user_question;
 pub(crate) mod background;
 mod bash;
 mod bash_background;
+mod changes;
 mod edit_file;
 mod exit_plan_mode;
 mod file_state;
@@ -22,6 +23,7 @@ mod web_search;
 mod web_shared;
 mod write_file;
 
+pub use changes::{FileChange, SessionChanges};
 pub use file_state::{file_mtime_ms, normalize_path, staleness_error, FileState, FileStateCache};
 
 use crate::cancel::CancellationToken;
@@ -112,7 +114,11 @@ pub struct ToolContext<'a> {
     pub session_id: &'a str,
     pub session_dir: &'a std::path::Path,
     pub file_locks: &'a FileLocks,
+    pub changes: &'a SessionChanges,
     pub engine_config: &'a crate::EngineConfig,
+    /// Rules for the turn, for tools that filter what they return.
+    pub permissions: &'a Permissions,
+    pub mode: Mode,
 }
 
 pub type ToolFuture<'a> = Pin<Box<dyn Future<Output = ToolResult> + Send + 'a>>;
@@ -151,6 +157,13 @@ pub trait Tool: Send + Sync {
         false
     }
 
+    /// Whether placeholders of redacted secrets in the arguments are swapped
+    /// back for the real values before execution. Only for tools that act
+    /// locally; anything sent over the network must keep the placeholders.
+    fn restores_secrets(&self) -> bool {
+        false
+    }
+
     /// Pre-flight validation run before showing the permission dialog.
     /// Return `Some(error)` to skip the dialog and fail the tool immediately.
     fn preflight(&self, _args: &HashMap<String, Value>) -> Option<String> {
@@ -184,6 +197,11 @@ impl ToolRegistry {
         self.tools.push(tool);
     }
 
+    /// Keep only the tools whose name passes `keep`.
+    pub fn retain(&mut self, keep: impl Fn(&str) -> bool) {
+        self.tools.retain(|t| keep(t.name()));
+    }
+
     pub fn get(&self, name: &str) -> Option<&dyn Tool> {
         self.tools
             .iter()
@@ -255,7 +273,11 @@ pub fn tool_arg_summary(tool_name: &str, args: &HashMap<String, Value>) -> Strin
         }
         "spawn_agent" => {
             let prompt = str_arg(args, "prompt");
-            prompt.lines().next().unwrap_or("").trim().to_string()
+            let first = prompt.lines().next().unwrap_or("").trim();
+            match str_arg(args, "profile") {
+                p if p.is_empty() => first.to_string(),
+                p => format!("[{p}] {first}"),
+            }
         }
         "message_agent" => {
             let targets: Vec<String> = args
@@ -500,6 +522,8 @@ pub struct MultiAgentToolConfig {
     pub api_key_env: String,
     pub model: String,
     pub provider_type: String,
+    /// Profiles `spawn_agent` can start subagents from.
+    pub profiles: std::sync::Arc<crate::AgentProfiles>,
     /// Broadcast channel for agent message notifications (used by blocking spawn).
     pub agent_msg_tx: Option<tokio::sync::broadcast::Sender<AgentMessageNotification>>,
     /// Channel for sending spawned child handles (stdout pipes) to the parent.
@@ -553,6 +577,7 @@ pub fn build_tools(
         r.register(Box::new(message_agent::MessageAgentTool {
             my_id: ma.agent_id.clone(),
             my_slug: ma.slug,
+            agent_msg_tx: ma.agent_msg_tx.clone(),
         }));
         r.register(Box::new(peek_agent::PeekAgentTool {
             my_id: ma.agent_id.clone(),
@@ -567,6 +592,7 @@ pub fn build_tools(
                 api_key_env: ma.api_key_env.clone(),
                 model: ma.model.clone(),
                 provider_type: ma.provider_type.clone(),
+                profiles: ma.profiles.clone(),
                 spawned_tx: ma.spawned_tx.clone(),
                 agent_msg_tx: ma.agent_msg_tx.clone(),
             }));
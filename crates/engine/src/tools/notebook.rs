@@ -380,6 +380,7 @@ impl Tool for NotebookEditTool {
         Box::pin(async move {
             let path = str_arg(&args, "notebook_path");
             let _guard = ctx.file_locks.lock(&path).await;
+            ctx.changes.record(&path);
             tokio::task::block_in_place(|| run_edit(&args, &self.files))
         })
     }
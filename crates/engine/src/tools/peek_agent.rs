@@ -42,8 +42,8 @@ impl Tool for PeekAgentTool {
             let question = str_arg(&args, "question");
 
             let entry = crate::registry::find_by_id(&target_id);
-            let socket_path = match entry {
-                Some(e) => std::path::PathBuf::from(&e.socket_path),
+            let endpoint = match entry {
+                Some(e) => e.endpoint(),
                 None => {
                     return ToolResult::err(format!("{target_id}: not found"));
                 }
@@ -57,7 +57,7 @@ impl Tool for PeekAgentTool {
                  what is known.\n\n{question}"
             );
 
-            match crate::socket::send_query(&socket_path, &self.my_id, &framed).await {
+            match crate::socket::send_query(&endpoint, &self.my_id, &framed).await {
                 Ok(answer) => ToolResult::ok(answer),
                 Err(e) => ToolResult::err(format!("{target_id}: {e}")),
             }
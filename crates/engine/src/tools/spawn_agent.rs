@@ -16,6 +16,7 @@ pub struct SpawnAgentTool {
     pub api_key_env: String,
     pub model: String,
     pub provider_type: String,
+    pub profiles: std::sync::Arc<crate::AgentProfiles>,
     pub spawned_tx: Option<tokio::sync::mpsc::UnboundedSender<super::SpawnedChild>>,
     pub agent_msg_tx: Option<tokio::sync::broadcast::Sender<AgentMessageNotification>>,
 }
@@ -48,6 +49,14 @@ impl Tool for SpawnAgentTool {
                 "wait": {
                     "type": "boolean",
                     "description": "If true, block until the subagent finishes and return its result. If false (default), spawn in the background and continue immediately."
+                },
+                "profile": {
+                    "type": "string",
+                    "description": "Agent profile to start the subagent from (model, tools and instructions). Omit to start a copy of yourself."
+                },
+                "schema": {
+                    "type": "object",
+                    "description": "Optional JSON schema the subagent's final answer must satisfy. With `wait`, the parsed value is returned as the structured result."
                 }
             },
             "required": ["prompt"]
@@ -62,12 +71,29 @@ impl Tool for SpawnAgentTool {
         Box::pin(async move {
             let prompt = str_arg(&args, "prompt");
             let blocking = bool_arg(&args, "wait");
+            let profile = str_arg(&args, "profile");
+            let schema = match crate::schema::from_args(&args) {
+                Ok(schema) => schema,
+                Err(e) => return ToolResult::err(e),
+            };
+            if !profile.is_empty() && self.profiles.get(&profile).is_none() {
+                let names = self.profiles.names();
+                return ToolResult::err(format!(
+                    "unknown profile '{profile}'. Available profiles: {}",
+                    if names.is_empty() {
+                        "none".to_string()
+                    } else {
+                        names.join(", ")
+                    }
+                ));
+            }
 
-            // Check agent count limit.
+            // Check agent count limit. Parent PIDs are only meaningful on
+            // this host.
             let current = crate::registry::discover(&self.scope);
             let count = current
                 .iter()
-                .filter(|e| e.parent_pid == Some(self.my_pid))
+                .filter(|e| e.is_local() && e.parent_pid == Some(self.my_pid))
                 .count();
             if count >= self.max_agents as usize {
                 return ToolResult::err(format!(
@@ -102,6 +128,12 @@ impl Tool for SpawnAgentTool {
                 "-m",
                 &self.model,
             ]);
+            if !profile.is_empty() {
+                cmd.args(["--profile", &profile, "--parent-mode", ctx.mode.as_str()]);
+            }
+            if let Some(ref schema) = schema {
+                cmd.args(["--result-schema", &schema.to_string()]);
+            }
             cmd.arg(&prompt);
             cmd.stdin(std::process::Stdio::null());
             cmd.env("FORCE_COLOR", "1");
@@ -152,6 +184,9 @@ impl Tool for SpawnAgentTool {
                         socket_path: String::new(),
                         depth: self.depth + 1,
                         started_at: String::new(),
+                        host: String::new(),
+                        address: None,
+                        updated_at: 0,
                     });
 
                     if let Some(ref tx) = self.spawned_tx {
@@ -172,7 +207,7 @@ impl Tool for SpawnAgentTool {
                     drop(child);
 
                     if blocking {
-                        self.wait_for_agent(&agent_id, ctx).await
+                        self.wait_for_agent(&agent_id, schema.as_ref(), ctx).await
                     } else {
                         ToolResult::ok(format!("agent {agent_id} is now working in the background"))
                             .with_metadata(serde_json::json!({
@@ -189,48 +224,135 @@ impl Tool for SpawnAgentTool {
 
 impl SpawnAgentTool {
     /// Block until the named agent sends a message back via the socket.
-    async fn wait_for_agent(&self, agent_id: &str, ctx: &ToolContext<'_>) -> ToolResult {
+    /// With a `schema`, the answer is parsed and returned as the `result`
+    /// metadata field.
+    async fn wait_for_agent(
+        &self,
+        agent_id: &str,
+        schema: Option<&Value>,
+        ctx: &ToolContext<'_>,
+    ) -> ToolResult {
         let Some(ref tx) = self.agent_msg_tx else {
             return ToolResult::err("blocking spawn not available (no message channel)");
         };
-        let mut rx = tx.subscribe();
-        let deadline = tokio::time::Instant::now() + BLOCKING_TIMEOUT;
-        let mut check_interval = tokio::time::interval(CHILD_CHECK_INTERVAL);
-        check_interval.tick().await; // consume immediate tick
-
-        loop {
-            tokio::select! {
-                result = rx.recv() => {
-                    match result {
-                        Ok(notif) if notif.from_id == agent_id => {
-                            return ToolResult::ok(format!("agent {} finished:\n{}", notif.from_id, notif.message))
-                                .with_metadata(serde_json::json!({
-                                    "agent_id": agent_id,
-                                    "blocking": true,
-                                }));
-                        }
-                        Ok(_) => continue, // message from a different agent
-                        Err(RecvError::Lagged(_)) => continue,
-                        Err(RecvError::Closed) => {
-                            return ToolResult::err(format!("agent {agent_id}: message channel closed"));
-                        }
-                    }
-                }
-                _ = tokio::time::sleep_until(deadline) => {
-                    return ToolResult::err(format!("agent {agent_id}: timed out after {}s", BLOCKING_TIMEOUT.as_secs()));
-                }
-                _ = check_interval.tick() => {
-                    let alive = crate::registry::children_of(self.my_pid)
-                        .iter()
-                        .any(|e| e.agent_id == agent_id && crate::registry::is_pid_alive(e.pid));
-                    if !alive {
-                        return ToolResult::err(format!("agent {agent_id} exited without sending a result"));
+        let alive = || {
+            crate::registry::children_of(self.my_pid)
+                .iter()
+                .any(|e| e.agent_id == agent_id && crate::registry::is_pid_alive(e.pid))
+        };
+        match wait_for_message(tx.subscribe(), agent_id, ctx, alive).await {
+            Ok(message) => finished(agent_id, &message, schema),
+            Err(e) => ToolResult::err(e),
+        }
+    }
+}
+
+/// Wait for the next message from `agent_id`. `alive` is checked now and
+/// then so an agent that exits without answering doesn't block for the
+/// full timeout. Subscribe before sending whatever prompts the message.
+pub(super) async fn wait_for_message(
+    mut rx: tokio::sync::broadcast::Receiver<AgentMessageNotification>,
+    agent_id: &str,
+    ctx: &ToolContext<'_>,
+    alive: impl Fn() -> bool,
+) -> Result<String, String> {
+    let deadline = tokio::time::Instant::now() + BLOCKING_TIMEOUT;
+    let mut check_interval = tokio::time::interval(CHILD_CHECK_INTERVAL);
+    check_interval.tick().await; // consume immediate tick
+
+    loop {
+        tokio::select! {
+            result = rx.recv() => {
+                match result {
+                    Ok(notif) if notif.from_id == agent_id => return Ok(notif.message),
+                    Ok(_) => continue, // message from a different agent
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => {
+                        return Err(format!("agent {agent_id}: message channel closed"));
                     }
                 }
-                _ = ctx.cancel.cancelled() => {
-                    return ToolResult::err("cancelled");
+            }
+            _ = tokio::time::sleep_until(deadline) => {
+                return Err(format!("agent {agent_id}: timed out after {}s", BLOCKING_TIMEOUT.as_secs()));
+            }
+            _ = check_interval.tick() => {
+                if !alive() {
+                    return Err(format!("agent {agent_id} exited without sending a result"));
                 }
             }
+            _ = ctx.cancel.cancelled() => {
+                return Err("cancelled".into());
+            }
+        }
+    }
+}
+
+/// The result of a blocking spawn: the agent's answer, plus the parsed
+/// value when a schema was requested.
+fn finished(agent_id: &str, message: &str, schema: Option<&Value>) -> ToolResult {
+    let metadata = serde_json::json!({
+        "agent_id": agent_id,
+        "blocking": true,
+    });
+    structured(
+        format!("agent {agent_id} finished:\n{message}"),
+        metadata,
+        message,
+        schema,
+    )
+}
+
+/// Attach the parsed answer as `result` metadata, or a `schema_error` when
+/// it does not match `schema`.
+pub(super) fn structured(
+    mut content: String,
+    mut metadata: Value,
+    message: &str,
+    schema: Option<&Value>,
+) -> ToolResult {
+    if let Some(schema) = schema {
+        match crate::schema::parse_result(message, schema) {
+            Ok(value) => metadata["result"] = value,
+            Err(e) => {
+                content.push_str(&format!(
+                    "\n\n(the answer does not match the requested schema: {e})"
+                ));
+                metadata["schema_error"] = Value::String(e);
+            }
         }
     }
+    ToolResult::ok(content).with_metadata(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn structured_answer_is_parsed_or_flagged() {
+        let schema = serde_json::json!({
+            "type": "object",
+            "required": ["verdict"],
+        });
+        let ok = structured(
+            "done".into(),
+            serde_json::json!({}),
+            r#"{"verdict": "pass"}"#,
+            Some(&schema),
+        );
+        let meta = ok.metadata.unwrap();
+        assert_eq!(meta["result"]["verdict"], "pass");
+        assert!(meta.get("schema_error").is_none());
+
+        let bad = structured(
+            "done".into(),
+            serde_json::json!({}),
+            "looks fine",
+            Some(&schema),
+        );
+        assert!(bad.content.contains("does not match the requested schema"));
+        let meta = bad.metadata.unwrap();
+        assert!(meta.get("result").is_none());
+        assert!(meta["schema_error"].is_string());
+    }
 }
@@ -39,7 +39,7 @@ impl Tool for StopAgentTool {
             let entry = crate::registry::find_by_id(&target_id);
             match entry {
                 Some(e) => {
-                    if !crate::registry::is_in_tree(e.pid, self.my_pid) {
+                    if !e.is_local() || !crate::registry::is_in_tree(e.pid, self.my_pid) {
                         return ToolResult::err(format!("{target_id} is not owned by you"));
                     }
                     crate::registry::kill_agent(e.pid);
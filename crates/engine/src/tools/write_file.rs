@@ -42,6 +42,10 @@ impl Tool for WriteFileTool {
         Some(display_path(&str_arg(args, "file_path")))
     }
 
+    fn restores_secrets(&self) -> bool {
+        true
+    }
+
     fn preflight(&self, args: &HashMap<String, Value>) -> Option<String> {
         let path = str_arg(args, "file_path");
         if !Path::new(&path).exists() {
@@ -61,6 +65,7 @@ impl Tool for WriteFileTool {
         Box::pin(async move {
             let path = str_arg(&args, "file_path");
             let _guard = ctx.file_locks.lock(&path).await;
+            ctx.changes.record(&path);
             tokio::task::block_in_place(|| self.run(&args))
         })
     }
@@ -1,9 +1,12 @@
 //! Wire protocol between the engine and the UI.
 
 use crate::content::Content;
-use crate::message::{Message, ToolOutcome};
+use crate::message::{Message, Redaction, ToolOutcome};
 use crate::mode::{Mode, ReasoningEffort};
-use crate::usage::{ModelConfigOverrides, PermissionOverrides, TokenUsage, TurnMeta};
+use crate::usage::{
+    BudgetScope, CapabilityOverrides, ModelConfigOverrides, PermissionOverrides, ProviderAuth,
+    StopReason, TokenUsage, TurnMeta,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -35,8 +38,14 @@ pub enum EngineEvent {
     /// Incremental text token from the LLM (streaming delta).
     TextDelta { delta: String },
 
-    /// A queued user message was consumed by the engine.
-    Steered { text: String, count: usize },
+    /// A queued user message was consumed by the engine. `id` echoes the
+    /// id of the `Steer` command it came from.
+    Steered {
+        text: String,
+        count: usize,
+        #[serde(default)]
+        id: u64,
+    },
 
     /// A tool call has started.
     ToolStarted {
@@ -84,6 +93,22 @@ pub enum EngineEvent {
         background: bool,
     },
 
+    /// Spend crossed the warning threshold of a budget. Sent once per
+    /// scope until the budget is reset.
+    BudgetWarning {
+        scope: BudgetScope,
+        spent_usd: f64,
+        limit_usd: f64,
+    },
+
+    /// A budget is exhausted. The engine stops the turn before its next
+    /// LLM call; a `TurnComplete` with `StopReason::Budget` follows.
+    BudgetExceeded {
+        scope: BudgetScope,
+        spent_usd: f64,
+        limit_usd: f64,
+    },
+
     /// LLM call failed, engine is retrying.
     Retrying { delay_ms: u64, attempt: u32 },
 
@@ -113,10 +138,16 @@ pub enum EngineEvent {
         turn_id: u64,
         messages: Vec<Message>,
         meta: Option<TurnMeta>,
+        #[serde(default)]
+        reason: StopReason,
     },
 
     /// The agent turn ended with an error.
-    TurnError { message: String },
+    TurnError {
+        message: String,
+        #[serde(default = "StopReason::error")]
+        reason: StopReason,
+    },
 
     /// Engine is shutting down.
     Shutdown { reason: Option<String> },
@@ -127,6 +158,14 @@ pub enum EngineEvent {
         exit_code: Option<i32>,
     },
 
+    /// Secrets were redacted from content entering the conversation.
+    /// `source` says where the content came from, e.g. `user message` or
+    /// `bash result`.
+    Redacted {
+        source: String,
+        redactions: Vec<Redaction>,
+    },
+
     /// An inter-agent message arrived via the socket.
     AgentMessage {
         from_id: String,
@@ -163,8 +202,18 @@ pub enum UiCommand {
         permission_overrides: Option<PermissionOverrides>,
     },
 
-    /// Inject a message mid-turn (steering / type-ahead).
-    Steer { text: String },
+    /// Inject a message mid-turn (steering / type-ahead). A non-zero `id`
+    /// lets the UI withdraw it with `CancelSteer` until it is consumed;
+    /// re-sending an id the turn already consumed is a no-op.
+    Steer {
+        text: String,
+        #[serde(default)]
+        id: u64,
+    },
+
+    /// Withdraw a `Steer` the engine has not consumed yet. A no-op once
+    /// the matching `Steered` event has been emitted.
+    CancelSteer { id: u64 },
 
     /// Remove the last `count` steered messages (user unqueued them).
     Unsteer { count: usize },
@@ -194,6 +243,10 @@ pub enum UiCommand {
         api_base: String,
         api_key: String,
         provider_type: String,
+        #[serde(default)]
+        auth: ProviderAuth,
+        #[serde(default)]
+        capabilities: CapabilityOverrides,
     },
 
     /// Compact conversation history.
@@ -15,9 +15,9 @@ pub mod usage;
 
 pub use content::{Content, ContentPart};
 pub use event::{EngineEvent, UiCommand};
-pub use message::{FunctionCall, Message, Role, ToolCall, ToolOutcome};
+pub use message::{FunctionCall, Message, Redaction, Role, ToolCall, ToolOutcome};
 pub use mode::{Mode, ReasoningEffort};
 pub use usage::{
-    AgentBlockData, AgentToolData, ModelConfigOverrides, PermissionOverrides, RuleSetOverride,
-    TokenUsage, TurnMeta,
+    AgentBlockData, AgentToolData, BudgetScope, CapabilityOverrides, ModelConfigOverrides,
+    PermissionOverrides, ProviderAuth, RuleSetOverride, StopReason, TokenUsage, TurnMeta,
 };
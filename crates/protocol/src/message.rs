@@ -198,3 +198,11 @@ pub struct ToolOutcome {
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub metadata: Option<serde_json::Value>,
 }
+
+/// A secret scrubbed from content entering the conversation: its type label
+/// and the 1-based line it was found on. The value itself is never kept.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Redaction {
+    pub label: String,
+    pub line: usize,
+}
@@ -32,6 +32,89 @@ impl TokenUsage {
         add(&mut self.cache_write_tokens, other.cache_write_tokens);
         add(&mut self.reasoning_tokens, other.reasoning_tokens);
     }
+
+    /// Tokens the prompt occupied in the context window. `prompt_tokens`
+    /// counts only uncached input, so cache reads and writes are added back.
+    pub fn context_tokens(&self) -> Option<u32> {
+        self.prompt_tokens
+            .map(|p| p + self.cache_read_tokens.unwrap_or(0) + self.cache_write_tokens.unwrap_or(0))
+    }
+
+    /// Fraction of input tokens served from the prompt cache.
+    pub fn cache_hit_rate(&self) -> Option<f64> {
+        let total = self.context_tokens()?;
+        if total == 0 {
+            return None;
+        }
+        Some(self.cache_read_tokens.unwrap_or(0) as f64 / total as f64)
+    }
+}
+
+/// Which spending limit a budget event refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetScope {
+    /// Spend in the current session (or headless run).
+    Session,
+    /// Spend since midnight UTC across all sessions.
+    Daily,
+}
+
+impl BudgetScope {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Session => "session",
+            Self::Daily => "daily",
+        }
+    }
+}
+
+/// Why a turn ended. Carried on `TurnComplete` and `TurnError` so
+/// headless consumers can tell a hit limit from a failure.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StopReason {
+    /// The model finished without requesting more tools.
+    #[default]
+    Completed,
+    /// The user cancelled the turn.
+    Cancelled,
+    /// `--max-turns` LLM round-trips were used up.
+    MaxTurns,
+    /// `--max-tool-calls` was reached and the model asked for more.
+    MaxToolCalls,
+    /// `--timeout` elapsed.
+    Timeout,
+    /// A spending budget was exhausted.
+    Budget,
+    /// The provider or engine failed.
+    Error,
+}
+
+impl StopReason {
+    /// True when the turn stopped because a configured limit was reached.
+    pub fn is_limit(self) -> bool {
+        matches!(
+            self,
+            Self::MaxTurns | Self::MaxToolCalls | Self::Timeout | Self::Budget
+        )
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Completed => "completed",
+            Self::Cancelled => "cancelled",
+            Self::MaxTurns => "turn limit reached",
+            Self::MaxToolCalls => "tool call limit reached",
+            Self::Timeout => "timed out",
+            Self::Budget => "budget exceeded",
+            Self::Error => "error",
+        }
+    }
+
+    pub(crate) fn error() -> Self {
+        Self::Error
+    }
 }
 
 /// Per-turn metadata emitted by the engine at turn completion.
@@ -64,6 +147,41 @@ pub struct AgentToolData {
     pub is_error: bool,
 }
 
+/// Provider-level request credentials beyond the static API key. Travels
+/// with `SetModel` so a model switch picks up the new provider's settings.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProviderAuth {
+    /// Shell command that prints an API key or token.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key_command: Option<String>,
+    /// Extra headers sent with every request.
+    #[serde(skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub headers: std::collections::BTreeMap<String, String>,
+}
+
+/// Capability overrides for one model from config. Unset fields fall back
+/// to the models.dev catalog. Travels with `SetModel` like [`ProviderAuth`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CapabilityOverrides {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_output: Option<u32>,
+    /// Whether the model accepts image input.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vision: Option<bool>,
+    /// Whether the model takes a reasoning effort.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<bool>,
+    /// Whether the model may call several tools in one response.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallel_tools: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_caching: Option<bool>,
+}
+
 /// Model-parameter overrides applied to a single turn.
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -12,6 +12,16 @@ impl App {
     ) {
         if let Some(reply_tx) = self.child_permission_replies.remove(&request_id) {
             let _ = reply_tx.send(engine::socket::PermissionReply { approved, message });
+            for tracked in &mut self.agents {
+                if tracked
+                    .pending_permission
+                    .as_ref()
+                    .is_some_and(|p| p.request_id == request_id)
+                {
+                    tracked.pending_permission = None;
+                }
+            }
+            self.sync_agent_snapshots();
         } else {
             self.engine.send(UiCommand::PermissionDecision {
                 request_id,
@@ -100,7 +110,9 @@ impl App {
         // history or is dispatched to the engine.
         let evaluated = crate::custom_commands::evaluate(&cmd.body);
         let evaluated = if self.settings.redact_secrets {
-            engine::redact::redact(&evaluated)
+            let (redacted, redactions) = engine::redact::redact_report(&evaluated);
+            self.record_redactions("user message", redactions);
+            redacted
         } else {
             evaluated
         };
@@ -267,6 +279,7 @@ impl App {
 
     pub(super) fn finish_turn(&mut self, cancelled: bool) {
         self.sleep_inhibit.release();
+        self.queued_messages.turn_ended();
         if cancelled {
             self.engine.send(UiCommand::Cancel);
             self.kill_blocking_agents();
@@ -289,7 +302,7 @@ impl App {
                 // turn's title generation request. If a slug existed before,
                 // keep it — we're just discarding the in-flight update.
             }
-            let leftover = std::mem::take(&mut self.queued_messages);
+            let leftover = self.queued_messages.take_all();
             if !leftover.is_empty() {
                 let mut combined = leftover.join("\n");
                 if !self.input.buf.is_empty() {
@@ -367,7 +380,7 @@ impl App {
                 background,
             } => {
                 if !background {
-                    if let Some(tokens) = usage.prompt_tokens {
+                    if let Some(tokens) = usage.context_tokens() {
                         if tokens > 0 {
                             self.screen.set_context_tokens(tokens);
                             self.session.context_tokens = Some(tokens);
@@ -381,36 +394,44 @@ impl App {
                 let cost = cost_usd.unwrap_or(0.0);
                 self.session_cost_usd += cost;
                 self.screen.set_session_cost(self.session_cost_usd);
-                crate::metrics::append(&crate::metrics::MetricsEntry {
-                    timestamp_ms: std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap_or_default()
-                        .as_millis() as u64,
-                    prompt_tokens: usage.prompt_tokens.unwrap_or(0),
-                    completion_tokens: usage.completion_tokens.unwrap_or(0),
-                    model: self.model.clone(),
-                    cost_usd,
-                    cache_read_tokens: usage.cache_read_tokens,
-                    cache_write_tokens: usage.cache_write_tokens,
-                    reasoning_tokens: usage.reasoning_tokens,
-                });
+                self.session_usage.accumulate(&usage);
+                self.record_metrics(&usage, cost_usd);
                 SessionControl::Continue
             }
             EngineEvent::ToolOutput { call_id, chunk } => {
                 self.screen.append_active_output(&call_id, &chunk);
                 SessionControl::Continue
             }
-            EngineEvent::Steered { text, count } => {
+            EngineEvent::BudgetWarning {
+                scope,
+                spent_usd,
+                limit_usd,
+            } => {
+                self.notify_budget_warning(scope, spent_usd, limit_usd);
+                SessionControl::Continue
+            }
+            EngineEvent::BudgetExceeded {
+                scope,
+                spent_usd,
+                limit_usd,
+            } => {
+                self.screen
+                    .notify_error(budget_exceeded_message(scope, spent_usd, limit_usd));
+                self.stop_agents_over_budget();
+                SessionControl::Continue
+            }
+            EngineEvent::Redacted { source, redactions } => {
+                self.record_redactions(&source, redactions);
+                SessionControl::Continue
+            }
+            EngineEvent::Steered { text, id, .. } => {
                 self.screen.flush_streaming_thinking();
                 self.screen.flush_streaming_text();
-                let drain_n = count.min(self.queued_messages.len());
-                self.queued_messages.drain(..drain_n);
-                if drain_n > 0 {
-                    self.screen.push(Block::User {
-                        text,
-                        image_labels: vec![],
-                    });
-                }
+                self.queued_messages.consumed(id);
+                self.screen.push(Block::User {
+                    text,
+                    image_labels: vec![],
+                });
                 SessionControl::Continue
             }
             EngineEvent::ThinkingDelta { delta } => {
@@ -593,15 +614,21 @@ impl App {
                 turn_id: id,
                 messages,
                 meta,
+                reason,
             } => {
                 if id != turn_id {
                     return SessionControl::Continue;
                 }
                 self.set_history(messages);
                 self.pending_turn_meta = meta;
+                // Budget stops already surfaced through `BudgetExceeded`.
+                if reason.is_limit() && reason != protocol::StopReason::Budget {
+                    self.screen
+                        .notify_error(format!("turn stopped: {}", reason.label()));
+                }
                 SessionControl::Done
             }
-            EngineEvent::TurnError { message } => {
+            EngineEvent::TurnError { message, .. } => {
                 self.screen.set_throbber(render::Throbber::Done);
                 self.screen.notify_error(message);
                 SessionControl::Done
@@ -681,10 +708,20 @@ impl App {
             EngineEvent::ProcessCompleted { id, exit_code } => {
                 self.handle_process_completed(id, exit_code);
             }
-            EngineEvent::TurnError { message } => {
+            EngineEvent::TurnError { message, .. } => {
                 self.screen.set_throbber(render::Throbber::Done);
                 self.screen.notify_error(message);
             }
+            EngineEvent::BudgetWarning {
+                scope,
+                spent_usd,
+                limit_usd,
+            } => {
+                self.notify_budget_warning(scope, spent_usd, limit_usd);
+            }
+            EngineEvent::Redacted { source, redactions } => {
+                self.record_redactions(&source, redactions);
+            }
             EngineEvent::AgentExited {
                 agent_id,
                 exit_code,
@@ -716,6 +753,15 @@ impl App {
         }
     }
 
+    fn notify_budget_warning(&mut self, scope: protocol::BudgetScope, spent: f64, limit: f64) {
+        self.screen.notify(format!(
+            "{} budget: {} of {} spent",
+            scope.label(),
+            crate::metrics::format_cost(spent),
+            crate::metrics::format_cost(limit),
+        ));
+    }
+
     fn handle_title_generated(&mut self, title: String, slug: String) {
         if !self.pending_title {
             return;
@@ -819,6 +865,24 @@ impl App {
         self.screen.cancel_active_agents();
     }
 
+    /// Kill every working subagent once a budget is exhausted; their
+    /// spend counts against this session. Returns how many were stopped.
+    fn stop_agents_over_budget(&mut self) -> usize {
+        let mut stopped = 0;
+        for agent in &mut self.agents {
+            if agent.status == super::AgentTrackStatus::Working {
+                engine::registry::kill_agent(agent.pid);
+                agent.status = super::AgentTrackStatus::Error;
+                stopped += 1;
+            }
+        }
+        if stopped > 0 {
+            self.screen.cancel_active_agents();
+            self.sync_agent_snapshots();
+        }
+        stopped
+    }
+
     pub(super) fn refresh_agent_counts(&mut self) {
         self.screen.set_agent_count(self.agents.len());
     }
@@ -870,10 +934,13 @@ impl App {
                 slug: None,
                 event_rx,
                 tool_calls: Vec::new(),
+                transcript: Default::default(),
+                pending_permission: None,
                 status: super::AgentTrackStatus::Working,
                 blocking: child.blocking,
                 started_at: Instant::now(),
-                context_tokens: None,
+                input_tokens: 0,
+                output_tokens: 0,
                 cost_usd: 0.0,
             });
         }
@@ -884,11 +951,26 @@ impl App {
     pub(super) fn drain_agent_events(&mut self) {
         let mut changed = false;
         let mut session_cost_delta = 0.0;
+        let mut budget_crossed = Vec::new();
 
         for agent in &mut self.agents {
             while let Ok(ev) = agent.event_rx.try_recv() {
                 changed = true;
                 match ev {
+                    EngineEvent::TextDelta { delta } => {
+                        agent.push_transcript_text(&delta, true);
+                    }
+                    EngineEvent::Text { content } => {
+                        agent.push_transcript_text(&content, false);
+                    }
+                    EngineEvent::AgentMessage {
+                        from_id, message, ..
+                    } => {
+                        agent.push_transcript(super::AgentTranscriptEntry::Message {
+                            from: from_id,
+                            text: message,
+                        });
+                    }
                     EngineEvent::ToolStarted {
                         call_id,
                         tool_name,
@@ -896,28 +978,44 @@ impl App {
                         ..
                     } => {
                         agent.status = super::AgentTrackStatus::Working;
-                        agent.tool_calls.push(super::AgentToolEntry {
+                        let entry = super::AgentToolEntry {
                             call_id,
                             tool_name,
                             summary,
                             status: ToolStatus::Pending,
                             elapsed: None,
-                        });
+                        };
+                        agent.push_transcript(super::AgentTranscriptEntry::Tool(entry.clone()));
+                        agent.tool_calls.push(entry);
                     }
                     EngineEvent::ToolFinished {
                         call_id,
                         result,
                         elapsed_ms,
                     } => {
-                        if let Some(entry) =
-                            agent.tool_calls.iter_mut().find(|t| t.call_id == call_id)
+                        let status = if result.is_error {
+                            ToolStatus::Err
+                        } else {
+                            ToolStatus::Ok
+                        };
+                        let elapsed = elapsed_ms.map(Duration::from_millis);
+                        let transcript_entry = std::sync::Arc::make_mut(&mut agent.transcript)
+                            .iter_mut()
+                            .rev()
+                            .find_map(|e| match e {
+                                super::AgentTranscriptEntry::Tool(t) if t.call_id == call_id => {
+                                    Some(t)
+                                }
+                                _ => None,
+                            });
+                        for entry in agent
+                            .tool_calls
+                            .iter_mut()
+                            .filter(|t| t.call_id == call_id)
+                            .chain(transcript_entry)
                         {
-                            entry.status = if result.is_error {
-                                ToolStatus::Err
-                            } else {
-                                ToolStatus::Ok
-                            };
-                            entry.elapsed = elapsed_ms.map(Duration::from_millis);
+                            entry.status = status;
+                            entry.elapsed = elapsed;
                         }
                     }
                     EngineEvent::TitleGenerated { slug, .. } => {
@@ -927,21 +1025,20 @@ impl App {
                         agent.status = super::AgentTrackStatus::Idle;
                     }
                     EngineEvent::TokenUsage {
-                        cost_usd,
-                        usage,
-                        background,
-                        ..
+                        cost_usd, usage, ..
                     } => {
                         let cost = cost_usd.unwrap_or(0.0);
                         agent.cost_usd += cost;
                         session_cost_delta += cost;
-                        if !background {
-                            if let Some(tokens) = usage.prompt_tokens {
-                                if tokens > 0 {
-                                    agent.context_tokens = Some(tokens);
-                                }
-                            }
-                        }
+                        // Subagents run in their own engine; count their
+                        // spend against this session's budget too.
+                        budget_crossed.extend(self.engine.budget.record(cost));
+                        agent.input_tokens = agent
+                            .input_tokens
+                            .saturating_add(usage.context_tokens().unwrap_or(0));
+                        agent.output_tokens = agent
+                            .output_tokens
+                            .saturating_add(usage.completion_tokens.unwrap_or(0));
                     }
                     EngineEvent::TurnError { .. } => {
                         agent.status = super::AgentTrackStatus::Error;
@@ -955,6 +1052,21 @@ impl App {
             self.session_cost_usd += session_cost_delta;
             self.screen.set_session_cost(self.session_cost_usd);
         }
+        for crossed in budget_crossed {
+            self.notify_budget_warning(crossed.scope, crossed.spent_usd, crossed.limit_usd);
+        }
+        if session_cost_delta > 0.0 {
+            if let Err(hit) = self.engine.budget.check() {
+                if self.stop_agents_over_budget() > 0 {
+                    self.screen.notify_error(format!(
+                        "{} budget exceeded ({} of {}), subagents stopped",
+                        hit.scope.label(),
+                        crate::metrics::format_cost(hit.spent_usd),
+                        crate::metrics::format_cost(hit.limit_usd),
+                    ));
+                }
+            }
+        }
 
         if !changed {
             return;
@@ -982,19 +1094,39 @@ impl App {
     }
 
     /// Update the shared snapshots so the /agents dialog sees live data.
-    fn sync_agent_snapshots(&self) {
+    pub(super) fn sync_agent_snapshots(&self) {
         let snaps: Vec<render::AgentSnapshot> = self
             .agents
             .iter()
             .map(|a| render::AgentSnapshot {
                 agent_id: a.agent_id.clone(),
                 prompt: a.prompt.clone(),
-                tool_calls: a.tool_calls.clone(),
-                context_tokens: a.context_tokens,
+                transcript: a.transcript.clone(),
+                permission: a.pending_permission.clone(),
+                input_tokens: a.input_tokens,
+                output_tokens: a.output_tokens,
                 cost_usd: a.cost_usd,
             })
             .collect();
-        *self.agent_snapshots.lock().unwrap() = snaps;
+        let mut shared = self.agent_snapshots.lock().unwrap();
+        shared.agents = snaps;
+        shared.version += 1;
+    }
+
+    /// Send a message from the /agents dashboard to a subagent. Delivery
+    /// is fire-and-forget, like `message_agent`.
+    pub(super) fn message_agent(&mut self, agent_id: &str, message: String) {
+        let Some(entry) = engine::registry::find_by_id(agent_id) else {
+            self.screen.notify_error(format!("{agent_id}: not found"));
+            return;
+        };
+        let endpoint = entry.endpoint();
+        let from_id = self.agent_id.clone();
+        let from_slug = self.session.slug.clone().unwrap_or_default();
+        tokio::spawn(async move {
+            let _ =
+                engine::socket::send_message(&endpoint, &from_id, &from_slug, &message, None).await;
+        });
     }
 
     fn handle_process_completed(&mut self, id: String, exit_code: Option<i32>) {
@@ -1097,6 +1229,15 @@ impl App {
             render::DialogResult::AgentsClosed => {
                 self.refresh_agent_counts();
             }
+            render::DialogResult::DiffClosed { reverted } => match reverted.as_slice() {
+                [] => {}
+                [one] => self.screen.notify(format!("reverted {one}")),
+                many => self.screen.notify(format!("reverted {} files", many.len())),
+            },
+            render::DialogResult::QueueClosed { items } => {
+                self.queued_messages.replace(items);
+                self.screen.mark_dirty();
+            }
             render::DialogResult::PsClosed | render::DialogResult::Dismissed => {}
         }
     }
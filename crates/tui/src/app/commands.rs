@@ -77,6 +77,7 @@ impl App {
                     CommandAction::OpenDialog(Box::new(render::AgentsDialog::new(
                         my_pid,
                         self.agent_snapshots.clone(),
+                        self.agent_action_tx.clone(),
                         self.input.vim_enabled(),
                     )))
                 }
@@ -91,16 +92,53 @@ impl App {
                     )))
                 }
             }
+            "/diff" => {
+                if self.engine.changes.list().is_empty() {
+                    self.screen
+                        .notify_error("no file changes this session".into());
+                    CommandAction::Continue
+                } else {
+                    CommandAction::OpenDialog(Box::new(render::DiffDialog::new(
+                        self.engine.changes.clone(),
+                        self.input.vim_enabled(),
+                    )))
+                }
+            }
+            "/queue" => {
+                if self.queued_messages.is_empty() {
+                    self.screen.notify_error("no queued messages".into());
+                    CommandAction::Continue
+                } else {
+                    CommandAction::OpenDialog(Box::new(render::QueueDialog::new(
+                        self.queued_messages.items().to_vec(),
+                        self.input.vim_enabled(),
+                    )))
+                }
+            }
             "/permissions" => {
                 let session_entries = self.session_permission_entries();
                 let workspace_rules = crate::workspace_permissions::load(&self.cwd);
-                if session_entries.is_empty() && workspace_rules.is_empty() {
+                let path_rules: Vec<String> = self
+                    .permissions
+                    .path_rules(self.mode)
+                    .into_iter()
+                    .map(|(decision, pattern)| format!("{}: {pattern}", decision.label()))
+                    .collect();
+                let managed = self.permissions.policy().summary();
+                if session_entries.is_empty()
+                    && workspace_rules.is_empty()
+                    && path_rules.is_empty()
+                    && managed.is_empty()
+                {
                     self.screen.notify_error("no permissions".into());
                     CommandAction::Continue
                 } else {
                     CommandAction::OpenDialog(Box::new(render::PermissionsDialog::new(
                         session_entries,
                         workspace_rules,
+                        path_rules,
+                        managed,
+                        self.mode.as_str(),
                         self.input.vim_enabled(),
                     )))
                 }
@@ -164,16 +202,30 @@ impl App {
                     self.session_cost_usd,
                     &self.model,
                     turns,
+                    &self.session_usage,
                     &resolved,
                 );
                 self.input.open_cost(lines);
                 self.screen.mark_dirty();
                 CommandAction::Continue
             }
+            "/redactions" => {
+                if self.redactions.is_empty() {
+                    self.screen
+                        .notify_error("nothing redacted this session".into());
+                } else {
+                    let lines = crate::metrics::render_redactions(&self.redactions);
+                    self.input.open_redactions(lines);
+                    self.screen.mark_dirty();
+                }
+                CommandAction::Continue
+            }
             _ if input.starts_with("/theme ") => {
                 let name = input.strip_prefix("/theme ").unwrap().trim();
                 if let Some(value) = crate::theme::preset_by_name(name) {
                     self.apply_accent(value);
+                } else if crate::theme::theme_file_names().iter().any(|n| n == name) {
+                    self.apply_theme_file(name);
                 } else {
                     self.screen.notify_error(format!("unknown theme: {}", name));
                 }
@@ -373,6 +425,16 @@ impl App {
         Some((rx, kill))
     }
 
+    /// Apply the current model's capabilities to the input and the context
+    /// display.
+    pub fn sync_capabilities(&mut self) {
+        let caps =
+            engine::capabilities::resolve(&self.model, &self.provider_type, &self.model_config);
+        self.input.accepts_images = caps.vision;
+        self.context_window = self.context_window_override.or(caps.context_window);
+        self.screen.set_context_window(self.context_window);
+    }
+
     /// Switch to a model by key, updating all relevant state. Silently does
     /// nothing if the key is not found.
     pub(super) fn apply_model(&mut self, key: &str) {
@@ -385,6 +447,7 @@ impl App {
         self.provider_type = resolved.provider_type.clone();
         self.model_config = (&resolved.config).into();
         self.screen.set_model_label(self.model.clone());
+        self.sync_capabilities();
         let api_key = self.resolve_api_key().unwrap_or_default();
         state::set_selected_model(resolved.key.clone());
         self.engine.send(UiCommand::SetModel {
@@ -392,6 +455,8 @@ impl App {
             api_base: self.api_base.clone(),
             api_key,
             provider_type: self.provider_type.clone(),
+            auth: self.model_config.auth.clone(),
+            capabilities: self.model_config.capabilities,
         });
     }
 
@@ -414,7 +479,16 @@ impl App {
     /// the command handlers.
     pub(super) fn update_settings<F: FnOnce(&mut state::ResolvedSettings)>(&mut self, f: F) {
         let prev_show_thinking = self.settings.show_thinking;
+        let prev_mouse = self.settings.mouse;
         f(&mut self.settings);
+        if self.permissions.policy().redact_secrets && !self.settings.redact_secrets {
+            self.settings.redact_secrets = true;
+            self.screen
+                .notify_error("redact_secrets is enforced by the managed policy".into());
+        }
+        if self.settings.mouse != prev_mouse {
+            super::set_mouse_capture(self.settings.mouse);
+        }
         self.input.set_vim_enabled(self.settings.vim);
         self.screen.apply_settings(&self.settings);
         state::save_settings(&self.settings);
@@ -432,8 +506,16 @@ impl App {
     }
 
     /// Set the agent mode, persist it, and notify the engine. Marks the
-    /// screen dirty so the mode indicator refreshes.
+    /// screen dirty so the mode indicator refreshes. Modes disabled by the
+    /// managed policy are refused.
     pub(super) fn set_mode(&mut self, mode: Mode) {
+        if self.permissions.policy().disables(mode) {
+            self.screen.notify_error(format!(
+                "{} mode is disabled by the managed policy",
+                mode.as_str()
+            ));
+            return;
+        }
         self.mode = mode;
         state::set_mode(self.mode);
         self.engine.send(UiCommand::SetMode { mode: self.mode });
@@ -458,10 +540,33 @@ impl App {
     }
 
     /// Apply an accent color: update the global theme, persist, and redraw.
+    /// Picking a preset drops any active theme file.
     pub(super) fn apply_accent(&mut self, value: u8) {
+        let had_palette = crate::theme::palette().is_some();
+        crate::theme::set_palette(None);
         crate::theme::set_accent(value);
         state::set_accent(value);
-        self.screen.redraw();
+        state::set_theme(None);
+        if had_palette {
+            self.screen.redraw_with_new_syntax();
+        } else {
+            self.screen.redraw();
+        }
+    }
+
+    /// Load and apply a theme file from the themes directory, persist the
+    /// choice, and re-lay out history under its syntax theme.
+    pub(super) fn apply_theme_file(&mut self, name: &str) {
+        match crate::theme::load_palette(name) {
+            Ok(palette) => {
+                crate::theme::set_palette(Some(std::sync::Arc::new(palette)));
+                state::set_theme(Some(name.to_string()));
+                self.screen.redraw_with_new_syntax();
+            }
+            Err(e) => {
+                self.screen.notify_error(format!("theme {name}: {e}"));
+            }
+        }
     }
 
     pub(super) fn export_to_clipboard(&mut self) {
@@ -1,7 +1,10 @@
 use super::*;
 
-use crate::keymap::{self, KeyAction};
-use crossterm::{event::Event, terminal};
+use crate::keymap::{self, nav_lookup, KeyAction, NavAction};
+use crossterm::{
+    event::{Event, MouseButton, MouseEvent, MouseEventKind},
+    terminal,
+};
 use std::time::{Duration, Instant};
 
 /// Coalesce-window for repeated `Action::PurgeRedraw` (Ctrl+L) presses.
@@ -10,6 +13,9 @@ use std::time::{Duration, Instant};
 /// time to drift.
 const PURGE_REDRAW_DEBOUNCE: Duration = Duration::from_millis(10);
 
+/// Rows scrolled per mouse wheel notch in the history and `/btw` views.
+const WHEEL_ROWS: isize = 3;
+
 impl App {
     /// Run a Ctrl+L purge+redraw, suppressing repeats inside the
     /// debounce window so a held key or rapid double-press only fires
@@ -45,6 +51,24 @@ impl App {
             return false;
         }
 
+        if let Event::Mouse(me) = ev {
+            self.handle_mouse(me, agent, active_dialog);
+            return false;
+        }
+        if let Event::Key(KeyEvent {
+            code, modifiers, ..
+        }) = ev
+        {
+            if self.screen.block_selecting() {
+                self.handle_block_select_key(code, modifiers);
+                return false;
+            }
+        }
+        // Typing returns a paged-back history view to the live tail.
+        if matches!(ev, Event::Key(_)) {
+            self.screen.leave_history_scroll();
+        }
+
         // Route events to the active dialog if one is showing.
         if active_dialog.is_some() {
             // Terminal resize: full clear + redraw screen + redraw dialog.
@@ -92,6 +116,7 @@ impl App {
                     return false;
                 }
                 let mut d = active_dialog.take().unwrap();
+                d.set_agent_running(agent.is_some());
                 if let Some(result) = d.handle_key(code, modifiers) {
                     // Sync kill ring back from dialog.
                     if let Some(kr) = d.kill_ring() {
@@ -163,6 +188,7 @@ impl App {
                     *agent = None;
                 }
                 self.queued_messages = remaining;
+                self.queued_messages.turn_ended();
                 false
             }
             EventOutcome::CancelAndClear => {
@@ -181,7 +207,10 @@ impl App {
                 match result {
                     MenuResult::Settings(ref s) => {
                         self.apply_settings_result(s);
-                        let items = crate::completer::Completer::settings_items(s);
+                        // Rebuilt from the applied settings, which the managed
+                        // policy may have overridden.
+                        let items =
+                            crate::completer::Completer::settings_items(&self.settings_state());
                         if let Some(comp) = self.input.completer.as_mut() {
                             if comp.kind == crate::completer::CompleterKind::Settings {
                                 comp.refresh_items(items);
@@ -199,10 +228,16 @@ impl App {
                         // state.
                         self.apply_accent(value);
                     }
+                    MenuResult::ThemeFileSelect(ref name) => {
+                        self.apply_theme_file(name);
+                    }
                     MenuResult::ColorSelect(_) => {
                         self.screen.redraw();
                     }
-                    MenuResult::Stats | MenuResult::Cost | MenuResult::Dismissed => {}
+                    MenuResult::Stats
+                    | MenuResult::Cost
+                    | MenuResult::Redactions
+                    | MenuResult::Dismissed => {}
                 }
                 let is_settings = matches!(&result, MenuResult::Settings(_));
                 if !is_settings {
@@ -233,7 +268,7 @@ impl App {
                 if self.is_compacting() {
                     let text = content.text_content();
                     if !text.is_empty() {
-                        self.queued_messages.push(text);
+                        self.queued_messages.push(text, false);
                         self.screen.erase_prompt();
                         self.screen.mark_dirty();
                     }
@@ -257,10 +292,9 @@ impl App {
                         ) {
                             return true;
                         }
-                    } else if !self.queued_messages.is_empty() {
+                    } else if let Some(queued) = self.queued_messages.pop_front() {
                         // Empty submit with queued messages: pop and send the
                         // oldest one immediately.
-                        let queued = self.queued_messages.remove(0);
                         if let Some(cmd) =
                             crate::custom_commands::resolve(queued.trim(), self.multi_agent)
                         {
@@ -422,6 +456,10 @@ impl App {
                                 self.input.vim_enabled(),
                             )));
                         }
+                        KeyAction::SelectBlock => {
+                            self.enter_block_select();
+                            return EventOutcome::Redraw;
+                        }
                         KeyAction::OpenHistorySearch => {
                             if self.input.history_search_query().is_none() {
                                 self.input.open_history_search(&self.input_history);
@@ -502,6 +540,10 @@ impl App {
                         self.screen.mark_dirty();
                         return EventOutcome::Noop;
                     }
+                    KeyAction::SelectBlock => {
+                        self.enter_block_select();
+                        return EventOutcome::Noop;
+                    }
                     _ => {
                         // Other keymap actions — continue to Esc / input handling.
                     }
@@ -533,14 +575,13 @@ impl App {
                     self.screen.mark_dirty();
                 }
                 EscAction::Unqueue => {
-                    let mut combined = self.queued_messages.join("\n");
+                    let mut combined = self.queued_messages.take_all().join("\n");
                     if !self.input.buf.is_empty() {
                         combined.push('\n');
                         combined.push_str(&self.input.buf);
                     }
                     self.input.buf = combined;
                     self.input.cpos = self.input.buf.len();
-                    self.queued_messages.clear();
                     self.screen.mark_dirty();
                 }
                 EscAction::Cancel { restore_vim } => {
@@ -572,7 +613,7 @@ impl App {
                     return outcome;
                 }
                 if !text.is_empty() {
-                    self.queued_messages.push(text);
+                    self.queued_messages.push(text, false);
                 }
                 self.screen.mark_dirty();
             }
@@ -677,11 +718,17 @@ impl App {
 
         // Suspend raw mode so the editor gets a normal terminal.
         terminal::disable_raw_mode().ok();
+        if self.settings.mouse {
+            set_mouse_capture(false);
+        }
 
         let status = std::process::Command::new(&editor).arg(tmp.path()).status();
 
         // Resume raw mode.
         terminal::enable_raw_mode().ok();
+        if self.settings.mouse {
+            set_mouse_capture(true);
+        }
 
         match status {
             Ok(s) if s.success() => match std::fs::read_to_string(tmp.path()) {
@@ -702,6 +749,79 @@ impl App {
         }
     }
 
+    /// Enter block select mode, or say why not.
+    fn enter_block_select(&mut self) {
+        if !self.screen.enter_block_select() {
+            self.screen.notify("no blocks to expand".into());
+        }
+    }
+
+    /// Keys while block select mode highlights a block: move between
+    /// expandable blocks, toggle the highlighted one, or leave.
+    fn handle_block_select_key(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        if code == KeyCode::Char('o') && modifiers == KeyModifiers::CONTROL {
+            self.screen.leave_block_select();
+            return;
+        }
+        if code == KeyCode::Char(' ') {
+            self.screen.toggle_selected_block();
+            return;
+        }
+        match nav_lookup(code, modifiers) {
+            Some(NavAction::Up) => self.screen.move_block_select(true),
+            Some(NavAction::Down) => self.screen.move_block_select(false),
+            Some(NavAction::Confirm | NavAction::Edit) => self.screen.toggle_selected_block(),
+            Some(NavAction::Dismiss) => self.screen.leave_block_select(),
+            Some(NavAction::PageUp) => self.screen.page_history(true),
+            Some(NavAction::PageDown) => self.screen.page_history(false),
+            None => {}
+        }
+    }
+
+    /// Mouse input, only delivered while the `mouse` setting has capture
+    /// on. Shift-modified events are ignored: terminals reserve
+    /// shift+drag for native text selection while an app owns the mouse.
+    fn handle_mouse(
+        &mut self,
+        ev: MouseEvent,
+        agent: &mut Option<TurnState>,
+        active_dialog: &mut Option<Box<dyn render::Dialog>>,
+    ) {
+        if ev.modifiers.contains(KeyModifiers::SHIFT) {
+            return;
+        }
+        match ev.kind {
+            MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => {
+                let rows = if ev.kind == MouseEventKind::ScrollUp {
+                    -WHEEL_ROWS
+                } else {
+                    WHEEL_ROWS
+                };
+                if let Some(mut d) = active_dialog.take() {
+                    if let Some(result) = d.handle_scroll(rows.signum()) {
+                        self.handle_dialog_result(result, agent);
+                        self.input.restore_stash();
+                    } else {
+                        *active_dialog = Some(d);
+                    }
+                } else if self.screen.has_btw() {
+                    self.screen.btw_scroll(rows);
+                } else {
+                    self.screen.scroll_history(-rows);
+                }
+            }
+            MouseEventKind::Down(MouseButton::Left) if active_dialog.is_none() => {
+                if let Some(pos) = self.screen.prompt_offset_at(&self.input, ev.column, ev.row) {
+                    self.input.cpos = pos;
+                    self.screen.mark_dirty();
+                } else {
+                    self.screen.toggle_block_at_row(ev.row);
+                }
+            }
+            _ => {}
+        }
+    }
+
     fn handle_resize(&mut self, w: u16, h: u16) {
         if w == self.last_width && h == self.last_height {
             return;
@@ -876,8 +996,13 @@ impl App {
         let show_queued = agent_running || self.is_compacting();
         self.screen.set_dialog_open(false);
 
+        let queued_texts = if show_queued {
+            self.queued_messages.texts()
+        } else {
+            Vec::new()
+        };
         let (queued, prediction): (&[String], Option<&str>) = if show_queued {
-            (&self.queued_messages, None)
+            (&queued_texts, None)
         } else {
             (&[], self.input_prediction.as_deref())
         };
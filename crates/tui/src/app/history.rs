@@ -7,13 +7,19 @@ impl App {
     /// in history. The `display` string is the rendered form of the submitted
     /// message; `content` is what gets sent to the engine. Both are scrubbed
     /// so the UI and the LLM see the same redacted form.
-    pub(super) fn redact_user_submission(&self, content: &mut Content, display: &mut String) {
+    pub(super) fn redact_user_submission(&mut self, content: &mut Content, display: &mut String) {
         if self.settings.redact_secrets {
-            engine::redact::redact_content(content);
+            let redactions = engine::redact::redact_content(content);
+            self.record_redactions("user message", redactions);
             *display = engine::redact::redact(display);
         }
     }
 
+    pub(super) fn record_redactions(&mut self, source: &str, redactions: Vec<protocol::Redaction>) {
+        self.redactions
+            .extend(redactions.into_iter().map(|r| (source.to_string(), r)));
+    }
+
     fn reset_subagents_for_new_session(&mut self) {
         let my_pid = std::process::id();
         engine::registry::kill_descendants(my_pid);
@@ -85,14 +91,18 @@ impl App {
         self.clear_snapshots();
         self.pending_agent_blocks.clear();
         self.reset_session_permissions();
+        self.redactions.clear();
+        engine::redact::forget_secrets();
         self.queued_messages.clear();
         self.screen.clear();
         self.input.clear();
         self.input.store.clear();
         self.engine.processes.clear();
+        self.engine.changes.clear();
         self.reset_subagents_for_new_session();
         self.session = session::Session::new();
         self.screen.set_session_cost(0.0);
+        self.engine.budget.reset_session(0.0);
         self.pending_title = false;
         self.compact_epoch += 1;
         if let Ok(mut guard) = self.shared_session.lock() {
@@ -140,7 +150,10 @@ impl App {
         self.history = self.session.messages.clone();
         self.restore_snapshots_from_session();
         self.screen.set_session_cost(self.session_cost_usd);
+        self.engine.budget.reset_session(self.session_cost_usd);
         self.reset_session_permissions();
+        self.redactions.clear();
+        engine::redact::forget_secrets();
         self.queued_messages.clear();
         self.input.clear();
         self.input.store.clear();
@@ -537,8 +550,10 @@ impl App {
         // valid, but the running cost carries forward.
         self.history = messages;
         let carried_cost = self.session_cost_usd;
+        let carried_usage = std::mem::take(&mut self.session_usage);
         self.clear_snapshots();
         self.session_cost_usd = carried_cost;
+        self.session_usage = carried_usage;
 
         self.restore_screen();
         self.screen.clear_context_tokens();
@@ -639,6 +654,7 @@ impl App {
         self.cost_snapshots.clear();
         self.turn_metas.clear();
         self.session_cost_usd = 0.0;
+        self.session_usage = protocol::TokenUsage::default();
     }
 
     pub(super) fn truncate_snapshots_to(&mut self, hist_idx: usize) {
@@ -662,5 +678,6 @@ impl App {
         self.cost_snapshots.retain(|(len, _)| *len <= hist_len);
         self.turn_metas = self.session.turn_metas.clone();
         self.session_cost_usd = self.cost_snapshots.last().map(|&(_, c)| c).unwrap_or(0.0);
+        self.session_usage = protocol::TokenUsage::default();
     }
 }
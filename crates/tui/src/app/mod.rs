@@ -12,13 +12,13 @@ use crate::render::{
 use crate::session::Session;
 use crate::{render, session, state, vim};
 use engine::{permissions::Decision, EngineHandle, Permissions};
-use protocol::{Content, EngineEvent, Message, Mode, ReasoningEffort, Role, UiCommand};
+use protocol::{Content, EngineEvent, Message, Mode, ReasoningEffort, Role, StopReason, UiCommand};
 
 use crossterm::{
     cursor,
     event::{
-        self, DisableBracketedPaste, DisableFocusChange, EnableBracketedPaste, EnableFocusChange,
-        EventStream, KeyCode, KeyEvent, KeyModifiers,
+        self, DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste,
+        EnableFocusChange, EnableMouseCapture, EventStream, KeyCode, KeyEvent, KeyModifiers,
     },
     terminal, ExecutableCommand,
 };
@@ -41,6 +41,29 @@ pub struct AgentToolEntry {
     pub elapsed: Option<Duration>,
 }
 
+/// One item of a subagent's live transcript, in arrival order.
+#[derive(Clone)]
+pub enum AgentTranscriptEntry {
+    Text(String),
+    Tool(AgentToolEntry),
+    /// A message the subagent received from another agent.
+    Message {
+        from: String,
+        text: String,
+    },
+}
+
+/// A subagent's permission request that is waiting for an answer.
+#[derive(Clone)]
+pub struct AgentPermission {
+    pub request_id: u64,
+    pub tool_name: String,
+    pub summary: String,
+}
+
+/// Oldest transcript entries are dropped beyond this many.
+const MAX_TRANSCRIPT_ENTRIES: usize = 2000;
+
 /// State for a spawned subagent (blocking or background).
 pub struct TrackedAgent {
     pub agent_id: String,
@@ -48,18 +71,50 @@ pub struct TrackedAgent {
     pub prompt: Arc<String>,
     pub slug: Option<String>,
     pub event_rx: tokio::sync::mpsc::UnboundedReceiver<EngineEvent>,
-    /// Completed tool calls (for /agents dialog and blocking block rendering).
+    /// Completed tool calls (for blocking block rendering).
     pub tool_calls: Vec<AgentToolEntry>,
+    /// Text, tool calls and incoming messages, for the /agents dashboard.
+    /// Shared with the dashboard snapshots; copied only when it changes
+    /// while a snapshot still holds it.
+    pub transcript: Arc<VecDeque<AgentTranscriptEntry>>,
+    /// Permission request waiting for an answer, if any.
+    pub pending_permission: Option<AgentPermission>,
     pub status: AgentTrackStatus,
     /// Whether the parent LLM is waiting for this agent (blocking spawn).
     pub blocking: bool,
     pub started_at: Instant,
-    /// Latest prompt-token count reported for this subagent.
-    pub context_tokens: Option<u32>,
+    /// Input tokens (cached included) summed over this subagent's requests.
+    pub input_tokens: u32,
+    /// Output tokens summed over this subagent's requests.
+    pub output_tokens: u32,
     /// Accumulated cost in USD from this subagent's TokenUsage events.
     pub cost_usd: f64,
 }
 
+impl TrackedAgent {
+    fn push_transcript(&mut self, entry: AgentTranscriptEntry) {
+        let transcript = Arc::make_mut(&mut self.transcript);
+        if transcript.len() >= MAX_TRANSCRIPT_ENTRIES {
+            transcript.pop_front();
+        }
+        transcript.push_back(entry);
+    }
+
+    /// Append text, extending the last entry when it is text and `continues`
+    /// is set (streamed deltas).
+    fn push_transcript_text(&mut self, text: &str, continues: bool) {
+        if continues {
+            if let Some(AgentTranscriptEntry::Text(last)) =
+                Arc::make_mut(&mut self.transcript).back_mut()
+            {
+                last.push_str(text);
+                return;
+            }
+        }
+        self.push_transcript(AgentTranscriptEntry::Text(text.to_string()));
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AgentTrackStatus {
     Working,
@@ -84,7 +139,7 @@ pub struct App {
     pub input: InputState,
     exec_rx: Option<tokio::sync::mpsc::UnboundedReceiver<commands::ExecEvent>>,
     exec_kill: Option<std::sync::Arc<tokio::sync::Notify>>,
-    pub queued_messages: Vec<String>,
+    pub queued_messages: crate::queue::MessageQueue,
     /// Agent messages waiting to trigger a turn.
     pending_agent_messages: Vec<protocol::Message>,
     /// Runtime approvals shared with the engine. The engine checks these
@@ -96,14 +151,21 @@ pub struct App {
     pub session: session::Session,
     pub shared_session: Arc<Mutex<Option<Session>>>,
     pub context_window: Option<u32>,
+    /// `settings.context_window` from config; outranks the model's own.
+    pub context_window_override: Option<u32>,
     pub settings: state::ResolvedSettings,
     pub multi_agent: bool,
     /// Human-readable name for this agent.
     pub agent_id: String,
+    /// Spawn depth: 0 for the main agent, 1+ for subagents.
+    pub agent_depth: u8,
     /// All tracked subagents (blocking and background).
     pub agents: Vec<TrackedAgent>,
     /// Shared agent snapshots for live dialog updates.
     pub agent_snapshots: render::SharedSnapshots,
+    /// Messages and permission answers from the /agents dashboard.
+    agent_action_tx: tokio::sync::mpsc::UnboundedSender<render::AgentAction>,
+    agent_action_rx: tokio::sync::mpsc::UnboundedReceiver<render::AgentAction>,
     pub available_models: Vec<crate::config::ResolvedModel>,
     pub engine: EngineHandle,
     permissions: Arc<Permissions>,
@@ -146,6 +208,11 @@ pub struct App {
     pending_agent_blocks: Vec<(String, protocol::AgentBlockData)>,
     /// Accumulated cost for the current session in USD.
     pub session_cost_usd: f64,
+    /// Token usage accumulated over the session, for cache statistics.
+    pub session_usage: protocol::TokenUsage,
+    /// Secrets redacted this session, with where each came from. Shown by
+    /// `/redactions`.
+    pub redactions: Vec<(String, protocol::Redaction)>,
     /// Active model config (for pricing lookups).
     pub model_config: engine::ModelConfig,
     /// Whether model was explicitly provided via CLI (takes precedence over session).
@@ -322,7 +389,7 @@ const CONFIRM_DEFER_MS: u64 = 1500;
 
 /// Relay a permission check to a parent socket and return the result.
 async fn relay_permission(
-    parent_socket: Option<&std::path::Path>,
+    parent_socket: Option<&engine::socket::Endpoint>,
     from_id: &str,
     tool_name: &str,
     args: &HashMap<String, serde_json::Value>,
@@ -352,6 +419,37 @@ async fn relay_permission(
 static NEXT_CHILD_REQUEST_ID: std::sync::atomic::AtomicU64 =
     std::sync::atomic::AtomicU64::new(1_000_000_000);
 
+/// A structured answer another agent asked a subagent for.
+struct StructuredRequest {
+    schema: serde_json::Value,
+    /// Agent to send the answer to; `None` for the parent.
+    reply_to: Option<String>,
+}
+
+/// A structured request that arrived while a turn was running. It is
+/// answered in its own turn once the current one ends.
+struct QueuedRequest {
+    from_id: String,
+    from_slug: String,
+    /// The message with the schema instruction appended.
+    message: String,
+    schema: serde_json::Value,
+}
+
+/// Most structured requests waiting behind a running turn; further ones are
+/// refused at once.
+const MAX_QUEUED_REQUESTS: usize = 8;
+
+/// Tell agent `to` that its structured request will not be answered, so it
+/// does not wait out its timeout.
+async fn refuse_request(to: &str, my_agent_id: &str, slug: &str, reason: &str) {
+    if let Some(entry) = engine::registry::find_by_id(to) {
+        let text = format!("error: {reason}");
+        let _ =
+            engine::socket::send_message(&entry.endpoint(), my_agent_id, slug, &text, None).await;
+    }
+}
+
 /// A permission dialog deferred because the user was actively typing.
 enum DeferredDialog {
     Confirm(ConfirmRequest),
@@ -413,15 +511,17 @@ impl App {
         if settings.vim {
             input.set_vim_enabled(true);
         }
-        let theme_names: Vec<String> = crate::theme::PRESETS
+        let preset_names: Vec<String> = crate::theme::PRESETS
             .iter()
             .map(|(n, _, _)| (*n).to_string())
             .collect();
+        let mut theme_names = preset_names.clone();
+        theme_names.extend(crate::theme::theme_file_names());
         let model_keys: Vec<String> = available_models.iter().map(|m| m.key.clone()).collect();
         input.command_arg_sources = vec![
             ("/model".into(), model_keys),
-            ("/theme".into(), theme_names.clone()),
-            ("/color".into(), theme_names),
+            ("/theme".into(), theme_names),
+            ("/color".into(), preset_names),
         ];
         // Only load accent from state if not already set from config
         if crate::theme::accent_value() == crate::theme::DEFAULT_ACCENT {
@@ -429,6 +529,17 @@ impl App {
                 crate::theme::set_accent(accent);
             }
         }
+        // Same for the theme file; a missing or broken file falls back to
+        // the accent preset.
+        if crate::theme::palette().is_none() {
+            if let Some(palette) = saved
+                .theme
+                .as_deref()
+                .and_then(|name| crate::theme::load_palette(name).ok())
+            {
+                crate::theme::set_palette(Some(std::sync::Arc::new(palette)));
+            }
+        }
         // Use saved reasoning effort if not set from config
         let reasoning_effort = if reasoning_effort == protocol::ReasoningEffort::Off
             && saved.reasoning_effort != protocol::ReasoningEffort::Off
@@ -450,6 +561,7 @@ impl App {
         // Runtime approvals are shared with the engine via Arc<RwLock>.
         // Load workspace rules from disk into them at startup.
         let runtime_approvals = engine.runtime_approvals();
+        let (agent_action_tx, agent_action_rx) = tokio::sync::mpsc::unbounded_channel();
 
         Self {
             model,
@@ -466,18 +578,22 @@ impl App {
             input,
             exec_rx: None,
             exec_kill: None,
-            queued_messages: Vec::new(),
+            queued_messages: crate::queue::MessageQueue::new(),
             pending_agent_messages: Vec::new(),
             runtime_approvals,
             cwd,
             session: session::Session::new(),
             shared_session,
             context_window: None,
+            context_window_override: None,
             settings,
             multi_agent,
             agent_id: String::new(),
+            agent_depth: 0,
             agents: Vec::new(),
-            agent_snapshots: std::sync::Arc::new(std::sync::Mutex::new(Vec::new())),
+            agent_snapshots: Default::default(),
+            agent_action_tx,
+            agent_action_rx,
             available_models,
             engine,
             permissions,
@@ -504,6 +620,8 @@ impl App {
             pending_turn_meta: None,
             pending_agent_blocks: Vec::new(),
             session_cost_usd: 0.0,
+            session_usage: protocol::TokenUsage::default(),
+            redactions: Vec::new(),
             model_config: engine::ModelConfig::default(),
             cli_model_override,
             cli_api_base_override,
@@ -538,6 +656,7 @@ impl App {
         let _ = io::stdout().execute(cursor::Hide);
         let _ = io::stdout().execute(EnableBracketedPaste);
         let _ = io::stdout().execute(EnableFocusChange);
+        set_mouse_capture(self.settings.mouse);
 
         if !self.history.is_empty() {
             self.restore_screen();
@@ -600,9 +719,9 @@ impl App {
             // ── Background polls ─────────────────────────────────────────
             if let Some(ref mut rx) = ctx_rx {
                 if let Ok(result) = rx.try_recv() {
-                    self.context_window = result;
-                    if let Some(w) = result {
-                        self.screen.set_context_window(w);
+                    if result.is_some() {
+                        self.context_window = result;
+                        self.screen.set_context_window(result);
                     }
                     ctx_rx = None;
                 }
@@ -656,8 +775,10 @@ impl App {
             }
 
             // ── Auto-start from leftover queued messages (one per turn) ──
-            if agent.is_none() && !self.queued_messages.is_empty() && !self.is_compacting() {
-                let text = self.queued_messages.remove(0);
+            if let Some(text) = (agent.is_none() && !self.is_compacting())
+                .then(|| self.queued_messages.pop_front())
+                .flatten()
+            {
                 if let Some(cmd) = crate::custom_commands::resolve(text.trim(), self.multi_agent) {
                     self.screen.erase_prompt();
                     agent = Some(self.begin_custom_command_turn(cmd));
@@ -684,22 +805,49 @@ impl App {
                 agent = Some(self.begin_agent_message_turn());
             }
 
+            // ── Keep the engine's pending steers in line with the queue ──
+            if agent.is_some() {
+                for cmd in self.queued_messages.sync() {
+                    self.engine.send(cmd);
+                }
+            }
+
             // ── Drain spawned children → track agents ─────────────────────
             self.drain_spawned_children();
 
             // ── Drain subagent events ────────────────────────────────────
             self.drain_agent_events();
 
+            // ── Apply /agents dashboard actions ──────────────────────────
+            while let Ok(action) = self.agent_action_rx.try_recv() {
+                match action {
+                    render::AgentAction::Message { agent_id, message } => {
+                        self.message_agent(&agent_id, message);
+                    }
+                    render::AgentAction::Permission {
+                        request_id,
+                        approved,
+                    } => {
+                        // Drop the queued confirm dialog for this request.
+                        pending_dialogs.retain(|d| {
+                            !matches!(d, DeferredDialog::Confirm(req) if req.request_id == request_id)
+                        });
+                        self.screen.set_pending_dialog(!pending_dialogs.is_empty());
+                        self.send_permission_decision(request_id, approved, None);
+                    }
+                }
+            }
+
             // ── Drain child permission requests ──────────────────────────
             while let Ok(msg) = self.child_permission_rx.try_recv() {
                 let engine::socket::IncomingMessage::PermissionCheck {
+                    from_id,
                     tool_name,
                     args,
                     confirm_message,
                     approval_patterns,
                     summary,
                     reply_tx,
-                    ..
                 } = msg
                 else {
                     continue;
@@ -708,6 +856,14 @@ impl App {
                 let request_id =
                     NEXT_CHILD_REQUEST_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
                 self.child_permission_replies.insert(request_id, reply_tx);
+                if let Some(tracked) = self.agents.iter_mut().find(|a| a.agent_id == from_id) {
+                    tracked.pending_permission = Some(AgentPermission {
+                        request_id,
+                        tool_name: tool_name.clone(),
+                        summary: summary.clone().unwrap_or_else(|| confirm_message.clone()),
+                    });
+                    self.sync_agent_snapshots();
+                }
 
                 let ctrl = SessionControl::NeedsConfirm(ConfirmRequest {
                     call_id: format!("child-perm-{request_id}"),
@@ -903,6 +1059,7 @@ impl App {
         let _ = io::stdout().execute(cursor::Show);
         let _ = io::stdout().execute(DisableBracketedPaste);
         let _ = io::stdout().execute(DisableFocusChange);
+        set_mouse_capture(false);
         terminal::disable_raw_mode().ok();
     }
 
@@ -955,6 +1112,9 @@ impl App {
         let turn_id = self.next_turn_id;
         self.next_turn_id += 1;
 
+        // `--max-cost` caps this run, even when resuming a session.
+        self.engine.budget.reset_session(0.0);
+
         self.engine.send(UiCommand::StartTurn {
             turn_id,
             content: Content::text(message),
@@ -979,6 +1139,7 @@ impl App {
 
         // Drain events. Break on cancellation (Ctrl+C) so the summary still prints.
         let mut interrupted = false;
+        let mut stop = StopReason::Completed;
         loop {
             let ev = tokio::select! {
                 ev = self.engine.recv() => match ev {
@@ -998,6 +1159,11 @@ impl App {
 
                     // Still need to handle side-effect events.
                     match ev {
+                        EngineEvent::TokenUsage {
+                            usage, cost_usd, ..
+                        } => {
+                            self.record_metrics(&usage, cost_usd);
+                        }
                         EngineEvent::RequestPermission { request_id, .. } => {
                             let approved = self.mode == Mode::Yolo;
                             self.engine.send(UiCommand::PermissionDecision {
@@ -1012,7 +1178,9 @@ impl App {
                                 answer: Some("User is not available (headless mode).".into()),
                             });
                         }
-                        EngineEvent::TurnError { .. } | EngineEvent::TurnComplete { .. } => {
+                        EngineEvent::TurnError { reason, .. }
+                        | EngineEvent::TurnComplete { reason, .. } => {
+                            stop = reason;
                             break;
                         }
                         _ => {}
@@ -1074,10 +1242,26 @@ impl App {
                         total_cost += cost_usd.unwrap_or(0.0);
                         total_usage.accumulate(&usage);
                         last_tps = tokens_per_sec.or(last_tps);
+                        self.record_metrics(&usage, cost_usd);
                     }
                     EngineEvent::Retrying { delay_ms, attempt } => {
                         log_retry(attempt, delay_ms);
                     }
+                    EngineEvent::BudgetWarning {
+                        scope,
+                        spent_usd,
+                        limit_usd,
+                    } => {
+                        log_budget_warning(scope, spent_usd, limit_usd);
+                    }
+                    EngineEvent::BudgetExceeded {
+                        scope,
+                        spent_usd,
+                        limit_usd,
+                    } => {
+                        log_error(&budget_exceeded_message(scope, spent_usd, limit_usd));
+                        engine::registry::kill_descendants(std::process::id());
+                    }
                     EngineEvent::RequestPermission { request_id, .. } => {
                         let approved = self.mode == Mode::Yolo;
                         self.engine.send(UiCommand::PermissionDecision {
@@ -1093,11 +1277,16 @@ impl App {
                         });
                     }
                     EngineEvent::Messages { .. } => {}
-                    EngineEvent::TurnError { message } => {
+                    EngineEvent::TurnError { message, reason } => {
                         log_error(&message);
+                        stop = reason;
                         break;
                     }
-                    EngineEvent::TurnComplete { .. } => {
+                    EngineEvent::TurnComplete { reason, .. } => {
+                        if reason.is_limit() && reason != StopReason::Budget {
+                            log_error(&format!("turn stopped: {}", reason.label()));
+                        }
+                        stop = reason;
                         break;
                     }
                     _ => {}
@@ -1134,12 +1323,34 @@ impl App {
             }
         }
 
-        if interrupted {
+        let code = headless_exit_code(stop, interrupted);
+        if code != 0 {
             let _ = io::stderr().flush();
-            std::process::exit(130);
+            std::process::exit(code);
         }
     }
 
+    /// Append one LLM call to `metrics.jsonl`. Every process records its
+    /// own calls, so subagent usage is attributed to the subagent.
+    pub(crate) fn record_metrics(&self, usage: &protocol::TokenUsage, cost_usd: Option<f64>) {
+        crate::metrics::append(&crate::metrics::MetricsEntry {
+            timestamp_ms: session::now_ms(),
+            prompt_tokens: usage.prompt_tokens.unwrap_or(0),
+            completion_tokens: usage.completion_tokens.unwrap_or(0),
+            model: self.model.clone(),
+            cost_usd,
+            cache_read_tokens: usage.cache_read_tokens,
+            cache_write_tokens: usage.cache_write_tokens,
+            reasoning_tokens: usage.reasoning_tokens,
+            project: Some(self.cwd.clone()),
+            session_id: Some(self.session.id.clone()),
+            provider: Some(self.provider_type.clone()),
+            mode: Some(self.mode.as_str().to_string()),
+            agent_depth: Some(self.agent_depth),
+            agent_id: (!self.agent_id.is_empty()).then(|| self.agent_id.clone()),
+        });
+    }
+
     // ── Subagent mode ────────────────────────────────────────────────────
 
     fn shutdown_subagent(&mut self, parent_pid: u32) {
@@ -1154,6 +1365,12 @@ impl App {
             from_slug: from_slug.to_string(),
             message: message.to_string(),
         });
+        self.engine
+            .notify_agent_message(engine::tools::AgentMessageNotification {
+                from_id: from_id.to_string(),
+                from_slug: from_slug.to_string(),
+                message: message.to_string(),
+            });
         self.engine.send(UiCommand::AgentMessage {
             from_id: from_id.to_string(),
             from_slug: from_slug.to_string(),
@@ -1173,28 +1390,35 @@ impl App {
     /// Run as a persistent subagent. Each `EngineEvent` is written to
     /// stdout as a JSON line so the parent can parse and render it.
     /// Processes the initial message, then loops: go idle → wait for
-    /// messages → run next turn → repeat.
+    /// messages → run next turn → repeat. With a `result_schema`, the answer
+    /// to the initial message must be JSON matching it.
     pub async fn run_subagent(
         &mut self,
         initial_message: String,
+        result_schema: Option<serde_json::Value>,
         parent_pid: u32,
         mut socket_rx: tokio::sync::mpsc::UnboundedReceiver<engine::socket::IncomingMessage>,
     ) {
         let parent_socket = engine::registry::read_entry(parent_pid)
             .ok()
-            .map(|e| std::path::PathBuf::from(&e.socket_path));
+            .map(|e| e.endpoint());
         let my_pid = std::process::id();
         let my_agent_id = engine::registry::read_entry(my_pid)
             .ok()
             .map(|e| e.agent_id)
             .unwrap_or_default();
 
-        // Run the initial turn.
+        // Run the initial turn. Its answer always goes to the parent.
+        let initial_message = with_schema_instruction(initial_message, result_schema.as_ref());
         self.run_subagent_turn(
             Content::text(initial_message),
+            result_schema.map(|schema| StructuredRequest {
+                schema,
+                reply_to: None,
+            }),
             &mut socket_rx,
             parent_pid,
-            parent_socket.as_deref(),
+            parent_socket.as_ref(),
             &my_agent_id,
         )
         .await;
@@ -1207,15 +1431,21 @@ impl App {
             tokio::select! {
                 Some(incoming) = socket_rx.recv() => {
                     match incoming {
-                        engine::socket::IncomingMessage::Message { from_id, from_slug, message } => {
+                        engine::socket::IncomingMessage::Message { from_id, from_slug, message, schema } => {
+                            let message = with_schema_instruction(message, schema.as_ref());
                             self.forward_agent_message(&from_id, &from_slug, &message);
                             self.history
                                 .push(protocol::Message::agent(&from_id, &from_slug, &message));
+                            let request = schema.map(|schema| StructuredRequest {
+                                schema,
+                                reply_to: Some(from_id),
+                            });
                             self.run_subagent_turn(
                                 Content::text(""),
+                                request,
                                 &mut socket_rx,
                                 parent_pid,
-                                parent_socket.as_deref(),
+                                parent_socket.as_ref(),
                                 &my_agent_id,
                             )
                             .await;
@@ -1235,7 +1465,7 @@ impl App {
                             approval_patterns, summary, reply_tx,
                         } => {
                             let (approved, message) = relay_permission(
-                                parent_socket.as_deref(), &from_id, &tool_name,
+                                parent_socket.as_ref(), &from_id, &tool_name,
                                 &args, &confirm_message, &approval_patterns, summary.as_deref(),
                             ).await;
                             let _ = reply_tx.send(engine::socket::PermissionReply { approved, message });
@@ -1252,146 +1482,232 @@ impl App {
         }
     }
 
+    /// Run one subagent turn and send its final answer to the parent. With a
+    /// structured `request`, an answer that does not match the schema gets
+    /// one corrective retry, and the answer goes to whoever asked for it.
+    /// Structured requests that arrive meanwhile are queued and each answered
+    /// in a follow-up turn, so the current answer still goes to its asker.
     async fn run_subagent_turn(
         &mut self,
-        content: Content,
+        mut content: Content,
+        mut request: Option<StructuredRequest>,
         socket_rx: &mut tokio::sync::mpsc::UnboundedReceiver<engine::socket::IncomingMessage>,
         parent_pid: u32,
-        parent_socket: Option<&std::path::Path>,
+        parent_socket: Option<&engine::socket::Endpoint>,
         my_agent_id: &str,
     ) {
         let my_pid = std::process::id();
         engine::registry::update_status(my_pid, engine::registry::AgentStatus::Working);
 
-        // Generate title/slug for the subagent.
-        let text = content.text_content();
-        if self.session.slug.is_none() && !text.is_empty() {
-            self.engine.send(UiCommand::GenerateTitle {
-                last_user_message: text,
-                assistant_tail: String::new(),
-            });
-        }
+        let mut queued: VecDeque<QueuedRequest> = VecDeque::new();
+        let mut pending_query_tx: Option<tokio::sync::oneshot::Sender<String>> = None;
 
-        let turn_id = self.next_turn_id;
-        self.next_turn_id += 1;
+        'turns: loop {
+            // Generate title/slug for the subagent.
+            let text = content.text_content();
+            if self.session.slug.is_none() && !text.is_empty() {
+                self.engine.send(UiCommand::GenerateTitle {
+                    last_user_message: text,
+                    assistant_tail: String::new(),
+                });
+            }
 
-        self.engine.send(UiCommand::StartTurn {
-            turn_id,
-            content,
-            mode: self.mode,
-            model: self.model.clone(),
-            reasoning_effort: self.reasoning_effort,
-            history: self.history.clone(),
-            api_base: Some(self.api_base.clone()),
-            api_key: Some(self.api_key()),
-            session_id: self.session.id.clone(),
-            session_dir: crate::session::dir_for(&self.session),
-            model_config_overrides: None,
-            permission_overrides: None,
-        });
+            self.start_subagent_turn(std::mem::replace(&mut content, Content::text("")));
 
-        let mut pending_query_tx: Option<tokio::sync::oneshot::Sender<String>> = None;
+            let mut retried = false;
 
-        loop {
-            let parent_check = tokio::time::sleep(std::time::Duration::from_secs(5));
-            tokio::pin!(parent_check);
+            loop {
+                let parent_check = tokio::time::sleep(std::time::Duration::from_secs(5));
+                tokio::pin!(parent_check);
 
-            tokio::select! {
-                Some(incoming) = socket_rx.recv() => {
-                    match incoming {
-                        engine::socket::IncomingMessage::Message { from_id, from_slug, message } => {
-                            self.forward_agent_message(&from_id, &from_slug, &message);
-                        }
-                        engine::socket::IncomingMessage::Query { from_id: _, question, reply_tx } => {
-                            self.send_btw_query(question);
-                            pending_query_tx = Some(reply_tx);
-                        }
-                        engine::socket::IncomingMessage::PermissionCheck {
-                            from_id, tool_name, args, confirm_message,
-                            approval_patterns, summary, reply_tx,
-                        } => {
-                            let (approved, message) = relay_permission(
-                                parent_socket, &from_id, &tool_name,
-                                &args, &confirm_message, &approval_patterns, summary.as_deref(),
-                            ).await;
-                            let _ = reply_tx.send(engine::socket::PermissionReply { approved, message });
+                tokio::select! {
+                    Some(incoming) = socket_rx.recv() => {
+                        match incoming {
+                            engine::socket::IncomingMessage::Message { from_id, from_slug, message, schema: None } => {
+                                self.forward_agent_message(&from_id, &from_slug, &message);
+                            }
+                            engine::socket::IncomingMessage::Message { from_id, from_slug, message, schema: Some(schema) } => {
+                                if queued.len() >= MAX_QUEUED_REQUESTS {
+                                    let slug = self.session.slug.as_deref().unwrap_or("");
+                                    refuse_request(&from_id, my_agent_id, slug, "too many pending requests; try again later").await;
+                                } else {
+                                    let message = with_schema_instruction(message, Some(&schema));
+                                    queued.push_back(QueuedRequest { from_id, from_slug, message, schema });
+                                }
+                            }
+                            engine::socket::IncomingMessage::Query { from_id: _, question, reply_tx } => {
+                                self.send_btw_query(question);
+                                pending_query_tx = Some(reply_tx);
+                            }
+                            engine::socket::IncomingMessage::PermissionCheck {
+                                from_id, tool_name, args, confirm_message,
+                                approval_patterns, summary, reply_tx,
+                            } => {
+                                let (approved, message) = relay_permission(
+                                    parent_socket, &from_id, &tool_name,
+                                    &args, &confirm_message, &approval_patterns, summary.as_deref(),
+                                ).await;
+                                let _ = reply_tx.send(engine::socket::PermissionReply { approved, message });
+                            }
                         }
                     }
-                }
-                _ = &mut parent_check => {
-                    if !engine::registry::is_pid_alive(parent_pid) {
-                        self.shutdown_subagent(parent_pid);
-                        return;
+                    _ = &mut parent_check => {
+                        if !engine::registry::is_pid_alive(parent_pid) {
+                            self.shutdown_subagent(parent_pid);
+                            return;
+                        }
                     }
-                }
-                maybe_ev = self.engine.recv() => {
-                    let Some(ev) = maybe_ev else {
-                        break;
-                    };
+                    maybe_ev = self.engine.recv() => {
+                        let Some(ev) = maybe_ev else {
+                            break 'turns;
+                        };
 
-                    // Forward every event to stdout as JSON.
-                    emit_json(&ev);
+                        // Forward every event to stdout as JSON.
+                        emit_json(&ev);
 
-                    // Handle side effects for events that need them.
-                    match ev {
-                        EngineEvent::RequestPermission {
-                            request_id, tool_name, args, confirm_message,
-                            approval_patterns, summary, ..
-                        } => {
-                            let (approved, message) = relay_permission(
-                                parent_socket, my_agent_id, &tool_name,
-                                &args, &confirm_message, &approval_patterns, summary.as_deref(),
-                            ).await;
-                            self.engine.send(UiCommand::PermissionDecision {
-                                request_id, approved, message,
-                            });
-                        }
-                        EngineEvent::RequestAnswer { request_id, .. } => {
-                            self.engine.send(UiCommand::QuestionAnswer {
-                                request_id,
-                                answer: Some("User is not available (subagent mode).".into()),
-                            });
-                        }
-                        EngineEvent::Messages { messages, .. } => {
-                            self.history = messages;
-                        }
-                        EngineEvent::BtwResponse { content } => {
-                            if let Some(tx) = pending_query_tx.take() {
-                                let _ = tx.send(content);
+                        // Handle side effects for events that need them.
+                        match ev {
+                            EngineEvent::TokenUsage { usage, cost_usd, .. } => {
+                                self.record_metrics(&usage, cost_usd);
                             }
-                        }
-                        EngineEvent::TitleGenerated { title, slug } => {
-                            self.session.title = Some(title);
-                            self.session.slug = Some(slug.clone());
-                            engine::registry::update_slug(my_pid, &slug);
-                        }
-                        EngineEvent::TurnError { .. } => {
-                            break;
-                        }
-                        EngineEvent::TurnComplete { messages, .. } => {
-                            self.history = messages;
+                            // Nested subagents spend against this agent's budget.
+                            EngineEvent::BudgetExceeded { .. } => {
+                                engine::registry::kill_descendants(my_pid);
+                            }
+                            EngineEvent::RequestPermission {
+                                request_id, tool_name, args, confirm_message,
+                                approval_patterns, summary, ..
+                            } => {
+                                let (approved, message) = relay_permission(
+                                    parent_socket, my_agent_id, &tool_name,
+                                    &args, &confirm_message, &approval_patterns, summary.as_deref(),
+                                ).await;
+                                self.engine.send(UiCommand::PermissionDecision {
+                                    request_id, approved, message,
+                                });
+                            }
+                            EngineEvent::RequestAnswer { request_id, .. } => {
+                                self.engine.send(UiCommand::QuestionAnswer {
+                                    request_id,
+                                    answer: Some("User is not available (subagent mode).".into()),
+                                });
+                            }
+                            EngineEvent::Messages { messages, .. } => {
+                                self.history = messages;
+                            }
+                            EngineEvent::BtwResponse { content } => {
+                                if let Some(tx) = pending_query_tx.take() {
+                                    let _ = tx.send(content);
+                                }
+                            }
+                            EngineEvent::TitleGenerated { title, slug } => {
+                                self.session.title = Some(title);
+                                self.session.slug = Some(slug.clone());
+                                engine::registry::update_slug(my_pid, &slug);
+                            }
+                            EngineEvent::TurnError { message, .. } => {
+                                if let Some(to) = request.as_ref().and_then(|req| req.reply_to.as_deref()) {
+                                    let slug = self.session.slug.as_deref().unwrap_or("");
+                                    refuse_request(to, my_agent_id, slug, &message).await;
+                                }
+                                break;
+                            }
+                            EngineEvent::TurnComplete { messages, .. } => {
+                                self.history = messages;
+                                let text = self
+                                    .history
+                                    .iter()
+                                    .rev()
+                                    .find(|m| m.role == protocol::Role::Assistant)
+                                    .and_then(|m| m.content.as_ref())
+                                    .map(|c| c.text_content())
+                                    .unwrap_or_default();
+
+                                // A structured answer that does not parse gets one retry.
+                                if let Some(ref req) = request {
+                                    if let Err(e) = engine::schema::parse_result(&text, &req.schema) {
+                                        if !retried {
+                                            retried = true;
+                                            self.start_subagent_turn(Content::text(
+                                                engine::schema::retry_message(&e),
+                                            ));
+                                            continue;
+                                        }
+                                    }
+                                }
 
-                            // Auto-return last assistant message to parent.
-                            if let Some(socket) = parent_socket {
-                                if let Some(last_asst) = self.history.iter().rev().find(|m| m.role == protocol::Role::Assistant) {
-                                    let text = last_asst.content.as_ref().map(|c| c.text_content()).unwrap_or_default();
+                                // Auto-return the last assistant message to the
+                                // requesting agent, or else the parent.
+                                let requester = request
+                                    .as_ref()
+                                    .and_then(|req| req.reply_to.as_deref())
+                                    .and_then(engine::registry::find_by_id)
+                                    .map(|e| e.endpoint());
+                                if let Some(socket) = requester.as_ref().or(parent_socket) {
                                     if !text.is_empty() {
                                         let slug = self.session.slug.as_deref().unwrap_or("");
-                                        let _ = engine::socket::send_message(socket, my_agent_id, slug, &text).await;
+                                        let _ = engine::socket::send_message(socket, my_agent_id, slug, &text, None).await;
                                     }
                                 }
-                            }
 
-                            break;
+                                break;
+                            }
+                            _ => {}
                         }
-                        _ => {}
                     }
                 }
             }
+
+            // Answer the next queued request in its own turn.
+            let Some(next) = queued.pop_front() else {
+                break;
+            };
+            self.forward_agent_message(&next.from_id, &next.from_slug, &next.message);
+            self.history.push(protocol::Message::agent(
+                &next.from_id,
+                &next.from_slug,
+                &next.message,
+            ));
+            request = Some(StructuredRequest {
+                schema: next.schema,
+                reply_to: Some(next.from_id),
+            });
+        }
+
+        // The engine went away; nobody will answer what is still queued.
+        let slug = self.session.slug.clone().unwrap_or_default();
+        for q in queued {
+            refuse_request(
+                &q.from_id,
+                my_agent_id,
+                &slug,
+                "the agent stopped before answering",
+            )
+            .await;
         }
         engine::registry::update_status(my_pid, engine::registry::AgentStatus::Idle);
     }
 
+    fn start_subagent_turn(&mut self, content: Content) {
+        let turn_id = self.next_turn_id;
+        self.next_turn_id += 1;
+
+        self.engine.send(UiCommand::StartTurn {
+            turn_id,
+            content,
+            mode: self.mode,
+            model: self.model.clone(),
+            reasoning_effort: self.reasoning_effort,
+            history: self.history.clone(),
+            api_base: Some(self.api_base.clone()),
+            api_key: Some(self.api_key()),
+            session_id: self.session.id.clone(),
+            session_dir: crate::session::dir_for(&self.session),
+            model_config_overrides: None,
+            permission_overrides: None,
+        });
+    }
+
     /// Render a complete frame. When a dialog is active, content + dialog +
     /// status line are all painted inside a single `Frame` (one atomic
     /// synchronized update). Without a dialog, content + prompt are rendered
@@ -1401,6 +1717,7 @@ impl App {
         agent_running: bool,
         active_dialog: &mut Option<Box<dyn render::Dialog>>,
     ) {
+        self.screen.follow_new_blocks();
         if let Some(d) = active_dialog.as_mut() {
             let dialog_height = d.height();
             let constrain = d.constrain_height();
@@ -1437,6 +1754,8 @@ impl App {
         mut dialog: Box<dyn render::Dialog>,
         active_dialog: &mut Option<Box<dyn render::Dialog>>,
     ) {
+        self.screen.leave_block_select();
+        self.screen.leave_history_scroll();
         if dialog.blocks_agent() {
             self.screen.render_pending_blocks();
             self.screen.pause_spinner();
@@ -1455,6 +1774,15 @@ impl App {
     }
 }
 
+/// Turn terminal mouse reporting on or off (the `mouse` setting).
+pub(super) fn set_mouse_capture(enabled: bool) {
+    if enabled {
+        let _ = io::stdout().execute(EnableMouseCapture);
+    } else {
+        let _ = io::stdout().execute(DisableMouseCapture);
+    }
+}
+
 /// Poll one item from a `futures_core::Stream`, equivalent to `StreamExt::next`.
 async fn stream_next<S>(stream: &mut S) -> Option<S::Item>
 where
@@ -1485,6 +1813,27 @@ fn emit_json(ev: &EngineEvent) {
     println!("{}", serde_json::to_string(ev).unwrap());
 }
 
+/// Process exit code for a headless run: 1 failed, 3 stopped by a limit,
+/// 130 interrupted.
+fn headless_exit_code(stop: StopReason, interrupted: bool) -> i32 {
+    match stop {
+        _ if interrupted => 130,
+        StopReason::Cancelled => 130,
+        StopReason::Error => 1,
+        reason if reason.is_limit() => 3,
+        _ => 0,
+    }
+}
+
+/// Append the structured-answer instruction to an agent message that
+/// requests one.
+fn with_schema_instruction(message: String, schema: Option<&serde_json::Value>) -> String {
+    match schema {
+        Some(schema) => format!("{message}\n\n{}", engine::schema::instruction(schema)),
+        None => message,
+    }
+}
+
 // ── Headless / subagent log helpers ─────────────────────────────────────────
 //
 // Bare-minimum style. Assistant text flows undecorated; only tool lifecycle
@@ -1608,6 +1957,26 @@ fn log_error(message: &str) {
     eprintln!("{c}! {message}{r}");
 }
 
+fn budget_exceeded_message(scope: protocol::BudgetScope, spent: f64, limit: f64) -> String {
+    format!(
+        "{} budget exceeded ({} of {}), turn stopped",
+        scope.label(),
+        crate::metrics::format_cost(spent),
+        crate::metrics::format_cost(limit),
+    )
+}
+
+fn log_budget_warning(scope: protocol::BudgetScope, spent: f64, limit: f64) {
+    let d = dim();
+    let r = reset();
+    eprintln!(
+        "{d}{} budget: {} of {} spent{r}",
+        scope.label(),
+        crate::metrics::format_cost(spent),
+        crate::metrics::format_cost(limit),
+    );
+}
+
 fn log_token_usage(usage: &protocol::TokenUsage, tokens_per_sec: Option<f64>, cost_usd: f64) {
     let d = dim();
     let r = reset();
@@ -1653,6 +2022,23 @@ fn format_elapsed(ms: Option<u64>) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn headless_exit_codes() {
+        let cases = [
+            (StopReason::Completed, 0),
+            (StopReason::Cancelled, 130),
+            (StopReason::MaxTurns, 3),
+            (StopReason::MaxToolCalls, 3),
+            (StopReason::Timeout, 3),
+            (StopReason::Budget, 3),
+            (StopReason::Error, 1),
+        ];
+        for (reason, code) in cases {
+            assert_eq!(headless_exit_code(reason, false), code, "{reason:?}");
+            assert_eq!(headless_exit_code(reason, true), 130, "{reason:?}");
+        }
+    }
+
     // ── is_allowed_while_running ─────────────────────────────────────
 
     #[test]
@@ -59,11 +59,14 @@ impl Completer {
             ("branch", "fork current session"),
             ("stats", "show token usage statistics"),
             ("cost", "show session cost"),
+            ("redactions", "show secrets redacted this session"),
             ("theme", "change accent color"),
             ("color", "set task slug color"),
             ("btw", "ask a side question"),
             ("permissions", "manage session permissions"),
             ("ps", "manage background processes"),
+            ("diff", "review files changed this session"),
+            ("queue", "edit queued messages"),
             ("agents", "manage running agents"),
             ("exit", "exit the app"),
             ("quit", "exit the app"),
@@ -110,6 +113,8 @@ impl Completer {
             all_items,
             selected_key: None,
             original_value: None,
+            original_palette: None,
+            palettes: Vec::new(),
         }
     }
 
@@ -131,6 +136,8 @@ impl Completer {
             all_items,
             selected_key: None,
             original_value: None,
+            original_palette: None,
+            palettes: Vec::new(),
         }
     }
 }
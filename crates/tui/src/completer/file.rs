@@ -22,6 +22,8 @@ impl Completer {
             all_items,
             selected_key: None,
             original_value: None,
+            original_palette: None,
+            palettes: Vec::new(),
         }
     }
 }
@@ -34,6 +34,8 @@ impl Completer {
             all_items,
             selected_key: None,
             original_value: None,
+            original_palette: None,
+            palettes: Vec::new(),
         }
     }
 }
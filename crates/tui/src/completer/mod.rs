@@ -53,6 +53,10 @@ pub struct Completer {
     pub(super) selected_key: Option<String>,
     /// Original value to restore on dismiss (Theme = accent, Color = slug color).
     pub original_value: Option<u8>,
+    /// Theme file active when the theme picker opened, restored on dismiss.
+    pub original_palette: Option<std::sync::Arc<crate::theme::Palette>>,
+    /// Theme files parsed when the theme picker opened, for live preview.
+    pub palettes: Vec<std::sync::Arc<crate::theme::Palette>>,
 }
 
 impl Completer {
@@ -303,6 +307,7 @@ mod settings_tests {
             show_thinking: true,
             restrict_to_workspace: false,
             redact_secrets: true,
+            mouse: false,
         }
     }
 
@@ -419,4 +424,23 @@ mod settings_tests {
         let key = comp.accept_extra();
         assert_eq!(key, Some("auto_compact"));
     }
+
+    #[test]
+    fn theme_picker_keeps_parsed_palettes() {
+        let files = vec![
+            (
+                "dusk".to_string(),
+                crate::theme::parse_palette("dusk", "description: evening\naccent: 61"),
+            ),
+            ("broken".to_string(), Err("bad".to_string())),
+        ];
+        let comp = Completer::themes(1, files, Some("dusk"));
+        assert_eq!(comp.palettes.len(), 1);
+        assert_eq!(comp.palettes[0].name, "dusk");
+        let selected = &comp.results[comp.selected];
+        assert_eq!(selected.extra.as_deref(), Some("dusk"));
+        assert_eq!(selected.description.as_deref(), Some("evening"));
+        let broken = comp.all_items.last().unwrap();
+        assert_eq!(broken.description.as_deref(), Some("invalid theme file"));
+    }
 }
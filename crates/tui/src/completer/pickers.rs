@@ -23,11 +23,53 @@ impl Completer {
             all_items,
             selected_key: None,
             original_value: None,
+            original_palette: None,
+            palettes: Vec::new(),
         }
     }
 
-    /// Picker for selecting a theme (accent color).
-    pub fn themes(original: u8) -> Self {
+    /// Picker for selecting a theme: accent presets followed by theme
+    /// files (`extra` = file name), each parsed or with its load error.
+    /// `active` names the current theme file.
+    pub fn themes(
+        original: u8,
+        files: Vec<(String, Result<crate::theme::Palette, String>)>,
+        active: Option<&str>,
+    ) -> Self {
+        let mut comp = Self::presets(original);
+        for (name, palette) in files {
+            let description = match &palette {
+                Ok(p) => p.description.clone(),
+                Err(_) => Some("invalid theme file".into()),
+            };
+            comp.all_items.push(CompletionItem {
+                label: name.clone(),
+                description: Some(description.unwrap_or_else(|| "theme file".into())),
+                extra: Some(name),
+                ..Default::default()
+            });
+            if let Ok(p) = palette {
+                comp.palettes.push(std::sync::Arc::new(p));
+            }
+        }
+        if let Some(i) = active.and_then(|name| {
+            comp.all_items
+                .iter()
+                .position(|i| i.extra.as_deref() == Some(name))
+        }) {
+            comp.selected = i;
+        }
+        comp.results = comp.all_items.clone();
+        comp.selected_key = comp
+            .results
+            .get(comp.selected)
+            .map(|item| Self::item_key(item).to_string());
+        comp.original_palette = crate::theme::palette();
+        comp
+    }
+
+    /// Picker over the accent presets, preselecting `original`.
+    fn presets(original: u8) -> Self {
         let all_items: Vec<CompletionItem> = crate::theme::PRESETS
             .iter()
             .map(|&(name, detail, ansi)| CompletionItem {
@@ -54,12 +96,14 @@ impl Completer {
             all_items,
             selected_key,
             original_value: Some(original),
+            original_palette: None,
+            palettes: Vec::new(),
         }
     }
 
     /// Picker for selecting a slug color.
     pub fn colors(original: u8) -> Self {
-        let mut comp = Self::themes(original);
+        let mut comp = Self::presets(original);
         comp.kind = CompleterKind::Color;
         comp
     }
@@ -134,6 +178,13 @@ impl Completer {
                 extra: Some("redact_secrets".into()),
                 ..Default::default()
             },
+            CompletionItem {
+                label: "mouse".into(),
+                description: Some(on_off(state.mouse).into()),
+                search_terms: Some("mouse click scroll wheel".into()),
+                extra: Some("mouse".into()),
+                ..Default::default()
+            },
         ]
     }
 
@@ -150,6 +201,8 @@ impl Completer {
             all_items,
             selected_key: None,
             original_value: None,
+            original_palette: None,
+            palettes: Vec::new(),
         }
     }
 }
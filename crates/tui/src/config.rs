@@ -1,5 +1,6 @@
 use serde::de::{self, Deserializer};
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 pub fn config_dir() -> PathBuf {
@@ -28,6 +29,20 @@ pub struct ModelConfig {
     pub cache_read_cost: Option<f64>,
     /// Cost per 1M cache-write tokens in USD.
     pub cache_write_cost: Option<f64>,
+    /// Price multiplier / per-request charge. Unset fields fall back to the
+    /// provider's `pricing`.
+    pub pricing: engine::pricing::PriceRule,
+    /// Anthropic prompt-cache lifetime: `5m`, `1h` or `off`. Falls back to
+    /// the provider's `cache_ttl`.
+    pub cache_ttl: Option<engine::config::CacheTtl>,
+    /// Azure OpenAI `api-version`. Falls back to the provider's.
+    pub api_version: Option<String>,
+    /// Command that prints an API token. Falls back to the provider's.
+    pub api_key_command: Option<String>,
+    /// Extra request headers, added to the provider's.
+    pub headers: BTreeMap<String, String>,
+    /// Overrides for the capability registry (context window, vision, ...).
+    pub capabilities: protocol::CapabilityOverrides,
 }
 
 impl From<&ModelConfig> for engine::ModelConfig {
@@ -44,6 +59,15 @@ impl From<&ModelConfig> for engine::ModelConfig {
             output_cost: c.output_cost,
             cache_read_cost: c.cache_read_cost,
             cache_write_cost: c.cache_write_cost,
+            pricing: c.pricing,
+            cache_ttl: c.cache_ttl,
+            service_tier: None,
+            api_version: c.api_version.clone(),
+            auth: protocol::ProviderAuth {
+                api_key_command: c.api_key_command.clone(),
+                headers: c.headers.clone(),
+            },
+            capabilities: c.capabilities,
         }
     }
 }
@@ -75,6 +99,16 @@ pub struct ProviderConfig {
     pub api_key_env: Option<String>,
     #[serde(deserialize_with = "deserialize_models", default)]
     pub models: Vec<ModelConfig>,
+    /// Price rule for every model of this provider.
+    pub pricing: engine::pricing::PriceRule,
+    /// Prompt-cache lifetime for every model of this provider.
+    pub cache_ttl: Option<engine::config::CacheTtl>,
+    /// Azure OpenAI `api-version` for every model of this provider.
+    pub api_version: Option<String>,
+    /// Command that prints an API token; re-run when it expires or on 401.
+    pub api_key_command: Option<String>,
+    /// Extra headers sent with every request to this provider.
+    pub headers: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Default, Deserialize)]
@@ -90,6 +124,7 @@ pub struct SettingsConfig {
     pub show_thinking: Option<bool>,
     pub restrict_to_workspace: Option<bool>,
     pub redact_secrets: Option<bool>,
+    pub mouse: Option<bool>,
     pub multi_agent: Option<bool>,
     /// Override the context window size (tokens). When unset, the engine
     /// fetches it from the provider API at startup.
@@ -115,6 +150,7 @@ impl SettingsConfig {
             "show_thinking" => self.show_thinking = b()?,
             "restrict_to_workspace" => self.restrict_to_workspace = b()?,
             "redact_secrets" => self.redact_secrets = b()?,
+            "mouse" => self.mouse = b()?,
             "multi_agent" => self.multi_agent = b()?,
             _ => return Err(format!("unknown setting '{key}'")),
         }
@@ -160,6 +196,8 @@ pub struct AuxiliaryConfig {
 #[serde(default)]
 pub struct ThemeConfig {
     pub accent: Option<String>,
+    /// Theme file in `<config>/themes/` (without extension).
+    pub name: Option<String>,
 }
 
 #[derive(Debug, Default, Deserialize)]
@@ -185,6 +223,18 @@ pub enum ConfigSource {
     NotFound,
 }
 
+/// Spending limits, in USD.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct BudgetConfig {
+    /// Cap per session (per run in headless mode). `--max-cost` overrides.
+    pub session_usd: Option<f64>,
+    /// Cap per UTC day across all sessions.
+    pub daily_usd: Option<f64>,
+    /// Warn when spend reaches this percentage of a limit (default 80).
+    pub warn_percent: Option<u8>,
+}
+
 /// Configuration for the skills system.
 #[derive(Debug, Default, Clone, Deserialize)]
 #[serde(default)]
@@ -208,6 +258,12 @@ pub struct Config {
     /// Skills configuration.
     #[serde(default)]
     pub skills: SkillsConfig,
+    /// Spending limits.
+    pub budget: BudgetConfig,
+    /// Custom secret patterns and an allowlist of false positives.
+    pub redaction: engine::redact::RedactionConfig,
+    /// Network transport and shared registry for agents on other hosts.
+    pub agents: engine::socket::AgentsConfig,
     /// Path the config was loaded from (not serialized).
     #[serde(skip)]
     pub path: PathBuf,
@@ -225,7 +281,8 @@ pub struct ResolvedModel {
     pub model_name: String,
     pub api_base: String,
     pub api_key_env: String,
-    /// Provider type from config: "openai", "anthropic", "codex", or "openai-compatible" (default).
+    /// Provider type from config: "openai", "anthropic", "codex", "bedrock",
+    /// "azure-openai", or "openai-compatible" (default).
     pub provider_type: String,
     pub config: ModelConfig,
 }
@@ -451,7 +508,10 @@ impl Config {
                     api_base: api_base.clone(),
                     api_key_env: api_key_env.clone(),
                     provider_type: provider_type.clone(),
-                    config: ModelConfig::default(),
+                    config: ModelConfig {
+                        pricing: provider.pricing,
+                        ..ModelConfig::default()
+                    },
                 });
                 continue;
             }
@@ -473,7 +533,25 @@ impl Config {
                     api_base: api_base.clone(),
                     api_key_env: api_key_env.clone(),
                     provider_type: provider_type.clone(),
-                    config: model.clone(),
+                    config: ModelConfig {
+                        pricing: model.pricing.or(provider.pricing),
+                        cache_ttl: model.cache_ttl.or(provider.cache_ttl),
+                        api_version: model
+                            .api_version
+                            .clone()
+                            .or_else(|| provider.api_version.clone()),
+                        api_key_command: model
+                            .api_key_command
+                            .clone()
+                            .or_else(|| provider.api_key_command.clone()),
+                        headers: provider
+                            .headers
+                            .iter()
+                            .chain(&model.headers)
+                            .map(|(k, v)| (k.clone(), v.clone()))
+                            .collect(),
+                        ..model.clone()
+                    },
                 });
             }
         }
@@ -505,6 +583,7 @@ impl Config {
                 provider_type: "codex".to_string(),
                 config: ModelConfig {
                     name: Some(slug.clone()),
+                    pricing: codex_provider.pricing,
                     ..ModelConfig::default()
                 },
             });
@@ -543,6 +622,7 @@ impl Config {
                 provider_type: "copilot".to_string(),
                 config: ModelConfig {
                     name: Some(id.clone()),
+                    pricing: copilot_provider.pricing,
                     ..ModelConfig::default()
                 },
             });
@@ -26,11 +26,19 @@ impl InputState {
                 Some(k) => Action::MenuResult(MenuResult::ModelSelect(k)),
                 None => Action::Redraw,
             },
-            CompleterKind::Theme => match comp.selected_item().and_then(|i| i.ansi_color) {
-                Some(v) => {
-                    crate::theme::set_accent(v);
-                    Action::MenuResult(MenuResult::ThemeSelect(v))
+            CompleterKind::Theme => match comp.selected_item() {
+                Some(item) if item.extra.is_some() => {
+                    let name = item.extra.clone().unwrap_or_default();
+                    Action::MenuResult(MenuResult::ThemeFileSelect(name))
                 }
+                Some(item) => match item.ansi_color {
+                    Some(v) => {
+                        crate::theme::set_palette(None);
+                        crate::theme::set_accent(v);
+                        Action::MenuResult(MenuResult::ThemeSelect(v))
+                    }
+                    None => Action::Redraw,
+                },
                 None => Action::Redraw,
             },
             CompleterKind::Color => match comp.selected_item().and_then(|i| i.ansi_color) {
@@ -60,6 +68,7 @@ impl InputState {
             show_thinking: s("show_thinking"),
             restrict_to_workspace: s("restrict_to_workspace"),
             redact_secrets: s("redact_secrets"),
+            mouse: s("mouse"),
         };
         match key {
             "vim" => state.vim ^= true,
@@ -72,6 +81,7 @@ impl InputState {
             "show_thinking" => state.show_thinking ^= true,
             "restrict_to_workspace" => state.restrict_to_workspace ^= true,
             "redact_secrets" => state.redact_secrets ^= true,
+            "mouse" => state.mouse ^= true,
             _ => return Action::Redraw,
         }
         Action::MenuResult(MenuResult::Settings(state))
@@ -149,6 +159,7 @@ impl InputState {
                         if let Some(orig) = comp.original_value {
                             crate::theme::set_accent(orig);
                         }
+                        crate::theme::set_palette(comp.original_palette.clone());
                     }
                     CompleterKind::Color => {
                         if let Some(orig) = comp.original_value {
@@ -297,11 +308,26 @@ impl InputState {
         if let Some(comp) = &self.completer {
             if let Some(item) = comp.results.get(comp.selected) {
                 match comp.kind {
-                    CompleterKind::Theme => {
-                        if let Some(c) = item.ansi_color {
-                            crate::theme::set_accent(c);
+                    CompleterKind::Theme => match item.extra.as_deref() {
+                        Some(name) => {
+                            // Broken files keep the previous preview.
+                            if let Some(p) = comp.palettes.iter().find(|p| p.name == name) {
+                                let unchanged = crate::theme::palette()
+                                    .is_some_and(|active| std::sync::Arc::ptr_eq(&active, p));
+                                if !unchanged {
+                                    crate::theme::set_palette(Some(p.clone()));
+                                }
+                            }
                         }
-                    }
+                        None => {
+                            if crate::theme::palette().is_some() {
+                                crate::theme::set_palette(None);
+                            }
+                            if let Some(c) = item.ansi_color {
+                                crate::theme::set_accent(c);
+                            }
+                        }
+                    },
                     CompleterKind::Color => {
                         if let Some(c) = item.ansi_color {
                             crate::theme::set_slug_color(c);
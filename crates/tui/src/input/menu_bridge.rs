@@ -33,6 +33,7 @@ impl InputState {
         Some(match ms.kind {
             MenuKind::Stats { .. } => MenuResult::Stats,
             MenuKind::Cost { .. } => MenuResult::Cost,
+            MenuKind::Redactions { .. } => MenuResult::Redactions,
         })
     }
 
@@ -41,7 +42,7 @@ impl InputState {
         match &self.menu {
             Some(ms) => match &ms.kind {
                 MenuKind::Stats { left, right } => crate::metrics::stats_row_count(left, right),
-                MenuKind::Cost { lines } => lines.len(),
+                MenuKind::Cost { lines } | MenuKind::Redactions { lines } => lines.len(),
             },
             None => 0,
         }
@@ -84,6 +85,18 @@ impl InputState {
         });
     }
 
+    pub fn open_redactions(&mut self, lines: Vec<crate::metrics::StatsLine>) {
+        self.completer = None;
+        self.menu = Some(MenuState {
+            nav: Menu {
+                selected: 0,
+                len: 0,
+                select_on_enter: false,
+            },
+            kind: MenuKind::Redactions { lines },
+        });
+    }
+
     pub fn open_model_completer(&mut self, models: &[(String, String, String)]) {
         self.menu = None;
         self.history_saved_buf = Some((self.buf.clone(), self.cpos));
@@ -95,7 +108,15 @@ impl InputState {
     pub fn open_theme_completer(&mut self) {
         self.menu = None;
         self.history_saved_buf = Some((self.buf.clone(), self.cpos));
-        let mut comp = Completer::themes(crate::theme::accent_value());
+        let files = crate::theme::theme_file_names()
+            .into_iter()
+            .map(|name| {
+                let palette = crate::theme::load_palette(&name);
+                (name, palette)
+            })
+            .collect();
+        let active = crate::theme::palette().map(|p| p.name.clone());
+        let mut comp = Completer::themes(crate::theme::accent_value(), files, active.as_deref());
         comp.update_query(self.buf.clone());
         self.completer = Some(comp);
     }
@@ -65,6 +65,9 @@ pub struct InputState {
     /// Byte position of the selection anchor for shift+key selection (non-vim).
     /// When `Some`, selection spans from anchor to `cpos`.
     selection_anchor: Option<usize>,
+    /// Whether the current model accepts images. When false, pasted images
+    /// are refused instead of attached.
+    pub accepts_images: bool,
 }
 
 /// What the caller should do after `handle_event`.
@@ -107,6 +110,7 @@ impl InputState {
             pending_ctrl_x: false,
             command_arg_sources: Vec::new(),
             selection_anchor: None,
+            accepts_images: true,
         }
     }
 
@@ -388,6 +392,15 @@ impl InputState {
         self.insert_attachment_id(id);
     }
 
+    /// Attach a pasted image, or refuse it if the model cannot see images.
+    fn attach_image(&mut self, label: String, data_url: String) -> Action {
+        if !self.accepts_images {
+            return Action::NotifyError("the current model does not accept images".into());
+        }
+        self.insert_image(label, data_url);
+        Action::Redraw
+    }
+
     /// Build the message content combining text and any attached images.
     ///
     /// Images referenced multiple times in the buffer are emitted only once
@@ -470,6 +483,7 @@ impl InputState {
             KeyAction::CancelAgent => Action::Noop, // caller checks
             KeyAction::OpenHelp => Action::Noop,    // caller checks
             KeyAction::OpenHistorySearch => Action::Noop, // caller checks
+            KeyAction::SelectBlock => Action::Noop, // caller checks
             KeyAction::AcceptGhostText => Action::Noop, // caller checks
 
             // ── App control ─────────────────────────────────────────────
@@ -758,8 +772,7 @@ impl InputState {
             KeyAction::ClipboardImage => {
                 if let Some(url) = clipboard_image_to_data_url() {
                     self.save_undo();
-                    self.insert_image("clipboard.png".into(), url);
-                    Action::Redraw
+                    self.attach_image("clipboard.png".into(), url)
                 } else {
                     Action::Noop
                 }
@@ -851,8 +864,7 @@ impl InputState {
                     match engine::image::read_image_as_data_url(&path) {
                         Ok(url) => {
                             let label = engine::image::image_label_from_path(&path);
-                            self.insert_image(label, url);
-                            return Action::Redraw;
+                            return self.attach_image(label, url);
                         }
                         Err(e) => {
                             return Action::NotifyError(format!("cannot read image: {e}"));
@@ -862,8 +874,7 @@ impl InputState {
             }
             if data.trim().is_empty() {
                 if let Some(url) = clipboard_image_to_data_url() {
-                    self.insert_image("clipboard.png".into(), url);
-                    return Action::Redraw;
+                    return self.attach_image("clipboard.png".into(), url);
                 }
             }
             self.insert_paste(data);
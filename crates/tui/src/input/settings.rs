@@ -97,6 +97,9 @@ pub enum MenuKind {
     Cost {
         lines: Vec<crate::metrics::StatsLine>,
     },
+    Redactions {
+        lines: Vec<crate::metrics::StatsLine>,
+    },
 }
 
 pub struct MenuState {
@@ -109,8 +112,11 @@ pub enum MenuResult {
     Settings(SettingsState),
     ModelSelect(String),
     ThemeSelect(u8),
+    /// A theme file from the themes directory, by name.
+    ThemeFileSelect(String),
     ColorSelect(u8),
     Stats,
     Cost,
+    Redactions,
     Dismissed,
 }
@@ -23,6 +23,7 @@ pub enum KeyAction {
     ToggleStash,
     OpenHelp,
     OpenHistorySearch,
+    SelectBlock,
     PurgeRedraw,
     AcceptGhostText,
 
@@ -281,6 +282,7 @@ static BINDINGS: &[Binding] = &[
         KeyAction::OpenHistorySearch,
     ),
     bind(KeyCode::Char('l'), CTRL, when(), KeyAction::PurgeRedraw),
+    bind(KeyCode::Char('o'), CTRL, when(), KeyAction::SelectBlock),
     bind(
         KeyCode::Char('?'),
         NONE,
@@ -640,8 +642,15 @@ pub mod hints {
     }
     pub const DD_PENDING: &str = "press d to confirm delete";
     pub const KILL_PROC: &str = "\u{232b}: kill selected";
+    pub const STOP_AGENT: &str = "\u{232b}: stop";
+    pub const MESSAGE_AGENT: &str = "enter: message";
+    pub const APPROVE_DENY: &str = "y/n: approve/deny";
     pub const BACK: &str = "esc: back";
     pub const NEXT_Q: &str = "tab: next question";
+    pub const EDIT_ITEM: &str = "enter: edit";
+    pub const SAVE: &str = "enter: save";
+    pub const MOVE_ITEM: &str = "shift+\u{2191}/\u{2193}: move";
+    pub const TOGGLE_STEER: &str = "s: steer/next turn";
 
     /// Build a hint line from fragments.
     pub fn join(parts: &[&str]) -> String {
@@ -673,6 +682,7 @@ pub mod hints {
         ("ctrl+r", "search input history"),
         ("ctrl+t", "cycle reasoning effort"),
         ("ctrl+s", "stash / unstash input"),
+        ("ctrl+o", "select a tool / thinking block to expand"),
         (
             "shift+tab",
             "cycle mode  (normal \u{2192} plan \u{2192} apply \u{2192} yolo)",
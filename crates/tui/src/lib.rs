@@ -12,6 +12,7 @@ pub mod keymap;
 pub mod metrics;
 pub mod perf;
 pub mod persist;
+pub mod queue;
 pub mod render;
 pub mod session;
 pub mod sleep_inhibit;
@@ -30,6 +30,22 @@ pub struct MetricsEntry {
     pub cache_write_tokens: Option<u32>,
     #[serde(default)]
     pub reasoning_tokens: Option<u32>,
+    /// Working directory the call was made from. Absent in old entries.
+    #[serde(default)]
+    pub project: Option<String>,
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// Provider type (`openai`, `anthropic`, ...).
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub mode: Option<String>,
+    /// 0 for the main agent, 1+ for subagents.
+    #[serde(default)]
+    pub agent_depth: Option<u8>,
+    /// Id of the agent that made the call.
+    #[serde(default)]
+    pub agent_id: Option<String>,
 }
 
 fn metrics_path() -> PathBuf {
@@ -79,6 +95,16 @@ fn day_key(ms: u64) -> u64 {
     ms / (24 * 3600 * 1000)
 }
 
+/// Total cost of entries recorded since midnight UTC. Seeds the daily budget.
+pub fn spent_today(entries: &[MetricsEntry]) -> f64 {
+    let today = day_key(now_ms());
+    entries
+        .iter()
+        .filter(|e| day_key(e.timestamp_ms) == today)
+        .filter_map(|e| e.cost_usd)
+        .sum()
+}
+
 fn hour_key(ms: u64) -> u64 {
     ms / (3600 * 1000)
 }
@@ -150,6 +176,200 @@ fn aggregate(entries: &[MetricsEntry]) -> Stats {
     stats
 }
 
+// ── Export ──────────────────────────────────────────────────────────────────
+
+/// Dimension for [`group_cost`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    Project,
+    Model,
+    Day,
+    /// The main agent vs each subagent.
+    Agent,
+}
+
+impl GroupBy {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Project => "project",
+            Self::Model => "model",
+            Self::Day => "day",
+            Self::Agent => "agent",
+        }
+    }
+
+    fn key(self, e: &MetricsEntry) -> String {
+        match self {
+            Self::Project => e.project.clone().unwrap_or_else(|| "(unknown)".into()),
+            Self::Model => e.model.clone(),
+            Self::Day => format_day(e.timestamp_ms),
+            Self::Agent => match (e.agent_depth, &e.agent_id) {
+                (Some(d), Some(id)) if d > 0 => id.clone(),
+                _ => "main".into(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CostGroup {
+    pub key: String,
+    pub calls: usize,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost_usd: f64,
+}
+
+/// Totals per group. Days sort chronologically, everything else by cost,
+/// highest first.
+pub fn group_cost(entries: &[MetricsEntry], by: GroupBy) -> Vec<CostGroup> {
+    let mut groups: BTreeMap<String, CostGroup> = BTreeMap::new();
+    for e in entries {
+        let key = by.key(e);
+        let g = groups.entry(key.clone()).or_insert(CostGroup {
+            key,
+            calls: 0,
+            prompt_tokens: 0,
+            completion_tokens: 0,
+            cost_usd: 0.0,
+        });
+        g.calls += 1;
+        g.prompt_tokens += e.prompt_tokens as u64;
+        g.completion_tokens += e.completion_tokens as u64;
+        g.cost_usd += e.cost_usd.unwrap_or(0.0);
+    }
+    let mut out: Vec<CostGroup> = groups.into_values().collect();
+    if by != GroupBy::Day {
+        out.sort_by(|a, b| b.cost_usd.total_cmp(&a.cost_usd));
+    }
+    out
+}
+
+/// Entries recorded in the last `days` days (today counts as one).
+pub fn since_days(entries: Vec<MetricsEntry>, days: u32) -> Vec<MetricsEntry> {
+    let first = (day_key(now_ms()) + 1).saturating_sub(days as u64);
+    entries
+        .into_iter()
+        .filter(|e| day_key(e.timestamp_ms) >= first)
+        .collect()
+}
+
+/// `YYYY-MM-DD` (UTC) for a millisecond timestamp.
+pub fn format_day(ms: u64) -> String {
+    // Civil-from-days, after Howard Hinnant's date algorithms.
+    let z = day_key(ms) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn csv_row(fields: &[String]) -> String {
+    let mut row = fields
+        .iter()
+        .map(|f| csv_field(f))
+        .collect::<Vec<_>>()
+        .join(",");
+    row.push('\n');
+    row
+}
+
+pub fn groups_csv(groups: &[CostGroup], by: GroupBy) -> String {
+    let mut out = csv_row(
+        &[
+            by.label(),
+            "calls",
+            "prompt_tokens",
+            "completion_tokens",
+            "cost_usd",
+        ]
+        .map(String::from),
+    );
+    for g in groups {
+        out.push_str(&csv_row(&[
+            g.key.clone(),
+            g.calls.to_string(),
+            g.prompt_tokens.to_string(),
+            g.completion_tokens.to_string(),
+            format!("{:.6}", g.cost_usd),
+        ]));
+    }
+    out
+}
+
+pub fn groups_json(groups: &[CostGroup]) -> String {
+    serde_json::to_string_pretty(groups).unwrap_or_else(|_| "[]".into())
+}
+
+/// One JSON object per line, in the same shape as `metrics.jsonl`.
+pub fn entries_jsonl(entries: &[MetricsEntry]) -> String {
+    let mut out = String::new();
+    for e in entries {
+        if let Ok(line) = serde_json::to_string(e) {
+            out.push_str(&line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+pub fn entries_csv(entries: &[MetricsEntry]) -> String {
+    let opt = |v: Option<u32>| v.map(|n| n.to_string()).unwrap_or_default();
+    let mut out = csv_row(
+        &[
+            "timestamp_ms",
+            "day",
+            "project",
+            "session_id",
+            "provider",
+            "model",
+            "mode",
+            "agent_depth",
+            "agent_id",
+            "prompt_tokens",
+            "completion_tokens",
+            "cache_read_tokens",
+            "cache_write_tokens",
+            "reasoning_tokens",
+            "cost_usd",
+        ]
+        .map(String::from),
+    );
+    for e in entries {
+        out.push_str(&csv_row(&[
+            e.timestamp_ms.to_string(),
+            format_day(e.timestamp_ms),
+            e.project.clone().unwrap_or_default(),
+            e.session_id.clone().unwrap_or_default(),
+            e.provider.clone().unwrap_or_default(),
+            e.model.clone(),
+            e.mode.clone().unwrap_or_default(),
+            e.agent_depth.map(|d| d.to_string()).unwrap_or_default(),
+            e.agent_id.clone().unwrap_or_default(),
+            e.prompt_tokens.to_string(),
+            e.completion_tokens.to_string(),
+            opt(e.cache_read_tokens),
+            opt(e.cache_write_tokens),
+            opt(e.reasoning_tokens),
+            e.cost_usd.map(|c| format!("{c:.6}")).unwrap_or_default(),
+        ]));
+    }
+    out
+}
+
 // ── Structured output for the renderer ──────────────────────────────────────
 
 pub enum StatsLine {
@@ -392,6 +612,7 @@ pub fn render_session_cost(
     cost_usd: f64,
     model: &str,
     turns: usize,
+    usage: &protocol::TokenUsage,
     resolved: &engine::pricing::ResolvedPricing,
 ) -> Vec<StatsLine> {
     let mut lines = Vec::new();
@@ -416,6 +637,28 @@ pub fn render_session_cost(
     });
     lines.push(StatsLine::Blank);
 
+    let cache_read = usage.cache_read_tokens.unwrap_or(0);
+    let cache_write = usage.cache_write_tokens.unwrap_or(0);
+    if let Some(rate) = usage
+        .cache_hit_rate()
+        .filter(|_| cache_read + cache_write > 0)
+    {
+        lines.push(StatsLine::Heading("prompt cache".into()));
+        lines.push(StatsLine::Kv {
+            label: "hit rate".into(),
+            value: format!("{:.0}%", rate * 100.0),
+        });
+        lines.push(StatsLine::Kv {
+            label: "read".into(),
+            value: fmt(cache_read as u64),
+        });
+        lines.push(StatsLine::Kv {
+            label: "written".into(),
+            value: fmt(cache_write as u64),
+        });
+        lines.push(StatsLine::Blank);
+    }
+
     let fmt_rate = |rate: f64| -> String {
         if rate == 0.0 {
             return "—".into();
@@ -449,6 +692,56 @@ pub fn render_session_cost(
                 value: fmt_rate(pricing.cache_write),
             });
         }
+        if pricing.per_request > 0.0 {
+            lines.push(StatsLine::Kv {
+                label: "per request".into(),
+                value: format_cost(pricing.per_request),
+            });
+        }
+    }
+    if !resolved.rule.is_empty() {
+        lines.push(StatsLine::Kv {
+            label: "rule".into(),
+            value: resolved.rule.label(),
+        });
+    }
+    lines
+}
+
+/// Render the `/redactions` report: counts per pattern, then where each
+/// secret was found. Only labels and locations are shown, never the values.
+pub fn render_redactions(redactions: &[(String, protocol::Redaction)]) -> Vec<StatsLine> {
+    let mut lines = Vec::new();
+    lines.push(StatsLine::Heading("redactions".into()));
+    lines.push(StatsLine::Kv {
+        label: "total".into(),
+        value: redactions.len().to_string(),
+    });
+    lines.push(StatsLine::Blank);
+
+    let mut by_label: Vec<(&str, usize)> = Vec::new();
+    for (_, r) in redactions {
+        match by_label.iter_mut().find(|(l, _)| *l == r.label) {
+            Some((_, n)) => *n += 1,
+            None => by_label.push((&r.label, 1)),
+        }
+    }
+    by_label.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    lines.push(StatsLine::Heading("by pattern".into()));
+    for (label, n) in by_label {
+        lines.push(StatsLine::Kv {
+            label: label.to_string(),
+            value: n.to_string(),
+        });
+    }
+    lines.push(StatsLine::Blank);
+
+    lines.push(StatsLine::Heading("where".into()));
+    for (source, r) in redactions {
+        lines.push(StatsLine::Kv {
+            label: source.clone(),
+            value: format!("line {}  {}", r.line, r.label),
+        });
     }
     lines
 }
@@ -462,3 +755,104 @@ fn fmt(n: u64) -> String {
         n.to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(day: u64, model: &str, project: &str, depth: u8, cost: f64) -> MetricsEntry {
+        MetricsEntry {
+            timestamp_ms: day * 24 * 3600 * 1000,
+            prompt_tokens: 10,
+            completion_tokens: 5,
+            model: model.into(),
+            cost_usd: Some(cost),
+            cache_read_tokens: None,
+            cache_write_tokens: None,
+            reasoning_tokens: None,
+            project: Some(project.into()),
+            session_id: None,
+            provider: None,
+            mode: None,
+            agent_depth: Some(depth),
+            agent_id: Some(format!("agent-{depth}")),
+        }
+    }
+
+    #[test]
+    fn groups_by_each_dimension() {
+        let entries = vec![
+            entry(0, "a", "/p1", 0, 1.0),
+            entry(1, "b", "/p2", 1, 3.0),
+            entry(1, "a", "/p1", 0, 0.5),
+        ];
+        let by_project = group_cost(&entries, GroupBy::Project);
+        assert_eq!(by_project[0].key, "/p2");
+        assert_eq!(by_project[1].calls, 2);
+        assert_eq!(by_project[1].prompt_tokens, 20);
+
+        let by_day = group_cost(&entries, GroupBy::Day);
+        let days: Vec<&str> = by_day.iter().map(|g| g.key.as_str()).collect();
+        assert_eq!(days, ["1970-01-01", "1970-01-02"]);
+
+        let by_agent = group_cost(&entries, GroupBy::Agent);
+        assert_eq!(by_agent[0].key, "agent-1");
+        assert_eq!(by_agent[1].key, "main");
+    }
+
+    #[test]
+    fn format_day_handles_leap_years() {
+        // 2024-02-29 is day 19782.
+        assert_eq!(format_day(19_782 * 24 * 3600 * 1000), "2024-02-29");
+        assert_eq!(format_day(19_783 * 24 * 3600 * 1000 - 1), "2024-02-29");
+    }
+
+    #[test]
+    fn csv_quotes_fields() {
+        let mut e = entry(0, "m", "/a,b", 0, 0.25);
+        e.agent_id = Some("say \"hi\"".into());
+        let csv = entries_csv(&[e]);
+        let row = csv.lines().nth(1).unwrap();
+        assert!(row.contains("\"/a,b\""));
+        assert!(row.contains("\"say \"\"hi\"\"\""));
+        assert!(row.ends_with("0.250000"));
+    }
+
+    #[test]
+    fn session_cost_reports_cache_hit_rate() {
+        let usage = protocol::TokenUsage {
+            prompt_tokens: Some(100),
+            cache_read_tokens: Some(300),
+            cache_write_tokens: Some(100),
+            ..Default::default()
+        };
+        let resolved =
+            engine::pricing::resolve("m", "openai-compatible", &engine::ModelConfig::default());
+        let lines = render_session_cost(0.0, "m", 1, &usage, &resolved);
+        assert!(lines.iter().any(|l| matches!(
+            l,
+            StatsLine::Kv { label, value } if label == "hit rate" && value == "60%"
+        )));
+    }
+
+    #[test]
+    fn redactions_count_by_label() {
+        let r = |label: &str, line| protocol::Redaction {
+            label: label.into(),
+            line,
+        };
+        let lines = render_redactions(&[
+            ("user message".into(), r("aws_key", 1)),
+            ("bash result".into(), r("employee_id", 4)),
+            ("bash result".into(), r("employee_id", 9)),
+        ]);
+        assert!(lines.iter().any(|l| matches!(
+            l,
+            StatsLine::Kv { label, value } if label == "employee_id" && value == "2"
+        )));
+        assert!(lines.iter().any(|l| matches!(
+            l,
+            StatsLine::Kv { label, value } if label == "bash result" && value == "line 9  employee_id"
+        )));
+    }
+}
@@ -0,0 +1,211 @@
+//! Messages typed while the agent is busy.
+//!
+//! Each queued message either waits to start the next turn or steers the
+//! running one. Steer messages are sent to the engine as `Steer` commands
+//! tagged with the message id but stay in the queue until the matching
+//! `Steered` event arrives, so they can still be edited, reordered or
+//! withdrawn from the queue view. [`MessageQueue::sync`] computes the
+//! commands that bring the engine's pending steers back in line with the
+//! queue; the engine ignores cancels and re-sends for ids it already
+//! consumed, so a late edit can never inject a message twice.
+
+use protocol::UiCommand;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedMessage {
+    pub id: u64,
+    pub text: String,
+    /// Inject into the running turn instead of waiting for the next one.
+    pub steer: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct MessageQueue {
+    items: Vec<QueuedMessage>,
+    next_id: u64,
+    /// Steer messages as last sent to the engine, in send order.
+    sent: Vec<(u64, String)>,
+}
+
+impl MessageQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a message and return its id.
+    pub fn push(&mut self, text: String, steer: bool) -> u64 {
+        self.next_id += 1;
+        let id = self.next_id;
+        self.items.push(QueuedMessage { id, text, steer });
+        id
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn items(&self) -> &[QueuedMessage] {
+        &self.items
+    }
+
+    pub fn texts(&self) -> Vec<String> {
+        self.items.iter().map(|m| m.text.clone()).collect()
+    }
+
+    /// Remove and return the oldest message to start a turn with.
+    pub fn pop_front(&mut self) -> Option<String> {
+        if self.items.is_empty() {
+            return None;
+        }
+        Some(self.items.remove(0).text)
+    }
+
+    /// Remove every message and return their texts in queue order.
+    pub fn take_all(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.items)
+            .into_iter()
+            .map(|m| m.text)
+            .collect()
+    }
+
+    /// Drop every message. Steers already sent are withdrawn by the next
+    /// [`sync`](Self::sync).
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    /// The engine injected the steer with `id` into the running turn.
+    pub fn consumed(&mut self, id: u64) {
+        self.items.retain(|m| m.id != id);
+        self.sent.retain(|(sid, _)| *sid != id);
+    }
+
+    /// The turn ended: the engine discarded any steers it did not consume,
+    /// so they are re-sent once the next turn is running.
+    pub fn turn_ended(&mut self) {
+        self.sent.clear();
+    }
+
+    /// Replace the queue with the list edited in the queue view. Messages
+    /// that were consumed while the view was open are not resurrected.
+    pub fn replace(&mut self, edited: Vec<QueuedMessage>) {
+        let live: Vec<u64> = self.items.iter().map(|m| m.id).collect();
+        self.items = edited
+            .into_iter()
+            .filter(|m| live.contains(&m.id) && !m.text.trim().is_empty())
+            .collect();
+    }
+
+    /// Commands that make the engine's pending steers match the queue.
+    /// Only call while a turn is running. The longest unchanged prefix is
+    /// left alone; everything after it is withdrawn and re-sent in order.
+    pub fn sync(&mut self) -> Vec<UiCommand> {
+        let desired: Vec<(u64, String)> = self
+            .items
+            .iter()
+            .filter(|m| m.steer)
+            .map(|m| (m.id, m.text.clone()))
+            .collect();
+        if desired == self.sent {
+            return Vec::new();
+        }
+        let keep = self
+            .sent
+            .iter()
+            .zip(&desired)
+            .take_while(|(a, b)| a == b)
+            .count();
+        let mut cmds: Vec<UiCommand> = self.sent[keep..]
+            .iter()
+            .map(|(id, _)| UiCommand::CancelSteer { id: *id })
+            .collect();
+        cmds.extend(desired[keep..].iter().map(|(id, text)| UiCommand::Steer {
+            text: text.clone(),
+            id: *id,
+        }));
+        self.sent = desired;
+        cmds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn describe(cmds: &[UiCommand]) -> Vec<String> {
+        cmds.iter()
+            .map(|c| match c {
+                UiCommand::Steer { text, id } => format!("steer {id} {text}"),
+                UiCommand::CancelSteer { id } => format!("cancel {id}"),
+                _ => "other".into(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sync_sends_only_new_steers() {
+        let mut q = MessageQueue::new();
+        q.push("later".into(), false);
+        let a = q.push("a".into(), true);
+        assert_eq!(describe(&q.sync()), vec![format!("steer {a} a")]);
+        let b = q.push("b".into(), true);
+        assert_eq!(describe(&q.sync()), vec![format!("steer {b} b")]);
+        assert!(q.sync().is_empty());
+    }
+
+    #[test]
+    fn editing_resends_from_the_changed_message() {
+        let mut q = MessageQueue::new();
+        let a = q.push("a".into(), true);
+        let b = q.push("b".into(), true);
+        q.sync();
+        let mut items = q.items().to_vec();
+        items.swap(0, 1);
+        items[1].text = "a2".into();
+        q.replace(items);
+        assert_eq!(
+            describe(&q.sync()),
+            vec![
+                format!("cancel {a}"),
+                format!("cancel {b}"),
+                format!("steer {b} b"),
+                format!("steer {a} a2"),
+            ]
+        );
+    }
+
+    #[test]
+    fn retargeting_to_next_turn_withdraws_the_steer() {
+        let mut q = MessageQueue::new();
+        let a = q.push("a".into(), true);
+        q.sync();
+        let mut items = q.items().to_vec();
+        items[0].steer = false;
+        q.replace(items);
+        assert_eq!(describe(&q.sync()), vec![format!("cancel {a}")]);
+        assert_eq!(q.texts(), vec!["a".to_string()]);
+    }
+
+    #[test]
+    fn replace_drops_consumed_and_emptied_messages() {
+        let mut q = MessageQueue::new();
+        let a = q.push("a".into(), true);
+        q.push("b".into(), false);
+        q.sync();
+        let mut items = q.items().to_vec();
+        q.consumed(a);
+        items[1].text = "  ".into();
+        q.replace(items);
+        assert!(q.is_empty());
+        assert!(q.sync().is_empty());
+    }
+
+    #[test]
+    fn turn_end_resends_unconsumed_steers() {
+        let mut q = MessageQueue::new();
+        let a = q.push("a".into(), true);
+        q.sync();
+        q.turn_ended();
+        assert_eq!(describe(&q.sync()), vec![format!("steer {a} a")]);
+    }
+}
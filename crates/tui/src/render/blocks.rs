@@ -44,9 +44,15 @@ pub(super) fn layout_block(
     ctx: &LayoutContext,
 ) -> DisplayBlock {
     let width = ctx.width as usize;
-    let show_thinking = ctx.show_thinking;
     let mut col = SpanCollector::new(ctx.width);
-    render_block(&mut col, block, state, width, show_thinking);
+    render_block(
+        &mut col,
+        block,
+        state,
+        width,
+        ctx.show_thinking,
+        ctx.expanded,
+    );
     col.finish()
 }
 
@@ -208,6 +214,7 @@ pub(super) fn render_block<S: LayoutSink>(
     state: Option<&ToolState>,
     width: usize,
     show_thinking: bool,
+    expanded: bool,
 ) -> u16 {
     let _perf = match block {
         Block::User { .. } => crate::perf::begin("render:user"),
@@ -294,7 +301,8 @@ pub(super) fn render_block<S: LayoutSink>(
             rows
         }
         Block::Thinking { content } => {
-            if !show_thinking {
+            // A toggled thinking block shows the opposite of the default.
+            if show_thinking == expanded {
                 let (label, line_count) = thinking_summary(content);
                 return render_thinking_summary(out, width, &label, line_count, false);
             }
@@ -337,6 +345,7 @@ pub(super) fn render_block<S: LayoutSink>(
                 state.output.as_deref(),
                 state.user_message.as_deref(),
                 width,
+                expanded,
             )
         }
         Block::Confirm { tool, desc, choice } => {
@@ -380,7 +389,7 @@ pub(super) fn render_block<S: LayoutSink>(
             out.newline();
             let mut rows = 1u16;
             if !output.is_empty() {
-                rows += render_wrapped_output(out, output, false, width);
+                rows += render_wrapped_output(out, output, false, width, false);
             }
             rows
         }
@@ -548,6 +557,7 @@ pub(super) fn render_tool<S: LayoutSink>(
     output: Option<&ToolOutput>,
     user_message: Option<&str>,
     width: usize,
+    expanded: bool,
 ) -> u16 {
     let color: ColorValue = match status {
         ToolStatus::Ok => theme::SUCCESS.into(),
@@ -574,7 +584,16 @@ pub(super) fn render_tool<S: LayoutSink>(
     } else {
         None
     };
-    let mut rows = print_tool_line(out, name, summary, color, time, tl.as_deref(), width);
+    let mut rows = print_tool_line(
+        out,
+        name,
+        summary,
+        color,
+        time,
+        tl.as_deref(),
+        width,
+        expanded,
+    );
     if name == "web_fetch" {
         if let Some(prompt) = args.get("prompt").and_then(|v| v.as_str()) {
             let segs = wrap_line(prompt, width.saturating_sub(4));
@@ -596,7 +615,7 @@ pub(super) fn render_tool<S: LayoutSink>(
     if status != ToolStatus::Denied {
         if let Some(out_data) = output {
             if !out_data.content.is_empty() {
-                rows += print_tool_output(out, name, out_data, args, width);
+                rows += print_tool_output(out, name, out_data, args, width, expanded);
             }
         }
     }
@@ -692,6 +711,7 @@ fn tool_line_layout(name: &str, suffix_len: usize, width: usize) -> ToolLineLayo
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn print_tool_line<S: LayoutSink>(
     out: &mut S,
     name: &str,
@@ -700,6 +720,7 @@ fn print_tool_line<S: LayoutSink>(
     elapsed: Option<Duration>,
     timeout_label: Option<&str>,
     width: usize,
+    expanded: bool,
 ) -> u16 {
     out.print(" ");
     out.push_fg(pill_color);
@@ -728,7 +749,8 @@ fn print_tool_line<S: LayoutSink>(
             wrapped.extend(segs);
         }
         let total = wrapped.len();
-        let show = total.min(MAX_TOOL_BLOCK_ROWS);
+        let cap = if expanded { total } else { MAX_TOOL_BLOCK_ROWS };
+        let show = total.min(cap);
         let mut line_num = 0;
         let mut bh = BashHighlighter::new();
 
@@ -749,8 +771,8 @@ fn print_tool_line<S: LayoutSink>(
             line_num += 1;
         }
 
-        if total > MAX_TOOL_BLOCK_ROWS {
-            let skipped = total - MAX_TOOL_BLOCK_ROWS;
+        if total > cap {
+            let skipped = total - cap;
             out.print_string(" ".repeat(ly.prefix_len));
             print_dim(
                 out,
@@ -827,9 +849,19 @@ fn print_tool_output<S: LayoutSink>(
     output: &ToolOutput,
     args: &HashMap<String, serde_json::Value>,
     width: usize,
+    expanded: bool,
 ) -> u16 {
     let content = &output.content;
     let is_error = output.is_error;
+    // Expanded listings show the raw result instead of a count/preview.
+    if expanded
+        && matches!(
+            name,
+            "web_search" | "read_file" | "glob" | "grep" | "web_fetch" | "peek_agent"
+        )
+    {
+        return render_wrapped_output(out, content, is_error, width, true);
+    }
     match name {
         "web_search" if !is_error => {
             let mut count = 0u16;
@@ -866,9 +898,9 @@ fn print_tool_output<S: LayoutSink>(
         "ask_user_question" if !is_error => render_question_output(out, content, width),
         "exit_plan_mode" if !is_error => render_plan_output(out, args, width),
         "bash" | "read_process_output" | "stop_process" => {
-            render_wrapped_output(out, content, is_error, width)
+            render_wrapped_output(out, content, is_error, width, expanded)
         }
-        "peek_agent" if !is_error => render_wrapped_output(out, content, false, width),
+        "peek_agent" if !is_error => render_wrapped_output(out, content, false, width, false),
         "list_agents" | "message_agent" | "stop_agent" | "spawn_agent" if !is_error => {
             let mut rows = 0u16;
             for line in content.lines() {
@@ -878,6 +910,7 @@ fn print_tool_output<S: LayoutSink>(
             }
             rows.max(1)
         }
+        _ if expanded => render_wrapped_output(out, content, is_error, width, true),
         _ => render_default_output(out, content, is_error, width),
     }
 }
@@ -1363,11 +1396,14 @@ fn render_plan_output<S: LayoutSink>(
     rows
 }
 
+/// Print tool output as dim (or error-colored) wrapped rows. Unless
+/// `expanded`, only the last `MAX_TOOL_BLOCK_ROWS` rows are shown.
 fn render_wrapped_output<S: LayoutSink>(
     out: &mut S,
     content: &str,
     is_error: bool,
     width: usize,
+    expanded: bool,
 ) -> u16 {
     let _perf = crate::perf::begin("render:wrapped_output");
     let max_cols = width.saturating_sub(4); // "   " prefix + 1 margin
@@ -1386,9 +1422,10 @@ fn render_wrapped_output<S: LayoutSink>(
         .collect();
 
     let total = wrapped.len();
+    let cap = if expanded { total } else { MAX_TOOL_BLOCK_ROWS };
     let mut rows = 0u16;
-    if total > MAX_TOOL_BLOCK_ROWS {
-        let skipped = total - MAX_TOOL_BLOCK_ROWS;
+    if total > cap {
+        let skipped = total - cap;
         print_dim(
             out,
             &format!("   ... {} above", pluralize(skipped, "line", "lines")),
@@ -1396,7 +1433,7 @@ fn render_wrapped_output<S: LayoutSink>(
         out.newline();
         rows += 1;
     }
-    let start = total.saturating_sub(MAX_TOOL_BLOCK_ROWS);
+    let start = total.saturating_sub(cap);
     for seg in &wrapped[start..] {
         if is_error {
             out.push_fg(theme::ERROR.into());
@@ -1557,7 +1594,7 @@ pub(super) fn render_active_exec<S: LayoutSink>(
     let mut rows = 1u16;
 
     if !exec.output.is_empty() {
-        rows += render_wrapped_output(out, &exec.output, false, width);
+        rows += render_wrapped_output(out, &exec.output, false, width, false);
     }
     rows
 }
@@ -1612,7 +1649,7 @@ mod tests {
     fn block_rows(block: &Block) -> u16 {
         let mut out = SpanCollector::new(W as u16);
         let st = state_for(block);
-        render_block(&mut out, block, st.as_ref(), W, true)
+        render_block(&mut out, block, st.as_ref(), W, true, false)
     }
 
     /// Compute total gap rows between the last history block and an active tool.
@@ -1637,7 +1674,7 @@ mod tests {
             };
             let rows = {
                 let st = state_for(&blocks[i]);
-                render_block(&mut out, &blocks[i], st.as_ref(), W, true)
+                render_block(&mut out, &blocks[i], st.as_ref(), W, true, false)
             };
             total += gap + rows;
         }
@@ -1662,7 +1699,7 @@ mod tests {
             };
             let rows = {
                 let st = state_for(&blocks[i]);
-                render_block(&mut out, &blocks[i], st.as_ref(), W, true)
+                render_block(&mut out, &blocks[i], st.as_ref(), W, true, false)
             };
             block_rows_total += gap + rows;
         }
@@ -1701,7 +1738,7 @@ mod tests {
             };
             let rows = {
                 let st = state_for(&blocks[i]);
-                render_block(&mut out, &blocks[i], st.as_ref(), W, true)
+                render_block(&mut out, &blocks[i], st.as_ref(), W, true, false)
             };
             cumulative += gap + rows;
         }
@@ -1881,7 +1918,7 @@ mod tests {
                 };
                 let rows = {
                     let st = state_for(&blocks[i]);
-                    render_block(&mut out, &blocks[i], st.as_ref(), W, true)
+                    render_block(&mut out, &blocks[i], st.as_ref(), W, true, false)
                 };
                 frame_block_rows += gap + rows;
             }
@@ -39,14 +39,19 @@ pub struct PersistedLayoutCache {
     /// stale syntax-highlight colors, so we drop the cache on mismatch.
     #[serde(default)]
     pub is_light: bool,
+    /// Syntax theme at the time of capture; a theme file can pick one
+    /// independently of light/dark mode.
+    #[serde(default)]
+    pub syntax_theme: String,
     pub blocks: HashMap<BlockId, BlockArtifact>,
 }
 
 impl PersistedLayoutCache {
-    pub fn new(is_light: bool) -> Self {
+    pub fn new(is_light: bool, syntax_theme: &str) -> Self {
         Self {
             version: LAYOUT_CACHE_VERSION,
             is_light,
+            syntax_theme: syntax_theme.to_string(),
             blocks: HashMap::new(),
         }
     }
@@ -77,16 +82,18 @@ impl PersistedLayoutCache {
     }
 
     /// Compatible iff version matches AND the persisted light/dark mode
-    /// matches current detection. Mismatched modes mean stale syntect
-    /// colors throughout the cache.
-    pub fn is_compatible(&self, current_is_light: bool) -> bool {
-        self.version == LAYOUT_CACHE_VERSION && self.is_light == current_is_light
+    /// and syntax theme match the current ones. A mismatch means stale
+    /// syntect colors throughout the cache.
+    pub fn is_compatible(&self, current_is_light: bool, current_syntax: &str) -> bool {
+        self.version == LAYOUT_CACHE_VERSION
+            && self.is_light == current_is_light
+            && self.syntax_theme == current_syntax
     }
 }
 
 impl Default for PersistedLayoutCache {
     fn default() -> Self {
-        Self::new(false)
+        Self::new(false, "")
     }
 }
 
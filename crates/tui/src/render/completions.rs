@@ -105,8 +105,10 @@ pub(super) fn draw_completions(
         if is_color_picker {
             out.print("  ");
             if selected {
-                let ansi = item.ansi_color.unwrap_or(theme::accent_value());
-                out.push_fg(Color::AnsiValue(ansi));
+                // Theme files have no swatch; the live preview makes
+                // `accent()` theirs.
+                let color = item.ansi_color.map_or_else(theme::accent, Color::AnsiValue);
+                out.push_fg(color);
                 out.print(&format!("● {}", label));
                 out.pop_style();
             } else {
@@ -184,7 +186,9 @@ pub(super) fn draw_menu(
     if let crate::input::MenuKind::Stats { left, right } = &ms.kind {
         return draw_stats(out, left, right, max_rows);
     }
-    if let crate::input::MenuKind::Cost { lines } = &ms.kind {
+    if let crate::input::MenuKind::Cost { lines } | crate::input::MenuKind::Redactions { lines } =
+        &ms.kind
+    {
         return draw_stats_sequential(out, lines, 0, max_rows);
     }
     0
@@ -364,6 +368,7 @@ mod tests {
             show_thinking: true,
             restrict_to_workspace: false,
             redact_secrets: true,
+            mouse: false,
         };
         let mut comp = crate::completer::Completer::settings(&state);
         comp.update_query("zzzzzz".into());
@@ -397,6 +402,7 @@ mod tests {
             show_thinking: true,
             restrict_to_workspace: false,
             redact_secrets: true,
+            mouse: false,
         };
         let mut comp = crate::completer::Completer::settings(&state);
         let rows_before = completion_reserved_rows(Some(&comp));
@@ -4,11 +4,15 @@ use crate::theme::Theme;
 
 /// Settings that flow through the layout stage. Layout produces a
 /// theme-independent `DisplayBlock` so the only width-relevant inputs
-/// are the terminal width and whether thinking blocks are expanded.
+/// are the terminal width, whether thinking blocks are expanded, and
+/// whether the user toggled this particular block.
 #[derive(Debug, Clone, Copy)]
 pub struct LayoutContext {
     pub width: u16,
     pub show_thinking: bool,
+    /// Per-block toggle: flips thinking visibility against
+    /// `show_thinking` and lifts the row cap on tool output.
+    pub expanded: bool,
 }
 
 /// Context for the paint stage. Carries the active theme snapshot so
@@ -1,23 +1,30 @@
-use crate::app::AgentToolEntry;
+use crate::app::{AgentPermission, AgentToolEntry, AgentTranscriptEntry};
 use crate::keymap::{hints, nav_lookup, NavAction};
-use crate::render::{draw_bar, wrap_line};
+use crate::render::{draw_bar, wrap_line, ToolStatus};
 use crate::utils::format_duration;
 use crossterm::event::{KeyCode, KeyModifiers};
 use crossterm::terminal;
 use engine::registry::{AgentStatus, RegistryEntry};
+use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc::UnboundedSender;
 
-use super::{end_dialog_draw, truncate_str, DialogResult, ListState, RenderOut};
+use super::{
+    begin_dialog_draw, end_dialog_draw, finish_dialog_frame, truncate_str, DialogResult, RenderOut,
+    TextArea,
+};
 
-/// Minimum number of detail content lines the dialog will show, even
-/// on a very short terminal. Below this it would be unreadable.
-const MIN_DETAIL_LINES: usize = 3;
+/// Minimum number of transcript rows the dashboard will show, even on a
+/// very short terminal. Below this it would be unreadable.
+const MIN_PANE_ROWS: usize = 3;
 
-/// Maximum detail content lines for an agent at the given terminal
-/// height. Caps at half the terminal minus the chrome, with a floor
-/// of `MIN_DETAIL_LINES` so the dialog stays usable on short screens.
-fn detail_max_lines(term_h: usize) -> usize {
-    (term_h / 2).saturating_sub(5).max(MIN_DETAIL_LINES)
+/// Bar, header, blank line and hints.
+const CHROME_ROWS: usize = 4;
+
+/// Transcript rows for the given terminal height: half the terminal minus
+/// the chrome, with a floor of `MIN_PANE_ROWS`.
+fn pane_max_rows(term_h: usize) -> usize {
+    (term_h / 2).saturating_sub(5).max(MIN_PANE_ROWS)
 }
 
 /// Snapshot of a tracked agent's state, passed to the dialog for rendering.
@@ -25,46 +32,84 @@ fn detail_max_lines(term_h: usize) -> usize {
 pub struct AgentSnapshot {
     pub agent_id: String,
     pub prompt: std::sync::Arc<String>,
-    pub tool_calls: Vec<AgentToolEntry>,
-    pub context_tokens: Option<u32>,
+    pub transcript: Arc<VecDeque<AgentTranscriptEntry>>,
+    pub permission: Option<AgentPermission>,
+    /// Input and output tokens summed over the agent's requests.
+    pub input_tokens: u32,
+    pub output_tokens: u32,
     pub cost_usd: f64,
 }
 
+/// Live agent snapshots. `version` is bumped on every update so an open
+/// dashboard knows to repaint.
+#[derive(Default)]
+pub struct AgentSnapshots {
+    pub agents: Vec<AgentSnapshot>,
+    pub version: u64,
+}
+
 /// Shared, live-updating list of agent snapshots.
-pub type SharedSnapshots = Arc<Mutex<Vec<AgentSnapshot>>>;
-
-enum View {
-    List,
-    Detail {
-        agent_id: String,
-        scroll: usize,
-        follow: bool,
-    },
+pub type SharedSnapshots = Arc<Mutex<AgentSnapshots>>;
+
+/// Something the dashboard asks the app to do for a subagent.
+#[derive(Debug, PartialEq)]
+pub enum AgentAction {
+    Message { agent_id: String, message: String },
+    Permission { request_id: u64, approved: bool },
 }
 
+/// Split-pane agent dashboard: subagents on the left, the selected agent's
+/// live transcript on the right. Messages and permission answers go to the
+/// app through `actions`; stopping an agent kills it directly.
 pub struct AgentsDialog {
     my_pid: u32,
     agents: Vec<RegistryEntry>,
     snapshots: SharedSnapshots,
-    list: ListState,
-    view: View,
-    list_selected: usize,
+    actions: UnboundedSender<AgentAction>,
+    selected: usize,
+    scroll: usize,
+    follow: bool,
+    textarea: TextArea,
+    kill_ring: String,
+    composing: bool,
+    dirty: bool,
+    drawn_version: u64,
     vim: bool,
     /// Cached terminal size, updated each draw().
     term_size: (u16, u16),
 }
 
 impl AgentsDialog {
-    pub fn new(my_pid: u32, snapshots: SharedSnapshots, vim: bool) -> Self {
+    pub fn new(
+        my_pid: u32,
+        snapshots: SharedSnapshots,
+        actions: UnboundedSender<AgentAction>,
+        vim: bool,
+    ) -> Self {
         let agents = Self::fetch(my_pid);
-        let list = ListState::new(agents.len().max(1));
+        Self::with_agents(my_pid, agents, snapshots, actions, vim)
+    }
+
+    fn with_agents(
+        my_pid: u32,
+        agents: Vec<RegistryEntry>,
+        snapshots: SharedSnapshots,
+        actions: UnboundedSender<AgentAction>,
+        vim: bool,
+    ) -> Self {
         Self {
             my_pid,
             agents,
             snapshots,
-            list,
-            view: View::List,
-            list_selected: 0,
+            actions,
+            selected: 0,
+            scroll: 0,
+            follow: true,
+            textarea: TextArea::new(),
+            kill_ring: String::new(),
+            composing: false,
+            dirty: true,
+            drawn_version: 0,
             vim,
             term_size: terminal::size().unwrap_or((80, 24)),
         }
@@ -74,87 +119,255 @@ impl AgentsDialog {
         engine::registry::children_of(my_pid)
     }
 
+    fn refresh(&mut self) {
+        self.agents = Self::fetch(self.my_pid);
+        self.selected = self.selected.min(self.agents.len().saturating_sub(1));
+    }
+
+    fn selected_agent(&self) -> Option<&RegistryEntry> {
+        self.agents.get(self.selected)
+    }
+
     fn find_snapshot(&self, agent_id: &str) -> Option<AgentSnapshot> {
         let snaps = self.snapshots.lock().unwrap();
-        snaps.iter().find(|s| s.agent_id == agent_id).cloned()
+        snaps
+            .agents
+            .iter()
+            .find(|s| s.agent_id == agent_id)
+            .cloned()
     }
 
-    fn max_detail_lines(&self) -> usize {
-        detail_max_lines(self.term_size.1 as usize)
+    fn selected_permission(&self) -> Option<AgentPermission> {
+        let agent = self.selected_agent()?;
+        self.find_snapshot(&agent.agent_id)?.permission
     }
 
-    /// Compute the detail-view height from the current snapshot.
-    fn compute_detail_height(&self, agent_id: &str) -> u16 {
-        let w = self.term_size.0 as usize;
-        let n = if let Some(ref snap) = self.find_snapshot(agent_id) {
-            Self::detail_line_count(snap, w)
-        } else {
+    fn select(&mut self, idx: usize) {
+        if idx != self.selected {
+            self.selected = idx;
+            self.scroll = 0;
+            self.follow = true;
+        }
+    }
+
+    fn pane_rows(&self) -> usize {
+        pane_max_rows(self.term_size.1 as usize).max(self.agents.len().max(1))
+    }
+
+    fn left_width(&self, width: usize) -> usize {
+        let name_w = self
+            .agents
+            .iter()
+            .map(|a| a.agent_id.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(6);
+        // "  name  working  $0.00 "
+        (name_w + 22).min(width / 2)
+    }
+
+    /// Rows below the panes: the message being composed or the pending
+    /// permission of the selected agent.
+    fn action_rows(&self) -> usize {
+        if self.composing {
+            let wrap_w = self.compose_wrap_width(self.term_size.0 as usize);
+            self.textarea.visual_row_count(wrap_w) as usize
+        } else if self.selected_permission().is_some() {
             1
-        };
-        let max_content = self.max_detail_lines();
-        let content = n.min(max_content) as u16;
-        let wanted = content + 5;
-        let half = self.term_size.1 / 2;
-        if n > max_content {
-            wanted.max(half)
         } else {
-            wanted
+            0
         }
     }
 
-    /// Count detail view lines without allocating. Used by `height()`
-    /// which runs every tick; the full Vec is only built in `draw()`.
-    fn detail_line_count(snapshot: &AgentSnapshot, width: usize) -> usize {
-        let content_width = width.saturating_sub(2);
-        let mut count = 1; // "Prompt:" label
-        for raw_line in snapshot.prompt.lines() {
-            count += wrap_line(raw_line, content_width).len();
+    fn compose_prefix(&self) -> String {
+        let target = self.selected_agent().map_or("", |a| a.agent_id.as_str());
+        format!(" message {target}: ")
+    }
+
+    fn compose_wrap_width(&self, width: usize) -> usize {
+        width
+            .saturating_sub(self.compose_prefix().chars().count() + 1)
+            .max(1)
+    }
+
+    fn send_message(&mut self) {
+        let message = self.textarea.text();
+        self.textarea.clear();
+        self.composing = false;
+        let Some(agent) = self.selected_agent() else {
+            return;
+        };
+        if !message.trim().is_empty() {
+            let _ = self.actions.send(AgentAction::Message {
+                agent_id: agent.agent_id.clone(),
+                message,
+            });
         }
-        count += 1; // blank
-        count += snapshot.tool_calls.len().max(1); // tool calls or "(no tool calls yet)"
-        count
     }
 
-    /// Build the detail view lines for an agent: prompt + tool calls.
-    fn detail_lines(snapshot: &AgentSnapshot, width: usize) -> Vec<DetailLine> {
-        let mut lines = Vec::new();
-        let content_width = width.saturating_sub(2);
+    fn answer_permission(&mut self, approved: bool) {
+        if let Some(permission) = self.selected_permission() {
+            let _ = self.actions.send(AgentAction::Permission {
+                request_id: permission.request_id,
+                approved,
+            });
+        }
+    }
 
-        // Prompt section
-        lines.push(DetailLine::Label("Prompt:".into()));
-        for raw_line in snapshot.prompt.lines() {
-            for seg in wrap_line(raw_line, content_width) {
-                lines.push(DetailLine::Text(seg));
+    fn stop_selected(&mut self) {
+        if let Some(agent) = self.selected_agent() {
+            let pid = agent.pid;
+            if engine::registry::is_in_tree(pid, self.my_pid) {
+                engine::registry::kill_agent(pid);
+                self.refresh();
             }
         }
-        lines.push(DetailLine::Blank);
+    }
 
-        // Tool calls
-        if snapshot.tool_calls.is_empty() {
-            lines.push(DetailLine::Text("(no tool calls yet)".into()));
-        } else {
-            for entry in &snapshot.tool_calls {
-                lines.push(DetailLine::ToolCall(entry.clone()));
+    /// Build the transcript pane for an agent: prompt, then its text, tool
+    /// calls and incoming messages in arrival order.
+    fn pane_lines(snapshot: &AgentSnapshot, width: usize) -> Vec<PaneLine> {
+        let mut lines = Vec::new();
+        for raw_line in snapshot.prompt.lines() {
+            for seg in wrap_line(raw_line, width) {
+                lines.push(PaneLine::Prompt(seg));
             }
         }
+        lines.push(PaneLine::Blank);
 
+        if snapshot.transcript.is_empty() {
+            lines.push(PaneLine::Prompt("(no output yet)".into()));
+        }
+        for entry in snapshot.transcript.iter() {
+            match entry {
+                AgentTranscriptEntry::Text(text) => {
+                    for raw_line in text.trim().lines() {
+                        if raw_line.trim().is_empty() {
+                            lines.push(PaneLine::Blank);
+                            continue;
+                        }
+                        for seg in wrap_line(raw_line, width) {
+                            lines.push(PaneLine::Text(seg));
+                        }
+                    }
+                }
+                AgentTranscriptEntry::Tool(entry) => lines.push(PaneLine::Tool(entry.clone())),
+                AgentTranscriptEntry::Message { from, text } => {
+                    for raw_line in format!("{from}: {text}").lines() {
+                        for seg in wrap_line(raw_line, width) {
+                            lines.push(PaneLine::Message(seg));
+                        }
+                    }
+                }
+            }
+        }
         lines
     }
+
+    fn draw_left_cell(&self, out: &mut RenderOut, row: usize, left_w: usize) {
+        let mut used = 0;
+        if let Some(agent) = self.agents.get(row) {
+            let name_w = left_w.saturating_sub(20).max(1);
+            let name = format!(
+                "{:<name_w$}",
+                truncate_str(&agent.agent_id, name_w),
+                name_w = name_w
+            );
+            out.print("  ");
+            if row == self.selected {
+                out.push_style(crate::render::StyleState {
+                    fg: Some(crate::theme::AGENT),
+                    bold: true,
+                    ..Default::default()
+                });
+                out.print(&name);
+                out.pop_style();
+            } else {
+                out.print(&name);
+            }
+            used += 2 + name_w;
+
+            let snapshot = self.find_snapshot(&agent.agent_id);
+            let waiting = snapshot.as_ref().is_some_and(|s| s.permission.is_some());
+            if waiting {
+                out.push_fg(crate::theme::accent());
+                out.print("  waiting");
+            } else {
+                out.push_dim();
+                out.print(match agent.status {
+                    AgentStatus::Working => "  working",
+                    AgentStatus::Idle => "  idle   ",
+                });
+            }
+            out.pop_style();
+            used += 9;
+
+            if let Some(snap) = snapshot.filter(|s| s.cost_usd > 0.0) {
+                let cost = format!("  {}", crate::metrics::format_cost(snap.cost_usd));
+                let cost = truncate_str(&cost, left_w.saturating_sub(used));
+                used += cost.chars().count();
+                out.push_dim();
+                out.print(&cost);
+                out.pop_style();
+            }
+        } else if row == 0 {
+            out.push_dim();
+            out.print("  No subagents running");
+            out.pop_style();
+            used = 22;
+        }
+        out.print(&" ".repeat(left_w.saturating_sub(used)));
+        out.push_dim();
+        out.print("\u{2502} ");
+        out.pop_style();
+    }
+
+    fn draw_pane_line(out: &mut RenderOut, line: &PaneLine, width: usize) {
+        match line {
+            PaneLine::Prompt(text) => {
+                out.push_dim();
+                out.print(&truncate_str(text, width));
+                out.pop_style();
+            }
+            PaneLine::Text(text) => out.print(&truncate_str(text, width)),
+            PaneLine::Blank => {}
+            PaneLine::Message(text) => {
+                out.push_fg(crate::theme::AGENT);
+                out.print(&truncate_str(text, width));
+                out.pop_style();
+            }
+            PaneLine::Tool(entry) => {
+                match entry.status {
+                    ToolStatus::Err => out.push_fg(crate::theme::ERROR),
+                    _ => out.push_dim(),
+                }
+                out.print(&truncate_str(&entry.tool_name, width));
+                out.pop_style();
+                let max_summary = width.saturating_sub(entry.tool_name.chars().count() + 1);
+                out.print(&format!(" {}", truncate_str(&entry.summary, max_summary)));
+                if let Some(d) = entry.elapsed {
+                    if d.as_secs_f64() >= 0.1 {
+                        out.push_dim();
+                        out.print(&format!("  {}", format_duration(d.as_secs())));
+                        out.pop_style();
+                    }
+                }
+            }
+        }
+    }
 }
 
-enum DetailLine {
-    Label(String),
+enum PaneLine {
+    Prompt(String),
     Text(String),
     Blank,
-    ToolCall(AgentToolEntry),
+    Message(String),
+    Tool(AgentToolEntry),
 }
 
 impl super::Dialog for AgentsDialog {
     fn height(&self) -> u16 {
-        match &self.view {
-            View::List => self.list.height(self.agents.len().max(1), 4),
-            View::Detail { agent_id, .. } => self.compute_detail_height(agent_id),
-        }
+        (self.pane_rows() + CHROME_ROWS + self.action_rows()) as u16
     }
 
     fn constrain_height(&self) -> bool {
@@ -162,347 +375,383 @@ impl super::Dialog for AgentsDialog {
     }
 
     fn mark_dirty(&mut self) {
-        self.list.dirty = true;
+        self.dirty = true;
     }
 
     fn handle_resize(&mut self) {
         self.term_size = crossterm::terminal::size().unwrap_or(self.term_size);
-        self.list.handle_resize();
+        self.dirty = true;
+    }
+
+    fn set_kill_ring(&mut self, contents: String) {
+        self.kill_ring = contents;
+    }
+
+    fn kill_ring(&self) -> Option<&str> {
+        Some(&self.kill_ring)
     }
 
     fn handle_key(&mut self, code: KeyCode, mods: KeyModifiers) -> Option<DialogResult> {
-        match &mut self.view {
-            View::Detail {
-                scroll,
-                follow,
-                agent_id,
-                ..
-            } => {
-                let (w, h) = (self.term_size.0 as usize, self.term_size.1 as usize);
-                let max_vis = detail_max_lines(h);
-                let n = {
-                    let snaps = self.snapshots.lock().unwrap();
-                    if let Some(snap) = snaps.iter().find(|s| s.agent_id == *agent_id) {
-                        Self::detail_lines(snap, w).len()
-                    } else {
-                        0
-                    }
-                };
-                let max_scroll = n.saturating_sub(max_vis);
-
-                match nav_lookup(code, mods) {
-                    Some(NavAction::Dismiss) => {
-                        self.view = View::List;
-                        self.list = ListState::new(self.agents.len().max(1));
-                        self.list.selected = self.list_selected;
-                        None
-                    }
-                    Some(NavAction::Up) => {
-                        *scroll = scroll.saturating_sub(1);
-                        *follow = false;
-                        self.list.dirty = true;
-                        None
-                    }
-                    Some(NavAction::Down) => {
-                        *scroll = (*scroll + 1).min(max_scroll);
-                        *follow = *scroll >= max_scroll;
-                        self.list.dirty = true;
-                        None
-                    }
-                    Some(NavAction::PageUp) => {
-                        *scroll = scroll.saturating_sub(max_vis / 2);
-                        *follow = false;
-                        self.list.dirty = true;
-                        None
-                    }
-                    Some(NavAction::PageDown) => {
-                        *scroll = (*scroll + max_vis / 2).min(max_scroll);
-                        *follow = *scroll >= max_scroll;
-                        self.list.dirty = true;
-                        None
-                    }
-                    _ => None,
+        self.dirty = true;
+
+        // ── Composing a message to the selected agent ───────────────────
+        if self.composing {
+            match nav_lookup(code, mods) {
+                Some(NavAction::Confirm) => self.send_message(),
+                Some(NavAction::Dismiss) => {
+                    self.composing = false;
+                    self.textarea.clear();
                 }
-            }
-            View::List => {
-                if code == KeyCode::Backspace {
-                    if let Some(agent) = self.agents.get(self.list.selected) {
-                        let pid = agent.pid;
-                        if engine::registry::is_in_tree(pid, self.my_pid) {
-                            engine::registry::kill_agent(pid);
-                            self.agents = Self::fetch(self.my_pid);
-                            self.list.set_items(self.agents.len().max(1));
-                        }
-                    }
-                    return None;
+                _ => {
+                    self.textarea
+                        .handle_key_with_kill_ring(code, mods, &mut self.kill_ring);
                 }
+            }
+            return None;
+        }
 
-                if code == KeyCode::Enter {
-                    if let Some(agent) = self.agents.get(self.list.selected) {
-                        self.list_selected = self.list.selected;
-                        self.view = View::Detail {
-                            agent_id: agent.agent_id.clone(),
-                            scroll: 0,
-                            follow: true,
-                        };
-                        self.list.dirty = true;
-                    }
-                    return None;
-                }
+        match (code, mods) {
+            (KeyCode::Char('y'), KeyModifiers::NONE) => {
+                self.answer_permission(true);
+                return None;
+            }
+            (KeyCode::Char('n'), KeyModifiers::NONE) => {
+                self.answer_permission(false);
+                return None;
+            }
+            (KeyCode::Backspace, _) => {
+                self.stop_selected();
+                return None;
+            }
+            _ => {}
+        }
 
-                let n = self.agents.len();
-                match nav_lookup(code, mods) {
-                    Some(NavAction::Dismiss) => Some(DialogResult::AgentsClosed),
-                    Some(nav) => {
-                        self.list.handle_nav(nav, n);
-                        None
-                    }
-                    None => None,
+        let page = (pane_max_rows(self.term_size.1 as usize) / 2).max(1);
+        match nav_lookup(code, mods) {
+            Some(NavAction::Dismiss) => Some(DialogResult::AgentsClosed),
+            Some(NavAction::Confirm | NavAction::Edit) => {
+                self.composing = self.selected_agent().is_some();
+                None
+            }
+            Some(NavAction::Up) => {
+                self.select(self.selected.saturating_sub(1));
+                None
+            }
+            Some(NavAction::Down) => {
+                if self.selected + 1 < self.agents.len() {
+                    self.select(self.selected + 1);
                 }
+                None
+            }
+            Some(NavAction::PageUp) => {
+                self.scroll = self.scroll.saturating_sub(page);
+                self.follow = false;
+                None
+            }
+            Some(NavAction::PageDown) => {
+                // Clamped to the transcript length on the next draw.
+                self.scroll += page;
+                self.follow = false;
+                None
             }
+            None => None,
         }
     }
 
     fn draw(&mut self, out: &mut RenderOut, start_row: u16, width: u16, granted_rows: u16) {
-        match &self.view {
-            View::Detail {
-                agent_id,
-                scroll,
-                follow,
-            } => {
-                let agent_id = agent_id.clone();
-                let mut scroll = *scroll;
-                let follow = *follow;
-                let w = self.term_size.0 as usize;
-
-                let snapshot = self.find_snapshot(&agent_id);
-                let lines = if let Some(ref snap) = snapshot {
-                    Self::detail_lines(snap, w)
-                } else {
-                    vec![DetailLine::Text("(agent not tracked)".into())]
-                };
+        let version = self.snapshots.lock().unwrap().version;
+        if version != self.drawn_version {
+            self.drawn_version = version;
+            self.dirty = true;
+        }
+        if !self.dirty {
+            return;
+        }
+        self.dirty = false;
+        if !self.composing {
+            self.refresh();
+        }
 
-                let max_vis = self.max_detail_lines();
-                let total = lines.len();
-                let max_scroll = total.saturating_sub(max_vis);
+        let w = width as usize;
+        let action_rows = self.action_rows();
+        let rows = self
+            .pane_rows()
+            .min((granted_rows as usize).saturating_sub(CHROME_ROWS + action_rows))
+            .max(1);
+        let left_w = self.left_width(w);
+        let right_w = w.saturating_sub(left_w + 3).max(1);
+
+        let selected = self.selected_agent().cloned();
+        let snapshot = selected
+            .as_ref()
+            .and_then(|a| self.find_snapshot(&a.agent_id));
+        let lines = match (&selected, &snapshot) {
+            (Some(_), Some(snap)) => Self::pane_lines(snap, right_w),
+            (Some(_), None) => vec![PaneLine::Prompt("(agent not tracked)".into())],
+            (None, _) => Vec::new(),
+        };
+        let max_scroll = lines.len().saturating_sub(rows);
+        if self.follow || self.scroll >= max_scroll {
+            self.scroll = max_scroll;
+            self.follow = true;
+        }
 
-                if follow {
-                    scroll = max_scroll;
+        begin_dialog_draw(out, start_row);
+        draw_bar(out, w, None, None, crate::theme::AGENT);
+        out.overlay_newline();
+
+        // Header: totals on the left, the selected agent on the right.
+        let total: f64 = self
+            .snapshots
+            .lock()
+            .unwrap()
+            .agents
+            .iter()
+            .map(|s| s.cost_usd)
+            .sum();
+        let mut title = " Agents".to_string();
+        if total > 0.0 {
+            title.push_str(&format!(
+                " \u{00b7} {} total",
+                crate::metrics::format_cost(total)
+            ));
+        }
+        let title = truncate_str(&title, left_w);
+        out.push_dim();
+        out.print(&title);
+        out.print(&" ".repeat(left_w.saturating_sub(title.chars().count())));
+        out.print("\u{2502} ");
+        out.pop_style();
+        if let Some(ref agent) = selected {
+            out.push_style(crate::render::StyleState {
+                fg: Some(crate::theme::AGENT),
+                bold: true,
+                ..Default::default()
+            });
+            out.print(&truncate_str(&agent.agent_id, right_w));
+            out.pop_style();
+            let mut details = String::new();
+            if let Some(ref slug) = agent.task_slug {
+                details.push_str(&format!(" \u{00b7} {slug}"));
+            }
+            if let Some(ref snap) = snapshot {
+                if snap.input_tokens > 0 || snap.output_tokens > 0 {
+                    details.push_str(&format!(
+                        "  {} in / {} out",
+                        crate::render::format_tokens(snap.input_tokens),
+                        crate::render::format_tokens(snap.output_tokens),
+                    ));
                 }
-                scroll = scroll.min(max_scroll);
-
-                let visible = total.min(max_vis);
-
-                let Some(w) =
-                    self.list
-                        .begin_draw(out, start_row, visible + 2, width, granted_rows, 5)
-                else {
-                    return;
-                };
-
-                draw_bar(out, w, None, None, crate::theme::AGENT);
-                out.overlay_newline();
-
-                // Header: agent name + slug
-                out.print(" ");
-                out.push_style(crate::render::StyleState {
-                    fg: Some(crate::theme::AGENT),
-                    bold: true,
-                    ..Default::default()
-                });
-                out.print(&agent_id);
-                out.pop_style();
+                if snap.cost_usd > 0.0 {
+                    details.push_str(&format!("  {}", crate::metrics::format_cost(snap.cost_usd)));
+                }
+            }
+            out.push_dim();
+            out.print(&truncate_str(
+                &details,
+                right_w.saturating_sub(agent.agent_id.chars().count()),
+            ));
+            out.pop_style();
+        }
+        out.overlay_newline();
+
+        // Panes.
+        let list_offset = (self.selected + 1).saturating_sub(rows);
+        for i in 0..rows {
+            self.draw_left_cell(out, list_offset + i, left_w);
+            if let Some(line) = lines.get(self.scroll + i) {
+                Self::draw_pane_line(out, line, right_w);
+            }
+            out.overlay_newline();
+        }
 
-                // Find status from registry entries
-                if let Some(entry) = self.agents.iter().find(|a| a.agent_id == agent_id) {
-                    match entry.status {
-                        AgentStatus::Working => {}
-                        AgentStatus::Idle => {
-                            out.push_fg(crate::theme::SUCCESS);
-                            out.print(" \u{2713}");
-                            out.pop_style();
-                        }
-                    }
-                    if let Some(ref slug) = entry.task_slug {
-                        out.push_dim();
-                        out.print(&format!(" \u{00b7} {slug}"));
-                        out.pop_style();
-                    }
+        // Compose box or pending permission.
+        let mut cursor_pos = None;
+        if self.composing {
+            let prefix = self.compose_prefix();
+            let prefix_cols = prefix.chars().count();
+            let (ta_lines, cursor) = self.textarea.wrap(self.compose_wrap_width(w));
+            let first_row = start_row + (2 + rows) as u16;
+            for (vi, line) in ta_lines.iter().enumerate() {
+                if vi == 0 {
+                    out.push_fg(crate::theme::AGENT);
+                    out.print(&prefix);
+                    out.pop_style();
+                } else {
+                    out.print(&" ".repeat(prefix_cols));
                 }
-                if let Some(ref snap) = snapshot {
-                    if let Some(tokens) = snap.context_tokens {
-                        out.push_dim();
-                        out.print(&format!("  {}", crate::render::format_tokens(tokens)));
-                        out.pop_style();
-                    }
-                    if snap.cost_usd > 0.0 {
-                        out.push_dim();
-                        out.print(&format!("  {}", crate::metrics::format_cost(snap.cost_usd)));
-                        out.pop_style();
-                    }
+                out.print(line);
+                if vi == cursor.0 {
+                    cursor_pos = Some(((prefix_cols + cursor.1) as u16, first_row + vi as u16));
                 }
                 out.overlay_newline();
-                out.overlay_newline();
+            }
+        } else if let Some(permission) = snapshot.as_ref().and_then(|s| s.permission.as_ref()) {
+            out.push_fg(crate::theme::accent());
+            out.print(" permission: ");
+            out.pop_style();
+            out.print(&truncate_str(
+                &format!("{} {}", permission.tool_name, permission.summary),
+                w.saturating_sub(14),
+            ));
+            out.overlay_newline();
+        }
 
-                // Content
-                for line in lines.iter().skip(scroll).take(visible) {
-                    match line {
-                        DetailLine::Label(text) => {
-                            out.push_dim();
-                            out.print(&format!("  {text}"));
-                            out.pop_style();
-                            out.overlay_newline();
-                        }
-                        DetailLine::Text(text) => {
-                            out.print(&format!("   {}", truncate_str(text, w.saturating_sub(4))));
-                            out.overlay_newline();
-                        }
-                        DetailLine::Blank => {
-                            out.overlay_newline();
-                        }
-                        DetailLine::ToolCall(entry) => {
-                            out.print("  ");
-                            out.push_dim();
-                            out.print(&entry.tool_name);
-                            out.pop_style();
-                            let max_summary = w.saturating_sub(5 + entry.tool_name.len());
-                            out.print(&format!(" {}", truncate_str(&entry.summary, max_summary)));
-                            if let Some(d) = entry.elapsed {
-                                if d.as_secs_f64() >= 0.1 {
-                                    out.push_dim();
-                                    out.print(&format!("  {}", format_duration(d.as_secs())));
-                                    out.pop_style();
-                                }
-                            }
-                            out.overlay_newline();
-                        }
-                    }
-                }
+        // Hints
+        out.overlay_newline();
+        out.push_dim();
+        let hint = if self.composing {
+            hints::join(&[hints::SEND, hints::CANCEL])
+        } else {
+            let mut parts = vec![hints::nav(self.vim), hints::MESSAGE_AGENT];
+            if snapshot.as_ref().is_some_and(|s| s.permission.is_some()) {
+                parts.push(hints::APPROVE_DENY);
+            }
+            parts.extend([hints::scroll(self.vim), hints::STOP_AGENT, hints::CLOSE]);
+            hints::join(&parts)
+        };
+        out.print(&hint);
+        out.pop_style();
+        end_dialog_draw(out);
+        finish_dialog_frame(out, cursor_pos, self.composing);
+    }
+}
 
-                // Hints
-                out.overlay_newline();
-                out.push_dim();
-                let can_scroll = total > max_vis;
-                if can_scroll {
-                    let end = (scroll + visible).min(total);
-                    out.print(&format!(
-                        " [{end}/{total}]  {}  {}  {}",
-                        hints::nav(self.vim),
-                        hints::scroll(self.vim),
-                        hints::BACK,
-                    ));
-                } else {
-                    out.print(&hints::join(&[hints::BACK]));
-                }
-                out.pop_style();
-                end_dialog_draw(out);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::Dialog;
+
+    fn entry(agent_id: &str) -> RegistryEntry {
+        RegistryEntry {
+            agent_id: agent_id.into(),
+            pid: 0,
+            parent_pid: None,
+            git_root: None,
+            git_branch: None,
+            cwd: String::new(),
+            status: AgentStatus::Working,
+            task_slug: None,
+            session_id: String::new(),
+            socket_path: String::new(),
+            depth: 1,
+            started_at: String::new(),
+            host: String::new(),
+            address: None,
+            updated_at: 0,
+        }
+    }
 
-                self.view = View::Detail {
-                    agent_id,
-                    scroll,
-                    follow,
-                };
-            }
-            View::List => {
-                let fresh = Self::fetch(self.my_pid);
-                if fresh.len() != self.agents.len() {
-                    self.list.set_items(fresh.len().max(1));
-                }
-                self.agents = fresh;
-
-                let Some(w) = self.list.begin_draw(
-                    out,
-                    start_row,
-                    self.agents.len().max(1),
-                    width,
-                    granted_rows,
-                    4,
-                ) else {
-                    return;
-                };
-
-                draw_bar(out, w, None, None, crate::theme::AGENT);
-                out.overlay_newline();
+    fn snapshot(agent_id: &str, permission: Option<u64>) -> AgentSnapshot {
+        AgentSnapshot {
+            agent_id: agent_id.into(),
+            prompt: Arc::new("fix the tests".into()),
+            transcript: Default::default(),
+            permission: permission.map(|request_id| AgentPermission {
+                request_id,
+                tool_name: "bash".into(),
+                summary: "cargo test".into(),
+            }),
+            input_tokens: 0,
+            output_tokens: 0,
+            cost_usd: 0.0,
+        }
+    }
 
-                out.push_dim();
-                out.print(" Agents");
-                out.pop_style();
-                out.overlay_newline();
+    fn dialog() -> (
+        AgentsDialog,
+        tokio::sync::mpsc::UnboundedReceiver<AgentAction>,
+    ) {
+        let snapshots = Arc::new(Mutex::new(AgentSnapshots {
+            agents: vec![snapshot("cedar", None), snapshot("birch", Some(7))],
+            version: 1,
+        }));
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let d = AgentsDialog::with_agents(
+            1,
+            vec![entry("cedar"), entry("birch")],
+            snapshots,
+            tx,
+            false,
+        );
+        (d, rx)
+    }
 
-                if self.agents.is_empty() {
-                    out.push_dim();
-                    out.print("  No subagents running");
-                    out.pop_style();
-                    out.overlay_newline();
-                } else {
-                    let name_w = self
-                        .agents
-                        .iter()
-                        .map(|a| a.agent_id.len())
-                        .max()
-                        .unwrap_or(0);
-                    let range = self.list.visible_range(self.agents.len());
-                    for (i, agent) in self
-                        .agents
-                        .iter()
-                        .enumerate()
-                        .take(range.end)
-                        .skip(range.start)
-                    {
-                        let status_str = match agent.status {
-                            AgentStatus::Working => "working",
-                            AgentStatus::Idle => "idle   ",
-                        };
-
-                        out.print("  ");
-                        let padded_name = format!("{:<name_w$}", agent.agent_id);
-                        if i == self.list.selected {
-                            out.push_style(crate::render::StyleState {
-                                fg: Some(crate::theme::AGENT),
-                                bold: true,
-                                ..Default::default()
-                            });
-                            out.print(&padded_name);
-                            out.pop_style();
-                        } else {
-                            out.print(&padded_name);
-                        }
-                        out.push_dim();
-                        out.print(&format!("  {status_str}"));
-                        out.pop_style();
-                        if let Some(slug) = &agent.task_slug {
-                            let max = w.saturating_sub(name_w + 12);
-                            out.print(&format!("  {}", truncate_str(slug, max)));
-                        }
-                        if let Some(snap) = self.find_snapshot(&agent.agent_id) {
-                            if let Some(tokens) = snap.context_tokens {
-                                out.push_dim();
-                                out.print(&format!("  {}", crate::render::format_tokens(tokens)));
-                                out.pop_style();
-                            }
-                            if snap.cost_usd > 0.0 {
-                                out.push_dim();
-                                out.print(&format!(
-                                    "  {}",
-                                    crate::metrics::format_cost(snap.cost_usd)
-                                ));
-                                out.pop_style();
-                            }
-                        }
-                        out.overlay_newline();
-                    }
-                }
+    fn key(d: &mut AgentsDialog, code: KeyCode) -> Option<DialogResult> {
+        d.handle_key(code, KeyModifiers::NONE)
+    }
 
-                out.overlay_newline();
-                out.push_dim();
-                out.print(&hints::join(&[
-                    "enter: view",
-                    hints::KILL_PROC,
-                    hints::CLOSE,
-                ]));
-                out.pop_style();
-                end_dialog_draw(out);
-            }
+    #[test]
+    fn sends_message_to_selected_agent() {
+        let (mut d, mut rx) = dialog();
+        key(&mut d, KeyCode::Enter);
+        for c in "stop".chars() {
+            key(&mut d, KeyCode::Char(c));
         }
+        key(&mut d, KeyCode::Enter);
+        assert_eq!(
+            rx.try_recv().unwrap(),
+            AgentAction::Message {
+                agent_id: "cedar".into(),
+                message: "stop".into()
+            }
+        );
+        // An empty message is not sent.
+        key(&mut d, KeyCode::Enter);
+        key(&mut d, KeyCode::Enter);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn answers_pending_permission_of_selected_agent() {
+        let (mut d, mut rx) = dialog();
+        key(&mut d, KeyCode::Char('y'));
+        assert!(rx.try_recv().is_err(), "cedar has no pending permission");
+        key(&mut d, KeyCode::Down);
+        key(&mut d, KeyCode::Char('n'));
+        assert_eq!(
+            rx.try_recv().unwrap(),
+            AgentAction::Permission {
+                request_id: 7,
+                approved: false
+            }
+        );
+        assert!(matches!(
+            key(&mut d, KeyCode::Esc),
+            Some(DialogResult::AgentsClosed)
+        ));
+    }
+
+    #[test]
+    fn transcript_keeps_arrival_order() {
+        let mut snap = snapshot("cedar", None);
+        snap.transcript = Arc::new(VecDeque::from([
+            AgentTranscriptEntry::Text("Looking.\n\nFound it.".into()),
+            AgentTranscriptEntry::Tool(AgentToolEntry {
+                call_id: "1".into(),
+                tool_name: "grep".into(),
+                summary: "fn main".into(),
+                status: ToolStatus::Ok,
+                elapsed: None,
+            }),
+            AgentTranscriptEntry::Message {
+                from: "plum".into(),
+                text: "hurry".into(),
+            },
+        ]));
+        let kinds: Vec<&str> = AgentsDialog::pane_lines(&snap, 40)
+            .iter()
+            .map(|l| match l {
+                PaneLine::Prompt(_) => "prompt",
+                PaneLine::Text(_) => "text",
+                PaneLine::Blank => "blank",
+                PaneLine::Message(_) => "message",
+                PaneLine::Tool(_) => "tool",
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            ["prompt", "blank", "text", "blank", "text", "tool", "message"]
+        );
     }
 }
@@ -0,0 +1,413 @@
+use crate::keymap::{hints, nav_lookup, NavAction};
+use crate::render::draw_bar;
+use crate::render::highlight::{
+    build_file_diff_cache, print_cached_inline_diff, print_cached_side_by_side, side_by_side_rows,
+    CachedInlineDiff,
+};
+use crate::theme;
+use crossterm::event::{KeyCode, KeyModifiers};
+use crossterm::terminal;
+use engine::tools::{display_path, FileChange, SessionChanges};
+
+use super::{end_dialog_draw, truncate_str, DialogResult, ListState, RenderOut};
+
+/// Rows of non-diff chrome in the detail view: bar, header, blank, hints.
+const DETAIL_CHROME: u16 = 4;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Layout {
+    Unified,
+    SideBySide,
+}
+
+enum View {
+    List,
+    Detail {
+        cache: CachedInlineDiff,
+        layout: Layout,
+        scroll: usize,
+    },
+}
+
+/// Session-wide change review: every file touched by the file tools,
+/// diffed against its content before the first edit, with per-file revert.
+pub struct DiffDialog {
+    changes: SessionChanges,
+    files: Vec<FileChange>,
+    list: ListState,
+    view: View,
+    list_selected: usize,
+    /// Set by the first `r` press; a second press performs the revert.
+    pending_revert: bool,
+    reverted: Vec<String>,
+    error: Option<String>,
+    /// A running turn may be editing the files, so reverts wait for it.
+    agent_running: bool,
+    vim: bool,
+    term_size: (u16, u16),
+}
+
+impl DiffDialog {
+    pub fn new(changes: SessionChanges, vim: bool) -> Self {
+        let files = changes.list();
+        let list = ListState::new(files.len().max(1));
+        Self {
+            changes,
+            files,
+            list,
+            view: View::List,
+            list_selected: 0,
+            pending_revert: false,
+            reverted: Vec::new(),
+            error: None,
+            agent_running: false,
+            vim,
+            term_size: terminal::size().unwrap_or((80, 24)),
+        }
+    }
+
+    fn close(&mut self) -> DialogResult {
+        DialogResult::DiffClosed {
+            reverted: std::mem::take(&mut self.reverted),
+        }
+    }
+
+    fn open_detail(&mut self) {
+        let Some(file) = self.files.get(self.list.selected) else {
+            return;
+        };
+        let cache = build_file_diff_cache(
+            file.original.as_deref().unwrap_or(""),
+            file.current.as_deref().unwrap_or(""),
+            &file.path,
+        );
+        self.list_selected = self.list.selected;
+        self.error = None;
+        self.view = View::Detail {
+            cache,
+            layout: Layout::Unified,
+            scroll: 0,
+        };
+        self.list.dirty = true;
+    }
+
+    fn back_to_list(&mut self) {
+        self.view = View::List;
+        self.list = ListState::new(self.files.len().max(1));
+        self.list.selected = self.list_selected.min(self.files.len().saturating_sub(1));
+    }
+
+    fn revert_selected(&mut self) {
+        let idx = match self.view {
+            View::List => self.list.selected,
+            View::Detail { .. } => self.list_selected,
+        };
+        let Some(file) = self.files.get(idx) else {
+            return;
+        };
+        let path = file.path.clone();
+        match self.changes.revert(&path) {
+            Ok(()) => {
+                self.reverted.push(display_path(&path));
+                self.error = None;
+            }
+            Err(e) => self.error = Some(format!("revert failed: {e}")),
+        }
+        self.files = self.changes.list();
+        self.list_selected = idx;
+        self.back_to_list();
+    }
+
+    fn detail_rows(cache: &CachedInlineDiff, layout: Layout) -> usize {
+        match layout {
+            Layout::Unified => cache.lines.len(),
+            Layout::SideBySide => side_by_side_rows(cache).len(),
+        }
+    }
+
+    fn max_detail_rows(&self) -> usize {
+        (self.term_size.1 as usize)
+            .saturating_sub(DETAIL_CHROME as usize + 4)
+            .max(3)
+    }
+}
+
+impl super::Dialog for DiffDialog {
+    fn height(&self) -> u16 {
+        match &self.view {
+            View::List => self.list.height(self.files.len().max(1), 4),
+            View::Detail { cache, layout, .. } => {
+                let rows = Self::detail_rows(cache, *layout).min(self.max_detail_rows());
+                rows as u16 + DETAIL_CHROME
+            }
+        }
+    }
+
+    fn constrain_height(&self) -> bool {
+        true
+    }
+
+    fn mark_dirty(&mut self) {
+        self.list.dirty = true;
+    }
+
+    fn handle_resize(&mut self) {
+        self.term_size = terminal::size().unwrap_or(self.term_size);
+        self.list.handle_resize();
+    }
+
+    fn set_agent_running(&mut self, running: bool) {
+        self.agent_running = running;
+    }
+
+    fn handle_key(&mut self, code: KeyCode, mods: KeyModifiers) -> Option<DialogResult> {
+        if code == KeyCode::Char('r') && mods == KeyModifiers::NONE {
+            if self.agent_running {
+                self.pending_revert = false;
+                self.error = Some("can't revert while the agent is working".into());
+            } else if self.pending_revert {
+                self.pending_revert = false;
+                self.revert_selected();
+                if self.files.is_empty() {
+                    return Some(self.close());
+                }
+            } else {
+                self.pending_revert = true;
+            }
+            self.list.dirty = true;
+            return None;
+        }
+        if self.pending_revert {
+            self.pending_revert = false;
+            self.list.dirty = true;
+        }
+
+        let viewport = self.list.max_visible.max(1);
+        match &mut self.view {
+            View::Detail {
+                cache,
+                layout,
+                scroll,
+            } => {
+                let max_scroll = Self::detail_rows(cache, *layout).saturating_sub(viewport);
+                match nav_lookup(code, mods) {
+                    Some(NavAction::Dismiss) => self.back_to_list(),
+                    Some(NavAction::Edit) => {
+                        *layout = match layout {
+                            Layout::Unified => Layout::SideBySide,
+                            Layout::SideBySide => Layout::Unified,
+                        };
+                        *scroll = 0;
+                    }
+                    Some(NavAction::Up) => *scroll = scroll.saturating_sub(1),
+                    Some(NavAction::Down) => *scroll = (*scroll + 1).min(max_scroll),
+                    Some(NavAction::PageUp) => *scroll = scroll.saturating_sub(viewport / 2),
+                    Some(NavAction::PageDown) => *scroll = (*scroll + viewport / 2).min(max_scroll),
+                    _ => return None,
+                }
+                self.list.dirty = true;
+                None
+            }
+            View::List => match nav_lookup(code, mods) {
+                Some(NavAction::Confirm) => {
+                    self.open_detail();
+                    None
+                }
+                Some(NavAction::Dismiss) => Some(self.close()),
+                Some(nav) => {
+                    self.list.handle_nav(nav, self.files.len());
+                    None
+                }
+                None => None,
+            },
+        }
+    }
+
+    fn draw(&mut self, out: &mut RenderOut, start_row: u16, width: u16, granted_rows: u16) {
+        match &self.view {
+            View::Detail {
+                cache,
+                layout,
+                scroll,
+            } => {
+                let total = Self::detail_rows(cache, *layout);
+                let Some(w) =
+                    self.list
+                        .begin_draw(out, start_row, total, width, granted_rows, DETAIL_CHROME)
+                else {
+                    return;
+                };
+                let viewport = self.list.max_visible;
+                let scroll = (*scroll).min(total.saturating_sub(viewport));
+
+                draw_bar(out, w, None, None, theme::accent());
+                out.overlay_newline();
+
+                if let Some(file) = self.files.get(self.list_selected) {
+                    out.print(" ");
+                    out.push_bold();
+                    out.print(&truncate_str(
+                        &display_path(&file.path),
+                        w.saturating_sub(24),
+                    ));
+                    out.pop_style();
+                    print_stats(out, file);
+                }
+                out.push_dim();
+                out.print(match layout {
+                    Layout::Unified => "  unified",
+                    Layout::SideBySide => "  side-by-side",
+                });
+                out.pop_style();
+                out.overlay_newline();
+
+                match layout {
+                    Layout::Unified => {
+                        print_cached_inline_diff(out, cache, scroll as u16, viewport as u16);
+                    }
+                    Layout::SideBySide => {
+                        print_cached_side_by_side(out, cache, scroll as u16, viewport as u16);
+                    }
+                }
+
+                out.overlay_newline();
+                out.push_dim();
+                if let Some(ref err) = self.error {
+                    out.push_fg(theme::ERROR);
+                    out.print(&format!(" {err}"));
+                    out.pop_style();
+                } else if self.pending_revert {
+                    out.print(" press r again to revert this file");
+                } else {
+                    let pos = format!(" [{}/{total}]", (scroll + viewport).min(total));
+                    out.print(&pos);
+                    out.print(&hints::join(&[
+                        hints::scroll(self.vim),
+                        "tab: layout",
+                        "r: revert",
+                        hints::BACK,
+                    ]));
+                }
+                out.pop_style();
+                end_dialog_draw(out);
+            }
+            View::List => {
+                let n = self.files.len();
+                let Some(w) =
+                    self.list
+                        .begin_draw(out, start_row, n.max(1), width, granted_rows, 4)
+                else {
+                    return;
+                };
+
+                draw_bar(out, w, None, None, theme::accent());
+                out.overlay_newline();
+
+                out.push_dim();
+                out.print(" Session changes");
+                out.pop_style();
+                if let Some(ref err) = self.error {
+                    out.push_fg(theme::ERROR);
+                    out.print(&format!("  {err}"));
+                    out.pop_style();
+                }
+                out.overlay_newline();
+
+                if self.files.is_empty() {
+                    out.push_dim();
+                    out.print("  No changes");
+                    out.pop_style();
+                    out.overlay_newline();
+                } else {
+                    let range = self.list.visible_range(n);
+                    for (i, file) in self
+                        .files
+                        .iter()
+                        .enumerate()
+                        .take(range.end)
+                        .skip(range.start)
+                    {
+                        let label = truncate_str(&display_path(&file.path), w.saturating_sub(24));
+                        out.print("  ");
+                        if i == self.list.selected {
+                            out.push_fg(theme::accent());
+                            out.print(&label);
+                            out.pop_style();
+                        } else {
+                            out.print(&label);
+                        }
+                        print_stats(out, file);
+                        out.overlay_newline();
+                    }
+                }
+
+                out.overlay_newline();
+                out.push_dim();
+                if self.pending_revert {
+                    out.print(" press r again to revert the selected file");
+                } else {
+                    out.print(&hints::join(&["enter: view", "r: revert", hints::CLOSE]));
+                }
+                out.pop_style();
+                end_dialog_draw(out);
+            }
+        }
+    }
+}
+
+fn print_stats(out: &mut RenderOut, file: &FileChange) {
+    let (added, removed) = file.line_stats();
+    out.print(" ");
+    out.push_fg(theme::SUCCESS);
+    out.print(&format!(" +{added}"));
+    out.pop_style();
+    out.push_fg(theme::ERROR);
+    out.print(&format!(" -{removed}"));
+    out.pop_style();
+    let marker = match (&file.original, &file.current) {
+        (None, _) => Some("new"),
+        (_, None) => Some("deleted"),
+        _ => None,
+    };
+    if let Some(marker) = marker {
+        out.push_dim();
+        out.print(&format!("  {marker}"));
+        out.pop_style();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::Dialog;
+
+    #[test]
+    fn refuses_to_revert_while_agent_is_running() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        std::fs::write(&path, "before").unwrap();
+        let path = path.to_string_lossy().into_owned();
+        let changes = SessionChanges::new();
+        changes.record(&path);
+        std::fs::write(&path, "after").unwrap();
+
+        let mut dialog = DiffDialog::new(changes, false);
+        dialog.set_agent_running(true);
+        assert!(dialog
+            .handle_key(KeyCode::Char('r'), KeyModifiers::NONE)
+            .is_none());
+        assert!(dialog
+            .handle_key(KeyCode::Char('r'), KeyModifiers::NONE)
+            .is_none());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "after");
+        assert!(dialog.error.is_some());
+
+        dialog.set_agent_running(false);
+        dialog.handle_key(KeyCode::Char('r'), KeyModifiers::NONE);
+        let result = dialog.handle_key(KeyCode::Char('r'), KeyModifiers::NONE);
+        assert!(
+            matches!(result, Some(DialogResult::DiffClosed { reverted }) if reverted.len() == 1)
+        );
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "before");
+    }
+}
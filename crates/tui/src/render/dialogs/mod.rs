@@ -1,20 +1,24 @@
 mod agents;
 mod confirm;
+mod diff;
 mod export;
 mod help;
 mod permissions;
 mod ps;
 mod question;
+mod queue;
 mod resume;
 mod rewind;
 
-pub use agents::{AgentSnapshot, AgentsDialog, SharedSnapshots};
+pub use agents::{AgentAction, AgentSnapshot, AgentSnapshots, AgentsDialog, SharedSnapshots};
 pub use confirm::ConfirmDialog;
+pub use diff::DiffDialog;
 pub use export::{ExportDialog, ExportTarget};
 pub use help::HelpDialog;
 pub use permissions::{PermissionEntry, PermissionsDialog};
 pub use ps::PsDialog;
 pub use question::{parse_questions, Question, QuestionDialog, QuestionOption};
+pub use queue::QueueDialog;
 pub use resume::ResumeDialog;
 pub use rewind::RewindDialog;
 
@@ -51,6 +55,12 @@ pub enum DialogResult {
         workspace_remaining: Vec<crate::workspace_permissions::Rule>,
     },
     AgentsClosed,
+    DiffClosed {
+        reverted: Vec<String>,
+    },
+    QueueClosed {
+        items: Vec<crate::queue::QueuedMessage>,
+    },
 }
 
 pub trait Dialog {
@@ -66,6 +76,22 @@ pub trait Dialog {
     fn handle_resize(&mut self);
     fn handle_key(&mut self, code: KeyCode, mods: KeyModifiers) -> Option<DialogResult>;
 
+    /// Mouse wheel by `delta` rows (negative = up). Defaults to the
+    /// equivalent arrow keys, which list dialogs route to `ListState`.
+    fn handle_scroll(&mut self, delta: isize) -> Option<DialogResult> {
+        let code = if delta < 0 {
+            KeyCode::Up
+        } else {
+            KeyCode::Down
+        };
+        for _ in 0..delta.unsigned_abs() {
+            if let Some(result) = self.handle_key(code, KeyModifiers::NONE) {
+                return Some(result);
+            }
+        }
+        None
+    }
+
     /// Whether the layout engine should apply a dynamic height cap
     /// (`max(h/2, natural_space)`) to limit scroll-up.  List-based
     /// dialogs return true; confirm/question dialogs return false.
@@ -73,6 +99,9 @@ pub trait Dialog {
         false
     }
 
+    /// Tell the dialog whether an agent turn is running, before each key.
+    fn set_agent_running(&mut self, _running: bool) {}
+
     /// Seed the dialog's kill ring from the main input's kill ring.
     fn set_kill_ring(&mut self, _contents: String) {}
     /// Retrieve the dialog's kill ring so the main input can sync it back.
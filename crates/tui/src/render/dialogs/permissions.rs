@@ -13,16 +13,26 @@ pub struct PermissionEntry {
     pub pattern: String,
 }
 
-/// A selectable row — one tool+pattern pair from either session or workspace.
+/// A selectable row — one tool+pattern pair from session or workspace
+/// approvals, a configured path rule, or a managed-policy restriction.
 #[derive(Clone)]
 enum Item {
+    Managed(usize),          // index into managed (read-only)
     Session(usize),          // index into session_entries
     Workspace(usize, usize), // (rule_index, pattern_index) into workspace_rules
+    PathRule(usize),         // index into path_rules (read-only)
 }
 
 pub struct PermissionsDialog {
     session_entries: Vec<PermissionEntry>,
     workspace_rules: Vec<workspace_permissions::Rule>,
+    /// `paths` rules from config for the current mode, as `decision: glob`.
+    /// Shown for reference; edited in config, not here.
+    path_rules: Vec<String>,
+    path_header: String,
+    /// Restrictions from the managed policy, one per line. Read-only.
+    managed: Vec<String>,
+    managed_header: String,
     items: Vec<Item>,
     list: ListState,
     pending_d: bool,
@@ -33,14 +43,21 @@ impl PermissionsDialog {
     pub fn new(
         session_entries: Vec<PermissionEntry>,
         workspace_rules: Vec<workspace_permissions::Rule>,
+        path_rules: Vec<String>,
+        managed: Vec<String>,
+        mode_label: &str,
         vim_enabled: bool,
     ) -> Self {
-        let items = build_items(&session_entries, &workspace_rules);
-        let total = display_row_count(&session_entries, &workspace_rules, &items);
+        let items = build_items(&managed, &session_entries, &workspace_rules, &path_rules);
+        let total = display_row_count(&items);
         let list = ListState::new(total.max(1));
         Self {
             session_entries,
             workspace_rules,
+            path_rules,
+            path_header: format!(" Path rules ({mode_label}, from config)"),
+            managed,
+            managed_header: format!(" Managed policy ({})", engine::policy::POLICY_PATH),
             items,
             list,
             pending_d: false,
@@ -49,8 +66,13 @@ impl PermissionsDialog {
     }
 
     fn rebuild_items(&mut self) {
-        self.items = build_items(&self.session_entries, &self.workspace_rules);
-        let total = display_row_count(&self.session_entries, &self.workspace_rules, &self.items);
+        self.items = build_items(
+            &self.managed,
+            &self.session_entries,
+            &self.workspace_rules,
+            &self.path_rules,
+        );
+        let total = display_row_count(&self.items);
         self.list.set_items(total.max(1));
     }
 
@@ -70,6 +92,7 @@ impl PermissionsDialog {
                     rule.patterns.remove(pat_idx);
                 }
             }
+            Item::Managed(_) | Item::PathRule(_) => return,
         }
         self.rebuild_items();
     }
@@ -84,7 +107,7 @@ impl PermissionsDialog {
 
 impl super::Dialog for PermissionsDialog {
     fn height(&self) -> u16 {
-        let total = display_row_count(&self.session_entries, &self.workspace_rules, &self.items);
+        let total = display_row_count(&self.items);
         self.list.height(total.max(1), 3)
     }
 
@@ -136,7 +159,7 @@ impl super::Dialog for PermissionsDialog {
     }
 
     fn draw(&mut self, out: &mut RenderOut, start_row: u16, width: u16, granted_rows: u16) {
-        let total = display_row_count(&self.session_entries, &self.workspace_rules, &self.items);
+        let total = display_row_count(&self.items);
         let Some(w) = self
             .list
             .begin_draw(out, start_row, total.max(1), width, granted_rows, 3)
@@ -153,22 +176,25 @@ impl super::Dialog for PermissionsDialog {
             out.pop_style();
             out.overlay_newline();
         } else {
-            let mut printed_workspace = false;
             for (i, item) in self.items.iter().enumerate() {
-                if matches!(item, Item::Session(_)) && i == 0 {
-                    print_header(out, " Session");
-                }
-                if matches!(item, Item::Workspace(_, _)) && !printed_workspace {
-                    printed_workspace = true;
+                if i == 0 || section(&self.items[i - 1]) != section(item) {
                     if i > 0 {
                         out.overlay_newline();
                     }
-                    print_header(out, " Workspace");
+                    let header = match item {
+                        Item::Managed(_) => &self.managed_header,
+                        Item::Session(_) => " Session",
+                        Item::Workspace(_, _) => " Workspace",
+                        Item::PathRule(_) => &self.path_header,
+                    };
+                    print_header(out, header);
                 }
 
                 let label = match item {
+                    Item::Managed(idx) => self.managed[*idx].clone(),
                     Item::Session(idx) => format_permission_entry(&self.session_entries[*idx]),
                     Item::Workspace(ri, pi) => format_rule_entry(&self.workspace_rules[*ri], *pi),
+                    Item::PathRule(idx) => self.path_rules[*idx].clone(),
                 };
                 render_entry_row(out, &label, i == self.list.selected, w, theme::accent());
             }
@@ -215,10 +241,12 @@ fn render_entry_row(
 }
 
 fn build_items(
+    managed: &[String],
     session_entries: &[PermissionEntry],
     workspace_rules: &[workspace_permissions::Rule],
+    path_rules: &[String],
 ) -> Vec<Item> {
-    let mut items = Vec::new();
+    let mut items: Vec<Item> = (0..managed.len()).map(Item::Managed).collect();
     for i in 0..session_entries.len() {
         items.push(Item::Session(i));
     }
@@ -231,22 +259,28 @@ fn build_items(
             }
         }
     }
+    items.extend((0..path_rules.len()).map(Item::PathRule));
     items
 }
 
-/// Total display rows: items + one header per non-empty section.
-fn display_row_count(
-    session_entries: &[PermissionEntry],
-    workspace_rules: &[workspace_permissions::Rule],
-    items: &[Item],
-) -> usize {
-    let headers = !session_entries.is_empty() as usize + !workspace_rules.is_empty() as usize;
-    let gap = if !session_entries.is_empty() && !workspace_rules.is_empty() {
-        1
-    } else {
-        0
-    };
-    items.len() + headers + gap
+fn section(item: &Item) -> u8 {
+    match item {
+        Item::Managed(_) => 0,
+        Item::Session(_) => 1,
+        Item::Workspace(_, _) => 2,
+        Item::PathRule(_) => 3,
+    }
+}
+
+/// Total display rows: items + one header per non-empty section, plus a
+/// blank line between sections.
+fn display_row_count(items: &[Item]) -> usize {
+    let sections = items
+        .iter()
+        .enumerate()
+        .filter(|(i, item)| *i == 0 || section(&items[i - 1]) != section(item))
+        .count();
+    items.len() + sections + sections.saturating_sub(1)
 }
 
 fn format_permission_entry(entry: &PermissionEntry) -> String {
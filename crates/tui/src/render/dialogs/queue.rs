@@ -0,0 +1,373 @@
+use crate::keymap::{hints, nav_lookup, NavAction};
+use crate::queue::QueuedMessage;
+use crate::render::draw_bar;
+use crate::theme;
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use super::{
+    end_dialog_draw, finish_dialog_frame, truncate_str, DialogResult, ListState, RenderOut,
+    TextArea,
+};
+
+/// Width of the "steer" / "next turn" column.
+const TARGET_COLS: usize = 10;
+
+/// Lists queued messages and lets the user edit, reorder, delete or
+/// retarget them. Works on a copy; the app reconciles it with the live
+/// queue on close.
+pub struct QueueDialog {
+    items: Vec<QueuedMessage>,
+    list: ListState,
+    textarea: TextArea,
+    kill_ring: String,
+    editing: bool,
+    pending_d: bool,
+    vim_enabled: bool,
+    last_width: usize,
+}
+
+impl QueueDialog {
+    pub fn new(items: Vec<QueuedMessage>, vim_enabled: bool) -> Self {
+        let list = ListState::new(items.len().max(1));
+        Self {
+            items,
+            list,
+            textarea: TextArea::new(),
+            kill_ring: String::new(),
+            editing: false,
+            pending_d: false,
+            vim_enabled,
+            last_width: 80,
+        }
+    }
+
+    fn close_result(&mut self) -> DialogResult {
+        DialogResult::QueueClosed {
+            items: std::mem::take(&mut self.items),
+        }
+    }
+
+    fn prefix_cols(&self) -> usize {
+        // "  N. " + target column
+        4 + self.items.len().to_string().len() + TARGET_COLS
+    }
+
+    fn start_edit(&mut self) {
+        let Some(item) = self.items.get(self.list.selected) else {
+            return;
+        };
+        self.textarea.lines = item.text.split('\n').map(str::to_string).collect();
+        self.textarea.row = self.textarea.lines.len() - 1;
+        self.textarea.col = self.textarea.lines[self.textarea.row].chars().count();
+        self.editing = true;
+    }
+
+    /// Save the edit. An emptied message is removed from the queue.
+    fn finish_edit(&mut self) {
+        self.editing = false;
+        let text = self.textarea.text();
+        self.textarea.clear();
+        let idx = self.list.selected;
+        if text.trim().is_empty() {
+            self.delete_selected();
+        } else if let Some(item) = self.items.get_mut(idx) {
+            item.text = text;
+        }
+    }
+
+    fn delete_selected(&mut self) {
+        if self.list.selected < self.items.len() {
+            self.items.remove(self.list.selected);
+            self.list.set_items(self.items.len().max(1));
+        }
+    }
+
+    fn move_selected(&mut self, up: bool) {
+        let i = self.list.selected;
+        let j = if up {
+            match i.checked_sub(1) {
+                Some(j) => j,
+                None => return,
+            }
+        } else if i + 1 < self.items.len() {
+            i + 1
+        } else {
+            return;
+        };
+        self.items.swap(i, j);
+        if up {
+            self.list.select_prev(self.items.len());
+        } else {
+            self.list.select_next(self.items.len());
+        }
+    }
+
+    fn edit_rows(&self) -> u16 {
+        if !self.editing {
+            return 0;
+        }
+        let wrap_w = self.last_width.saturating_sub(self.prefix_cols()).max(1);
+        self.textarea.visual_row_count(wrap_w).saturating_sub(1)
+    }
+}
+
+impl super::Dialog for QueueDialog {
+    fn height(&self) -> u16 {
+        self.list.height(self.items.len().max(1), 4) + self.edit_rows()
+    }
+
+    fn constrain_height(&self) -> bool {
+        true
+    }
+
+    fn mark_dirty(&mut self) {
+        self.list.dirty = true;
+    }
+
+    fn handle_resize(&mut self) {
+        self.list.handle_resize();
+    }
+
+    fn set_kill_ring(&mut self, contents: String) {
+        self.kill_ring = contents;
+    }
+
+    fn kill_ring(&self) -> Option<&str> {
+        Some(&self.kill_ring)
+    }
+
+    fn handle_key(&mut self, code: KeyCode, mods: KeyModifiers) -> Option<DialogResult> {
+        self.list.dirty = true;
+
+        // ── Editing the selected message ────────────────────────────────
+        if self.editing {
+            match nav_lookup(code, mods) {
+                Some(NavAction::Confirm) => self.finish_edit(),
+                Some(NavAction::Dismiss) => {
+                    self.editing = false;
+                    self.textarea.clear();
+                }
+                _ => {
+                    self.textarea
+                        .handle_key_with_kill_ring(code, mods, &mut self.kill_ring);
+                }
+            }
+            return None;
+        }
+
+        if self.pending_d {
+            self.pending_d = false;
+            if code == KeyCode::Char('d') && mods == KeyModifiers::NONE {
+                self.delete_selected();
+                return None;
+            }
+        }
+
+        match (code, mods) {
+            (KeyCode::Char('q'), KeyModifiers::NONE) => return Some(self.close_result()),
+            (KeyCode::Char('d'), KeyModifiers::NONE) => {
+                self.pending_d = true;
+                return None;
+            }
+            (KeyCode::Backspace | KeyCode::Delete, _) => {
+                self.delete_selected();
+                return None;
+            }
+            (KeyCode::Char('s'), KeyModifiers::NONE) => {
+                if let Some(item) = self.items.get_mut(self.list.selected) {
+                    item.steer = !item.steer;
+                }
+                return None;
+            }
+            (KeyCode::Up, m) if m.contains(KeyModifiers::SHIFT) => {
+                self.move_selected(true);
+                return None;
+            }
+            (KeyCode::Down, m) if m.contains(KeyModifiers::SHIFT) => {
+                self.move_selected(false);
+                return None;
+            }
+            (KeyCode::Char('K'), _) => {
+                self.move_selected(true);
+                return None;
+            }
+            (KeyCode::Char('J'), _) => {
+                self.move_selected(false);
+                return None;
+            }
+            _ => {}
+        }
+
+        let n = self.items.len();
+        match nav_lookup(code, mods) {
+            Some(NavAction::Dismiss) => Some(self.close_result()),
+            Some(NavAction::Confirm | NavAction::Edit) => {
+                self.start_edit();
+                None
+            }
+            Some(nav) => {
+                self.list.handle_nav(nav, n);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn draw(&mut self, out: &mut RenderOut, start_row: u16, width: u16, granted_rows: u16) {
+        self.last_width = width as usize;
+        let extra = self.edit_rows();
+        let Some(w) = self.list.begin_draw(
+            out,
+            start_row,
+            self.items.len().max(1),
+            width,
+            granted_rows.saturating_sub(extra),
+            4,
+        ) else {
+            return;
+        };
+
+        draw_bar(out, w, None, None, theme::accent());
+        out.overlay_newline();
+
+        out.push_dim();
+        out.print(" Queued Messages");
+        out.pop_style();
+        out.overlay_newline();
+
+        let mut row = start_row + 2;
+        let mut cursor_pos = None;
+        if self.items.is_empty() {
+            out.push_dim();
+            out.print("  No queued messages");
+            out.pop_style();
+            out.overlay_newline();
+        } else {
+            let num_w = self.items.len().to_string().len();
+            let prefix = self.prefix_cols();
+            let text_w = w.saturating_sub(prefix + 1).max(1);
+            let range = self.list.visible_range(self.items.len());
+            for (i, item) in self
+                .items
+                .iter()
+                .enumerate()
+                .take(range.end)
+                .skip(range.start)
+            {
+                let selected = i == self.list.selected;
+                out.push_dim();
+                out.print(&format!("  {:>num_w$}. ", i + 1));
+                out.pop_style();
+                let target = if item.steer { "steer" } else { "next turn" };
+                if item.steer {
+                    out.push_fg(theme::accent());
+                } else {
+                    out.push_dim();
+                }
+                out.print(&format!("{target:<TARGET_COLS$}"));
+                out.pop_style();
+
+                if selected && self.editing {
+                    let (lines, cursor) = self.textarea.wrap(text_w);
+                    for (vi, line) in lines.iter().enumerate() {
+                        if vi > 0 {
+                            out.print(&" ".repeat(prefix));
+                        }
+                        out.print(line);
+                        if vi == cursor.0 {
+                            cursor_pos = Some(((prefix + cursor.1) as u16, row));
+                        }
+                        if vi + 1 < lines.len() {
+                            out.overlay_newline();
+                            row += 1;
+                        }
+                    }
+                } else {
+                    let first = item.text.lines().next().unwrap_or("");
+                    let mut label = truncate_str(first, text_w);
+                    if item.text.lines().nth(1).is_some() && label.chars().count() < text_w {
+                        label.push('\u{2026}');
+                    }
+                    if selected {
+                        out.push_fg(theme::accent());
+                        out.print(&label);
+                        out.pop_style();
+                    } else {
+                        out.print(&label);
+                    }
+                }
+                out.overlay_newline();
+                row += 1;
+            }
+        }
+
+        out.overlay_newline();
+        out.push_dim();
+        let hint = if self.editing {
+            hints::join(&[hints::SAVE, hints::CANCEL])
+        } else if self.pending_d {
+            hints::join(&[hints::DD_PENDING, hints::CLOSE])
+        } else {
+            hints::join(&[
+                hints::EDIT_ITEM,
+                hints::TOGGLE_STEER,
+                hints::MOVE_ITEM,
+                hints::dd_delete(self.vim_enabled),
+                hints::CLOSE,
+            ])
+        };
+        out.print(&hint);
+        out.pop_style();
+        end_dialog_draw(out);
+        finish_dialog_frame(out, cursor_pos, self.editing);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::Dialog;
+
+    fn msg(id: u64, text: &str, steer: bool) -> QueuedMessage {
+        QueuedMessage {
+            id,
+            text: text.into(),
+            steer,
+        }
+    }
+
+    fn close(d: &mut QueueDialog) -> Vec<QueuedMessage> {
+        match d.handle_key(KeyCode::Esc, KeyModifiers::NONE) {
+            Some(DialogResult::QueueClosed { items }) => items,
+            _ => panic!("expected QueueClosed"),
+        }
+    }
+
+    #[test]
+    fn reorder_toggle_and_delete() {
+        let mut d = QueueDialog::new(
+            vec![msg(1, "a", false), msg(2, "b", false), msg(3, "c", true)],
+            false,
+        );
+        d.handle_key(KeyCode::Down, KeyModifiers::NONE);
+        d.handle_key(KeyCode::Up, KeyModifiers::SHIFT);
+        d.handle_key(KeyCode::Char('s'), KeyModifiers::NONE);
+        d.handle_key(KeyCode::Down, KeyModifiers::NONE);
+        d.handle_key(KeyCode::Down, KeyModifiers::NONE);
+        d.handle_key(KeyCode::Char('d'), KeyModifiers::NONE);
+        d.handle_key(KeyCode::Char('d'), KeyModifiers::NONE);
+        assert_eq!(close(&mut d), vec![msg(2, "b", true), msg(1, "a", false)]);
+    }
+
+    #[test]
+    fn edit_saves_on_enter_and_cancels_on_esc() {
+        let mut d = QueueDialog::new(vec![msg(1, "a", false)], false);
+        d.handle_key(KeyCode::Enter, KeyModifiers::NONE);
+        d.handle_key(KeyCode::Char('b'), KeyModifiers::NONE);
+        d.handle_key(KeyCode::Enter, KeyModifiers::NONE);
+        d.handle_key(KeyCode::Enter, KeyModifiers::NONE);
+        d.handle_key(KeyCode::Char('x'), KeyModifiers::NONE);
+        d.handle_key(KeyCode::Esc, KeyModifiers::NONE);
+        assert_eq!(close(&mut d), vec![msg(1, "ab", false)]);
+    }
+}
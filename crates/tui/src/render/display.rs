@@ -78,6 +78,8 @@ pub enum ColorRole {
     ToolPending,
     ReasonOff,
     Muted,
+    DiffAddedBg,
+    DiffRemovedBg,
 }
 
 /// Mirror of crossterm's named colors. We can't store crossterm::Color
@@ -25,14 +25,26 @@ pub fn warm_up_syntect() {
     LazyLock::force(&THEME_SET);
 }
 
-fn syntax_theme() -> &'static syntect::highlighting::Theme {
-    if theme::is_light() {
-        &THEME_SET[two_face::theme::EmbeddedThemeName::MonokaiExtendedLight]
+fn syntax_theme_id() -> two_face::theme::EmbeddedThemeName {
+    if let Some(id) = theme::palette_syntax() {
+        id
+    } else if theme::is_light() {
+        two_face::theme::EmbeddedThemeName::MonokaiExtendedLight
     } else {
-        &THEME_SET[two_face::theme::EmbeddedThemeName::MonokaiExtended]
+        two_face::theme::EmbeddedThemeName::MonokaiExtended
     }
 }
 
+fn syntax_theme() -> &'static syntect::highlighting::Theme {
+    &THEME_SET[syntax_theme_id()]
+}
+
+/// Name of the syntax theme baked into laid-out spans. Cached layouts are
+/// only reusable under the same syntax theme.
+pub(crate) fn syntax_theme_name() -> &'static str {
+    syntax_theme_id().as_name()
+}
+
 pub(crate) fn render_code_block<S: LayoutSink>(
     out: &mut S,
     lines: &[&str],
@@ -604,8 +616,8 @@ pub(super) fn print_cached_inline_diff<S: LayoutSink>(
     // bounds, so the layout cannot be replayed at a different width.
     out.mark_wrapped();
     let emit_limit = if max_rows == 0 { u16::MAX } else { max_rows };
-    let bg_del = ColorValue::Rgb(60, 20, 20);
-    let bg_add = ColorValue::Rgb(20, 50, 20);
+    let bg_del = ColorValue::Role(ColorRole::DiffRemovedBg);
+    let bg_add = ColorValue::Role(ColorRole::DiffAddedBg);
     let blank_gutter = " ".repeat(1 + gutter_width + 3);
 
     let mut emitted = 0u16;
@@ -676,6 +688,154 @@ pub(super) fn print_cached_inline_diff<S: LayoutSink>(
     emitted
 }
 
+/// Build a whole-file diff between a file's session baseline and its
+/// current content. `new` must match what is on disk at `path`.
+pub(super) fn build_file_diff_cache(old: &str, new: &str, path: &str) -> CachedInlineDiff {
+    build_inline_diff_cache(old, new, path, new)
+}
+
+/// One row of a side-by-side diff: the old line on the left, the new line
+/// on the right. Paired deletes/inserts share a row.
+pub(super) enum SideBySideRow<'a> {
+    Pair {
+        left: Option<&'a CachedDiffLine>,
+        right: Option<&'a CachedDiffLine>,
+    },
+    Ellipsis,
+}
+
+/// Regroup a unified diff cache into side-by-side rows. Each run of
+/// deletions is zipped with the run of insertions that follows it.
+pub(super) fn side_by_side_rows(cache: &CachedInlineDiff) -> Vec<SideBySideRow<'_>> {
+    let mut rows = Vec::new();
+    let mut i = 0;
+    while i < cache.lines.len() {
+        match &cache.lines[i] {
+            CachedDiffLine::Ellipsis => {
+                rows.push(SideBySideRow::Ellipsis);
+                i += 1;
+            }
+            line @ CachedDiffLine::Context { .. } => {
+                rows.push(SideBySideRow::Pair {
+                    left: Some(line),
+                    right: Some(line),
+                });
+                i += 1;
+            }
+            CachedDiffLine::Delete { .. } | CachedDiffLine::Insert { .. } => {
+                let mut dels = Vec::new();
+                while let Some(l @ CachedDiffLine::Delete { .. }) = cache.lines.get(i) {
+                    dels.push(l);
+                    i += 1;
+                }
+                let mut ins = Vec::new();
+                while let Some(l @ CachedDiffLine::Insert { .. }) = cache.lines.get(i) {
+                    ins.push(l);
+                    i += 1;
+                }
+                for k in 0..dels.len().max(ins.len()) {
+                    rows.push(SideBySideRow::Pair {
+                        left: dels.get(k).copied(),
+                        right: ins.get(k).copied(),
+                    });
+                }
+            }
+        }
+    }
+    rows
+}
+
+/// Render a side-by-side diff. Lines are truncated to their half of the
+/// terminal rather than wrapped, so each row is exactly one visual row.
+pub(super) fn print_cached_side_by_side<S: LayoutSink>(
+    out: &mut S,
+    cache: &CachedInlineDiff,
+    skip: u16,
+    max_rows: u16,
+) -> u16 {
+    let _perf = crate::perf::begin("render:side_by_side_diff");
+    let indent = "   ";
+    let gutter_width = format!("{}", cache.max_display_lineno).len();
+    let tw = term_width();
+    let half = tw.saturating_sub(indent.len() * 2 + 1) / 2;
+    let content_w = half.saturating_sub(gutter_width + 4).max(1);
+    let emit_limit = if max_rows == 0 { u16::MAX } else { max_rows };
+    let bg_del = ColorValue::Role(ColorRole::DiffRemovedBg);
+    let bg_add = ColorValue::Role(ColorRole::DiffAddedBg);
+
+    let print_side = |out: &mut S, line: Option<&CachedDiffLine>| {
+        let Some(line) = line else {
+            out.print_string(" ".repeat(half));
+            return;
+        };
+        let (lineno, spans, sign) = match line {
+            CachedDiffLine::Context { lineno, spans, .. } => (*lineno, spans, None),
+            CachedDiffLine::Delete { lineno, spans, .. } => (
+                *lineno,
+                spans,
+                Some(('-', ColorValue::Named(NamedColor::Red), bg_del)),
+            ),
+            CachedDiffLine::Insert { lineno, spans, .. } => (
+                *lineno,
+                spans,
+                Some(('+', ColorValue::Named(NamedColor::Green), bg_add)),
+            ),
+            CachedDiffLine::Ellipsis => unreachable!(),
+        };
+        let row = split_cached_spans_into_rows(out, spans, content_w)
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        match sign {
+            Some((ch, color, bg)) => {
+                out.set_bg(bg);
+                out.set_fg(color);
+                out.print_string(format!(" {:>w$} {} ", lineno, ch, w = gutter_width));
+                let cols = print_cached_spans(out, &row, Some(bg));
+                out.set_bg(bg);
+                out.print_string(" ".repeat(content_w.saturating_sub(cols)));
+                out.reset_style();
+            }
+            None => {
+                out.set_fg(ColorValue::Named(NamedColor::DarkGrey));
+                out.print_string(format!(" {:>w$}   ", lineno, w = gutter_width));
+                out.reset_style();
+                let cols = print_cached_spans(out, &row, None);
+                out.print_string(" ".repeat(content_w.saturating_sub(cols)));
+            }
+        }
+        let used = gutter_width + 4 + content_w;
+        if half > used {
+            out.print_string(" ".repeat(half - used));
+        }
+    };
+
+    let mut emitted = 0u16;
+    for row in side_by_side_rows(cache).iter().skip(skip as usize) {
+        if emitted >= emit_limit {
+            break;
+        }
+        out.print(indent);
+        match row {
+            SideBySideRow::Ellipsis => {
+                out.set_fg(ColorValue::Named(NamedColor::DarkGrey));
+                out.print_string(format!("{:>w$}", "...", w = 1 + gutter_width));
+                out.reset_style();
+            }
+            SideBySideRow::Pair { left, right } => {
+                print_side(out, *left);
+                out.set_fg(ColorValue::Named(NamedColor::DarkGrey));
+                out.print("\u{2502}");
+                out.reset_style();
+                print_side(out, *right);
+            }
+        }
+        out.newline();
+        emitted += 1;
+    }
+    emitted
+}
+
 /// Count rows an inline diff would take without rendering.
 pub(super) fn count_inline_diff_rows(old: &str, new: &str, path: &str, anchor: &str) -> u16 {
     let cache = build_inline_diff_cache(old, new, path, anchor);
@@ -1541,6 +1701,61 @@ mod tests {
         ("strike", s.into())
     }
 
+    // ── Side-by-side diff ──────────────────────────────────────────────
+
+    fn diff_line(kind: char, lineno: usize) -> CachedDiffLine {
+        let (text, spans) = (String::new(), Vec::new());
+        match kind {
+            '-' => CachedDiffLine::Delete {
+                lineno,
+                text,
+                spans,
+            },
+            '+' => CachedDiffLine::Insert {
+                lineno,
+                text,
+                spans,
+            },
+            _ => CachedDiffLine::Context {
+                lineno,
+                text,
+                spans,
+            },
+        }
+    }
+
+    #[test]
+    fn side_by_side_pairs_deletes_with_inserts() {
+        let cache = CachedInlineDiff {
+            max_display_lineno: 5,
+            lines: vec![
+                diff_line(' ', 1),
+                diff_line('-', 2),
+                diff_line('-', 3),
+                diff_line('+', 2),
+                CachedDiffLine::Ellipsis,
+                diff_line('+', 5),
+            ],
+        };
+        let shape: Vec<(bool, bool)> = side_by_side_rows(&cache)
+            .iter()
+            .map(|row| match row {
+                SideBySideRow::Pair { left, right } => (left.is_some(), right.is_some()),
+                SideBySideRow::Ellipsis => (false, false),
+            })
+            .collect();
+        assert_eq!(
+            shape,
+            vec![
+                (true, true),
+                (true, true),
+                (true, false),
+                (false, false),
+                (false, true)
+            ]
+        );
+    }
+
     // ── Plain ──────────────────────────────────────────────────────────
 
     #[test]
@@ -10,7 +10,7 @@ use super::blocks::{gap_between, layout_block, Element};
 use super::cache::ToolOutputRenderCache;
 use super::context::{LayoutContext, PaintContext};
 use super::display::DisplayBlock;
-use super::paint::paint_block;
+use super::paint::{paint_block, paint_line};
 use super::RenderOut;
 use std::collections::{HashMap, HashSet};
 use std::time::{Duration, Instant};
@@ -254,6 +254,8 @@ pub struct BlockId(pub u64);
 pub struct LayoutKey {
     pub width: u16,
     pub show_thinking: bool,
+    #[serde(default)]
+    pub expanded: bool,
 }
 
 /// Per-block cached artifacts. Keeps a bounded LRU of the most recent
@@ -321,6 +323,20 @@ pub(super) struct BlockHistory {
     /// Set by `redraw` when a single block exceeds the redraw budget;
     /// consumed by the next `render` call and reset to 0 afterwards.
     pub(super) pending_head_skip: u16,
+    /// Blocks the user toggled away from their default presentation
+    /// (thinking shown/hidden against `show_thinking`, tool output
+    /// uncapped). Keyed by content id, so identical blocks toggle together.
+    pub(super) expanded: HashSet<BlockId>,
+}
+
+/// One visual row of the fully laid-out history, as produced by
+/// `BlockHistory::visual_rows`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum HistoryRow {
+    /// Blank separator row between two blocks.
+    Gap,
+    /// Row `line` of the block at index `block`.
+    Line { block: usize, line: usize },
 }
 
 impl BlockHistory {
@@ -336,6 +352,7 @@ impl BlockHistory {
             last_block_rows: 0,
             suppress_leading_gap: false,
             pending_head_skip: 0,
+            expanded: HashSet::new(),
         }
     }
 
@@ -388,6 +405,47 @@ impl BlockHistory {
         })
     }
 
+    /// Layout key for block `id`: the shared `key` plus its expand toggle.
+    fn block_key(&self, id: BlockId, key: LayoutKey) -> LayoutKey {
+        LayoutKey {
+            expanded: self.expanded.contains(&id),
+            ..key
+        }
+    }
+
+    /// Thinking and tool blocks are the only ones with a collapsed form.
+    pub(super) fn is_expandable(&self, i: usize) -> bool {
+        self.order.get(i).is_some_and(|id| {
+            matches!(
+                self.blocks.get(id),
+                Some(Block::Thinking { .. } | Block::ToolCall { .. })
+            )
+        })
+    }
+
+    /// Nearest expandable block before (`older`) or after block `from`,
+    /// or the newest one when `from` is `None`.
+    pub(super) fn next_expandable(&self, from: Option<usize>, older: bool) -> Option<usize> {
+        match from {
+            None => (0..self.order.len()).rev().find(|&i| self.is_expandable(i)),
+            Some(from) if older => (0..from).rev().find(|&i| self.is_expandable(i)),
+            Some(from) => (from + 1..self.order.len()).find(|&i| self.is_expandable(i)),
+        }
+    }
+
+    /// Flip the expand toggle of the block at `i`. Returns false for
+    /// blocks that have nothing to expand or collapse.
+    pub(super) fn toggle_expanded(&mut self, i: usize) -> bool {
+        if !self.is_expandable(i) {
+            return false;
+        }
+        let id = self.order[i];
+        if !self.expanded.remove(&id) {
+            self.expanded.insert(id);
+        }
+        true
+    }
+
     /// Drop every cached layout for a single block id.
     pub(super) fn invalidate_block_layout(&mut self, id: BlockId) {
         if let Some(artifact) = self.artifacts.get_mut(&id) {
@@ -398,6 +456,20 @@ impl BlockHistory {
         }
     }
 
+    /// Drop every cached layout and tool-output IR. Both bake in syntax
+    /// highlight colors, so they go stale when the syntax theme changes.
+    pub(super) fn invalidate_syntax(&mut self) {
+        for artifact in self.artifacts.values_mut() {
+            artifact.clear();
+        }
+        for state in self.tool_states.values_mut() {
+            if let Some(out) = state.output.as_mut() {
+                out.render_cache = None;
+            }
+        }
+        self.cache_dirty = true;
+    }
+
     pub(super) fn has_unflushed(&self) -> bool {
         self.flushed < self.order.len()
     }
@@ -407,6 +479,7 @@ impl BlockHistory {
         self.blocks.clear();
         self.artifacts.clear();
         self.tool_states.clear();
+        self.expanded.clear();
         self.flushed = 0;
         self.last_block_rows = 0;
         self.cache_dirty = true;
@@ -450,6 +523,7 @@ impl BlockHistory {
     /// pass gets a cache hit.
     pub(super) fn ensure_rows(&mut self, i: usize, key: LayoutKey) -> u16 {
         let id = self.order[i];
+        let key = self.block_key(id, key);
         if let Some(rows) = self
             .artifacts
             .get(&id)
@@ -467,6 +541,7 @@ impl BlockHistory {
         let lctx = LayoutContext {
             width: key.width,
             show_thinking: key.show_thinking,
+            expanded: key.expanded,
         };
         let display = layout_block(block, tool_state, &lctx);
         let rows = display.rows();
@@ -520,6 +595,7 @@ impl BlockHistory {
             if !live.contains(&id) {
                 self.blocks.remove(&id);
                 self.artifacts.remove(&id);
+                self.expanded.remove(&id);
             }
         }
         self.flushed = self.flushed.min(self.order.len());
@@ -545,6 +621,47 @@ impl BlockHistory {
         self.tool_states.retain(|cid, _| live.contains(cid));
     }
 
+    /// Flatten the whole history into visual rows at `key`, laying out
+    /// any block that has no cached layout yet. Used to page through
+    /// history that has already scrolled off screen and to hit-test
+    /// mouse clicks against blocks.
+    pub(super) fn visual_rows(&mut self, key: LayoutKey) -> Vec<HistoryRow> {
+        let mut rows = Vec::new();
+        for i in 0..self.order.len() {
+            for _ in 0..self.block_gap(i) {
+                rows.push(HistoryRow::Gap);
+            }
+            let n = self.ensure_rows(i, key);
+            rows.extend((0..n as usize).map(|line| HistoryRow::Line { block: i, line }));
+        }
+        rows
+    }
+
+    /// Paint a single row from `visual_rows`. Layouts must already be
+    /// cached for `key` (guaranteed when `visual_rows` ran with it).
+    pub(super) fn paint_row(
+        &self,
+        out: &mut RenderOut,
+        row: HistoryRow,
+        key: LayoutKey,
+        pctx: &PaintContext,
+    ) {
+        let line = match row {
+            HistoryRow::Gap => None,
+            HistoryRow::Line { block, line } => {
+                let id = self.order[block];
+                self.artifacts
+                    .get(&id)
+                    .and_then(|a| a.get(self.block_key(id, key)))
+                    .and_then(|d| d.lines.get(line))
+            }
+        };
+        match line {
+            Some(line) => paint_line(out, line, pctx),
+            None => out.newline(),
+        }
+    }
+
     /// Render unflushed blocks. Returns total rows printed.
     pub(super) fn render(&mut self, out: &mut RenderOut, width: usize, show_thinking: bool) -> u16 {
         if !self.has_unflushed() {
@@ -565,6 +682,7 @@ impl BlockHistory {
         let key = LayoutKey {
             width: width as u16,
             show_thinking,
+            expanded: false,
         };
 
         let mut total = 0u16;
@@ -587,6 +705,7 @@ impl BlockHistory {
             }
 
             let id = self.order[i];
+            let key = self.block_key(id, key);
             let block = &self.blocks[&id];
             let tool_state = if let Block::ToolCall { call_id, .. } = block {
                 self.tool_states.get(call_id)
@@ -604,6 +723,7 @@ impl BlockHistory {
                     let lctx = LayoutContext {
                         width: width as u16,
                         show_thinking,
+                        expanded: key.expanded,
                     };
                     let display = layout_block(block, tool_state, &lctx);
                     paint_block(out, &display, &pctx, head_skip_block as usize);
@@ -618,6 +738,7 @@ impl BlockHistory {
                 let lctx = LayoutContext {
                     width: width as u16,
                     show_thinking,
+                    expanded: key.expanded,
                 };
                 let display = layout_block(block, tool_state, &lctx);
                 paint_block(out, &display, &pctx, head_skip_block as usize);
@@ -713,16 +834,83 @@ mod tests {
         let k100 = LayoutKey {
             width: 100,
             show_thinking: true,
+            expanded: false,
         };
         let k80 = LayoutKey {
             width: 80,
             show_thinking: true,
+            expanded: false,
         };
         assert!(keys.contains(&k100), "expected width=100 cached: {keys:?}");
         assert!(keys.contains(&k80), "expected width=80 cached: {keys:?}");
         assert!(keys.len() <= BlockArtifact::MAX_LAYOUTS);
     }
 
+    #[test]
+    fn expanded_tool_output_lifts_row_cap() {
+        let mut history = BlockHistory::new();
+        let content = (0..50)
+            .map(|i| format!("line {i}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        history.push_with_state(
+            Block::ToolCall {
+                call_id: "c1".into(),
+                name: "bash".into(),
+                summary: "seq 50".into(),
+                args: HashMap::new(),
+            },
+            "c1".into(),
+            ToolState {
+                status: ToolStatus::Ok,
+                elapsed: None,
+                output: Some(Box::new(ToolOutput {
+                    content,
+                    is_error: false,
+                    metadata: None,
+                    render_cache: None,
+                })),
+                user_message: None,
+            },
+        );
+        let key = LayoutKey {
+            width: 80,
+            show_thinking: true,
+            expanded: false,
+        };
+        // Header + "... 30 lines above" + the last 20 lines.
+        assert_eq!(history.visual_rows(key).len(), 22);
+        assert!(history.toggle_expanded(0));
+        assert_eq!(history.visual_rows(key).len(), 51);
+        assert!(history.toggle_expanded(0));
+        assert_eq!(history.visual_rows(key).len(), 22);
+    }
+
+    #[test]
+    fn toggle_expanded_flips_thinking_only_for_expandable_blocks() {
+        let mut history = BlockHistory::new();
+        history.push(Block::Text {
+            content: "hello".into(),
+        });
+        history.push(Block::Thinking {
+            content: "alpha\nbeta\ngamma".into(),
+        });
+        let key = LayoutKey {
+            width: 80,
+            show_thinking: true,
+            expanded: false,
+        };
+        let thinking_rows = |rows: &[HistoryRow]| {
+            rows.iter()
+                .filter(|r| matches!(r, HistoryRow::Line { block: 1, .. }))
+                .count()
+        };
+        assert_eq!(thinking_rows(&history.visual_rows(key)), 3);
+        assert!(!history.toggle_expanded(0));
+        assert!(history.toggle_expanded(1));
+        assert_eq!(thinking_rows(&history.visual_rows(key)), 1);
+    }
+
     #[test]
     fn duplicate_block_ids_share_artifact() {
         // Two identical blocks at different positions should resolve to the
@@ -739,4 +927,26 @@ mod tests {
         assert_eq!(history.blocks.len(), 1);
         assert_eq!(history.artifacts.len(), 1);
     }
+
+    #[test]
+    fn next_expandable_skips_text_blocks() {
+        let mut history = BlockHistory::new();
+        history.push(Block::Thinking {
+            content: "a".into(),
+        });
+        history.push(Block::Text {
+            content: "b".into(),
+        });
+        history.push(Block::Thinking {
+            content: "c".into(),
+        });
+        history.push(Block::Text {
+            content: "d".into(),
+        });
+        assert_eq!(history.next_expandable(None, true), Some(2));
+        assert_eq!(history.next_expandable(Some(2), true), Some(0));
+        assert_eq!(history.next_expandable(Some(0), true), None);
+        assert_eq!(history.next_expandable(Some(0), false), Some(2));
+        assert_eq!(history.next_expandable(Some(2), false), None);
+    }
 }
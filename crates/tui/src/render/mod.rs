@@ -27,9 +27,10 @@ pub(crate) use selection::{scan_at_token, truncate_str, try_at_ref, wrap_line};
 pub(crate) use status::{draw_bar, BarSpan};
 
 pub use dialogs::{
-    parse_questions, AgentSnapshot, AgentsDialog, ConfirmDialog, Dialog, DialogResult,
-    ExportDialog, ExportTarget, HelpDialog, PermissionEntry, PermissionsDialog, PsDialog, Question,
-    QuestionDialog, QuestionOption, ResumeDialog, RewindDialog, SharedSnapshots,
+    parse_questions, AgentAction, AgentSnapshot, AgentSnapshots, AgentsDialog, ConfirmDialog,
+    Dialog, DialogResult, DiffDialog, ExportDialog, ExportTarget, HelpDialog, PermissionEntry,
+    PermissionsDialog, PsDialog, Question, QuestionDialog, QuestionOption, QueueDialog,
+    ResumeDialog, RewindDialog, SharedSnapshots,
 };
 
 /// Layout placement computed by `draw_frame` for the active dialog.
@@ -741,6 +742,8 @@ pub(super) fn resolve_role_live(role: display::ColorRole) -> Color {
         R::ToolPending => theme::tool_pending(),
         R::ReasonOff => theme::reason_off(),
         R::Muted => theme::muted(),
+        R::DiffAddedBg => theme::diff_added_bg(),
+        R::DiffRemovedBg => theme::diff_removed_bg(),
     }
 }
 
@@ -94,6 +94,8 @@ pub(super) fn resolve(c: ColorValue, theme: &Theme, _is_bg: bool) -> Color {
             ColorRole::ToolPending => theme.tool_pending,
             ColorRole::ReasonOff => theme.reason_off,
             ColorRole::Muted => theme.muted,
+            ColorRole::DiffAddedBg => theme.diff_added_bg,
+            ColorRole::DiffRemovedBg => theme.diff_removed_bg,
         },
     }
 }
@@ -31,6 +31,21 @@ pub(super) struct PromptState {
     /// Screen position `(col, row)` of the software block cursor from
     /// the last prompt frame. Used to erase it on exit.
     pub soft_cursor: Option<(u16, u16)>,
+    /// Where the visible input lines landed in the last prompt frame.
+    /// Used to map mouse clicks back to buffer positions.
+    pub input_area: Option<InputArea>,
+}
+
+#[derive(Clone, Copy)]
+pub struct InputArea {
+    /// Screen row of the first visible input line.
+    pub row: u16,
+    /// Number of visible input lines.
+    pub rows: usize,
+    /// Index of the first visible visual line (the input scroll offset).
+    pub first_line: usize,
+    /// Wrap width the input lines were laid out at.
+    pub usable: usize,
 }
 
 impl PromptState {
@@ -46,6 +61,7 @@ impl PromptState {
             prev_dialog_gap: 0,
             input_scroll: 0,
             soft_cursor: None,
+            input_area: None,
         }
     }
 }
@@ -17,14 +17,16 @@ use super::completions::{completion_reserved_rows, draw_completions, draw_menu};
 use super::context::PaintContext;
 use super::history::{
     ActiveAgent, ActiveExec, ActiveText, ActiveThinking, ActiveTool, AgentBlockStatus, Block,
-    BlockHistory, BlockId, LayoutKey, Throbber, ToolOutput, ToolOutputRef, ToolState, ToolStatus,
+    BlockHistory, BlockId, HistoryRow, LayoutKey, Throbber, ToolOutput, ToolOutputRef, ToolState,
+    ToolStatus,
 };
 use super::layout_out::{LayoutSink, SpanCollector};
 use super::paint::paint_line;
-use super::prompt::PromptState;
+use super::prompt::{InputArea, PromptState};
 use super::selection::{
-    build_char_kinds, build_display_spans, compute_visual_line_offsets, map_cursor,
-    render_styled_chars, spans_to_string, wrap_and_locate_cursor, wrap_line, SpanKind,
+    build_char_kinds, build_display_spans, char_at_column, compute_visual_line_offsets, map_cursor,
+    render_styled_chars, spans_to_string, unmap_cursor, wrap_and_locate_cursor, wrap_line,
+    SpanKind,
 };
 use super::status::{draw_bar, render_status_spans, vim_mode_label, BarSpan, StatusSpan};
 use super::working::WorkingState;
@@ -94,10 +96,20 @@ pub struct Screen {
     notification: Option<Notification>,
     /// Short task label (slug) shown on the status bar after the throbber.
     task_label: Option<String>,
+    /// Rows the mouse wheel has paged back from the live tail of the
+    /// history (0 = live). While non-zero, the rows above the prompt are
+    /// repainted from the block layouts instead of the terminal's own
+    /// scrollback, which mouse capture makes unreachable.
+    history_scroll: usize,
+    /// Block highlighted in block select mode (index into history). While
+    /// set, the history window is painted from layouts like a paged view.
+    block_select: Option<usize>,
 
     /// Terminal I/O backend (real terminal or test buffer).
     backend: Box<dyn TerminalBackend>,
     focused: bool,
+    /// `theme::palette_generation()` the history layouts were built under.
+    palette_generation: u64,
 }
 
 /// A short ephemeral notification rendered above the prompt bar.
@@ -163,8 +175,11 @@ impl Screen {
             btw: None,
             notification: None,
             task_label: None,
+            history_scroll: 0,
+            block_select: None,
             backend,
             focused: true,
+            palette_generation: crate::theme::palette_generation(),
         }
     }
 
@@ -660,6 +675,36 @@ impl Screen {
             });
         }
 
+        if self.block_select.is_some() {
+            spans.push(StatusSpan {
+                text: "block select".into(),
+                style: StyleState {
+                    fg: Some(theme::accent()),
+                    bg: Some(status_bg),
+                    bold: true,
+                    ..StyleState::default()
+                },
+                priority: 2,
+                group: true,
+                truncatable: false,
+            });
+        }
+
+        // Mouse wheel paged back through history.
+        if self.history_scroll > 0 {
+            spans.push(StatusSpan {
+                text: format!("history ↑{}", self.history_scroll),
+                style: StyleState {
+                    fg: Some(theme::accent()),
+                    bg: Some(status_bg),
+                    ..StyleState::default()
+                },
+                priority: 2,
+                group: true,
+                truncatable: false,
+            });
+        }
+
         // ── Responsive layout ──
         render_status_spans(out, &mut spans, width, status_bg);
     }
@@ -1210,8 +1255,8 @@ impl Screen {
         self.prompt.dirty = true;
     }
 
-    pub fn set_context_window(&mut self, window: u32) {
-        self.context_window = Some(window);
+    pub fn set_context_window(&mut self, window: Option<u32>) {
+        self.context_window = window;
         self.prompt.dirty = true;
     }
 
@@ -1463,6 +1508,13 @@ impl Screen {
         }
     }
 
+    /// Re-lay out history under a new syntax theme, then repaint.
+    pub fn redraw_with_new_syntax(&mut self) {
+        self.palette_generation = crate::theme::palette_generation();
+        self.history.invalidate_syntax();
+        self.redraw();
+    }
+
     /// Clear screen + scrollback and repaint the last
     /// `MAX_REDRAW_LINES` rows of committed history in scroll mode.
     /// Rows past the viewport scroll into the fresh scrollback so the
@@ -1482,10 +1534,7 @@ impl Screen {
             let _p = crate::perf::begin("redraw:invalidate");
             self.history.invalidate_for_width(w as usize);
         }
-        let key = LayoutKey {
-            width: w,
-            show_thinking: self.show_thinking,
-        };
+        let key = self.layout_key(w as usize);
         let (start_idx, head_skip) = {
             let _p = crate::perf::begin("redraw:start_idx");
             self.history.redraw_start(MAX_REDRAW_LINES, key)
@@ -1503,6 +1552,7 @@ impl Screen {
         self.prompt.prev_rows = 0;
         self.content_start_row = Some(0);
         self.has_scrollback = false;
+        self.history_scroll = 0;
         self.prompt.anchor_row = Some(block_rows.min(height.saturating_sub(1)));
     }
 
@@ -1520,6 +1570,8 @@ impl Screen {
         self.session_cost_usd = 0.0;
         self.task_label = None;
         self.has_scrollback = false;
+        self.history_scroll = 0;
+        self.block_select = None;
         self.content_start_row = None;
         let mut frame = Frame::begin(&*self.backend);
         let _ = frame.queue(cursor::MoveTo(0, 0));
@@ -1527,6 +1579,255 @@ impl Screen {
         let _ = frame.queue(terminal::Clear(terminal::ClearType::Purge));
     }
 
+    // ── Mouse ────────────────────────────────────────────────────────
+
+    fn layout_key(&self, width: usize) -> LayoutKey {
+        LayoutKey {
+            width: width as u16,
+            show_thinking: self.show_thinking,
+            expanded: false,
+        }
+    }
+
+    /// Rows of committed history visible above the prompt region.
+    fn history_view_rows(&self) -> usize {
+        self.prompt.anchor_row.unwrap_or(0) as usize
+    }
+
+    pub fn history_scrolled(&self) -> bool {
+        self.history_scroll > 0
+    }
+
+    /// Page the history view by `delta` rows (positive = older). Scrolling
+    /// back to the live tail hands the screen back to the terminal.
+    pub fn scroll_history(&mut self, delta: isize) {
+        let view = self.history_view_rows();
+        if view == 0 || self.history.has_unflushed() {
+            return;
+        }
+        let width = self.size().0 as usize;
+        let total = self.history.visual_rows(self.layout_key(width)).len();
+        let max = total.saturating_sub(view);
+        let old = self.history_scroll;
+        let new = if delta > 0 {
+            (old + delta as usize).min(max)
+        } else {
+            old.saturating_sub(delta.unsigned_abs())
+        };
+        if new == old {
+            return;
+        }
+        if new == 0 {
+            self.leave_history_scroll();
+        } else {
+            self.history_scroll = new;
+            self.prompt.dirty = true;
+        }
+    }
+
+    /// Page the history view by half its height.
+    pub fn page_history(&mut self, older: bool) {
+        let half = (self.history_view_rows() / 2).max(1) as isize;
+        self.scroll_history(if older { half } else { -half });
+    }
+
+    /// Return to the live tail. The terminal rows were overpainted, so
+    /// this repaints them from scratch.
+    pub fn leave_history_scroll(&mut self) {
+        if self.history_scroll > 0 {
+            self.redraw();
+        }
+    }
+
+    /// New blocks can only be appended to the live view, so jump back
+    /// to it when any arrive while the history is paged back.
+    pub fn follow_new_blocks(&mut self) {
+        if self.history.has_unflushed() {
+            self.leave_block_select();
+            self.leave_history_scroll();
+        }
+    }
+
+    // ── Block select ─────────────────────────────────────────────────
+
+    pub fn block_selecting(&self) -> bool {
+        self.block_select.is_some()
+    }
+
+    /// Highlight the newest expandable block. Returns false when there is
+    /// nothing to select.
+    pub fn enter_block_select(&mut self) -> bool {
+        if self.history_view_rows() == 0 || self.history.has_unflushed() {
+            return false;
+        }
+        let Some(idx) = self.history.next_expandable(None, true) else {
+            return false;
+        };
+        self.block_select = Some(idx);
+        self.reveal_block(idx);
+        true
+    }
+
+    /// Move the highlight to the previous (`older`) or next expandable
+    /// block. Stays put at either end.
+    pub fn move_block_select(&mut self, older: bool) {
+        let Some(cur) = self.block_select else {
+            return;
+        };
+        if let Some(idx) = self.history.next_expandable(Some(cur), older) {
+            self.block_select = Some(idx);
+            self.reveal_block(idx);
+        }
+    }
+
+    /// Expand or collapse the highlighted block, keeping it in view.
+    pub fn toggle_selected_block(&mut self) {
+        let Some(idx) = self.block_select else {
+            return;
+        };
+        if self.history.toggle_expanded(idx) {
+            self.reveal_block(idx);
+        }
+    }
+
+    /// Drop the highlight and repaint the live tail.
+    pub fn leave_block_select(&mut self) {
+        if self.block_select.take().is_some() {
+            self.redraw();
+        }
+    }
+
+    /// Adjust the paged view so block `idx` is visible, preferring its
+    /// first row when the block is taller than the view.
+    fn reveal_block(&mut self, idx: usize) {
+        let view = self.history_view_rows();
+        let key = self.layout_key(self.size().0 as usize);
+        let rows = self.history.visual_rows(key);
+        let total = rows.len();
+        let in_block =
+            |r: &HistoryRow| matches!(r, HistoryRow::Line { block, .. } if *block == idx);
+        let Some(first) = rows.iter().position(in_block) else {
+            return;
+        };
+        let last = rows.iter().rposition(in_block).unwrap_or(first);
+        let mut scroll = self.history_scroll.min(total);
+        if last >= total - scroll {
+            scroll = total - (last + 1);
+        }
+        if first < (total - scroll).saturating_sub(view) {
+            scroll = total.saturating_sub(first + view);
+        }
+        self.history_scroll = scroll.min(total.saturating_sub(view));
+        self.prompt.dirty = true;
+    }
+
+    /// Index of the committed block painted at terminal `row`, if any.
+    fn block_at_row(&mut self, row: u16) -> Option<usize> {
+        let view = self.history_view_rows();
+        if row as usize >= view {
+            return None;
+        }
+        let width = self.size().0 as usize;
+        let rows = self.history.visual_rows(self.layout_key(width));
+        // Rows above the prompt hold the tail of the history, offset by
+        // however far the wheel has paged back.
+        let end = rows.len().saturating_sub(self.history_scroll);
+        let idx = (end + row as usize).checked_sub(view)?;
+        match rows.get(idx)? {
+            HistoryRow::Line { block, .. } => Some(*block),
+            HistoryRow::Gap => None,
+        }
+    }
+
+    /// Expand or collapse the thinking/tool block under terminal `row`.
+    /// Returns true if a block was toggled.
+    pub fn toggle_block_at_row(&mut self, row: u16) -> bool {
+        let Some(idx) = self.block_at_row(row) else {
+            return false;
+        };
+        if self.history_scroll == 0 {
+            self.history.toggle_expanded(idx);
+            self.redraw();
+            return true;
+        }
+        // Keep the top of the paged view steady while the block grows
+        // or shrinks beneath it.
+        let key = self.layout_key(self.size().0 as usize);
+        let before = self.history.visual_rows(key).len();
+        self.history.toggle_expanded(idx);
+        let after = self.history.visual_rows(key).len();
+        let max = after.saturating_sub(self.history_view_rows());
+        let scroll = (self.history_scroll + after)
+            .saturating_sub(before)
+            .min(max);
+        if scroll == 0 {
+            self.redraw();
+        } else {
+            self.history_scroll = scroll;
+            self.prompt.dirty = true;
+        }
+        true
+    }
+
+    /// Buffer byte offset under terminal cell `(col, row)` in the prompt
+    /// input, if the click landed on a visible input line.
+    pub fn prompt_offset_at(&self, state: &InputState, col: u16, row: u16) -> Option<usize> {
+        let area = self.prompt.input_area?;
+        if row < area.row || (row - area.row) as usize >= area.rows {
+            return None;
+        }
+        let spans = build_display_spans(&state.buf, &state.attachment_ids, &state.store);
+        let display_buf = spans_to_string(&spans);
+        let char_kinds = build_char_kinds(&spans);
+        let (visual_lines, ..) = wrap_and_locate_cursor(&display_buf, &char_kinds, 0, area.usable);
+        let line = area.first_line + (row - area.row) as usize;
+        let (text, _) = visual_lines.get(line)?;
+        let offsets = compute_visual_line_offsets(&display_buf, &visual_lines);
+        // Column 0 is the one-space gutter in front of the input.
+        let display = offsets[line] + char_at_column(text, col.saturating_sub(1) as usize);
+        let raw = unmap_cursor(display, &spans);
+        Some(crate::input::byte_of_char(&state.buf, raw))
+    }
+
+    /// Overpaint the `rows` terminal rows above the prompt region with
+    /// the paged-back slice of history.
+    fn paint_history_window(&mut self, out: &mut RenderOut, width: usize, rows: u16) {
+        let key = self.layout_key(width);
+        let map = self.history.visual_rows(key);
+        let end = map.len().saturating_sub(self.history_scroll);
+        let start = end.saturating_sub(rows as usize);
+        let theme = crate::theme::snapshot();
+        let pctx = PaintContext {
+            theme: &theme,
+            term_width: width as u16,
+        };
+        let saved_row = out.row;
+        let _ = out.queue(cursor::SavePosition);
+        out.row = Some(0);
+        let _ = out.queue(cursor::MoveTo(0, 0));
+        for _ in (end - start)..rows as usize {
+            out.newline();
+        }
+        for &row in &map[start..end] {
+            self.history.paint_row(out, row, key, &pctx);
+        }
+        out.reset_style();
+        // Gutter bar beside every row of the selected block.
+        if let Some(sel) = self.block_select {
+            let top = rows as usize - (end - start);
+            out.push_fg(theme.accent);
+            for (i, row) in map[start..end].iter().enumerate() {
+                if matches!(row, HistoryRow::Line { block, .. } if *block == sel) {
+                    let _ = out.queue(cursor::MoveTo(0, (top + i) as u16));
+                    out.print("▌");
+                }
+            }
+            out.pop_style();
+        }
+        out.row = saved_row;
+        let _ = out.queue(cursor::RestorePosition);
+    }
+
     pub fn has_history(&self) -> bool {
         !self.history.is_empty()
     }
@@ -1570,7 +1871,10 @@ impl Screen {
         if self.history.is_empty() {
             return None;
         }
-        let mut cache = PersistedLayoutCache::new(crate::theme::is_light());
+        let mut cache = PersistedLayoutCache::new(
+            crate::theme::is_light(),
+            super::highlight::syntax_theme_name(),
+        );
         // Walk `order` so we only export artifacts for blocks currently in
         // history (and so we can inspect the `ToolState` of each tool block
         // exactly once — duplicates in `order` resolve to the same entry).
@@ -1614,7 +1918,10 @@ impl Screen {
     /// misses on the next render. Tool blocks in a non-terminal state
     /// still skip cache adoption so the next render rebuilds their layout.
     pub fn import_layout_cache(&mut self, cache: PersistedLayoutCache) {
-        if !cache.is_compatible(crate::theme::is_light()) {
+        if !cache.is_compatible(
+            crate::theme::is_light(),
+            super::highlight::syntax_theme_name(),
+        ) {
             return;
         }
         let nw = self.size().0;
@@ -1759,7 +2066,7 @@ impl Screen {
                         .map(|p| gap_between(&Element::Block(p), &Element::Block(&block)))
                         .unwrap_or(0);
                     emit_newlines(out, gap);
-                    render_block(out, &block, None, width, self.show_thinking);
+                    render_block(out, &block, None, width, self.show_thinking, false);
                     prev_synth = Some(block);
                     had_streaming = true;
                 }
@@ -1850,7 +2157,7 @@ impl Screen {
                     .map(|p| gap_between(&Element::Block(p), &Element::Block(&block)))
                     .unwrap_or(0);
                 emit_newlines(out, gap);
-                render_block(out, &block, None, width, self.show_thinking);
+                render_block(out, &block, None, width, self.show_thinking, false);
                 prev_synth = Some(block);
                 had_streaming = true;
             }
@@ -1882,6 +2189,7 @@ impl Screen {
                 tool.output.as_deref(),
                 tool.user_message.as_deref(),
                 width,
+                false,
             );
             tool_count += 1;
         }
@@ -1907,7 +2215,7 @@ impl Screen {
                 status: agent.status,
                 elapsed: Some(elapsed),
             };
-            render_block(out, &agent_block, None, width, self.show_thinking);
+            render_block(out, &agent_block, None, width, self.show_thinking, false);
         }
 
         // ── Active exec ────────────────────────────────────────────
@@ -1944,6 +2252,11 @@ impl Screen {
     ) -> (bool, Option<DialogPlacement>) {
         let _perf = crate::perf::begin("render:frame");
 
+        // A theme preview swapped the palette under the cached layouts.
+        if self.palette_generation != crate::theme::palette_generation() {
+            self.redraw_with_new_syntax();
+        }
+
         self.update_spinner();
 
         let has_new_blocks = self.history.has_unflushed();
@@ -2188,6 +2501,10 @@ impl Screen {
                 }
             }
 
+            if self.history_scroll > 0 || self.block_select.is_some() {
+                self.paint_history_window(out, width, final_anchor);
+            }
+
             (false, None)
         } else {
             // ── Dialog mode ─────────────────────────────────────────
@@ -2324,6 +2641,7 @@ impl Screen {
         self.last_vim_mode = state.vim_mode();
         self.last_mode = mode;
         self.prompt.soft_cursor = None;
+        self.prompt.input_area = None;
         let usable = width.saturating_sub(2);
         // Neutralize any styling carried over from the preceding
         // history/overlay paint in this same frame before the prompt
@@ -2550,6 +2868,15 @@ impl Screen {
         // display buffer contributes 1 additional char between logical lines.
         let line_char_offsets = compute_visual_line_offsets(&display_buf, &visual_lines);
 
+        if !show_prediction {
+            self.prompt.input_area = Some(InputArea {
+                row: out.row.unwrap_or(0),
+                rows: content_rows,
+                first_line: scroll_offset,
+                usable,
+            });
+        }
+
         let has_scrollbar = total_content_rows > content_rows && content_rows > 0;
         let (thumb_start, thumb_end) = if has_scrollbar {
             let thumb_size = (content_rows * content_rows / total_content_rows).max(1);
@@ -3010,6 +3337,75 @@ mod tests {
         );
     }
 
+    /// Fixed-size backend whose output is discarded.
+    struct SizedBackend(u16, u16);
+
+    impl TerminalBackend for SizedBackend {
+        fn size(&self) -> (u16, u16) {
+            (self.0, self.1)
+        }
+        fn cursor_y(&self) -> u16 {
+            0
+        }
+        fn make_output(&self) -> RenderOut {
+            RenderOut::buffer()
+        }
+    }
+
+    /// A thinking block followed by enough text to push it out of a
+    /// 10-row view, flushed to the (fake) terminal.
+    fn screen_with_old_thinking() -> Screen {
+        let mut screen = Screen::with_backend(Box::new(SizedBackend(40, 10)));
+        screen.push(Block::Thinking {
+            content: (1..=12)
+                .map(|i| format!("step {i}"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        });
+        for i in 0..30 {
+            screen.push(Block::Text {
+                content: format!("line {i}"),
+            });
+        }
+        screen.redraw();
+        screen
+    }
+
+    fn visible_blocks(screen: &mut Screen) -> Vec<usize> {
+        (0..screen.history_view_rows() as u16)
+            .filter_map(|row| screen.block_at_row(row))
+            .collect()
+    }
+
+    #[test]
+    fn block_select_reveals_scrolled_off_block() {
+        let mut screen = screen_with_old_thinking();
+        assert!(!visible_blocks(&mut screen).contains(&0));
+        assert!(screen.enter_block_select());
+        assert!(screen.history_scrolled());
+        assert!(visible_blocks(&mut screen).contains(&0));
+        // Nothing newer is expandable, so the highlight stays put.
+        screen.move_block_select(false);
+        assert_eq!(screen.block_select, Some(0));
+    }
+
+    #[test]
+    fn toggle_selected_block_keeps_its_top_in_view() {
+        let mut screen = screen_with_old_thinking();
+        assert!(screen.enter_block_select());
+        let key = screen.layout_key(40);
+        let before = screen.history.visual_rows(key).len();
+        screen.toggle_selected_block();
+        let key = screen.layout_key(40);
+        assert_ne!(screen.history.visual_rows(key).len(), before);
+        assert_eq!(visible_blocks(&mut screen).first(), Some(&0));
+        // Toggling back restores the original layout.
+        screen.toggle_selected_block();
+        let key = screen.layout_key(40);
+        assert_eq!(screen.history.visual_rows(key).len(), before);
+        assert!(visible_blocks(&mut screen).contains(&0));
+    }
+
     #[test]
     fn export_render_cache_skips_blocks_without_ir() {
         let mut screen = Screen::new();
@@ -453,6 +453,46 @@ pub(super) fn map_cursor(raw_cursor: usize, raw_buf: &str, spans: &[Span]) -> us
     display_pos
 }
 
+/// Inverse of `map_cursor`: raw char position for a display char
+/// position. Positions inside an attachment label snap to the marker.
+pub(super) fn unmap_cursor(display_cursor: usize, spans: &[Span]) -> usize {
+    let mut raw_pos = 0;
+    let mut display_pos = 0;
+    for span in spans {
+        let (raw_len, display_len) = match span {
+            Span::Plain(t) | Span::AtRef(t) => {
+                let n = t.chars().count();
+                (n, n)
+            }
+            Span::Attachment(label) => (1, label.chars().count()),
+        };
+        if display_cursor < display_pos + display_len {
+            return match span {
+                Span::Attachment(_) => raw_pos,
+                _ => raw_pos + (display_cursor - display_pos),
+            };
+        }
+        raw_pos += raw_len;
+        display_pos += display_len;
+    }
+    raw_pos
+}
+
+/// Number of chars of a prompt visual line that fit before display
+/// column `col` (relative to the first input column).
+pub(super) fn char_at_column(line: &str, col: usize) -> usize {
+    let prompt_col = 1usize;
+    let mut width = 0;
+    for (i, ch) in line.chars().enumerate() {
+        let w = display_char_width(ch, prompt_col + width);
+        if width + w > col {
+            return i;
+        }
+        width += w;
+    }
+    line.chars().count()
+}
+
 /// Render a line using pre-computed per-character span kinds.
 /// `selection` is an optional (start_char, end_char) range within this line.
 /// `cursor_pos` is an optional char index within this line to render as a
@@ -528,6 +568,31 @@ pub(super) fn render_styled_chars(
 mod tests {
     use super::*;
 
+    #[test]
+    fn unmap_cursor_inverts_map_cursor() {
+        let spans = vec![
+            Span::Plain("ab".into()),
+            Span::Attachment("[paste]".into()),
+            Span::Plain("cd".into()),
+        ];
+        let raw = "ab\u{0}cd";
+        for raw_cursor in 0..=4 {
+            let display = map_cursor(raw_cursor, raw, &spans);
+            assert_eq!(unmap_cursor(display, &spans), raw_cursor);
+        }
+        // Inside the attachment label snaps to the marker itself.
+        assert_eq!(unmap_cursor(5, &spans), 2);
+    }
+
+    #[test]
+    fn char_at_column_counts_wide_chars() {
+        assert_eq!(char_at_column("abc", 0), 0);
+        assert_eq!(char_at_column("abc", 2), 2);
+        assert_eq!(char_at_column("abc", 10), 3);
+        assert_eq!(char_at_column("界b", 1), 0);
+        assert_eq!(char_at_column("界b", 2), 1);
+    }
+
     fn vlines(strs: &[&str]) -> Vec<(String, Vec<SpanKind>)> {
         strs.iter()
             .map(|s| (s.to_string(), vec![SpanKind::Plain; s.chars().count()]))
@@ -188,7 +188,12 @@ pub fn time_ago(ts_ms: u64, now_ms: u64) -> String {
 
 /// Return the directory for a session on disk.
 pub fn dir_for(session: &Session) -> PathBuf {
-    sessions_dir().join(&session.id)
+    dir_for_id(&session.id)
+}
+
+/// Return the directory for the session with `id`, whether or not it exists.
+pub fn dir_for_id(id: &str) -> PathBuf {
+    sessions_dir().join(id)
 }
 
 pub fn save(session: &Session, store: &crate::attachment::AttachmentStore) {
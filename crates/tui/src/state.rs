@@ -27,6 +27,8 @@ pub struct PersistedSettings {
     pub restrict_to_workspace: Option<bool>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub redact_secrets: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mouse: Option<bool>,
 }
 
 impl PersistedSettings {
@@ -49,6 +51,7 @@ impl PersistedSettings {
                 .or(cfg.restrict_to_workspace)
                 .unwrap_or(true),
             redact_secrets: self.redact_secrets.or(cfg.redact_secrets).unwrap_or(true),
+            mouse: self.mouse.or(cfg.mouse).unwrap_or(false),
         }
     }
 }
@@ -66,6 +69,7 @@ pub struct ResolvedSettings {
     pub show_thinking: bool,
     pub restrict_to_workspace: bool,
     pub redact_secrets: bool,
+    pub mouse: bool,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -81,6 +85,9 @@ pub struct State {
     pub reasoning_effort: ReasoningEffort,
     #[serde(default)]
     pub accent_color: Option<u8>,
+    /// Name of the active theme file, if one was picked over a preset.
+    #[serde(default)]
+    pub theme: Option<String>,
     // Legacy field — migrated into `settings.show_thinking` on load.
     #[serde(default)]
     pub show_thinking: Option<bool>,
@@ -229,6 +236,12 @@ pub fn set_accent(value: u8) {
     });
 }
 
+pub fn set_theme(name: Option<String>) {
+    update_state(|s| {
+        s.theme = name;
+    });
+}
+
 /// Persist all toggle settings from the resolved values.
 pub fn save_settings(resolved: &ResolvedSettings) {
     update_state(|s| {
@@ -243,6 +256,7 @@ pub fn save_settings(resolved: &ResolvedSettings) {
             show_thinking: Some(resolved.show_thinking),
             restrict_to_workspace: Some(resolved.restrict_to_workspace),
             redact_secrets: Some(resolved.redact_secrets),
+            mouse: Some(resolved.mouse),
         };
     });
 }
@@ -284,6 +298,7 @@ mod tests {
             show_thinking: true,
             restrict_to_workspace: true,
             redact_secrets: true,
+            mouse: false,
         }
     }
 
@@ -1,5 +1,9 @@
 use crossterm::style::Color;
-use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+use std::sync::{Arc, LazyLock, RwLock};
+use two_face::theme::{EmbeddedLazyThemeSet, EmbeddedThemeName};
 
 /// Immutable snapshot of the theme atomics taken at the start of a paint
 /// pass. Resolved span colors look up roles in this snapshot instead of
@@ -14,6 +18,8 @@ pub struct Theme {
     pub tool_pending: Color,
     pub reason_off: Color,
     pub muted: Color,
+    pub diff_added_bg: Color,
+    pub diff_removed_bg: Color,
     pub is_light: bool,
 }
 
@@ -27,6 +33,8 @@ pub fn snapshot() -> Theme {
         tool_pending: tool_pending(),
         reason_off: reason_off(),
         muted: muted(),
+        diff_added_bg: diff_added_bg(),
+        diff_removed_bg: diff_removed_bg(),
         is_light: is_light(),
     }
 }
@@ -38,6 +46,9 @@ static ACCENT_VALUE: AtomicU8 = AtomicU8::new(DEFAULT_ACCENT);
 static ACCENT_LIGHT_VALUE: AtomicU8 = AtomicU8::new(DEFAULT_ACCENT_LIGHT);
 
 pub fn accent() -> Color {
+    if let Some(c) = custom(|p| p.accent) {
+        return c;
+    }
     if is_light() {
         Color::AnsiValue(ACCENT_LIGHT_VALUE.load(Ordering::Relaxed))
     } else {
@@ -59,7 +70,7 @@ static SLUG_COLOR_VALUE: AtomicU8 = AtomicU8::new(0);
 pub fn slug_color() -> Color {
     let v = SLUG_COLOR_VALUE.load(Ordering::Relaxed);
     if v == 0 {
-        accent()
+        custom(|p| p.slug).unwrap_or_else(accent)
     } else {
         Color::AnsiValue(v)
     }
@@ -250,75 +261,107 @@ fn wait_for_input(fd: std::os::fd::RawFd, timeout_ms: u64) -> bool {
 // ---------------------------------------------------------------------------
 
 pub fn tool_pending() -> Color {
-    if is_light() {
-        Color::AnsiValue(250)
-    } else {
-        Color::DarkGrey
-    }
+    custom(|p| p.tool_pending).unwrap_or_else(|| {
+        if is_light() {
+            Color::AnsiValue(250)
+        } else {
+            Color::DarkGrey
+        }
+    })
 }
 
 pub const APPLY: Color = Color::AnsiValue(141);
 
 pub fn user_bg() -> Color {
-    if is_light() {
-        Color::AnsiValue(254)
-    } else {
-        Color::AnsiValue(236)
-    }
+    custom(|p| p.user_bg).unwrap_or_else(|| {
+        if is_light() {
+            Color::AnsiValue(254)
+        } else {
+            Color::AnsiValue(236)
+        }
+    })
 }
 
 pub fn code_block_bg() -> Color {
-    if is_light() {
-        Color::AnsiValue(255)
-    } else {
-        Color::AnsiValue(233)
-    }
+    custom(|p| p.code_block_bg).unwrap_or_else(|| {
+        if is_light() {
+            Color::AnsiValue(255)
+        } else {
+            Color::AnsiValue(233)
+        }
+    })
 }
 
 pub fn bar() -> Color {
-    if is_light() {
-        Color::AnsiValue(252)
-    } else {
-        Color::AnsiValue(237)
-    }
+    custom(|p| p.bar).unwrap_or_else(|| {
+        if is_light() {
+            Color::AnsiValue(252)
+        } else {
+            Color::AnsiValue(237)
+        }
+    })
 }
 
 pub fn selection_bg() -> Color {
-    if is_light() {
-        Color::AnsiValue(189)
-    } else {
-        Color::AnsiValue(238)
-    }
+    custom(|p| p.selection_bg).unwrap_or_else(|| {
+        if is_light() {
+            Color::AnsiValue(189)
+        } else {
+            Color::AnsiValue(238)
+        }
+    })
 }
 
 pub fn scrollbar_track() -> Color {
-    if is_light() {
-        Color::AnsiValue(254)
-    } else {
-        Color::AnsiValue(235)
-    }
+    custom(|p| p.scrollbar_track).unwrap_or_else(|| {
+        if is_light() {
+            Color::AnsiValue(254)
+        } else {
+            Color::AnsiValue(235)
+        }
+    })
 }
 
 pub fn scrollbar_thumb() -> Color {
-    if is_light() {
-        Color::AnsiValue(247)
-    } else {
-        Color::AnsiValue(243)
-    }
+    custom(|p| p.scrollbar_thumb).unwrap_or_else(|| {
+        if is_light() {
+            Color::AnsiValue(247)
+        } else {
+            Color::AnsiValue(243)
+        }
+    })
 }
 
 pub const HEADING: Color = Color::AnsiValue(117);
 
 pub fn muted() -> Color {
-    Color::AnsiValue(244)
+    custom(|p| p.muted).unwrap_or(Color::AnsiValue(244))
+}
+
+pub fn diff_added_bg() -> Color {
+    custom(|p| p.diff_added_bg).unwrap_or(Color::Rgb {
+        r: 20,
+        g: 50,
+        b: 20,
+    })
+}
+
+pub fn diff_removed_bg() -> Color {
+    custom(|p| p.diff_removed_bg).unwrap_or(Color::Rgb {
+        r: 60,
+        g: 20,
+        b: 20,
+    })
 }
 
 pub fn reason_off() -> Color {
-    if is_light() {
-        Color::AnsiValue(250)
-    } else {
-        Color::DarkGrey
-    }
+    custom(|p| p.reason_off).unwrap_or_else(|| {
+        if is_light() {
+            Color::AnsiValue(250)
+        } else {
+            Color::DarkGrey
+        }
+    })
 }
 
 pub const REASON_LOW: Color = Color::AnsiValue(75);
@@ -348,6 +391,254 @@ pub const PRESETS: &[(&str, &str, u8)] = &[
     ("silver", "grey", 244),
 ];
 
+// ---------------------------------------------------------------------------
+// Theme files
+// ---------------------------------------------------------------------------
+
+/// A full theme loaded from `<config>/themes/<name>.yaml`. Every role is
+/// optional: unset roles keep their built-in light/dark defaults, so a file
+/// can override as little as the syntax theme.
+#[derive(Debug, Clone, Default)]
+pub struct Palette {
+    pub name: String,
+    pub description: Option<String>,
+    pub accent: Option<Color>,
+    pub slug: Option<Color>,
+    pub user_bg: Option<Color>,
+    pub code_block_bg: Option<Color>,
+    pub bar: Option<Color>,
+    pub tool_pending: Option<Color>,
+    pub reason_off: Option<Color>,
+    pub muted: Option<Color>,
+    pub selection_bg: Option<Color>,
+    pub scrollbar_track: Option<Color>,
+    pub scrollbar_thumb: Option<Color>,
+    pub syntax: Option<EmbeddedThemeName>,
+    pub diff_added_bg: Option<Color>,
+    pub diff_removed_bg: Option<Color>,
+}
+
+/// On-disk shape of a theme file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ThemeFile {
+    description: Option<String>,
+    accent: Option<ColorSpec>,
+    slug: Option<ColorSpec>,
+    user_bg: Option<ColorSpec>,
+    code_block_bg: Option<ColorSpec>,
+    bar: Option<ColorSpec>,
+    tool_pending: Option<ColorSpec>,
+    reason_off: Option<ColorSpec>,
+    muted: Option<ColorSpec>,
+    selection_bg: Option<ColorSpec>,
+    scrollbar_track: Option<ColorSpec>,
+    scrollbar_thumb: Option<ColorSpec>,
+    /// Name of a bundled syntax theme, e.g. `Dracula` or `gruvbox-dark`.
+    syntax: Option<String>,
+    diff: DiffFile,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct DiffFile {
+    added_bg: Option<ColorSpec>,
+    removed_bg: Option<ColorSpec>,
+}
+
+/// A color as written in a theme file: an ANSI 256 index, `#rrggbb`,
+/// a preset name (`ember`, `sky`, ...) or a basic color name (`dark_grey`).
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ColorSpec {
+    Ansi(u8),
+    Text(String),
+}
+
+static PALETTE: RwLock<Option<Arc<Palette>>> = RwLock::new(None);
+static PALETTE_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Look up a role in the active theme file, if any.
+fn custom(pick: impl FnOnce(&Palette) -> Option<Color>) -> Option<Color> {
+    PALETTE.read().ok()?.as_deref().and_then(pick)
+}
+
+/// Install (or clear) the active theme file. Roles it leaves unset fall
+/// back to the accent atomics and light/dark defaults.
+pub fn set_palette(palette: Option<Arc<Palette>>) {
+    *PALETTE.write().unwrap_or_else(|e| e.into_inner()) = palette;
+    PALETTE_GENERATION.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Bumped by every [`set_palette`]. Cached layouts bake in the palette's
+/// colors, so the screen re-lays out history when this changes.
+pub fn palette_generation() -> u64 {
+    PALETTE_GENERATION.load(Ordering::Relaxed)
+}
+
+pub fn palette() -> Option<Arc<Palette>> {
+    PALETTE.read().ok()?.clone()
+}
+
+/// Syntax theme requested by the active theme file.
+pub fn palette_syntax() -> Option<EmbeddedThemeName> {
+    PALETTE.read().ok()?.as_deref().and_then(|p| p.syntax)
+}
+
+pub fn themes_dir() -> PathBuf {
+    crate::config::config_dir().join("themes")
+}
+
+/// Names of the theme files in [`themes_dir`], sorted.
+pub fn theme_file_names() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(themes_dir()) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .filter_map(|e| {
+            let path = e.path();
+            let ext = path.extension()?.to_str()?;
+            if !matches!(ext, "yaml" | "yml") {
+                return None;
+            }
+            Some(path.file_stem()?.to_str()?.to_string())
+        })
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Load `<themes_dir>/<name>.yaml` (or `.yml`).
+pub fn load_palette(name: &str) -> Result<Palette, String> {
+    let dir = themes_dir();
+    let path = ["yaml", "yml"]
+        .iter()
+        .map(|ext| dir.join(format!("{name}.{ext}")))
+        .find(|p| p.is_file())
+        .ok_or_else(|| format!("no theme file named {name}"))?;
+    let contents = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    parse_palette(name, &contents)
+}
+
+pub fn parse_palette(name: &str, yaml: &str) -> Result<Palette, String> {
+    let file: ThemeFile = serde_yml::from_str(yaml).map_err(|e| e.to_string())?;
+    let color = |field: &str, spec: Option<ColorSpec>| {
+        spec.map(|s| parse_color(&s).map_err(|e| format!("{field}: {e}")))
+            .transpose()
+    };
+    let syntax = match file.syntax {
+        Some(ref s) => {
+            Some(syntax_theme_by_name(s).ok_or_else(|| format!("syntax: unknown theme {s}"))?)
+        }
+        None => None,
+    };
+    Ok(Palette {
+        name: name.to_string(),
+        description: file.description,
+        accent: color("accent", file.accent)?,
+        slug: color("slug", file.slug)?,
+        user_bg: color("user_bg", file.user_bg)?,
+        code_block_bg: color("code_block_bg", file.code_block_bg)?,
+        bar: color("bar", file.bar)?,
+        tool_pending: color("tool_pending", file.tool_pending)?,
+        reason_off: color("reason_off", file.reason_off)?,
+        muted: color("muted", file.muted)?,
+        selection_bg: color("selection_bg", file.selection_bg)?,
+        scrollbar_track: color("scrollbar_track", file.scrollbar_track)?,
+        scrollbar_thumb: color("scrollbar_thumb", file.scrollbar_thumb)?,
+        syntax,
+        diff_added_bg: color("diff.added_bg", file.diff.added_bg)?,
+        diff_removed_bg: color("diff.removed_bg", file.diff.removed_bg)?,
+    })
+}
+
+/// Match a bundled syntax theme by its display name, case-insensitively.
+fn syntax_theme_by_name(name: &str) -> Option<EmbeddedThemeName> {
+    EmbeddedLazyThemeSet::theme_names()
+        .iter()
+        .copied()
+        .find(|t| t.as_name().eq_ignore_ascii_case(name.trim()))
+}
+
+fn parse_color(spec: &ColorSpec) -> Result<Color, String> {
+    let s = match spec {
+        ColorSpec::Ansi(v) => return Ok(Color::AnsiValue(*v)),
+        ColorSpec::Text(s) => s.trim(),
+    };
+    if let Some(hex) = s.strip_prefix('#') {
+        let (r, g, b) = parse_hex(hex).ok_or_else(|| format!("invalid hex color {s}"))?;
+        return Ok(rgb(r, g, b));
+    }
+    if let Ok(v) = s.parse::<u8>() {
+        return Ok(Color::AnsiValue(v));
+    }
+    if let Some(v) = preset_by_name(s) {
+        return Ok(Color::AnsiValue(v));
+    }
+    Color::try_from(s).map_err(|_| format!("unknown color {s}"))
+}
+
+/// Parse `rrggbb` or the short `rgb` form.
+fn parse_hex(hex: &str) -> Option<(u8, u8, u8)> {
+    if !hex.is_ascii() {
+        return None;
+    }
+    match hex.len() {
+        6 => Some((
+            u8::from_str_radix(&hex[0..2], 16).ok()?,
+            u8::from_str_radix(&hex[2..4], 16).ok()?,
+            u8::from_str_radix(&hex[4..6], 16).ok()?,
+        )),
+        3 => {
+            let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok().map(|v| v * 17);
+            Some((digit(0)?, digit(1)?, digit(2)?))
+        }
+        _ => None,
+    }
+}
+
+/// Whether the terminal advertises 24-bit color via `$COLORTERM`.
+static TRUECOLOR: LazyLock<bool> = LazyLock::new(|| {
+    std::env::var("COLORTERM").is_ok_and(|v| matches!(v.as_str(), "truecolor" | "24bit"))
+});
+
+/// An RGB theme color: emitted as-is on truecolor terminals, otherwise
+/// mapped to the nearest entry of the 256-color palette.
+fn rgb(r: u8, g: u8, b: u8) -> Color {
+    if *TRUECOLOR {
+        Color::Rgb { r, g, b }
+    } else {
+        Color::AnsiValue(rgb_to_ansi256(r, g, b))
+    }
+}
+
+/// Nearest xterm-256 index: the closer of the 6x6x6 cube entry and the
+/// 24-step grayscale ramp.
+pub fn rgb_to_ansi256(r: u8, g: u8, b: u8) -> u8 {
+    const STEPS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let level = |v: u8| {
+        (0..STEPS.len())
+            .min_by_key(|&i| (STEPS[i] as i32 - v as i32).abs())
+            .unwrap_or(0)
+    };
+    let dist = |(cr, cg, cb): (u8, u8, u8)| {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        d(cr, r) + d(cg, g) + d(cb, b)
+    };
+    let (ri, gi, bi) = (level(r), level(g), level(b));
+    let cube = (STEPS[ri], STEPS[gi], STEPS[bi]);
+    let avg = (r as u32 + g as u32 + b as u32) / 3;
+    let gray_idx = (avg.saturating_sub(3) / 10).min(23) as u8;
+    let gray = 8 + 10 * gray_idx;
+    if dist((gray, gray, gray)) < dist(cube) {
+        232 + gray_idx
+    } else {
+        16 + 36 * ri as u8 + 6 * gi as u8 + bi as u8
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -391,4 +682,52 @@ mod tests {
         assert!(parse_osc11_response("garbage").is_none());
         assert!(parse_osc11_response("").is_none());
     }
+
+    #[test]
+    fn parse_palette_reads_roles_syntax_and_diff() {
+        let yaml = r##"
+description: night
+accent: 75
+user_bg: dark_grey
+muted: silver
+syntax: dracula
+diff:
+  added_bg: "#143214"
+"##;
+        let p = parse_palette("night", yaml).unwrap();
+        assert_eq!(p.description.as_deref(), Some("night"));
+        assert_eq!(p.accent, Some(Color::AnsiValue(75)));
+        assert_eq!(p.user_bg, Some(Color::DarkGrey));
+        assert_eq!(p.muted, Some(Color::AnsiValue(244)));
+        assert_eq!(p.syntax, Some(EmbeddedThemeName::Dracula));
+        assert!(p.diff_added_bg.is_some());
+        assert!(p.code_block_bg.is_none());
+        assert!(p.diff_removed_bg.is_none());
+    }
+
+    #[test]
+    fn parse_palette_rejects_bad_values() {
+        assert!(parse_palette("x", "accent: \"#12345\"").is_err());
+        assert!(parse_palette("x", "muted: nope").is_err());
+        assert!(parse_palette("x", "syntax: not-a-theme").is_err());
+        assert!(parse_palette("x", "acent: 1").is_err());
+    }
+
+    #[test]
+    fn parse_hex_long_and_short() {
+        assert_eq!(parse_hex("ff8000"), Some((255, 128, 0)));
+        assert_eq!(parse_hex("f80"), Some((255, 136, 0)));
+        assert_eq!(parse_hex("zzzzzz"), None);
+        assert_eq!(parse_hex("ff80"), None);
+    }
+
+    #[test]
+    fn rgb_downsamples_to_nearest_256_color() {
+        assert_eq!(rgb_to_ansi256(0, 0, 0), 16);
+        assert_eq!(rgb_to_ansi256(255, 255, 255), 231);
+        assert_eq!(rgb_to_ansi256(255, 0, 0), 196);
+        assert_eq!(rgb_to_ansi256(255, 135, 0), 208);
+        assert_eq!(rgb_to_ansi256(128, 128, 128), 244);
+        assert_eq!(rgb_to_ansi256(48, 48, 48), 236);
+    }
 }
@@ -0,0 +1,98 @@
+//! `smelt audit`: query the permission decision log the engine writes to
+//! `audit.jsonl`.
+
+use engine::audit::{self, AuditEntry, AuditQuery};
+
+const DAY_MS: u64 = 24 * 3600 * 1000;
+const SUMMARY_WIDTH: usize = 60;
+
+/// `days` keeps the last N days (today counts as one); `limit` keeps the
+/// newest N matches.
+pub fn run_audit_command(
+    mut query: AuditQuery,
+    days: Option<u32>,
+    limit: Option<usize>,
+    json: bool,
+) {
+    if let Some(days) = days {
+        let today = audit::now_ms() / DAY_MS;
+        query.since_ms = Some((today + 1).saturating_sub(days as u64) * DAY_MS);
+    }
+    let mut entries: Vec<AuditEntry> = audit::load()
+        .into_iter()
+        .filter(|e| query.matches(e))
+        .collect();
+    if let Some(limit) = limit {
+        entries.drain(..entries.len().saturating_sub(limit));
+    }
+
+    if json {
+        print!("{}", audit::entries_jsonl(&entries));
+        return;
+    }
+    if entries.is_empty() {
+        println!("No matching audit entries.");
+        return;
+    }
+
+    let mut rows = vec![[
+        "TIME (UTC)",
+        "SESSION",
+        "AGENT",
+        "MODE",
+        "TOOL",
+        "DECISION",
+        "BY",
+        "CALL",
+    ]
+    .map(String::from)];
+    for e in &entries {
+        let by = match &e.rule {
+            Some(rule) => format!("{}: {rule}", e.source.label()),
+            None => e.source.label().to_string(),
+        };
+        rows.push([
+            format_time(e.timestamp_ms),
+            e.session_id.chars().take(8).collect(),
+            e.agent_id.clone().unwrap_or_else(|| "main".into()),
+            e.mode.clone(),
+            e.tool.clone(),
+            e.decision.clone(),
+            by,
+            truncate(&e.summary),
+        ]);
+    }
+    let widths: Vec<usize> = (0..8)
+        .map(|i| rows.iter().map(|r| r[i].chars().count()).max().unwrap_or(0))
+        .collect();
+    for row in &rows {
+        let line: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, w)| format!("{cell:<w$}"))
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    }
+}
+
+/// `YYYY-MM-DD HH:MM:SS` (UTC).
+fn format_time(ms: u64) -> String {
+    let secs = (ms % DAY_MS) / 1000;
+    format!(
+        "{} {:02}:{:02}:{:02}",
+        tui::metrics::format_day(ms),
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+fn truncate(s: &str) -> String {
+    let line = s.lines().next().unwrap_or("");
+    if line.chars().count() > SUMMARY_WIDTH || line.len() < s.len() {
+        let cut: String = line.chars().take(SUMMARY_WIDTH - 1).collect();
+        format!("{cut}…")
+    } else {
+        line.to_string()
+    }
+}
@@ -1,5 +1,10 @@
+mod audit;
+mod models;
+mod permissions;
+mod pricing;
 mod setup;
 mod startup;
+mod stats;
 
 use clap::{Parser, Subcommand, ValueEnum};
 use crossterm::ExecutableCommand;
@@ -26,7 +31,7 @@ pub struct Args {
     #[arg(
         long,
         value_name = "TYPE",
-        help = "Provider type: openai-compatible, openai, anthropic, codex, copilot"
+        help = "Provider type: openai-compatible, openai, anthropic, codex, copilot, bedrock, azure-openai"
     )]
     r#type: Option<String>,
     #[arg(short, long)]
@@ -113,6 +118,58 @@ pub struct Args {
         help = "Maximum concurrent agents per session"
     )]
     max_agents: u8,
+    #[arg(
+        long,
+        value_name = "NAME",
+        requires = "subagent",
+        help = "Agent profile to run as (for subagents)"
+    )]
+    profile: Option<String>,
+    #[arg(
+        long,
+        value_name = "MODE",
+        requires = "profile",
+        help = "Mode of the spawning agent; caps a project profile's mode"
+    )]
+    parent_mode: Option<String>,
+    #[arg(
+        long,
+        value_name = "JSON",
+        requires = "subagent",
+        help = "JSON schema the subagent's final answer must satisfy"
+    )]
+    result_schema: Option<String>,
+    #[arg(
+        long,
+        value_name = "USD",
+        help = "Stop once this session (or headless run) has spent this much"
+    )]
+    max_cost: Option<f64>,
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Stop a turn after this many LLM round-trips"
+    )]
+    max_turns: Option<u32>,
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Stop a turn after this many tool calls"
+    )]
+    max_tool_calls: Option<u32>,
+    #[arg(
+        long,
+        value_name = "SECS",
+        help = "Stop a turn after this many seconds of wall-clock time"
+    )]
+    timeout: Option<u64>,
+    #[arg(
+        long,
+        value_enum,
+        requires = "headless",
+        help = "Use a cheaper, slower processing tier (headless only)"
+    )]
+    service_tier: Option<ServiceTierArg>,
     #[arg(short, long, num_args = 0..=1, default_missing_value = "", value_name = "SESSION_ID")]
     resume: Option<String>,
     #[arg(
@@ -140,6 +197,111 @@ enum ColorMode {
 enum Commands {
     /// Manage provider authentication (add providers, Codex or Copilot login/logout)
     Auth,
+    /// Show the price and pricing source of each configured model
+    Pricing,
+    /// Show the capabilities (context window, vision, reasoning, ...) of each configured model
+    Models,
+    /// Break recorded cost down by project, model, day or agent, or export it
+    Stats {
+        /// Dimension to group by
+        #[arg(long, value_enum, default_value_t = StatsGroup::Project)]
+        by: StatsGroup,
+        #[arg(long, value_enum, default_value_t = stats::StatsFormat::Table)]
+        format: stats::StatsFormat,
+        /// Only include the last N days (today counts as one)
+        #[arg(long, value_name = "N")]
+        days: Option<u32>,
+        /// Export individual LLM calls instead of totals (csv or json)
+        #[arg(long)]
+        raw: bool,
+    },
+    /// Inspect how permission rules apply
+    Permissions {
+        #[command(subcommand)]
+        command: PermissionsCommand,
+    },
+    /// Query the log of permission decisions (allowed, prompted and denied tool calls)
+    Audit {
+        /// Only include the last N days (today counts as one)
+        #[arg(long, value_name = "N")]
+        days: Option<u32>,
+        /// Only this session (id or id prefix)
+        #[arg(long, value_name = "ID")]
+        session: Option<String>,
+        /// Only this agent id
+        #[arg(long, value_name = "ID")]
+        agent: Option<String>,
+        /// Only this tool
+        #[arg(long, value_name = "NAME")]
+        tool: Option<String>,
+        /// Only this decision
+        #[arg(long, value_enum)]
+        decision: Option<AuditDecision>,
+        /// Only decisions made by this
+        #[arg(long, value_enum)]
+        source: Option<AuditSourceArg>,
+        /// Show only the newest N matches
+        #[arg(long, value_name = "N")]
+        limit: Option<usize>,
+        /// Print matching entries as JSON lines
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum PermissionsCommand {
+    /// Show how the rules decide a tool call, without running it
+    Explain {
+        /// Mode to evaluate in: normal, plan, apply, yolo
+        #[arg(long, default_value = "normal")]
+        mode: String,
+        /// Tool name (bash, web_fetch, edit_file, ...), or an MCP tool with --mcp
+        tool: String,
+        /// The command, URL or path the call targets
+        target: Option<String>,
+        /// Full tool arguments as a JSON object, instead of TARGET
+        #[arg(long, value_name = "JSON")]
+        args: Option<String>,
+        /// Treat TOOL as a qualified MCP tool name
+        #[arg(long)]
+        mcp: bool,
+        /// Session id whose plan files the tool overrides recognize
+        #[arg(long, value_name = "ID")]
+        session: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum AuditDecision {
+    Allow,
+    Ask,
+    Deny,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum AuditSourceArg {
+    Rule,
+    Default,
+    Tool,
+    Session,
+    Workspace,
+    User,
+    Managed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ServiceTierArg {
+    Flex,
+    Batch,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum StatsGroup {
+    Project,
+    Model,
+    Day,
+    Agent,
 }
 
 #[tokio::main]
@@ -147,6 +309,7 @@ async fn main() {
     std::panic::set_hook(Box::new(|info| {
         let _ = crossterm::terminal::disable_raw_mode();
         let _ = std::io::stdout().execute(crossterm::event::DisableBracketedPaste);
+        let _ = std::io::stdout().execute(crossterm::event::DisableMouseCapture);
         let _ = std::io::stdout().execute(crossterm::event::DisableFocusChange);
         let _ = std::io::stdout().execute(crossterm::cursor::Show);
         eprintln!("{info}");
@@ -155,14 +318,106 @@ async fn main() {
     let mut args = Args::parse();
 
     // Handle subcommands before loading config.
-    if let Some(Commands::Auth) = args.command {
-        setup::run_auth_command().await;
-        return;
+    match args.command {
+        Some(Commands::Auth) => {
+            setup::run_auth_command().await;
+            return;
+        }
+        Some(Commands::Pricing) => {
+            pricing::run_pricing_command().await;
+            return;
+        }
+        Some(Commands::Models) => {
+            models::run_models_command().await;
+            return;
+        }
+        Some(Commands::Stats {
+            by,
+            format,
+            days,
+            raw,
+        }) => {
+            let by = match by {
+                StatsGroup::Project => tui::metrics::GroupBy::Project,
+                StatsGroup::Model => tui::metrics::GroupBy::Model,
+                StatsGroup::Day => tui::metrics::GroupBy::Day,
+                StatsGroup::Agent => tui::metrics::GroupBy::Agent,
+            };
+            stats::run_stats_command(by, format, days, raw);
+            return;
+        }
+        Some(Commands::Permissions {
+            command:
+                PermissionsCommand::Explain {
+                    mode,
+                    tool,
+                    target,
+                    args,
+                    mcp,
+                    session,
+                },
+        }) => {
+            permissions::run_explain_command(&mode, &tool, target, args, mcp, session);
+            return;
+        }
+        Some(Commands::Audit {
+            days,
+            session,
+            agent,
+            tool,
+            decision,
+            source,
+            limit,
+            json,
+        }) => {
+            use engine::audit::AuditSource;
+            let query = engine::audit::AuditQuery {
+                since_ms: None,
+                session,
+                agent,
+                tool,
+                decision: decision.map(|d| {
+                    match d {
+                        AuditDecision::Allow => "allow",
+                        AuditDecision::Ask => "ask",
+                        AuditDecision::Deny => "deny",
+                    }
+                    .to_string()
+                }),
+                source: source.map(|s| match s {
+                    AuditSourceArg::Rule => AuditSource::Rule,
+                    AuditSourceArg::Default => AuditSource::Default,
+                    AuditSourceArg::Tool => AuditSource::Tool,
+                    AuditSourceArg::Session => AuditSource::Session,
+                    AuditSourceArg::Workspace => AuditSource::Workspace,
+                    AuditSourceArg::User => AuditSource::User,
+                    AuditSourceArg::Managed => AuditSource::Managed,
+                }),
+            };
+            audit::run_audit_command(query, days, limit, json);
+            return;
+        }
+        None => {}
+    }
+
+    let profiles = engine::AgentProfiles::load();
+    for warning in profiles.warnings() {
+        eprintln!("warning: {warning}");
+    }
+    let profile = args.profile.as_deref().map(|name| {
+        profiles.get(name).cloned().unwrap_or_else(|| {
+            eprintln!("error: unknown agent profile '{name}'");
+            std::process::exit(1);
+        })
+    });
+    if let Some(ref profile) = profile {
+        apply_profile_args(&mut args, profile);
     }
 
     let s = startup::resolve(&args).await;
     let startup::ResolvedStartup {
         cfg,
+        policy,
         available_models,
         auxiliary,
         api_base,
@@ -214,6 +469,28 @@ async fn main() {
             std::process::exit(1);
         }
     }
+    let result_schema = args.result_schema.as_deref().map(|s| {
+        serde_json::from_str::<serde_json::Value>(s).unwrap_or_else(|e| {
+            eprintln!("error: invalid --result-schema: {e}");
+            std::process::exit(1);
+        })
+    });
+
+    let mut model_config: engine::ModelConfig = (&model_config).into();
+    model_config.service_tier = args.service_tier.map(|t| match t {
+        ServiceTierArg::Flex => engine::config::ServiceTier::Flex,
+        ServiceTierArg::Batch => engine::config::ServiceTier::Batch,
+    });
+    if let Some(tier) = model_config.service_tier {
+        if !tier.supported_by(&provider_type) {
+            eprintln!(
+                "error: --service-tier {} is not supported by {} providers",
+                tier.label(),
+                provider_type
+            );
+            std::process::exit(1);
+        }
+    }
 
     if (args.headless || args.subagent) && startup_auth_error.is_some() {
         eprintln!(
@@ -237,6 +514,12 @@ async fn main() {
         };
         tui::theme::set_accent(theme_value);
     }
+    if let Some(ref name) = cfg.theme.name {
+        match tui::theme::load_palette(name) {
+            Ok(palette) => tui::theme::set_palette(Some(Arc::new(palette))),
+            Err(e) => eprintln!("warning: theme {name}: {e}"),
+        }
+    }
 
     let shared_session: Arc<Mutex<Option<tui::session::Session>>> = Arc::new(Mutex::new(None));
     let headless_cancel = Arc::new(tokio::sync::Notify::new());
@@ -285,6 +568,7 @@ async fn main() {
             };
             let _ = crossterm::terminal::disable_raw_mode();
             let _ = std::io::stdout().execute(crossterm::event::DisableBracketedPaste);
+            let _ = std::io::stdout().execute(crossterm::event::DisableMouseCapture);
             let _ = std::io::stdout().execute(crossterm::event::DisableFocusChange);
             if let Some(id) = session_id {
                 tui::session::print_resume_hint(&id);
@@ -302,7 +586,14 @@ async fn main() {
     let instructions = if args.no_system_prompt {
         None
     } else {
-        tui::instructions::load()
+        let profile_instructions = profile
+            .as_ref()
+            .map(|p| p.instructions.clone())
+            .filter(|s| !s.is_empty());
+        match (tui::instructions::load(), profile_instructions) {
+            (Some(base), Some(extra)) => Some(format!("{base}\n\n{extra}")),
+            (base, extra) => base.or(extra),
+        }
     };
     let system_prompt_override = if args.no_system_prompt {
         Some(String::new())
@@ -326,6 +617,10 @@ async fn main() {
     // Start the engine.
     let workspace = engine::paths::git_root(&cwd).unwrap_or_else(|| cwd.clone());
     let mut permissions = engine::Permissions::load();
+    if let Some(ref profile) = profile {
+        permissions = permissions.with_overrides(&profile.permissions);
+    }
+    permissions.set_policy(&policy);
     permissions.set_workspace(workspace);
     permissions.set_restrict_to_workspace(settings.restrict_to_workspace);
     let permissions = Arc::new(permissions);
@@ -356,7 +651,7 @@ async fn main() {
             key: api_key,
             key_env: api_key_env.clone(),
             provider_type,
-            model_config: (&model_config).into(),
+            model_config: model_config.clone(),
         },
         model: model.clone(),
         auxiliary,
@@ -372,6 +667,7 @@ async fn main() {
                 max_agents: args.max_agents,
                 parent_pid: args.parent_pid,
                 agent_id: planned_agent_id.clone(),
+                profiles: Arc::new(profiles),
             })
         } else {
             None
@@ -391,12 +687,32 @@ async fn main() {
         auto_compact: settings.auto_compact,
         context_window: cfg.settings.context_window,
         redact_secrets: settings.redact_secrets,
+        turn_limits: engine::TurnLimits {
+            max_turns: args.max_turns,
+            max_tool_calls: args.max_tool_calls,
+            timeout: args.timeout.map(std::time::Duration::from_secs),
+        },
+        allowed_tools: profile.and_then(|p| p.tools),
+        budget: Arc::new(engine::budget::Budget::new(
+            engine::budget::BudgetLimits {
+                session_usd: args.max_cost.or(cfg.budget.session_usd),
+                daily_usd: cfg.budget.daily_usd,
+                warn_percent: cfg
+                    .budget
+                    .warn_percent
+                    .unwrap_or(engine::budget::DEFAULT_WARN_PERCENT),
+            },
+            tui::metrics::spent_today(&tui::metrics::load()),
+        )),
     });
     let engine_injector = engine_handle.injector();
 
     // Fetch context window in background (only needed for interactive TUI display).
-    // If the user set it in config, skip the fetch entirely.
-    let ctx_rx = if !args.headless && cfg.settings.context_window.is_none() {
+    // If the user set it in config or the model's capabilities, skip the fetch entirely.
+    let ctx_rx = if !args.headless
+        && cfg.settings.context_window.is_none()
+        && model_config.capabilities.context_window.is_none()
+    {
         let ctx_api_base = args
             .api_base
             .clone()
@@ -410,6 +726,7 @@ async fn main() {
             .unwrap_or_default();
         let ctx_model = model.clone();
         let ctx_provider_type = initial_provider_type.clone();
+        let ctx_model_config = model_config.clone();
         let (tx, rx) = tokio::sync::oneshot::channel();
         tokio::spawn(async move {
             let provider = engine::Provider::new(
@@ -417,7 +734,8 @@ async fn main() {
                 ctx_api_key,
                 &ctx_provider_type,
                 reqwest::Client::new(),
-            );
+            )
+            .with_model_config(ctx_model_config);
             let _ = tx.send(provider.fetch_context_window(&ctx_model).await);
         });
         Some(rx)
@@ -445,10 +763,13 @@ async fn main() {
         args.api_key_env.is_some(),
         startup_auth_error.take(),
     );
-    app.model_config = (&model_config).into();
+    app.model_config = model_config;
+    app.context_window_override = cfg.settings.context_window;
+    app.sync_capabilities();
     if let Some(mode) = mode_override {
         app.mode = mode;
     }
+    app.mode = policy.effective_mode(app.mode);
     if !app.mode_cycle.contains(&app.mode) {
         app.mode_cycle.push(app.mode);
     }
@@ -482,8 +803,8 @@ async fn main() {
         }
 
         // Start socket listener.
-        let (socket_path, socket_rx) =
-            engine::socket::start_listener(my_pid).expect("failed to start agent socket");
+        let listening =
+            engine::socket::start_listener(my_pid, false).expect("failed to start agent socket");
 
         // Detect scope for registry.
         let scope = engine::paths::git_root(&cwd)
@@ -497,6 +818,8 @@ async fn main() {
             .ok()
             .map(|e| e.agent_id)
             .unwrap_or_else(|| format!("agent-{my_pid}"));
+        app.agent_id = agent_id.clone();
+        app.agent_depth = depth;
         engine::registry::register(&engine::registry::RegistryEntry {
             agent_id,
             pid: my_pid,
@@ -507,14 +830,23 @@ async fn main() {
             status: engine::registry::AgentStatus::Idle,
             task_slug: None,
             session_id: app.session.id.clone(),
-            socket_path: socket_path.to_string_lossy().into_owned(),
+            socket_path: listening.socket_path.to_string_lossy().into_owned(),
             depth,
             started_at: timestamp_now(),
+            host: String::new(),
+            address: listening.address,
+            updated_at: 0,
         })
         .expect("failed to register agent");
+        engine::registry::spawn_heartbeat(my_pid);
 
-        app.run_subagent(args.message.unwrap(), parent_pid, socket_rx)
-            .await;
+        app.run_subagent(
+            args.message.unwrap(),
+            result_schema,
+            parent_pid,
+            listening.rx,
+        )
+        .await;
 
         engine::registry::cleanup_self(my_pid);
     } else if args.headless {
@@ -549,12 +881,12 @@ async fn main() {
                 .into_owned();
             let branch = engine::paths::git_branch(&cwd);
 
-            let (socket_path, socket_rx) =
-                engine::socket::start_listener(my_pid).expect("failed to start agent socket");
+            let listening =
+                engine::socket::start_listener(my_pid, true).expect("failed to start agent socket");
 
             // Bridge socket messages to the engine + child permission channel.
             let (child_perm_tx, child_perm_rx) = tokio::sync::mpsc::unbounded_channel();
-            spawn_socket_bridge(socket_rx, engine_injector.clone(), child_perm_tx);
+            spawn_socket_bridge(listening.rx, engine_injector.clone(), child_perm_tx);
             app.set_child_permission_rx(child_perm_rx);
 
             let my_agent_id = planned_agent_id
@@ -571,12 +903,16 @@ async fn main() {
                 status: engine::registry::AgentStatus::Idle,
                 task_slug: None,
                 session_id: app.session.id.clone(),
-                socket_path: socket_path.to_string_lossy().into_owned(),
+                socket_path: listening.socket_path.to_string_lossy().into_owned(),
                 depth: 0,
                 started_at: timestamp_now(),
+                host: String::new(),
+                address: listening.address,
+                updated_at: 0,
             }) {
                 eprintln!("warning: failed to register in agent registry: {e}");
             }
+            engine::registry::spawn_heartbeat(my_pid);
 
             // Prune dead entries on startup.
             engine::registry::prune_dead();
@@ -598,6 +934,25 @@ async fn main() {
     tui::perf::print_summary();
 }
 
+/// Point a subagent's startup arguments at its profile's model, reasoning
+/// effort and mode. A profile model is resolved from config like `--model`,
+/// so the connection inherited from the parent is dropped.
+fn apply_profile_args(args: &mut Args, profile: &engine::profiles::AgentProfile) {
+    if let Some(ref model) = profile.model {
+        args.model = Some(model.clone());
+        args.api_base = None;
+        args.api_key_env = None;
+        args.r#type = None;
+    }
+    if let Some(effort) = profile.reasoning_effort {
+        args.reasoning_effort = Some(effort.label().into());
+    }
+    let parent_mode = args.parent_mode.as_deref().and_then(protocol::Mode::parse);
+    if let Some(mode) = profile.mode_under(parent_mode) {
+        args.mode = Some(mode.as_str().into());
+    }
+}
+
 /// Redirect stderr (fd 2) to a file in the logs directory so that any stray
 /// output from system daemons, libraries, or child processes doesn't pollute
 /// the TUI display.
@@ -636,9 +991,31 @@ fn spawn_socket_bridge(
                     from_id,
                     from_slug,
                     message,
+                    schema: None,
                 } => {
                     injector.inject_agent_message(from_id, from_slug, message);
                 }
+                // A person answers here; a structured reply can't be promised.
+                engine::socket::IncomingMessage::Message {
+                    from_id,
+                    schema: Some(_),
+                    ..
+                } => {
+                    let Some(sender) = engine::registry::find_by_id(&from_id) else {
+                        continue;
+                    };
+                    let my_id = engine::registry::read_entry(std::process::id())
+                        .map(|e| e.agent_id)
+                        .unwrap_or_default();
+                    let _ = engine::socket::send_message(
+                        &sender.endpoint(),
+                        &my_id,
+                        "",
+                        "error: this agent is an interactive session and cannot reply with a schema",
+                        None,
+                    )
+                    .await;
+                }
                 engine::socket::IncomingMessage::Query { reply_tx, .. } => {
                     let _ = reply_tx.send(
                         "agent is in interactive mode and cannot serve queries at this time".into(),
@@ -0,0 +1,77 @@
+//! `smelt models`: show what each configured model supports, as resolved by
+//! the capability registry.
+
+use std::time::Duration;
+
+const CATALOG_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub async fn run_models_command() {
+    let cfg = tui::config::Config::load();
+    let models = cfg.resolve_models();
+    if models.is_empty() {
+        eprintln!("error: no models configured in {}", cfg.path.display());
+        std::process::exit(1);
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(CATALOG_TIMEOUT)
+        .build()
+        .unwrap_or_default();
+    engine::pricing::load_catalog(&client).await;
+
+    let header = [
+        "MODEL",
+        "CONTEXT",
+        "OUTPUT",
+        "VISION",
+        "REASONING",
+        "TOOLS",
+        "PARALLEL",
+        "CACHING",
+    ]
+    .map(String::from);
+    let mut rows = vec![header];
+    for m in &models {
+        if m.model_name.is_empty() {
+            // Codex/Copilot placeholder: models are fetched after login.
+            let mut row: [String; 8] = Default::default();
+            row[0] = m.key.clone();
+            row[1] = "fetched at runtime".into();
+            rows.push(row);
+            continue;
+        }
+        let c = engine::capabilities::resolve(&m.model_name, &m.provider_type, &(&m.config).into());
+        rows.push([
+            m.key.clone(),
+            tokens(c.context_window),
+            tokens(c.max_output),
+            yes_no(c.vision),
+            c.reasoning.label().into(),
+            yes_no(c.tool_calling),
+            yes_no(c.parallel_tools),
+            yes_no(c.prompt_caching),
+        ]);
+    }
+
+    let widths: Vec<usize> = (0..8)
+        .map(|i| rows.iter().map(|r| r[i].chars().count()).max().unwrap_or(0))
+        .collect();
+    for row in &rows {
+        let line: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, w)| format!("{cell:<w$}"))
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    }
+    println!();
+    println!("Set `capabilities:` on a model in config to override any of these.");
+}
+
+fn tokens(n: Option<u32>) -> String {
+    n.map_or("—".into(), |n| n.to_string())
+}
+
+fn yes_no(b: bool) -> String {
+    if b { "yes" } else { "no" }.into()
+}
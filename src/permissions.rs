@@ -0,0 +1,184 @@
+//! `smelt permissions explain`: dry-run the permission rules for a tool call
+//! and print how they reach their decision.
+
+use engine::audit::AuditSource;
+use engine::permissions::{Decision, Explanation, RuntimeApprovals, Step, ToolCall};
+use protocol::Mode;
+use serde_json::Value;
+use std::collections::HashMap;
+
+pub fn run_explain_command(
+    mode: &str,
+    tool: &str,
+    target: Option<String>,
+    args_json: Option<String>,
+    mcp: bool,
+    session: Option<String>,
+) {
+    let Some(mode) = Mode::parse(mode) else {
+        eprintln!("error: unknown mode '{mode}' (expected normal, plan, apply or yolo)");
+        std::process::exit(1);
+    };
+    let args = match (args_json, target) {
+        (Some(json), _) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            eprintln!("error: --args must be a JSON object: {e}");
+            std::process::exit(1);
+        }),
+        (None, Some(target)) => target_args(tool, target),
+        (None, None) => HashMap::new(),
+    };
+
+    let cfg = tui::config::Config::load();
+    let settings = tui::state::State::load().settings.resolve(&cfg.settings);
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let policy = engine::policy::ManagedPolicy::load().unwrap_or_else(|e| {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    });
+    let mut permissions = engine::Permissions::load();
+    permissions.set_policy(&policy);
+    permissions.set_workspace(engine::paths::git_root(&cwd).unwrap_or_else(|| cwd.clone()));
+    permissions.set_restrict_to_workspace(settings.restrict_to_workspace);
+
+    let rules = tui::workspace_permissions::load(&cwd.to_string_lossy());
+    let (ws_tools, ws_dirs) = tui::workspace_permissions::into_approvals(&rules);
+    let mut approvals = RuntimeApprovals::new();
+    approvals.load_workspace(ws_tools, ws_dirs);
+
+    // The engine's own tool instances, so their overrides, secret handling
+    // and confirm text apply exactly as in a real call.
+    let tools = engine::tools::build_tools(engine::tools::ProcessRegistry::new(), None, None);
+    let session_dir = session.map(|id| tui::session::dir_for_id(&id));
+    let call = ToolCall {
+        name: tool,
+        args: &args,
+        is_mcp: mcp,
+        tool: if mcp { None } else { tools.get(tool) },
+        session_dir: session_dir.as_deref(),
+    };
+    print_explanation(&permissions.explain(mode, &call, &approvals));
+}
+
+/// Arguments for a call given only its main target.
+fn target_args(tool: &str, target: String) -> HashMap<String, Value> {
+    let key = match tool {
+        "bash" => "command",
+        "web_fetch" => "url",
+        "read_file" | "write_file" | "edit_file" => "file_path",
+        "edit_notebook" => "notebook_path",
+        "glob" | "grep" => "path",
+        "web_search" => "query",
+        "load_skill" => "name",
+        _ => "target",
+    };
+    HashMap::from([(key.to_string(), Value::String(target))])
+}
+
+fn print_explanation(e: &Explanation) {
+    match e.fallback_mode {
+        Some(fallback) => println!(
+            "mode: {} (disabled by the managed policy; {} rules apply)",
+            e.mode.as_str(),
+            fallback.as_str()
+        ),
+        None => println!("mode: {}", e.mode.as_str()),
+    }
+    println!("tool: {}", e.tool);
+    match &e.tool_rule {
+        Some(rule) => println!("tool rule: {rule}"),
+        None => println!("tool rule: none"),
+    }
+
+    if !e.steps.is_empty() {
+        let heading = match e.tool.as_str() {
+            "bash" => "sub-commands",
+            "web_fetch" => "url",
+            _ if e.steps.len() == 1 => "target",
+            _ => "targets",
+        };
+        println!();
+        println!("{heading}:");
+        let width = e
+            .steps
+            .iter()
+            .map(|s| one_line(&s.subject).chars().count())
+            .max()
+            .unwrap_or(0);
+        for step in &e.steps {
+            print_step(step, width);
+        }
+    }
+
+    if !e.outside_workspace.is_empty() {
+        println!();
+        println!("outside the workspace:");
+        for (path, approval) in &e.outside_workspace {
+            match approval {
+                Some(a) => println!("  {path}  {a}"),
+                None => println!("  {path}"),
+            }
+        }
+    }
+    if e.downgraded {
+        println!();
+        println!("restrict_to_workspace: allow lowered to ask");
+    }
+
+    if let Some(rule) = &e.managed {
+        println!();
+        println!("managed policy: {rule}");
+        println!("decision: {}", e.decision.label());
+        return;
+    }
+
+    println!();
+    if e.source == AuditSource::Tool {
+        println!(
+            "the tool's own override decides: {}",
+            e.rule_decision.label()
+        );
+    } else if e.uses_secrets {
+        println!("restores a redacted secret: {}", e.rule_decision.label());
+    } else {
+        println!("rules decide: {}", e.rule_decision.label());
+    }
+    if e.decision != e.rule_decision {
+        let approval = e
+            .approval
+            .as_ref()
+            .map(|a| format!(" by {a}"))
+            .unwrap_or_default();
+        println!(
+            "saved approvals raise it to {}{approval}",
+            e.decision.label()
+        );
+    }
+    println!("decision: {}", e.decision.label());
+}
+
+fn print_step(step: &Step, width: usize) {
+    let decision = step.decision.as_ref().map_or("-", Decision::label);
+    let mut why: Vec<String> = Vec::new();
+    if let Some(rule) = &step.rule {
+        why.push(rule.to_string());
+    }
+    if let Some(note) = step.note {
+        why.push(note.to_string());
+    }
+    println!(
+        "  {:<width$}  {decision:<5}  {}",
+        one_line(&step.subject),
+        why.join("; ")
+    );
+    if let Some(approval) = &step.approval {
+        println!("  {:<width$}  {:<5}  {approval}", "", "");
+    }
+}
+
+/// First line of a sub-command; heredoc bodies are elided.
+fn one_line(s: &str) -> String {
+    match s.split_once('\n') {
+        Some((first, _)) => format!("{first} …"),
+        None => s.to_string(),
+    }
+}
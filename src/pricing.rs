@@ -0,0 +1,71 @@
+//! `smelt pricing`: show how each configured model is priced and where the
+//! prices came from.
+
+use std::time::Duration;
+
+const CATALOG_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub async fn run_pricing_command() {
+    let cfg = tui::config::Config::load();
+    let models = cfg.resolve_models();
+    if models.is_empty() {
+        eprintln!("error: no models configured in {}", cfg.path.display());
+        std::process::exit(1);
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(CATALOG_TIMEOUT)
+        .build()
+        .unwrap_or_default();
+    engine::pricing::load_catalog(&client).await;
+
+    let header = ["MODEL", "SOURCE", "INPUT", "OUTPUT", "CACHE R/W", "RULE"].map(String::from);
+    let mut rows = vec![header];
+    for m in &models {
+        if m.model_name.is_empty() {
+            // Codex/Copilot placeholder: models are fetched after login.
+            rows.push([
+                m.key.clone(),
+                "fetched at runtime".into(),
+                String::new(),
+                String::new(),
+                String::new(),
+                String::new(),
+            ]);
+            continue;
+        }
+        let r = engine::pricing::resolve(&m.model_name, &m.provider_type, &(&m.config).into());
+        let p = r.pricing;
+        rows.push([
+            m.key.clone(),
+            r.source.label().to_string(),
+            rate(p.input),
+            rate(p.output),
+            format!("{} / {}", rate(p.cache_read), rate(p.cache_write)),
+            r.rule.label(),
+        ]);
+    }
+
+    let widths: Vec<usize> = (0..6)
+        .map(|i| rows.iter().map(|r| r[i].chars().count()).max().unwrap_or(0))
+        .collect();
+    for row in &rows {
+        let line: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, w)| format!("{cell:<w$}"))
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    }
+    println!();
+    println!("Prices are USD per 1M tokens.");
+}
+
+fn rate(usd: f64) -> String {
+    if usd == 0.0 {
+        "—".into()
+    } else {
+        let s = format!("{usd:.4}");
+        format!("${}", s.trim_end_matches('0').trim_end_matches('.'))
+    }
+}
@@ -22,6 +22,7 @@ pub fn resolve_api_key(key_env: &str) -> Result<String, String> {
 /// starts. Produced by [`resolve`] and consumed by the mode dispatch in `main`.
 pub struct ResolvedStartup {
     pub cfg: tui::config::Config,
+    pub policy: engine::policy::ManagedPolicy,
     pub available_models: Vec<tui::config::ResolvedModel>,
     pub auxiliary: engine::AuxiliaryModelConfig,
     pub api_base: String,
@@ -81,6 +82,38 @@ fn resolve_model_reference(
     }
 }
 
+/// Drop the models the managed policy does not allow. Exits when a model
+/// named with `--model` is among them, or when none are left.
+fn apply_model_policy(
+    policy: &engine::policy::ManagedPolicy,
+    args: &Args,
+    available_models: &mut Vec<tui::config::ResolvedModel>,
+) {
+    let (allowed, blocked): (Vec<_>, Vec<_>) = std::mem::take(available_models)
+        .into_iter()
+        .partition(|m| policy.allows_model(&m.provider_type, &m.model_name));
+    *available_models = allowed;
+    if blocked.is_empty() {
+        return;
+    }
+    if let Some(ref cli_model) = args.model {
+        if tui::config::resolve_model_ref(&blocked, cli_model).is_ok() {
+            eprintln!(
+                "error: model '{cli_model}' is not allowed by the managed policy ({})",
+                engine::policy::POLICY_PATH
+            );
+            std::process::exit(1);
+        }
+    }
+    if available_models.is_empty() {
+        eprintln!(
+            "error: none of the configured models are allowed by the managed policy ({})",
+            engine::policy::POLICY_PATH
+        );
+        std::process::exit(1);
+    }
+}
+
 /// Load config (honouring `--config` + `--set`), fetch dynamic model lists,
 /// resolve the active model, auxiliary routing, API keys, and all pure
 /// defaults merges (mode, reasoning, settings, multi-agent).
@@ -114,6 +147,33 @@ pub async fn resolve(args: &Args) -> ResolvedStartup {
         }
     }
 
+    let policy = engine::policy::ManagedPolicy::load().unwrap_or_else(|e| {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    });
+    cfg.mcp.retain(|name, _| {
+        let allowed = policy.allows_mcp_server(name);
+        if !allowed {
+            eprintln!(
+                "warning: MCP server '{name}' is not allowed by the managed policy, skipping"
+            );
+        }
+        allowed
+    });
+
+    if let Err(e) = engine::redact::configure(&cfg.redaction) {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    }
+    if let Err(e) = engine::socket::configure(&cfg.agents) {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    }
+    if let Some(dir) = &cfg.agents.registry {
+        let dir = engine::paths::expand_tilde(std::path::Path::new(dir));
+        engine::registry::set_backend(Box::new(engine::registry::FileRegistry::new(dir)));
+    }
+
     let app_state = tui::state::State::load();
     let mut available_models = cfg.resolve_models();
 
@@ -146,6 +206,8 @@ pub async fn resolve(args: &Args) -> ResolvedStartup {
         });
     }
 
+    apply_model_policy(&policy, args, &mut available_models);
+
     let auxiliary_routing = match cfg.resolve_auxiliary_routing(&available_models) {
         Ok(routing) => routing,
         Err(err) => {
@@ -189,6 +251,7 @@ pub async fn resolve(args: &Args) -> ResolvedStartup {
             }
             cfg = tui::config::Config::load_from(&cfg.path);
             available_models = cfg.resolve_models();
+            apply_model_policy(&policy, args, &mut available_models);
             if let Some(r) = available_models.first() {
                 let key = match resolve_api_key(&r.api_key_env) {
                     Ok(key) => key,
@@ -263,6 +326,14 @@ pub async fn resolve(args: &Args) -> ResolvedStartup {
             .to_string();
     }
 
+    if !policy.allows_model(&provider_type, &model) {
+        eprintln!(
+            "error: model '{model}' ({provider_type}) is not allowed by the managed policy ({})",
+            engine::policy::POLICY_PATH
+        );
+        std::process::exit(1);
+    }
+
     // Apply CLI sampling overrides to model_config.
     if let Some(v) = args.temperature {
         model_config.temperature = Some(v);
@@ -325,15 +396,30 @@ pub async fn resolve(args: &Args) -> ResolvedStartup {
                 eprintln!("warning: unknown mode '{s}', defaulting to normal");
                 Mode::Normal
             })
+        })
+        .map(|mode| {
+            let effective = policy.effective_mode(mode);
+            if effective != mode {
+                eprintln!(
+                    "warning: {} mode is disabled by the managed policy, using {}",
+                    mode.as_str(),
+                    effective.as_str()
+                );
+            }
+            effective
         });
 
-    let mode_cycle = args
+    let mut mode_cycle = args
         .mode_cycle
         .as_deref()
         .or(cfg.defaults.mode_cycle.as_deref())
         .map(Mode::parse_list)
         .filter(|v| !v.is_empty())
         .unwrap_or_else(|| Mode::ALL.to_vec());
+    mode_cycle.retain(|m| !policy.disables(*m));
+    if mode_cycle.is_empty() {
+        mode_cycle.push(policy.fallback_mode());
+    }
 
     // Reasoning effort: CLI --reasoning-effort > config defaults > saved state.
     let reasoning_effort = args
@@ -365,9 +451,13 @@ pub async fn resolve(args: &Args) -> ResolvedStartup {
     if args.subagent || args.headless {
         settings.auto_compact = true;
     }
+    if policy.redact_secrets {
+        settings.redact_secrets = true;
+    }
 
     ResolvedStartup {
         cfg,
+        policy,
         available_models,
         auxiliary,
         api_base,
@@ -0,0 +1,73 @@
+//! `smelt stats`: export recorded usage and break cost down by project,
+//! model, day or agent.
+
+use tui::metrics::{self, GroupBy};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum StatsFormat {
+    Table,
+    Csv,
+    Json,
+}
+
+pub fn run_stats_command(by: GroupBy, format: StatsFormat, days: Option<u32>, raw: bool) {
+    let mut entries = metrics::load();
+    if let Some(days) = days {
+        entries = metrics::since_days(entries, days);
+    }
+
+    if raw {
+        match format {
+            StatsFormat::Csv => print!("{}", metrics::entries_csv(&entries)),
+            StatsFormat::Json => print!("{}", metrics::entries_jsonl(&entries)),
+            StatsFormat::Table => {
+                eprintln!("error: --raw needs --format csv or --format json");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let groups = metrics::group_cost(&entries, by);
+    match format {
+        StatsFormat::Csv => print!("{}", metrics::groups_csv(&groups, by)),
+        StatsFormat::Json => println!("{}", metrics::groups_json(&groups)),
+        StatsFormat::Table => print_table(&groups, by),
+    }
+}
+
+fn print_table(groups: &[metrics::CostGroup], by: GroupBy) {
+    if groups.is_empty() {
+        println!("No metrics recorded.");
+        return;
+    }
+    let mut rows = vec![[
+        by.label().to_uppercase(),
+        "CALLS".into(),
+        "TOKENS".into(),
+        "COST".into(),
+    ]];
+    for g in groups {
+        rows.push([
+            g.key.clone(),
+            g.calls.to_string(),
+            (g.prompt_tokens + g.completion_tokens).to_string(),
+            metrics::format_cost(g.cost_usd),
+        ]);
+    }
+    let total: f64 = groups.iter().map(|g| g.cost_usd).sum();
+    let widths: Vec<usize> = (0..4)
+        .map(|i| rows.iter().map(|r| r[i].chars().count()).max().unwrap_or(0))
+        .collect();
+    for row in &rows {
+        let key = format!("{:<w$}", row[0], w = widths[0]);
+        let nums: Vec<String> = row[1..]
+            .iter()
+            .zip(&widths[1..])
+            .map(|(cell, w)| format!("{cell:>w$}"))
+            .collect();
+        println!("{key}  {}", nums.join("  "));
+    }
+    println!();
+    println!("total {}", metrics::format_cost(total));
+}